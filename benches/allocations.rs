@@ -0,0 +1,69 @@
+//! Counts the allocations and the allocated bytes while building and rendering a synthetic document
+//! with 5000 text nodes, once with text created from `&str`, once with zero-copy static text and
+//! once with owned strings that are either copied with `roff()` or moved into the document.
+//!
+//! Run with `cargo bench --bench allocations`.
+
+use roffman::{Roff, RoffNode, RoffText, Roffable, SectionNumber};
+
+mod common;
+
+use common::CountingAllocator;
+
+const NODES: usize = 5000;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn document(nodes: impl Iterator<Item = RoffNode>) -> Roff {
+    Roff::new("bench", SectionNumber::Miscellaneous)
+        .section("DESCRIPTION", [RoffNode::paragraph(nodes)])
+}
+
+fn measure(name: &str, text: impl Fn() -> RoffText) {
+    measure_document(name, || {
+        document((0..NODES).map(|_| RoffNode::text(text())))
+    });
+}
+
+fn measure_document(name: &str, build: impl FnOnce() -> Roff) {
+    let before = common::allocations();
+    let bytes_before = common::allocated_bytes();
+    let roff = build();
+    let mut out = Vec::with_capacity(NODES * 128);
+    roff.render(&mut out).expect("render failed");
+    let allocations = common::allocations() - before;
+    let bytes = common::allocated_bytes() - bytes_before;
+    println!(
+        "{:<12} {:>8} allocations {:>10} bytes allocated ({} bytes)",
+        name,
+        allocations,
+        bytes,
+        out.len()
+    );
+}
+
+fn main() {
+    measure("&str", || "plain text without escapes ".roff());
+    measure("from_static", || {
+        RoffText::from_static("plain text without escapes ")
+    });
+    measure("escaped", || "text-with-escapes ".roff());
+
+    let owned = || {
+        (0..NODES).map(|i| {
+            format!(
+                "owned text number {} that is long enough for copying it to matter ",
+                i
+            )
+        })
+    };
+    let strings: Vec<String> = owned().collect();
+    measure_document("String", || {
+        document(strings.iter().map(|text| RoffNode::text(text.roff())))
+    });
+    let strings: Vec<String> = owned().collect();
+    measure_document("String move", || {
+        document(strings.into_iter().map(RoffNode::text))
+    });
+}
@@ -0,0 +1,49 @@
+//! Compares the single pass `escape` with the previous implementation that built the output
+//! character by character and then replaced leading dots in a second pass.
+//!
+//! Run with `cargo bench --bench escape`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// The implementation of `escape` before it was rewritten to a single pass.
+fn escape_two_pass(text: &str) -> String {
+    let mut out = String::new();
+    for ch in text.chars() {
+        match ch {
+            '-' => out.push_str("\\-"),
+            '\'' => out.push_str("\\(aq"),
+            '‘' => out.push_str("\\(oq"),
+            '’' => out.push_str("\\(cq"),
+            '"' => out.push_str("\\(dq"),
+            '“' => out.push_str("\\(lq"),
+            '”' => out.push_str("\\(rq"),
+            '`' => out.push_str("\\(ga"),
+            '^' => out.push_str("\\(ha"),
+            '\\' => out.push_str("\\e"),
+            '~' => out.push_str("\\(ti"),
+            ch => out.push(ch),
+        }
+    }
+    out.replace("\n.", "\n\\&.")
+}
+
+fn escape(c: &mut Criterion) {
+    let plain = "The quick brown fox jumps over the lazy dog. ".repeat(2000);
+    let special = "Use --force or 'rm -rf ~/.cache' with \"care\" ^_^\n.dot ".repeat(2000);
+
+    let mut group = c.benchmark_group("escape");
+    for (name, input) in [("plain", &plain), ("special", &special)] {
+        assert_eq!(escape_two_pass(input), roffman::escape(input));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::new("two pass", name), input, |b, input| {
+            b.iter(|| escape_two_pass(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("single pass", name), input, |b, input| {
+            b.iter(|| roffman::escape(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, escape);
+criterion_main!(benches);
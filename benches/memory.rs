@@ -0,0 +1,56 @@
+//! Measures the heap memory held by a synthetic document with 50000 nodes repeating the same
+//! strings, once with every node built from its own `String` and once with clones of shared text
+//! and nodes.
+//!
+//! Run with `cargo bench --bench memory`.
+
+use roffman::{Roff, RoffNode, Roffable, SectionNumber};
+
+mod common;
+
+use common::CountingAllocator;
+
+const NODES: usize = 50_000;
+const SENTENCE: &str = "Returns the number of bytes written to the underlying writer.";
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn document(nodes: impl Iterator<Item = RoffNode>) -> Roff {
+    Roff::new("bench", SectionNumber::LibraryCalls)
+        .section("DESCRIPTION", [RoffNode::paragraph(nodes)])
+}
+
+/// Returns the document built by `build` together with the heap bytes it holds.
+fn measure(name: &str, build: impl FnOnce() -> Roff) -> (Roff, usize) {
+    let before = common::live_bytes();
+    let roff = build();
+    let bytes = common::live_bytes() - before;
+    println!(
+        "{:<8} {:>10} bytes ({:.1} bytes per node)",
+        name,
+        bytes,
+        bytes as f64 / NODES as f64
+    );
+    (roff, bytes)
+}
+
+fn main() {
+    let (owned, owned_bytes) = measure("owned", || {
+        document((0..NODES).map(|_| RoffNode::text(SENTENCE.to_string().roff())))
+    });
+    let (shared, shared_bytes) = measure("shared", || {
+        let node = RoffNode::text(SENTENCE.to_string().roff());
+        document((0..NODES).map(|_| node.clone()))
+    });
+    println!(
+        "shared text saves {} bytes ({:.0}%)",
+        owned_bytes - shared_bytes,
+        100.0 * (owned_bytes - shared_bytes) as f64 / owned_bytes as f64
+    );
+
+    let owned = owned.render_to_string().expect("render failed");
+    let shared = shared.render_to_string().expect("render failed");
+    assert_eq!(owned, shared, "sharing text changed the output");
+    assert!(shared_bytes < owned_bytes);
+}
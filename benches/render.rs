@@ -0,0 +1,41 @@
+//! Compares rendering a large document to a `String`, which validates the output as UTF-8, with
+//! rendering it to bytes with `render_to_vec` and into a reused buffer with `render_into`.
+//!
+//! Run with `cargo bench --bench render`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use roffman::{Roff, RoffNode, Roffable, SectionNumber};
+
+const NODES: usize = 20_000;
+
+fn render(c: &mut Criterion) {
+    let nodes = (0..NODES).map(|i| {
+        RoffNode::paragraph([
+            "Zażółć gęślą jaźń, the quick brown fox ".roff(),
+            i.to_string().roff().bold(),
+            " jumps over the lazy dog.".roff(),
+        ])
+    });
+    let roff = Roff::new("bench", SectionNumber::Miscellaneous).section("DESCRIPTION", nodes);
+    let len = roff.render_to_vec().expect("render failed").len();
+
+    let mut group = c.benchmark_group("render");
+    group.throughput(Throughput::Bytes(len as u64));
+    group.bench_function("render_to_string", |b| {
+        b.iter(|| roff.render_to_string().expect("render failed"))
+    });
+    group.bench_function("render_to_vec", |b| {
+        b.iter(|| roff.render_to_vec().expect("render failed"))
+    });
+    let mut buf = Vec::with_capacity(len);
+    group.bench_function("render_into", |b| {
+        b.iter(|| {
+            buf.clear();
+            roff.render_into(&mut buf).expect("render failed");
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, render);
+criterion_main!(benches);
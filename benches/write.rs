@@ -0,0 +1,124 @@
+//! Measures rendering a small page, a page with an `OPTIONS` section of 1000 options and a
+//! document with 10000 paragraphs to a byte vector and to an unbuffered file, counting the writes
+//! that reach the writer.
+//!
+//! Run with `cargo bench --bench write`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use roffman::{Roff, RoffNode, Roffable, SectionNumber, SynopsisOpt};
+
+use std::fs::File;
+use std::io::{self, Seek, SeekFrom, Write};
+
+/// Writer that counts the writes passed on to the inner writer.
+struct CountingWriter<W: Write> {
+    inner: W,
+    writes: usize,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writes += 1;
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn small_page() -> Roff {
+    Roff::new("bench", SectionNumber::UserCommands)
+        .date("August 2021")
+        .section(
+            "NAME",
+            [RoffNode::whatis(["bench"], "renders a small page")],
+        )
+        .section(
+            "SYNOPSIS",
+            [RoffNode::synopsis(
+                "bench",
+                ["[OPTIONS]"],
+                [
+                    SynopsisOpt::new("--verbose").description(["print more"]),
+                    SynopsisOpt::new("--output")
+                        .argument("FILE")
+                        .description(["write to FILE"]),
+                ],
+            )],
+        )
+        .section(
+            "DESCRIPTION",
+            [
+                RoffNode::paragraph([
+                    "The ".roff(),
+                    "bench".roff().bold(),
+                    " command renders a page.".roff(),
+                ]),
+                RoffNode::example(["$ bench --verbose\n$ bench --output out.1"]),
+                RoffNode::url("Homepage", "https://github.com/vv9k/roffman"),
+            ],
+        )
+}
+
+fn options_page() -> Roff {
+    let opts = (0..1000).map(|i| {
+        SynopsisOpt::new(format!("--option-{}", i))
+            .argument("VALUE")
+            .description([format!("Sets the option number {} to VALUE.", i)])
+    });
+    Roff::new("bench", SectionNumber::UserCommands)
+        .section("OPTIONS", [RoffNode::option_list::<_, SynopsisOpt>(opts)])
+}
+
+fn paragraphs_page() -> Roff {
+    let nodes = (0..10_000).map(|i| {
+        RoffNode::paragraph([
+            "The quick brown fox ".roff(),
+            i.to_string().roff().bold(),
+            " jumps over the lazy dog.".roff(),
+        ])
+    });
+    Roff::new("bench", SectionNumber::Miscellaneous).section("DESCRIPTION", nodes)
+}
+
+fn write(c: &mut Criterion) {
+    let mut file = CountingWriter {
+        inner: tempfile::tempfile().expect("failed to create a temporary file"),
+        writes: 0,
+    };
+    let mut group = c.benchmark_group("write");
+    for (name, roff) in [
+        ("small page", small_page()),
+        ("1k options", options_page()),
+        ("10k paragraphs", paragraphs_page()),
+    ] {
+        file.writes = 0;
+        roff.render(&mut file).expect("render failed");
+        println!("{}: {} writes", name, file.writes);
+
+        group.bench_with_input(BenchmarkId::new("render_to_vec", name), &roff, |b, roff| {
+            b.iter(|| roff.render_to_vec().expect("render failed"))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("unbuffered file", name),
+            &roff,
+            |b, roff| {
+                b.iter(|| {
+                    rewind(&mut file.inner);
+                    roff.render(&mut file).expect("render failed");
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn rewind(file: &mut File) {
+    file.set_len(0).expect("failed to truncate the file");
+    file.seek(SeekFrom::Start(0))
+        .expect("failed to seek the file");
+}
+
+criterion_group!(benches, write);
+criterion_main!(benches);
@@ -0,0 +1,113 @@
+use crate::{Roff, RoffError, RoffStream};
+
+use std::iter;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+impl Roff {
+    /// Renders this roff to an asynchronous `writer`. The document is rendered one section at a
+    /// time and every section is written to `writer` before the next one is rendered, so the whole
+    /// document is never buffered. The output is the same as the one of [`render`](Roff::render).
+    pub async fn render_async<W>(&self, writer: &mut W) -> Result<(), RoffError>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut stream = RoffStream::start(vec![], &self.options);
+        for page in iter::once(self).chain(&self.pages) {
+            stream.write_page_header(page)?;
+            writer.write_all(&stream.take_written()?).await?;
+            for section in &page.sections {
+                stream.write_page_section(section, page)?;
+                writer.write_all(&stream.take_written()?).await?;
+            }
+        }
+        writer.write_all(&stream.finish()?).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::readme_example;
+    use crate::{RenderOptions, Roff, RoffNode, SectionNumber};
+
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncReadExt, AsyncWrite};
+
+    /// Records every write as a separate chunk.
+    #[derive(Default)]
+    struct Chunks(Vec<Vec<u8>>);
+
+    impl AsyncWrite for Chunks {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.0.push(buf.to_vec());
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn roff() -> Roff {
+        readme_example()
+            .render_options(RenderOptions::new().max_line_width(40))
+            .section(
+                "DESCRIPTION",
+                [RoffNode::text("a line that is not terminated")],
+            )
+            .append_page(
+                Roff::new("second", SectionNumber::UserCommands).section("NAME", ["second"]),
+            )
+    }
+
+    #[tokio::test]
+    async fn it_renders_the_same_as_render() {
+        let roff = roff();
+        let rendered = roff.render_to_vec().unwrap();
+
+        // The pipe is much smaller than the document, so the output has to be read while it is
+        // being rendered.
+        let (mut writer, mut reader) = tokio::io::duplex(64);
+        let read = async {
+            let mut out = vec![];
+            reader.read_to_end(&mut out).await.unwrap();
+            out
+        };
+        let write = async {
+            roff.render_async(&mut writer).await.unwrap();
+            drop(writer);
+        };
+        let (out, _) = tokio::join!(read, write);
+        assert_eq!(out, rendered);
+    }
+
+    #[tokio::test]
+    async fn it_writes_a_chunk_per_section() {
+        let roff = roff();
+        let mut chunks = Chunks::default();
+        roff.render_async(&mut chunks).await.unwrap();
+
+        let chunks: Vec<_> = chunks.0.into_iter().filter(|c| !c.is_empty()).collect();
+        // A chunk for the header and every section of both pages, and the held back end of the
+        // last line. Lines that are not terminated yet are delivered with the next chunk.
+        assert_eq!(chunks.len(), 6);
+        assert_eq!(chunks[0], b".TH roffman 7 \"August 2021\"\n");
+        assert!(chunks[1].starts_with(b".SH \"BASIC USAGE\"\n"));
+        assert_eq!(chunks[2], b"vv9k\\(tm\n.SH DESCRIPTION\n");
+        assert_eq!(chunks[3], b"a line that is not terminated\n.TH second 1\n");
+        assert_eq!(chunks[4], b".SH NAME\n");
+        assert_eq!(chunks[5], b"second");
+        assert_eq!(chunks.concat(), roff.render_to_vec().unwrap());
+    }
+}
@@ -0,0 +1,52 @@
+use crate::{Roff, SectionNumber, StandardSection};
+
+/// Creates a [`Roff`](crate::Roff) from the metadata of the package being compiled, read from the
+/// `CARGO_PKG_NAME`, `CARGO_PKG_VERSION` and `CARGO_PKG_DESCRIPTION` environment variables at
+/// compile time. See [`Roff::from_cargo_metadata`](crate::Roff::from_cargo_metadata) for the
+/// fields that are filled.
+///
+/// ```
+/// use roffman::{roff_from_cargo, SectionNumber};
+///
+/// let roff = roff_from_cargo!(SectionNumber::UserCommands).date("August 2021");
+/// assert_eq!(
+///     roff.render_to_string()?,
+///     format!(
+///         r#".TH roffman 1 "August 2021" "roffman {}" "User Commands"
+/// .SH NAME
+/// roffman \- Crate to generate ROFF files used for manual pages."#,
+///         env!("CARGO_PKG_VERSION")
+///     )
+/// );
+/// # Ok::<(), roffman::RoffError>(())
+/// ```
+#[macro_export]
+macro_rules! roff_from_cargo {
+    ($section:expr $(,)?) => {
+        $crate::Roff::from_cargo_metadata(
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION"),
+            env!("CARGO_PKG_DESCRIPTION"),
+            $section,
+        )
+    };
+}
+
+impl Roff {
+    /// Creates a new `Roff` for the package `name` with the title set to `name`, the source set to
+    /// `name version` and a `NAME` section with the `description`. If the description is empty the
+    /// `NAME` section only contains the name. Use [`roff_from_cargo!`](crate::roff_from_cargo) to
+    /// fill the fields from the metadata of the package being compiled.
+    pub fn from_cargo_metadata(
+        name: &str,
+        version: &str,
+        description: &str,
+        section: SectionNumber,
+    ) -> Self {
+        let roff = Roff::new(name, section).source(format!("{} {}", name, version));
+        match description.trim() {
+            "" => roff.section(StandardSection::Name, [name]),
+            description => roff.name_section(description),
+        }
+    }
+}
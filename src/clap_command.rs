@@ -0,0 +1,268 @@
+use crate::{Roff, RoffNode, SectionNumber, StandardSection, SynopsisOpt};
+
+use clap::{Arg, Command};
+
+impl Roff {
+    /// Creates a page documenting a [`clap::Command`](clap::Command) in section 1 with:
+    ///
+    /// * a `NAME` section with the name and the `about` text,
+    /// * a `SYNOPSIS` section with the options, the positional arguments and the subcommand,
+    /// * a `DESCRIPTION` section with the `long_about` text, if set,
+    /// * an `OPTIONS` section listing the options followed by the positional arguments, with their
+    ///   value names, possible values and defaults,
+    /// * a `SUBCOMMANDS` section with the synopsis and options of every subcommand.
+    ///
+    /// Hidden arguments are marked as [`hidden`](SynopsisOpt::hidden) and hidden subcommands and
+    /// possible values are skipped, as are defaults and possible values hidden with
+    /// `hide_default_value` and `hide_possible_values`. The flags generated by clap, like `--version`,
+    /// are included. More sections like `EXAMPLES` can be added to the returned page before
+    /// rendering.
+    pub fn from_clap(command: &Command) -> Self {
+        let mut command = command.clone();
+        command.build();
+        let name = command.get_name().to_string();
+
+        let mut roff = Roff::new(&name, SectionNumber::UserCommands);
+        roff = match command.get_about() {
+            Some(about) => roff.name_section(about.to_string()),
+            None => roff.section(StandardSection::Name, [&name]),
+        };
+        roff = roff.section(StandardSection::Synopsis, [synopsis(&command, &name)]);
+        if let Some(long_about) = command.get_long_about() {
+            let long_about = long_about.to_string();
+            roff = roff.section(
+                StandardSection::Description,
+                long_about
+                    .split("\n\n")
+                    .map(|paragraph| RoffNode::paragraph([paragraph.trim()])),
+            );
+        }
+        roff = roff.options_section(option_list(&command));
+
+        let subcommands: Vec<_> = command
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .collect();
+        if !subcommands.is_empty() {
+            let mut nodes = vec![];
+            for subcommand in subcommands {
+                let name = format!("{} {}", name, subcommand.get_name());
+                nodes.push(RoffNode::subheading(&name));
+                if let Some(about) = subcommand
+                    .get_long_about()
+                    .or_else(|| subcommand.get_about())
+                {
+                    nodes.push(RoffNode::paragraph([about.to_string()]));
+                }
+                nodes.push(synopsis(subcommand, &name));
+                nodes.push(RoffNode::option_list(option_list(subcommand)));
+            }
+            roff = roff.section("SUBCOMMANDS", nodes);
+        }
+        roff
+    }
+}
+
+/// Returns the synopsis of `command` invoked as `name` listing its options, followed by the
+/// positional arguments and the subcommand.
+fn synopsis(command: &Command, name: &str) -> RoffNode {
+    let mut usage: Vec<String> = command
+        .get_arguments()
+        .filter(|arg| arg.is_positional() && !arg.is_hide_set())
+        .map(positional_name)
+        .collect();
+    if command.get_subcommands().any(|sub| !sub.is_hide_set()) {
+        usage.push(match command.is_subcommand_required_set() {
+            true => "<COMMAND>".to_string(),
+            false => "[COMMAND]".to_string(),
+        });
+    }
+    let opts: Vec<_> = command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .map(synopsis_opt)
+        .collect();
+    let usage = Some(usage.join(" ")).filter(|usage| !usage.is_empty());
+    RoffNode::synopsis(name, usage, opts)
+}
+
+/// Returns the options of `command` followed by its positional arguments, with descriptions.
+fn option_list(command: &Command) -> Vec<SynopsisOpt> {
+    let options = command.get_arguments().filter(|arg| !arg.is_positional());
+    let positionals = command.get_arguments().filter(|arg| arg.is_positional());
+    options
+        .map(|arg| describe(arg, synopsis_opt(arg)))
+        .chain(positionals.map(|arg| describe(arg, SynopsisOpt::new(positional_name(arg)))))
+        .collect()
+}
+
+/// Returns the name of a positional argument like `<FILE>...` or `[FILE]`.
+fn positional_name(arg: &Arg) -> String {
+    let name = value_names(arg);
+    let multiple = arg
+        .get_num_args()
+        .is_some_and(|range| range.max_values() > 1);
+    let name = match arg.is_required_set() {
+        true => format!("<{}>", name),
+        false => format!("[{}]", name),
+    };
+    match multiple {
+        true => name + "...",
+        false => name,
+    }
+}
+
+/// Returns the value names of `arg` separated with spaces, or its id in uppercase.
+fn value_names(arg: &Arg) -> String {
+    match arg.get_value_names() {
+        Some(names) if !names.is_empty() => names
+            .iter()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => arg.get_id().as_str().to_uppercase(),
+    }
+}
+
+/// Creates an option with the flags and the value names of `arg`, like `-c, --color WHEN`.
+fn synopsis_opt(arg: &Arg) -> SynopsisOpt {
+    let flags = arg
+        .get_short()
+        .map(|short| format!("-{}", short))
+        .into_iter()
+        .chain(arg.get_long().map(|long| format!("--{}", long)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut opt = SynopsisOpt::new(flags).hidden(arg.is_hide_set());
+    if arg.get_action().takes_values() {
+        opt = opt.argument(value_names(arg));
+    }
+    opt
+}
+
+/// Adds the help text, the possible values and the defaults of `arg` to `opt`.
+fn describe(arg: &Arg, mut opt: SynopsisOpt) -> SynopsisOpt {
+    opt = opt.hidden(arg.is_hide_set());
+    if let Some(help) = arg.get_long_help().or_else(|| arg.get_help()) {
+        opt = opt.description([help.to_string()]);
+    }
+    if !arg.get_action().takes_values() {
+        return opt;
+    }
+    if !arg.is_hide_possible_values_set() {
+        let values: Vec<_> = arg
+            .get_possible_values()
+            .into_iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        if !values.is_empty() {
+            opt = opt.possible_values(values);
+        }
+    }
+    let defaults = arg.get_default_values();
+    if !arg.is_hide_default_value_set() && !defaults.is_empty() {
+        let defaults: Vec<_> = defaults
+            .iter()
+            .map(|value| value.to_string_lossy())
+            .collect();
+        opt = opt.default_value(defaults.join(", "));
+    }
+    opt
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Roff;
+
+    use clap::{Arg, ArgAction, Command};
+
+    fn command() -> Command {
+        Command::new("fixture")
+            .version("1.0.0")
+            .about("does fixture things")
+            .long_about("Does fixture things.\n\nIn great detail.")
+            .arg(
+                Arg::new("verbose")
+                    .short('v')
+                    .long("verbose")
+                    .action(ArgAction::Count)
+                    .help("print more output"),
+            )
+            .arg(
+                Arg::new("color")
+                    .long("color")
+                    .value_name("WHEN")
+                    .value_parser(["auto", "always", "never"])
+                    .default_value("auto")
+                    .help("colorize the output"),
+            )
+            .arg(
+                Arg::new("debug")
+                    .long("debug")
+                    .action(ArgAction::SetTrue)
+                    .hide(true),
+            )
+            .arg(
+                Arg::new("files")
+                    .value_name("FILE")
+                    .num_args(1..)
+                    .required(true)
+                    .help("files to process"),
+            )
+            .subcommand(
+                Command::new("check")
+                    .about("checks the files")
+                    .arg(Arg::new("strict").long("strict").action(ArgAction::SetTrue)),
+            )
+            .subcommand(Command::new("internal").hide(true))
+    }
+
+    #[test]
+    fn it_documents_a_clap_command() {
+        let roff = Roff::from_clap(&command()).date("August 2021");
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            r#".TH fixture 1 "August 2021"
+.SH NAME
+fixture \- does fixture things
+.SH SYNOPSIS
+.SY fixture
+<FILE>... [COMMAND]
+.OP "\-v, \-\-verbose"
+.OP \-\-color WHEN
+.OP "\-V, \-\-version"
+.YS
+.SH DESCRIPTION
+Does fixture things.
+.P
+In great detail.
+.SH OPTIONS
+.TP
+\fB\-v, \-\-verbose\fR
+print more output
+.TP
+\fB\-\-color\fR \fIWHEN\fR
+colorize the output
+.br
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.br
+(default: auto)
+.TP
+\fB\-V, \-\-version\fR
+Print version
+.TP
+\fB<FILE>...\fR
+files to process
+.SH SUBCOMMANDS
+.SS "fixture check"
+checks the files
+.SY "fixture check"
+.OP \-\-strict
+.YS
+.TP
+\fB\-\-strict\fR
+"#
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::Roff;
+#[cfg(any(feature = "time", feature = "chrono"))]
+use crate::{RoffText, Roffable};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable used by reproducible builds to fix the build date.
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>.
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Converts the number of days since `1970-01-01` to a `(year, month, day)` triple in the
+/// proleptic Gregorian calendar.
+///
+/// Based on the `civil_from_days` algorithm by Howard Hinnant.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats the date as `YYYY-MM-DD`.
+fn format_date(year: i64, month: u32, day: u32) -> String {
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats the date of the `timestamp` (seconds since the Unix epoch) as `YYYY-MM-DD`.
+pub(crate) fn format_timestamp(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY));
+    format_date(year, month, day)
+}
+
+/// Returns the timestamp from `source_date_epoch` if it is a valid number of seconds, otherwise
+/// the current system time.
+fn today_timestamp(source_date_epoch: Option<&str>) -> i64 {
+    if let Some(timestamp) = source_date_epoch.and_then(|epoch| epoch.trim().parse().ok()) {
+        return timestamp;
+    }
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+impl Roff {
+    /// Builder method for setting the date of this roff to the current date in the `YYYY-MM-DD`
+    /// format recommended by [`man-pages(7)`](https://man7.org/linux/man-pages/man7/man-pages.7.html).
+    ///
+    /// If the `SOURCE_DATE_EPOCH` environment variable is set to a valid timestamp it is used
+    /// instead of the current date so that reproducible builds produce identical pages.
+    pub fn date_today(self) -> Self {
+        let source_date_epoch = std::env::var(SOURCE_DATE_EPOCH).ok();
+        self.date(format_timestamp(today_timestamp(
+            source_date_epoch.as_deref(),
+        )))
+    }
+}
+
+/// Dates are formatted as `YYYY-MM-DD`.
+#[cfg(feature = "time")]
+impl Roffable for time::Date {
+    fn roff(&self) -> RoffText {
+        format_date(
+            self.year().into(),
+            u8::from(self.month()).into(),
+            self.day().into(),
+        )
+        .into()
+    }
+}
+#[cfg(feature = "time")]
+impl From<time::Date> for RoffText {
+    fn from(item: time::Date) -> Self {
+        item.roff()
+    }
+}
+
+/// Only the date is used, in the offset of the value, formatted as `YYYY-MM-DD`.
+#[cfg(feature = "time")]
+impl Roffable for time::OffsetDateTime {
+    fn roff(&self) -> RoffText {
+        self.date().roff()
+    }
+}
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for RoffText {
+    fn from(item: time::OffsetDateTime) -> Self {
+        item.roff()
+    }
+}
+
+/// Dates are formatted as `YYYY-MM-DD`.
+#[cfg(feature = "chrono")]
+impl Roffable for chrono::NaiveDate {
+    fn roff(&self) -> RoffText {
+        use chrono::Datelike;
+        format_date(self.year().into(), self.month(), self.day()).into()
+    }
+}
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for RoffText {
+    fn from(item: chrono::NaiveDate) -> Self {
+        item.roff()
+    }
+}
+
+/// Only the date is used, in the time zone of the value, formatted as `YYYY-MM-DD`.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Roffable for chrono::DateTime<Tz> {
+    fn roff(&self) -> RoffText {
+        self.date_naive().roff()
+    }
+}
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for RoffText {
+    fn from(item: chrono::DateTime<Tz>) -> Self {
+        item.roff()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_days_around_the_epoch() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(1), (1970, 1, 2));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(-365), (1969, 1, 1));
+        assert_eq!(civil_from_days(364), (1970, 12, 31));
+    }
+
+    #[test]
+    fn converts_leap_years() {
+        // 2000 is divisible by 400 so it is a leap year
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        // 1900 is divisible by 100 but not by 400 so it is not a leap year
+        assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+        assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert_eq!(civil_from_days(19_416), (2023, 2, 28));
+        assert_eq!(civil_from_days(19_417), (2023, 3, 1));
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01");
+        assert_eq!(format_timestamp(86_399), "1970-01-01");
+        assert_eq!(format_timestamp(86_400), "1970-01-02");
+        assert_eq!(format_timestamp(-1), "1969-12-31");
+        assert_eq!(format_timestamp(1_628_000_000), "2021-08-03");
+    }
+
+    #[test]
+    fn uses_source_date_epoch() {
+        assert_eq!(today_timestamp(Some("1628000000")), 1_628_000_000);
+        assert_eq!(today_timestamp(Some(" 0\n")), 0);
+        assert!(today_timestamp(Some("invalid")) > 1_628_000_000);
+        assert!(today_timestamp(None) > 1_628_000_000);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_dates_work() {
+        use time::{Date, Month, UtcOffset};
+
+        let date = Date::from_calendar_date(2021, Month::August, 1).unwrap();
+        assert_eq!(date.roff().original(), "2021-08-01");
+        let roff = Roff::new("foo", crate::SectionNumber::UserCommands).date(date);
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 2021\\-08\\-01\n"
+        );
+
+        // 2021-08-01T23:00:00Z is already the next day at UTC+2.
+        let time = date.with_hms(23, 0, 0).unwrap().assume_utc();
+        assert_eq!(time.roff().original(), "2021-08-01");
+        let offset = UtcOffset::from_hms(2, 0, 0).unwrap();
+        assert_eq!(time.to_offset(offset).roff().original(), "2021-08-02");
+        assert_eq!(
+            Date::from_calendar_date(987, Month::January, 9)
+                .unwrap()
+                .roff()
+                .original(),
+            "0987-01-09"
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_dates_work() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        let date = NaiveDate::from_ymd_opt(2021, 8, 1).unwrap();
+        assert_eq!(date.roff().original(), "2021-08-01");
+        let roff = Roff::new("foo", crate::SectionNumber::UserCommands).date(date);
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 2021\\-08\\-01\n"
+        );
+
+        let time = Utc.with_ymd_and_hms(2021, 8, 1, 23, 0, 0).unwrap();
+        assert_eq!(time.roff().original(), "2021-08-01");
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(time.with_timezone(&offset).roff().original(), "2021-08-02");
+    }
+}
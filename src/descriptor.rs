@@ -0,0 +1,447 @@
+use crate::{Roff, RoffError, RoffNode, Section, SectionId, SectionNumber, SynopsisOpt};
+
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+/// Format of a descriptor read by [`Roff::from_descriptor`](Roff::from_descriptor).
+pub enum DescriptorFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Roff {
+    /// Builds a document from a declarative descriptor so that the content of a page can be
+    /// edited without writing Rust. The descriptor has the same schema in every format, in TOML:
+    ///
+    /// ```toml
+    /// title = "foo"
+    /// section = 1                     # or a string like "3pm"
+    /// date = "August 2021"            # optional
+    /// source = "foo 1.0.0"            # optional
+    /// manual = "User Commands"        # optional
+    /// aliases = ["bar"]               # optional
+    ///
+    /// [[sections]]
+    /// title = "DESCRIPTION"
+    /// subtitle = "details"            # optional
+    /// content = [
+    ///     { text = "Text continuing the previous line." },
+    ///     { paragraph = "A new paragraph." },
+    ///     { subheading = "A sub heading" },
+    ///     { example = "$ foo --bar" },
+    ///     { comment = "Not displayed." },
+    ///     { url = { name = "GitHub", address = "https://github.com/vv9k/roffman" } },
+    ///     { email = { name = "the author", address = "author@example.com" } },
+    ///     { tagged = { tag = "FOO_DIR", body = "Directory with the data." } },
+    ///     { indented = { body = "Indented text.", title = "note", indentation = 4 } },
+    ///     { whatis = { names = ["foo", "bar"], description = "does things" } },
+    ///     { synopsis = { command = "foo", description = "[OPTIONS]", options = [{ name = "-v" }] } },
+    ///     { options = [
+    ///         { name = "--color", argument = "WHEN", description = "Colorize the output.",
+    ///           possible_values = ["auto", "never"], default = "auto", hidden = false },
+    ///     ] },
+    /// ]
+    /// ```
+    ///
+    /// Only `title`, `section`, the section `title` and the option `name` are required. The text
+    /// is escaped like any other text of a document. Unknown keys and values of a wrong type are
+    /// reported as [`RoffError::InvalidDescriptor`](RoffError::InvalidDescriptor) with the path of
+    /// the offending value, like `sections[1].content[0].paragraph`.
+    pub fn from_descriptor(descriptor: &str, format: DescriptorFormat) -> Result<Self, RoffError> {
+        let value: Value = match format {
+            DescriptorFormat::Toml => toml::from_str(descriptor).map_err(|err| err.to_string()),
+            DescriptorFormat::Yaml => {
+                serde_yaml_ng::from_str(descriptor).map_err(|err| err.to_string())
+            }
+            DescriptorFormat::Json => {
+                serde_json::from_str(descriptor).map_err(|err| err.to_string())
+            }
+        }
+        .map_err(|message| RoffError::InvalidDescriptor {
+            path: String::new(),
+            message,
+        })?;
+
+        let root = Field::new(&value, String::new());
+        let table = root.table(&[
+            "title", "section", "date", "source", "manual", "aliases", "sections",
+        ])?;
+        let title = root.required(table, "title")?.string()?;
+        let mut roff = Roff::new(title, root.required(table, "section")?.section_id()?);
+        if let Some(date) = root.optional(table, "date") {
+            roff = roff.date(date.string()?);
+        }
+        if let Some(source) = root.optional(table, "source") {
+            roff = roff.source(source.string()?);
+        }
+        if let Some(manual) = root.optional(table, "manual") {
+            roff = roff.manual(manual.string()?);
+        }
+        if let Some(aliases) = root.optional(table, "aliases") {
+            roff = roff.aliases(aliases.strings()?);
+        }
+        if let Some(sections) = root.optional(table, "sections") {
+            for section in sections.array()? {
+                roff = roff.add_section(section.section()?);
+            }
+        }
+        Ok(roff)
+    }
+}
+
+/// A value of the descriptor together with its path used in errors.
+struct Field<'a> {
+    value: &'a Value,
+    path: String,
+}
+
+impl<'a> Field<'a> {
+    fn new(value: &'a Value, path: String) -> Self {
+        Self { value, path }
+    }
+
+    fn error(&self, message: impl Into<String>) -> RoffError {
+        RoffError::InvalidDescriptor {
+            path: self.path.clone(),
+            message: message.into(),
+        }
+    }
+
+    fn child(&self, key: &str, value: &'a Value) -> Field<'a> {
+        match self.path.is_empty() {
+            true => Field::new(value, key.to_string()),
+            false => Field::new(value, format!("{}.{}", self.path, key)),
+        }
+    }
+
+    /// Returns the entries of this table making sure that all keys are `allowed`.
+    fn table(&self, allowed: &[&str]) -> Result<&'a Map<String, Value>, RoffError> {
+        let table = self
+            .value
+            .as_object()
+            .ok_or_else(|| self.error("expected a table"))?;
+        match table.keys().find(|key| !allowed.contains(&key.as_str())) {
+            Some(key) => Err(self.child(key, &Value::Null).error(format!(
+                "unknown key `{}`, expected one of {}",
+                key,
+                allowed.join(", ")
+            ))),
+            None => Ok(table),
+        }
+    }
+
+    fn optional(&self, table: &'a Map<String, Value>, key: &str) -> Option<Field<'a>> {
+        table.get(key).map(|value| self.child(key, value))
+    }
+
+    fn required(&self, table: &'a Map<String, Value>, key: &str) -> Result<Field<'a>, RoffError> {
+        self.optional(table, key)
+            .ok_or_else(|| self.error(format!("missing key `{}`", key)))
+    }
+
+    fn string(&self) -> Result<&'a str, RoffError> {
+        self.value
+            .as_str()
+            .ok_or_else(|| self.error("expected a string"))
+    }
+
+    fn u8(&self) -> Result<u8, RoffError> {
+        self.value
+            .as_u64()
+            .and_then(|n| u8::try_from(n).ok())
+            .ok_or_else(|| self.error("expected a number between 0 and 255"))
+    }
+
+    /// Reads a section number like `1` or a string identifier like `"3pm"`.
+    fn section_id(&self) -> Result<SectionId, RoffError> {
+        let section = match self.value.as_str() {
+            Some(id) => id.parse(),
+            None => SectionNumber::try_from(self.u8()?).map(SectionId::from),
+        };
+        section
+            .map_err(|_| self.error("expected a number between 1 and 255 or a section like `3pm`"))
+    }
+
+    fn bool(&self) -> Result<bool, RoffError> {
+        self.value
+            .as_bool()
+            .ok_or_else(|| self.error("expected a boolean"))
+    }
+
+    fn array(&self) -> Result<Vec<Field<'a>>, RoffError> {
+        let items = self
+            .value
+            .as_array()
+            .ok_or_else(|| self.error("expected an array"))?;
+        Ok(items
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Field::new(value, format!("{}[{}]", self.path, i)))
+            .collect())
+    }
+
+    fn strings(&self) -> Result<Vec<&'a str>, RoffError> {
+        self.array()?.iter().map(Field::string).collect()
+    }
+
+    fn section(&self) -> Result<Section, RoffError> {
+        let table = self.table(&["title", "subtitle", "content"])?;
+        let mut content = vec![];
+        if let Some(items) = self.optional(table, "content") {
+            for item in items.array()? {
+                content.push(item.node()?);
+            }
+        }
+        let mut section = Section::new(self.required(table, "title")?.string()?, content);
+        if let Some(subtitle) = self.optional(table, "subtitle") {
+            section = section.subtitle(subtitle.string()?);
+        }
+        Ok(section)
+    }
+
+    /// Parses a content item, a table with a single key naming the kind of the node.
+    fn node(&self) -> Result<RoffNode, RoffError> {
+        let table = self.table(&[
+            "text",
+            "paragraph",
+            "subheading",
+            "example",
+            "comment",
+            "url",
+            "email",
+            "tagged",
+            "indented",
+            "whatis",
+            "synopsis",
+            "options",
+        ])?;
+        let mut entries = table.iter();
+        let (kind, value) = match (entries.next(), entries.next()) {
+            (Some(entry), None) => entry,
+            _ => return Err(self.error("expected a table with a single key")),
+        };
+        let field = self.child(kind, value);
+        Ok(match kind.as_str() {
+            "text" => RoffNode::text(field.string()?),
+            "paragraph" => RoffNode::paragraph([field.string()?]),
+            "subheading" => RoffNode::subheading(field.string()?),
+            "example" => RoffNode::example([field.string()?]),
+            "comment" => RoffNode::comment(field.string()?),
+            "url" | "email" => {
+                let table = field.table(&["name", "address"])?;
+                let name = field.required(table, "name")?.string()?;
+                let address = field.required(table, "address")?.string()?;
+                match kind.as_str() {
+                    "url" => RoffNode::url(name, address),
+                    _ => RoffNode::email(name, address),
+                }
+            }
+            "tagged" => {
+                let table = field.table(&["tag", "body"])?;
+                RoffNode::tagged_paragraph(
+                    [field.required(table, "body")?.string()?],
+                    field.required(table, "tag")?.string()?,
+                )
+            }
+            "indented" => {
+                let table = field.table(&["body", "title", "indentation"])?;
+                RoffNode::indented_paragraph(
+                    [field.required(table, "body")?.string()?],
+                    field
+                        .optional(table, "indentation")
+                        .map(|indentation| indentation.u8())
+                        .transpose()?,
+                    field
+                        .optional(table, "title")
+                        .map(|title| title.string())
+                        .transpose()?,
+                )
+            }
+            "whatis" => {
+                let table = field.table(&["names", "description"])?;
+                RoffNode::whatis(
+                    field.required(table, "names")?.strings()?,
+                    field.required(table, "description")?.string()?,
+                )
+            }
+            "synopsis" => {
+                let table = field.table(&["command", "description", "options"])?;
+                let description = field
+                    .optional(table, "description")
+                    .map(|description| description.string())
+                    .transpose()?;
+                RoffNode::synopsis(
+                    field.required(table, "command")?.string()?,
+                    description,
+                    field
+                        .optional(table, "options")
+                        .map(|options| options.options())
+                        .transpose()?
+                        .unwrap_or_default(),
+                )
+            }
+            _ => RoffNode::option_list(field.options()?),
+        })
+    }
+
+    fn options(&self) -> Result<Vec<SynopsisOpt>, RoffError> {
+        self.array()?.iter().map(Field::option).collect()
+    }
+
+    fn option(&self) -> Result<SynopsisOpt, RoffError> {
+        let table = self.table(&[
+            "name",
+            "argument",
+            "description",
+            "possible_values",
+            "default",
+            "hidden",
+        ])?;
+        let mut opt = SynopsisOpt::new(self.required(table, "name")?.string()?);
+        if let Some(argument) = self.optional(table, "argument") {
+            opt = opt.argument(argument.string()?);
+        }
+        if let Some(description) = self.optional(table, "description") {
+            opt = opt.description([description.string()?]);
+        }
+        if let Some(values) = self.optional(table, "possible_values") {
+            opt = opt.possible_values(values.strings()?);
+        }
+        if let Some(default) = self.optional(table, "default") {
+            opt = opt.default_value(default.string()?);
+        }
+        if let Some(hidden) = self.optional(table, "hidden") {
+            opt = opt.hidden(hidden.bool()?);
+        }
+        Ok(opt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DescriptorFormat;
+    use crate::{Roff, RoffError};
+
+    const REFERENCE: &str = include_str!("../tests/descriptor/reference.toml");
+
+    fn error(descriptor: &str, format: DescriptorFormat) -> (String, String) {
+        match Roff::from_descriptor(descriptor, format) {
+            Err(RoffError::InvalidDescriptor { path, message }) => (path, message),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_builds_the_reference_descriptor() {
+        let roff = Roff::from_descriptor(REFERENCE, DescriptorFormat::Toml).unwrap();
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            include_str!("../tests/golden/descriptor.1")
+        );
+    }
+
+    #[test]
+    fn all_formats_build_the_same_document() {
+        let toml = Roff::from_descriptor(
+            r#"
+title = "foo"
+section = 5
+[[sections]]
+title = "NAME"
+content = [{ whatis = { names = ["foo"], description = "does things" } }]
+"#,
+            DescriptorFormat::Toml,
+        )
+        .unwrap();
+        let yaml = Roff::from_descriptor(
+            "
+title: foo
+section: 5
+sections:
+  - title: NAME
+    content:
+      - whatis:
+          names: [foo]
+          description: does things
+",
+            DescriptorFormat::Yaml,
+        )
+        .unwrap();
+        let json = Roff::from_descriptor(
+            r#"{"title": "foo", "section": 5, "sections": [{"title": "NAME", "content": [
+                {"whatis": {"names": ["foo"], "description": "does things"}}
+            ]}]}"#,
+            DescriptorFormat::Json,
+        )
+        .unwrap();
+
+        assert_eq!(toml, yaml);
+        assert_eq!(toml, json);
+        assert_eq!(
+            toml.render_to_string().unwrap(),
+            ".TH foo 5\n.SH NAME\nfoo \\- does things"
+        );
+    }
+
+    #[test]
+    fn it_reports_the_path_of_invalid_values() {
+        let (path, message) = error(
+            r#"
+title = "foo"
+section = 1
+[[sections]]
+title = "NAME"
+[[sections]]
+title = "DESCRIPTION"
+content = [{ paragraph = "text" }, { paragraf = "text" }]
+"#,
+            DescriptorFormat::Toml,
+        );
+        assert_eq!(path, "sections[1].content[1].paragraf");
+        assert!(message.starts_with("unknown key `paragraf`, expected one of text, paragraph"));
+
+        let (path, message) = error(
+            r#"{"title": "foo", "section": 1, "sections": [{"title": "OPTIONS", "content": [
+                {"options": [{"name": "-v", "hidden": "yes"}]}
+            ]}]}"#,
+            DescriptorFormat::Json,
+        );
+        assert_eq!(path, "sections[0].content[0].options[0].hidden");
+        assert_eq!(message, "expected a boolean");
+
+        let (path, message) = error("title: foo\nsection: 1\ndate: 2021", DescriptorFormat::Yaml);
+        assert_eq!(path, "date");
+        assert_eq!(message, "expected a string");
+
+        assert_eq!(
+            error("title = \"foo\"", DescriptorFormat::Toml),
+            (String::new(), "missing key `section`".to_string())
+        );
+        assert_eq!(
+            error("title = \"foo\"\nsection = 0", DescriptorFormat::Toml),
+            (
+                "section".to_string(),
+                "expected a number between 1 and 255 or a section like `3pm`".to_string()
+            )
+        );
+        assert_eq!(
+            error(
+                "title = \"foo\"\nsection = \"3 pm\"",
+                DescriptorFormat::Toml
+            )
+            .0,
+            "section"
+        );
+        assert_eq!(error("title = ", DescriptorFormat::Toml).0, "");
+        assert_eq!(
+            RoffError::InvalidDescriptor {
+                path: "sections[0]".to_string(),
+                message: "expected a table".to_string(),
+            }
+            .to_string(),
+            "Invalid descriptor at `sections[0]` - expected a table"
+        );
+    }
+}
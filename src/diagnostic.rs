@@ -0,0 +1,244 @@
+use crate::{Roff, RoffNode};
+
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Describes how serious a [`Diagnostic`](Diagnostic) is.
+pub enum Severity {
+    /// Something that might be worth looking at but is not necessarily wrong.
+    Info,
+    /// The document can be rendered but likely won't look as intended.
+    Warning,
+    /// The document is malformed.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+/// Location of an element in a [`Roff`](Roff) document. An empty path points to the document
+/// itself, a path with only a section points to the whole section and every additional node index
+/// descends one level deeper into the nodes nested inside of the previous node. Paths into pages
+/// appended with [`Roff::append_page`](Roff::append_page) also carry the index of the page.
+pub struct NodePath {
+    page: Option<usize>,
+    section: Option<(usize, String)>,
+    nodes: Vec<usize>,
+}
+
+impl NodePath {
+    /// Creates a path pointing to the whole document.
+    pub fn document() -> Self {
+        Self::default()
+    }
+
+    /// Creates a path pointing to the section at `index` with `title`.
+    pub fn section(index: usize, title: impl Into<String>) -> Self {
+        Self {
+            page: None,
+            section: Some((index, title.into())),
+            nodes: vec![],
+        }
+    }
+
+    /// Creates a path pointing to the whole page appended at `index`.
+    pub fn page(index: usize) -> Self {
+        Self::document().in_page(index)
+    }
+
+    /// Returns this path relative to the page appended at `index` instead of the document.
+    pub fn in_page(mut self, index: usize) -> Self {
+        self.page = Some(index);
+        self
+    }
+
+    /// Returns this path extended with the node at `index`.
+    pub fn node(mut self, index: usize) -> Self {
+        self.nodes.push(index);
+        self
+    }
+
+    /// Returns the index of the appended page this path points into, or `None` if it points into
+    /// the document itself.
+    pub fn page_index(&self) -> Option<usize> {
+        self.page
+    }
+
+    /// Returns the index of the section this path points to.
+    pub fn section_index(&self) -> Option<usize> {
+        self.section.as_ref().map(|(index, _)| *index)
+    }
+
+    /// Returns the title of the section this path points to.
+    pub fn section_title(&self) -> Option<&str> {
+        self.section.as_ref().map(|(_, title)| title.as_str())
+    }
+
+    /// Returns the chain of node indices of this path.
+    pub fn node_indices(&self) -> &[usize] {
+        &self.nodes
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(page) = self.page {
+            write!(f, "pages[{}]", page)?;
+        }
+        match (&self.section, self.page) {
+            (Some((index, title)), None) => write!(f, "sections[{}] ({})", index, title)?,
+            (Some((index, title)), Some(_)) => write!(f, ".sections[{}] ({})", index, title)?,
+            (None, Some(_)) => return Ok(()),
+            (None, None) => return f.write_str("document"),
+        }
+        for index in &self.nodes {
+            write!(f, ".nodes[{}]", index)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A problem found in a [`Roff`](Roff) document together with its location.
+pub struct Diagnostic {
+    severity: Severity,
+    message: String,
+    path: NodePath,
+}
+
+impl Diagnostic {
+    /// Creates a new diagnostic with `severity` and `message` located at `path`.
+    pub fn new(severity: Severity, message: impl Into<String>, path: NodePath) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            path,
+        }
+    }
+
+    /// Returns the severity of this diagnostic.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the human-readable description of this diagnostic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the location of this diagnostic.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+
+    /// Returns the node of `roff` this diagnostic points to. Returns `None` if the diagnostic
+    /// doesn't point to a node or the node doesn't exist in `roff`.
+    pub fn node<'a>(&self, roff: &'a Roff) -> Option<&'a RoffNode> {
+        roff.node_at(&self.path)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} at {}", self.severity, self.message, self.path)
+    }
+}
+
+impl Roff {
+    /// Returns the node located at `path`. Returns `None` if the path doesn't point to a node or
+    /// the node doesn't exist.
+    pub fn node_at(&self, path: &NodePath) -> Option<&RoffNode> {
+        let page = match path.page_index() {
+            Some(index) => self.pages.get(index)?,
+            None => self,
+        };
+        let section = page.sections.get(path.section_index()?)?;
+        let (first, rest) = path.node_indices().split_first()?;
+        let mut node = section.get_nodes().get(*first)?;
+        for index in rest {
+            node = node.children().get(*index)?;
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RoffNode, SectionNumber};
+
+    #[test]
+    fn displays_paths() {
+        assert_eq!(NodePath::document().to_string(), "document");
+        assert_eq!(
+            NodePath::section(3, "OPTIONS").node(7).node(1).to_string(),
+            "sections[3] (OPTIONS).nodes[7].nodes[1]"
+        );
+        assert_eq!(NodePath::page(1).to_string(), "pages[1]");
+        assert_eq!(
+            NodePath::section(0, "NAME").node(2).in_page(1).to_string(),
+            "pages[1].sections[0] (NAME).nodes[2]"
+        );
+        assert_eq!(
+            Diagnostic::new(
+                Severity::Warning,
+                "something is wrong",
+                NodePath::section(0, "NAME").node(2)
+            )
+            .to_string(),
+            "warning: something is wrong at sections[0] (NAME).nodes[2]"
+        );
+    }
+
+    #[test]
+    fn finds_nodes_by_path() {
+        let roff = Roff::new("test", SectionNumber::UserCommands)
+            .section("FIRST", ["first"])
+            .section(
+                "SECOND",
+                [
+                    RoffNode::text("text"),
+                    RoffNode::paragraph([
+                        RoffNode::text("paragraph"),
+                        RoffNode::nested([RoffNode::linebreak(), RoffNode::text("nested")]),
+                    ]),
+                ],
+            );
+
+        let diagnostic = Diagnostic::new(
+            Severity::Info,
+            "nested text",
+            NodePath::section(1, "SECOND").node(1).node(1).node(1),
+        );
+        let node = diagnostic.node(&roff).unwrap();
+        assert_eq!(
+            format!("{:?}", node),
+            format!("{:?}", RoffNode::text("nested"))
+        );
+
+        assert!(roff.node_at(&NodePath::document()).is_none());
+        assert!(roff.node_at(&NodePath::section(1, "SECOND")).is_none());
+        assert!(roff
+            .node_at(&NodePath::section(1, "SECOND").node(0).node(0))
+            .is_none());
+        assert!(roff
+            .node_at(&NodePath::section(2, "THIRD").node(0))
+            .is_none());
+
+        let roff = Roff::new("first", SectionNumber::UserCommands)
+            .append_page(roff)
+            .append_page(Roff::new("empty", SectionNumber::UserCommands));
+        let path = NodePath::section(1, "SECOND").node(0).in_page(0);
+        assert_eq!(roff.node_at(&path), Some(&RoffNode::text("text")));
+        assert!(roff.node_at(&path.clone().in_page(1)).is_none());
+        assert!(roff.node_at(&path.in_page(2)).is_none());
+    }
+}
@@ -0,0 +1,679 @@
+use crate::node::RoffNodeInner;
+use crate::{NodePath, Roff, RoffNode, RoffText, Section, SynopsisItem, SynopsisOpt};
+
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// The kind of a change found by [`diff`](diff).
+pub enum ChangeKind {
+    /// The title of the document changed.
+    TitleChanged,
+    /// The section number of the document changed.
+    SectionNumberChanged,
+    /// The date was added, removed or changed.
+    DateChanged,
+    /// The source was added, removed or changed.
+    SourceChanged,
+    /// The manual title was added, removed or changed.
+    ManualChanged,
+    /// A section is only present in the new document.
+    SectionAdded,
+    /// A section is only present in the old document.
+    SectionRemoved,
+    /// The sections present in both documents are in a different order.
+    SectionsReordered,
+    /// The subtitle of a section was added, removed or changed.
+    SubtitleChanged,
+    /// A node is only present in the new version of a section.
+    NodeAdded,
+    /// A node is only present in the old version of a section.
+    NodeRemoved,
+    /// A node was replaced by a different node of the same kind.
+    NodeChanged,
+    /// An option was added to a synopsis or an option list.
+    OptionAdded,
+    /// An option was removed from a synopsis or an option list.
+    OptionRemoved,
+    /// The description, argument or values of an option changed.
+    OptionChanged,
+    /// An appended page is only present in the new document.
+    PageAdded,
+    /// An appended page is only present in the old document.
+    PageRemoved,
+    /// The appended pages present in both documents are in a different order.
+    PagesReordered,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A single difference between two documents found by [`diff`](diff).
+pub struct DocumentChange {
+    kind: ChangeKind,
+    message: String,
+    path: NodePath,
+    /// The name of the appended page the change was found in.
+    page: Option<String>,
+}
+
+impl DocumentChange {
+    fn new(kind: ChangeKind, message: impl Into<String>, path: NodePath) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            path,
+            page: None,
+        }
+    }
+
+    /// Returns the kind of this change.
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+
+    /// Returns the human-readable description of this change.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the location of the change. Removed pages, sections and nodes point into the old
+    /// document, everything else points into the new one.
+    pub fn path(&self) -> &NodePath {
+        &self.path
+    }
+}
+
+impl fmt::Display for DocumentChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ChangeKind::*;
+        if let Some(page) = &self.page {
+            write!(f, "page `{}`: ", page)?;
+        }
+        match (self.kind, self.path.section_title()) {
+            (
+                SubtitleChanged | NodeAdded | NodeRemoved | NodeChanged | OptionAdded
+                | OptionRemoved | OptionChanged,
+                Some(title),
+            ) => {
+                write!(f, "{}: {}", title, self.message)
+            }
+            _ => f.write_str(&self.message),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+/// The differences between two documents returned by [`diff`](diff), in the order of the
+/// document. Displayed as one change per line.
+pub struct RoffDiff {
+    changes: Vec<DocumentChange>,
+}
+
+impl RoffDiff {
+    /// Returns `true` if the documents have the same content.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns all of the changes.
+    pub fn changes(&self) -> &[DocumentChange] {
+        &self.changes
+    }
+
+    fn push(&mut self, kind: ChangeKind, message: impl Into<String>, path: NodePath) {
+        self.changes.push(DocumentChange::new(kind, message, path));
+    }
+}
+
+impl fmt::Display for RoffDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            change.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the structure of two documents and returns what changed from `old` to `new`: the title,
+/// section number and date, the sections that were added, removed or reordered and the nodes
+/// changed in every section present in both documents. Sections are matched by their titles and
+/// nodes by equality. Appended pages are matched by their title and section number and compared
+/// the same way.
+///
+/// ```
+/// use roffman::{diff, Roff, SectionNumber, SynopsisOpt};
+///
+/// let old = Roff::new("foo", SectionNumber::UserCommands)
+///     .options_section([SynopsisOpt::new("--no-color")]);
+/// let new = Roff::new("foo", SectionNumber::UserCommands)
+///     .options_section([SynopsisOpt::new("--color")]);
+///
+/// assert_eq!(
+///     diff(&old, &new).to_string(),
+///     "OPTIONS: option `--color` added\nOPTIONS: option `--no-color` removed"
+/// );
+/// ```
+pub fn diff(old: &Roff, new: &Roff) -> RoffDiff {
+    let mut out = RoffDiff::default();
+
+    let (old_title, new_title) = (old.get_title().original(), new.get_title().original());
+    if old_title != new_title {
+        out.push(
+            ChangeKind::TitleChanged,
+            format!("title changed from `{}` to `{}`", old_title, new_title),
+            NodePath::document(),
+        );
+    }
+    let (old_number, new_number) = (
+        old.get_section_id().to_string(),
+        new.get_section_id().to_string(),
+    );
+    if old_number != new_number {
+        out.push(
+            ChangeKind::SectionNumberChanged,
+            format!(
+                "section number changed from {} to {}",
+                old_number, new_number
+            ),
+            NodePath::document(),
+        );
+    }
+    if let Some(message) = describe_text_change("date", old.get_date(), new.get_date()) {
+        out.push(ChangeKind::DateChanged, message, NodePath::document());
+    }
+    if let Some(message) = describe_text_change("source", old.get_source(), new.get_source()) {
+        out.push(ChangeKind::SourceChanged, message, NodePath::document());
+    }
+    if let Some(message) = describe_text_change("manual", old.get_manual(), new.get_manual()) {
+        out.push(ChangeKind::ManualChanged, message, NodePath::document());
+    }
+
+    diff_sections(old.get_sections(), new.get_sections(), &mut out);
+    diff_pages(&old.pages, &new.pages, &mut out);
+    out
+}
+
+/// Returns the name of a page like `foo(1)`.
+fn page_name(page: &Roff) -> String {
+    format!("{}({})", page.get_title().original(), page.get_section_id())
+}
+
+/// Returns the index of the matching old item for every new item. Items with the same key are
+/// matched in order of appearance.
+fn match_by<T, K: PartialEq>(old: &[T], new: &[T], key: impl Fn(&T) -> K) -> Vec<Option<usize>> {
+    let mut matched = vec![false; old.len()];
+    new.iter()
+        .map(|item| {
+            let wanted = key(item);
+            let i = (0..old.len()).find(|&i| !matched[i] && key(&old[i]) == wanted)?;
+            matched[i] = true;
+            Some(i)
+        })
+        .collect()
+}
+
+fn diff_pages(old: &[Roff], new: &[Roff], out: &mut RoffDiff) {
+    let pairs = match_by(old, new, page_name);
+
+    for (i, page) in old.iter().enumerate() {
+        if !pairs.contains(&Some(i)) {
+            out.push(
+                ChangeKind::PageRemoved,
+                format!("page `{}` removed", page_name(page)),
+                NodePath::page(i),
+            );
+        }
+    }
+    for (j, page) in new.iter().enumerate() {
+        if pairs[j].is_none() {
+            out.push(
+                ChangeKind::PageAdded,
+                format!("page `{}` added", page_name(page)),
+                NodePath::page(j),
+            );
+        }
+    }
+
+    let common: Vec<usize> = pairs.iter().flatten().copied().collect();
+    if common.windows(2).any(|pair| pair[0] > pair[1]) {
+        let names: Vec<_> = common.iter().map(|&i| page_name(&old[i])).collect();
+        out.push(
+            ChangeKind::PagesReordered,
+            format!("pages reordered to {}", names.join(", ")),
+            NodePath::document(),
+        );
+    }
+
+    for (j, i) in pairs.into_iter().enumerate() {
+        if let Some(i) = i {
+            for mut change in diff(&old[i], &new[j]).changes {
+                let removed = matches!(
+                    change.kind,
+                    ChangeKind::SectionRemoved | ChangeKind::NodeRemoved
+                );
+                change.path = change.path.in_page(if removed { i } else { j });
+                change.page = Some(page_name(&new[j]));
+                out.changes.push(change);
+            }
+        }
+    }
+}
+
+/// Describes how an optional piece of text named `what` changed, if it did.
+fn describe_text_change(
+    what: &str,
+    old: Option<&RoffText>,
+    new: Option<&RoffText>,
+) -> Option<String> {
+    match (old, new) {
+        (Some(old), Some(new)) if old.original() != new.original() => Some(format!(
+            "{} changed from `{}` to `{}`",
+            what,
+            old.original(),
+            new.original()
+        )),
+        (None, Some(new)) => Some(format!("{} `{}` added", what, new.original())),
+        (Some(old), None) => Some(format!("{} `{}` removed", what, old.original())),
+        _ => None,
+    }
+}
+
+fn diff_sections(old: &[Section], new: &[Section], out: &mut RoffDiff) {
+    let pairs = match_by(old, new, |section| {
+        section.get_title().original().into_owned()
+    });
+
+    for (i, section) in old.iter().enumerate() {
+        if !pairs.contains(&Some(i)) {
+            let title = section.get_title().original();
+            out.push(
+                ChangeKind::SectionRemoved,
+                format!("section `{}` removed", title),
+                NodePath::section(i, title),
+            );
+        }
+    }
+    for (j, section) in new.iter().enumerate() {
+        if pairs[j].is_none() {
+            let title = section.get_title().original();
+            out.push(
+                ChangeKind::SectionAdded,
+                format!("section `{}` added", title),
+                NodePath::section(j, title),
+            );
+        }
+    }
+
+    let common: Vec<usize> = pairs.iter().flatten().copied().collect();
+    if common.windows(2).any(|pair| pair[0] > pair[1]) {
+        let titles: Vec<_> = common
+            .iter()
+            .map(|&i| old[i].get_title().original().into_owned())
+            .collect();
+        out.push(
+            ChangeKind::SectionsReordered,
+            format!("sections reordered to {}", titles.join(", ")),
+            NodePath::document(),
+        );
+    }
+
+    for (j, i) in pairs.into_iter().enumerate() {
+        if let Some(i) = i {
+            let title = new[j].get_title().original();
+            let paths = (
+                NodePath::section(i, title.clone()),
+                NodePath::section(j, title),
+            );
+            diff_section(&old[i], &new[j], paths, out);
+        }
+    }
+}
+
+/// A step of the alignment of two node lists.
+enum Step {
+    Same,
+    Removed(usize),
+    Added(usize),
+}
+
+/// Aligns `old` and `new` along their longest common subsequence.
+fn align(old: &[RoffNode], new: &[RoffNode]) -> Vec<Step> {
+    // lengths[i][j] is the length of the LCS of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            steps.push(Step::Same);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            steps.push(Step::Removed(i));
+            i += 1;
+        } else {
+            steps.push(Step::Added(j));
+            j += 1;
+        }
+    }
+    steps
+}
+
+/// Returns the 1-based position of every node among the nodes of the same kind, so that the third
+/// node of a section can be described as `paragraph 2`.
+fn ordinals(nodes: &[RoffNode]) -> Vec<usize> {
+    let mut counts: Vec<(&str, usize)> = vec![];
+    nodes
+        .iter()
+        .map(|node| {
+            let kind = node.kind_name();
+            match counts.iter_mut().find(|(name, _)| *name == kind) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    counts.push((kind, 1));
+                    1
+                }
+            }
+        })
+        .collect()
+}
+
+/// Reports the changes between two versions of a section located at `old_path` in the old document
+/// and at `path` in the new one.
+fn diff_section(
+    old: &Section,
+    new: &Section,
+    (old_path, path): (NodePath, NodePath),
+    out: &mut RoffDiff,
+) {
+    if let Some(message) = describe_text_change("subtitle", old.get_subtitle(), new.get_subtitle())
+    {
+        out.push(ChangeKind::SubtitleChanged, message, path.clone());
+    }
+
+    let (old_nodes, new_nodes) = (old.get_nodes(), new.get_nodes());
+    let (old_ordinals, new_ordinals) = (ordinals(old_nodes), ordinals(new_nodes));
+    let describe = |node: &RoffNode, ordinal: usize| format!("{} {}", node.kind_name(), ordinal);
+
+    let mut removed = vec![];
+    let mut added = vec![];
+    let mut steps = align(old_nodes, new_nodes).into_iter().peekable();
+    while let Some(step) = steps.next() {
+        match step {
+            Step::Removed(i) => removed.push(i),
+            Step::Added(j) => added.push(j),
+            Step::Same => {}
+        }
+        if !matches!(steps.peek(), None | Some(Step::Same)) {
+            continue;
+        }
+
+        // A run of removed and added nodes between two unchanged ones. Nodes of the same kind at
+        // the same position of the run are reported as changed.
+        let mut added_run = std::mem::take(&mut added).into_iter().peekable();
+        for i in std::mem::take(&mut removed) {
+            let old_node = &old_nodes[i];
+            match added_run.next_if(|&j| new_nodes[j].kind_name() == old_node.kind_name()) {
+                Some(j) => {
+                    let new_node = &new_nodes[j];
+                    let node_path = path.clone().node(j);
+                    if !diff_options(old_node, new_node, &node_path, out) {
+                        out.push(
+                            ChangeKind::NodeChanged,
+                            format!("{} changed", describe(new_node, new_ordinals[j])),
+                            node_path,
+                        );
+                    }
+                }
+                None => out.push(
+                    ChangeKind::NodeRemoved,
+                    format!("{} removed", describe(old_node, old_ordinals[i])),
+                    old_path.clone().node(i),
+                ),
+            }
+        }
+        for j in added_run {
+            out.push(
+                ChangeKind::NodeAdded,
+                format!("{} added", describe(&new_nodes[j], new_ordinals[j])),
+                path.clone().node(j),
+            );
+        }
+    }
+}
+
+/// Returns the options of a synopsis or an option list node, including the options in groups.
+fn options(node: &RoffNode) -> Option<Vec<&SynopsisOpt>> {
+    let items = match node.inner_ref() {
+        RoffNodeInner::Synopsis { opts, .. } | RoffNodeInner::OptionList(opts) => opts,
+        _ => return None,
+    };
+    Some(
+        items
+            .iter()
+            .flat_map(|item| match item {
+                SynopsisItem::Opt(opt) => std::slice::from_ref(opt),
+                SynopsisItem::Group(group) => group.opts.as_slice(),
+            })
+            .collect(),
+    )
+}
+
+/// Reports the options added, removed or changed between two versions of a synopsis or an option
+/// list. Returns `false` if the nodes don't have options or their options are the same.
+fn diff_options(old: &RoffNode, new: &RoffNode, path: &NodePath, out: &mut RoffDiff) -> bool {
+    let (old_opts, new_opts) = match (options(old), options(new)) {
+        (Some(old_opts), Some(new_opts)) => (old_opts, new_opts),
+        _ => return false,
+    };
+
+    fn find<'a>(opts: &[&'a SynopsisOpt], name: &str) -> Option<&'a SynopsisOpt> {
+        opts.iter().find(|opt| opt.name.original() == name).copied()
+    }
+    let mut changed = false;
+    for opt in &new_opts {
+        let name = opt.name.original();
+        match find(&old_opts, &name) {
+            None => out.push(
+                ChangeKind::OptionAdded,
+                format!("option `{}` added", name),
+                path.clone(),
+            ),
+            Some(old_opt) if old_opt != *opt => out.push(
+                ChangeKind::OptionChanged,
+                format!("option `{}` changed", name),
+                path.clone(),
+            ),
+            Some(_) => continue,
+        }
+        changed = true;
+    }
+    for opt in &old_opts {
+        let name = opt.name.original();
+        if find(&new_opts, &name).is_none() {
+            out.push(
+                ChangeKind::OptionRemoved,
+                format!("option `{}` removed", name),
+                path.clone(),
+            );
+            changed = true;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, ChangeKind};
+    use crate::{NodePath, Roff, RoffNode, Section, SectionNumber, SynopsisOpt};
+
+    fn page() -> Roff {
+        Roff::new("foo", SectionNumber::UserCommands)
+            .date("2021-08-01")
+            .section("NAME", ["foo \\- does things"])
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::paragraph(["First paragraph."]),
+                    RoffNode::paragraph(["Second paragraph."]),
+                    RoffNode::example(["foo --bar"]),
+                ],
+            )
+            .section(
+                "OPTIONS",
+                [RoffNode::option_list([
+                    SynopsisOpt::new("--bar").description(["bars"]),
+                    SynopsisOpt::new("--no-color"),
+                ])],
+            )
+    }
+
+    fn kinds(old: &Roff, new: &Roff) -> Vec<ChangeKind> {
+        diff(old, new)
+            .changes()
+            .iter()
+            .map(|change| change.kind())
+            .collect()
+    }
+
+    #[test]
+    fn identical_documents_have_no_changes() {
+        let diff = diff(&page(), &page());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "");
+    }
+
+    #[test]
+    fn it_reports_changed_pages() {
+        let other = || {
+            Roff::new("bar", SectionNumber::UserCommands).section("NAME", ["bar \\- does things"])
+        };
+        let old = page();
+        let new = page().append_page(other());
+        assert_ne!(old, new);
+        let changes = diff(&old, &new);
+        assert_eq!(changes.to_string(), "page `bar(1)` added");
+        assert_eq!(changes.changes()[0].kind(), ChangeKind::PageAdded);
+        assert_eq!(changes.changes()[0].path(), &NodePath::page(0));
+        assert_eq!(kinds(&new, &old), [ChangeKind::PageRemoved]);
+
+        let baz = Roff::new("baz", SectionNumber::UserCommands);
+        let old = page().append_page(other()).append_page(baz.clone());
+        let new = page()
+            .append_page(baz)
+            .append_page(other().section("NOTES", ["Notes."]));
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes.to_string(),
+            "pages reordered to baz(1), bar(1)
+page `bar(1)`: section `NOTES` added"
+        );
+        assert_eq!(
+            changes.changes()[1].path(),
+            &NodePath::section(1, "NOTES").in_page(1)
+        );
+        assert_eq!(
+            new.node_at(&NodePath::section(1, "NOTES").node(0).in_page(1)),
+            Some(&RoffNode::text("Notes."))
+        );
+    }
+
+    #[test]
+    fn it_reports_document_changes() {
+        let new =
+            Roff::new("bar", SectionNumber::Miscellaneous).section("NAME", ["foo \\- does things"]);
+        let old = Roff::new("foo", SectionNumber::UserCommands)
+            .date("2021-08-01")
+            .section("NAME", ["foo \\- does things"]);
+
+        assert_eq!(
+            diff(&old, &new).to_string(),
+            "title changed from `foo` to `bar`
+section number changed from 1 to 7
+date `2021-08-01` removed"
+        );
+    }
+
+    #[test]
+    fn it_reports_added_removed_and_reordered_sections() {
+        let new = page()
+            .section("EXAMPLES", [RoffNode::example(["foo"])])
+            .sort_sections_conventionally();
+        let mut old = page().section("NOTES", ["Some notes."]);
+        old.sections.swap(1, 2);
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.to_string(),
+            "section `NOTES` removed
+section `EXAMPLES` added
+sections reordered to NAME, DESCRIPTION, OPTIONS"
+        );
+        assert_eq!(diff.changes()[1].path().section_index(), Some(3));
+    }
+
+    #[test]
+    fn it_reports_changed_paragraph_text() {
+        let mut new = page();
+        new.sections[1].get_nodes_mut()[1] = RoffNode::paragraph(["Changed paragraph."]);
+        new.sections[1]
+            .get_nodes_mut()
+            .push(RoffNode::text("Trailing text."));
+
+        let diff = diff(&page(), &new);
+        assert_eq!(
+            diff.to_string(),
+            "DESCRIPTION: paragraph 2 changed\nDESCRIPTION: text 1 added"
+        );
+        assert_eq!(diff.changes()[0].path().node_indices(), &[1]);
+        assert_eq!(diff.changes()[1].path().node_indices(), &[3]);
+    }
+
+    #[test]
+    fn it_reports_removed_nodes_and_subtitles() {
+        let mut new = page();
+        new.sections[1].get_nodes_mut().remove(0);
+        new.sections[1] = new.sections[1].clone().subtitle("in detail");
+
+        assert_eq!(
+            diff(&page(), &new).to_string(),
+            "DESCRIPTION: subtitle `in detail` added\nDESCRIPTION: paragraph 1 removed"
+        );
+        assert_eq!(
+            kinds(&page(), &new),
+            [ChangeKind::SubtitleChanged, ChangeKind::NodeRemoved]
+        );
+    }
+
+    #[test]
+    fn it_reports_changed_options() {
+        let mut new = page();
+        new.sections[2] = Section::new(
+            "OPTIONS",
+            [RoffNode::option_list([
+                SynopsisOpt::new("--bar").description(["bars harder"]),
+                SynopsisOpt::new("--color").argument("WHEN"),
+            ])],
+        );
+
+        assert_eq!(
+            diff(&page(), &new).to_string(),
+            "OPTIONS: option `--bar` changed
+OPTIONS: option `--color` added
+OPTIONS: option `--no-color` removed"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{FontStyle, RoffText, Roffable};
+
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Wrapper that makes any [`Display`](fmt::Display) value [`Roffable`](Roffable). The value is
+/// formatted with `to_string` and the output is escaped like any other text, so values like IP
+/// addresses, durations or error messages can be used directly as paragraph content.
+///
+/// Types that already implement `Roffable`, like strings and numbers, produce the same text when
+/// wrapped, the wrapper only adds support for the remaining `Display` types. The exception is
+/// [`RoffText`](RoffText) whose `Display` output is already escaped and styled ROFF, wrapping it
+/// escapes the backslashes a second time, so it should be used as it is instead.
+///
+/// Usually created with [`display`](display).
+///
+/// ```
+/// use roffman::{display, styled, FontStyle, Roff, RoffNode, Roffable, SectionNumber};
+/// use std::net::Ipv4Addr;
+///
+/// let address = Ipv4Addr::new(192, 168, 0, 1);
+/// let limit = u128::MAX;
+///
+/// let roff = Roff::new("netd", SectionNumber::SystemManagementCommands).section(
+///     "DESCRIPTION",
+///     [RoffNode::paragraph([
+///         "Listens on ".roff(),
+///         display(address).roff(),
+///         " and accepts at most ".roff(),
+///         styled(limit, FontStyle::Bold),
+///         " bytes.".roff(),
+///     ])],
+/// );
+///
+/// assert_eq!(
+///     roff.render_to_string().unwrap(),
+///     r#".TH netd 8
+/// .SH DESCRIPTION
+/// Listens on 192.168.0.1 and accepts at most \fB340282366920938463463374607431768211455\fR bytes."#
+/// );
+/// ```
+pub struct DisplayRoff<T>(pub T);
+
+impl<T: fmt::Display> Roffable for DisplayRoff<T> {
+    fn roff(&self) -> RoffText {
+        RoffText::new(self.0.to_string(), None)
+    }
+}
+
+impl<T: fmt::Display> From<DisplayRoff<T>> for RoffText {
+    fn from(item: DisplayRoff<T>) -> Self {
+        item.roff()
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for DisplayRoff<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Wraps `value` in a [`DisplayRoff`](DisplayRoff) so that it can be used anywhere a
+/// [`Roffable`](Roffable) item is expected.
+pub fn display<T: fmt::Display>(value: T) -> DisplayRoff<T> {
+    DisplayRoff(value)
+}
+
+/// Formats `value` like [`display`](display) and returns it as text with the font `style`.
+pub fn styled<T: fmt::Display>(value: T, style: FontStyle) -> RoffText {
+    RoffText::new(value.to_string(), Some(style))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display, styled, DisplayRoff};
+    use crate::{FontStyle, RoffNode, Roffable};
+
+    use std::net::Ipv6Addr;
+
+    struct Ratio(u32, u32);
+
+    impl std::fmt::Display for Ratio {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}/{}", self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn display_roff_escapes_formatted_values() {
+        assert_eq!(display(Ratio(1, 2)).roff().as_str(), "1/2");
+        assert_eq!(display(-42).roff(), (-42).roff());
+        assert_eq!(display("a-b").roff(), "a-b".roff());
+        assert_eq!(display("a-b").roff().as_str(), r"a\-b");
+        assert_eq!(DisplayRoff(Ipv6Addr::LOCALHOST).roff().as_str(), "::1");
+
+        let text = styled(r"C:\temp", FontStyle::Italic);
+        assert_eq!(text.style(), FontStyle::Italic);
+        assert_eq!(text.to_string(), r"\fIC:\etemp\fR");
+
+        let node: RoffNode = RoffNode::paragraph([display(Ratio(3, 4))]);
+        assert_eq!(node.children()[0], RoffNode::text("3/4"));
+    }
+}
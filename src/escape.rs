@@ -1,21 +1,490 @@
-//!
-//!
-//!
-
-pub fn escape<T: AsRef<str>>(text: T) -> String {
-    let text = text.as_ref();
-    let mut out = String::new();
-    for token in text.chars().map(EscapeToken::from) {
-        if let Some(ch) = token.unescaped_char() {
-            out.push(ch);
+use std::borrow::Cow;
+use std::iter::Peekable;
+
+/// Escapes `text` so that it is displayed as is by the viewers. If nothing has to be escaped the
+/// text is borrowed without allocating. Control characters other than newlines and tabs, like
+/// carriage returns or the escape character of ANSI sequences, are removed.
+///
+/// This is the escaping applied to all [`RoffText`](crate::RoffText) created with
+/// [`RoffText::new`](crate::RoffText::new) under the default [`EscapePolicy`](EscapePolicy).
+///
+/// ```
+/// assert_eq!(r"\-\-help", roffman::escape("--help"));
+/// ```
+pub fn escape(text: &str) -> Cow<'_, str> {
+    escape_with_policy(text, &EscapePolicy::default())
+}
+
+/// Escapes `text` like [`escape`](escape) leaving the character classes disabled in the `policy`
+/// as they are.
+///
+/// ```
+/// use roffman::{escape_with_policy, EscapePolicy};
+///
+/// let policy = EscapePolicy::new().dashes(false);
+/// assert_eq!(r"well-known \e", escape_with_policy(r"well-known \", &policy));
+/// ```
+pub fn escape_with_policy<'a>(text: &'a str, policy: &EscapePolicy) -> Cow<'a, str> {
+    if policy.smart_punctuation {
+        if let Cow::Owned(text) = smarten(text) {
+            let policy = policy.smart_punctuation(false);
+            return Cow::Owned(escape_with_policy(&text, &policy).into_owned());
+        }
+    }
+
+    // The output is only allocated once the first character that has to be escaped is found.
+    let mut out: Option<String> = None;
+    let mut line_start = false;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        let token = EscapeToken::from(ch);
+        let replacement = match (ch, line_start) {
+            // Dots and apostrophes at the beginning of the line start control lines, they are
+            // preceded with a zero-width character so that they don't get interpreted as macros.
+            ('.', true) => Some(Cow::Borrowed("\\&.")),
+            ('\'', true) if token.is_escaped_by(policy) => Some(Cow::Borrowed("\\&\\(aq")),
+            ('\'', true) => Some(Cow::Borrowed("\\&'")),
+            ('…', true) if token.is_escaped_by(policy) => Some(Cow::Borrowed("\\&.\\|.\\|.")),
+            ('\t', _) => match policy.tabs {
+                None | Some(TabPolicy::Keep) => None,
+                Some(TabPolicy::Expand(width)) => Some(Cow::Owned(" ".repeat(width.into()))),
+                Some(TabPolicy::Escape) => Some(Cow::Borrowed("\\t")),
+            },
+            ('-', _) => match policy.hyphens {
+                HyphenMode::Minus => Some(Cow::Borrowed(token.escape_sequence())),
+                HyphenMode::Hyphen => None,
+                HyphenMode::TypographicHyphen => Some(Cow::Borrowed(TYPOGRAPHIC_HYPHEN)),
+            },
+            _ if token.is_escaped_by(policy) => Some(Cow::Borrowed(token.escape_sequence())),
+            // Control characters would corrupt the document or reach the terminal of the reader as
+            // raw escape sequences, including the carriage return of `\r\n` line endings.
+            _ if is_removed_control(ch) => Some(Cow::Borrowed("")),
+            _ if policy.ascii_only
+                && (!ch.is_ascii()
+                    || chars.peek().is_some_and(|&(_, next)| is_combining(next))) =>
+            {
+                Some(Cow::Owned(unicode_escape(ch, &mut chars)))
+            }
+            _ => None,
+        };
+        // Removed control characters don't end up in the output, so a dot after `\n\r` still
+        // starts the line.
+        line_start = ch == '\n' || (line_start && is_removed_control(ch));
+
+        match (replacement, &mut out) {
+            (Some(replacement), Some(out)) => out.push_str(&replacement),
+            (Some(replacement), None) => {
+                let mut escaped = String::with_capacity(text.len() + text.len() / 8);
+                escaped.push_str(&text[..i]);
+                escaped.push_str(&replacement);
+                out = Some(escaped);
+            }
+            (None, Some(out)) => out.push(ch),
+            (None, None) => {}
+        }
+    }
+
+    match out {
+        Some(out) => Cow::Owned(out),
+        None => Cow::Borrowed(text),
+    }
+}
+
+/// Replaces straight quotes, `--` and `...` in `text` with the typographic punctuation, see
+/// [`EscapePolicy::smart_punctuation`](EscapePolicy::smart_punctuation).
+fn smarten(text: &str) -> Cow<'_, str> {
+    if !text.contains(['"', '\'', '-', '.']) {
+        return Cow::Borrowed(text);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let prev = out.chars().next_back();
+        let next = chars.get(i + 1).copied();
+        let opens = prev.map_or(true, opens_quote) && next.is_some_and(|ch| !ch.is_whitespace());
+        match chars[i] {
+            '"' if opens => out.push('“'),
+            '"' => out.push('”'),
+            '\'' if prev.is_some_and(char::is_alphanumeric)
+                && next.is_some_and(char::is_alphanumeric) =>
+            {
+                out.push('’')
+            }
+            '\'' if opens => out.push('‘'),
+            '\'' => out.push('’'),
+            '-' if next == Some('-')
+                && !(prev.map_or(true, char::is_whitespace)
+                    && chars.get(i + 2).is_some_and(|ch| ch.is_alphanumeric())) =>
+            {
+                out.push('—');
+                i += 1;
+                if next == chars.get(i + 1).copied() {
+                    i += 1;
+                }
+            }
+            '.' if next == Some('.') && chars.get(i + 2) == Some(&'.') => {
+                out.push('…');
+                i += 2;
+            }
+            ch => out.push(ch),
+        }
+        i += 1;
+    }
+    if out == text {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(out)
+    }
+}
+
+/// Returns `true` if a quote following `ch` opens a quotation.
+fn opens_quote(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, '(' | '[' | '{' | '“' | '‘' | '—' | '–' | '-' | '/')
+}
+
+/// Returns the `\[uXXXX]` escape of `ch`. Combining characters following `ch` are consumed from
+/// `chars` and added to the escape as a composite glyph like `\[u0065_0301]`.
+fn unicode_escape<I>(ch: char, chars: &mut Peekable<I>) -> String
+where
+    I: Iterator<Item = (usize, char)>,
+{
+    let mut escaped = format!("\\[u{:04X}", u32::from(ch));
+    while let Some(&(_, next)) = chars.peek() {
+        if !is_combining(next) {
+            break;
+        }
+        escaped.push_str(&format!("_{:04X}", u32::from(next)));
+        chars.next();
+    }
+    escaped.push(']');
+    escaped
+}
+
+/// Returns `true` if `ch` is a control character removed from the escaped text. Only newlines and
+/// tabs are kept.
+pub(crate) fn is_removed_control(ch: char) -> bool {
+    ch.is_control() && ch != '\n' && ch != '\t'
+}
+
+/// Returns `true` if `ch` is one of the combining diacritical marks.
+pub(crate) fn is_combining(ch: char) -> bool {
+    matches!(
+        u32::from(ch),
+        0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE20..=0xFE2F
+    )
+}
+
+/// Accented letters with the accent used in their groff glyph names, like `'` in `\('e`.
+const ACCENTED_GLYPHS: &[(char, &str, &str)] = &[
+    ('`', "ÀÈÌÒÙàèìòù", "AEIOUaeiou"),
+    ('\'', "ÁÉÍÓÚÝáéíóúý", "AEIOUYaeiouy"),
+    ('^', "ÂÊÎÔÛâêîôû", "AEIOUaeiou"),
+    ('~', "ÃÑÕãñõ", "ANOano"),
+    (':', "ÄËÏÖÜäëïöüÿ", "AEIOUaeiouy"),
+    ('o', "Åå", "Aa"),
+    (',', "Çç", "Cc"),
+];
+
+/// Characters with a two character groff glyph name that are not accented letters.
+const NAMED_GLYPHS: &[(char, &str)] = &[
+    ('Æ', "AE"),
+    ('æ', "ae"),
+    ('Œ', "OE"),
+    ('œ', "oe"),
+    ('Ø', "/O"),
+    ('ø', "/o"),
+    ('ß', "ss"),
+    ('Ð', "-D"),
+    ('ð', "Sd"),
+    ('Þ', "TP"),
+    ('þ', "Tp"),
+    ('¡', "r!"),
+    ('¿', "r?"),
+    ('¢', "ct"),
+    ('£', "Po"),
+    ('¥', "Ye"),
+    ('€', "Eu"),
+    ('§', "sc"),
+    ('¶', "ps"),
+    ('°', "de"),
+    ('±', "+-"),
+    ('×', "mu"),
+    ('÷', "di"),
+    ('¬', "no"),
+    ('µ', "mc"),
+    ('·', "pc"),
+    ('¹', "S1"),
+    ('²', "S2"),
+    ('³', "S3"),
+    ('¼', "14"),
+    ('½', "12"),
+    ('¾', "34"),
+    ('«', "Fo"),
+    ('»', "Fc"),
+    ('‘', "oq"),
+    ('’', "cq"),
+    ('“', "lq"),
+    ('”', "rq"),
+    ('–', "en"),
+    ('—', "em"),
+    ('©', "co"),
+    ('®', "rg"),
+    ('™', "tm"),
+    ('•', "bu"),
+    ('†', "dg"),
+    ('‡', "dd"),
+    ('→', "->"),
+    ('←', "<-"),
+    ('↑', "ua"),
+    ('↓', "da"),
+    ('↔', "<>"),
+    ('⇒', "rA"),
+    ('⇐', "lA"),
+    ('⇔', "hA"),
+    ('≤', "<="),
+    ('≥', ">="),
+    ('≠', "!="),
+    ('≈', "~~"),
+    ('∞', "if"),
+];
+
+/// Returns the groff named glyph escape of `ch`, like `\('e` for `é`, or `None` if `ch` has no
+/// name known to all groff versions.
+pub(crate) fn named_glyph(ch: char) -> Option<String> {
+    if let Some((_, name)) = NAMED_GLYPHS.iter().find(|(glyph, _)| *glyph == ch) {
+        return Some(format!("\\({}", name));
+    }
+    ACCENTED_GLYPHS.iter().find_map(|(accent, letters, bases)| {
+        let i = letters.chars().position(|letter| letter == ch)?;
+        let base = bases.chars().nth(i)?;
+        Some(format!("\\({}{}", accent, base))
+    })
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Defines which classes of characters are replaced with escape sequences when text is rendered.
+/// The default policy escapes everything. Backslashes and dots at the start of a line are always
+/// escaped as they would otherwise break the document.
+///
+/// The policy can be set for a whole document with
+/// [`Roff::escape_policy`](crate::Roff::escape_policy) or for a single text with
+/// [`RoffText::with_policy`](crate::RoffText::with_policy).
+pub struct EscapePolicy {
+    hyphens: HyphenMode,
+    quotes: bool,
+    special_chars: bool,
+    punctuation: bool,
+    ascii_only: bool,
+    smart_punctuation: bool,
+    tabs: Option<TabPolicy>,
+}
+
+impl Default for EscapePolicy {
+    fn default() -> Self {
+        Self {
+            hyphens: HyphenMode::Minus,
+            quotes: true,
+            special_chars: true,
+            punctuation: true,
+            ascii_only: false,
+            smart_punctuation: false,
+            tabs: None,
+        }
+    }
+}
+
+impl EscapePolicy {
+    /// Creates the default policy that escapes all characters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `-` is escaped as `\-`. The escaped dash is always rendered as a minus sign which
+    /// is required for options and commands, while a plain `-` may be rendered as a hyphen. Same
+    /// as setting [`hyphens`](EscapePolicy::hyphens) to [`HyphenMode::Minus`](HyphenMode::Minus)
+    /// or [`HyphenMode::Hyphen`](HyphenMode::Hyphen).
+    pub fn dashes(self, escape: bool) -> Self {
+        self.hyphens(if escape {
+            HyphenMode::Minus
         } else {
-            out.push_str(token.escape_sequence());
+            HyphenMode::Hyphen
+        })
+    }
+
+    /// Sets how `-` is rendered, see [`HyphenMode`](HyphenMode).
+    pub fn hyphens(mut self, hyphens: HyphenMode) -> Self {
+        self.hyphens = hyphens;
+        self
+    }
+
+    /// Whether apostrophes and quotes like `'`, `"`, `‘` or `”` are escaped. Without escaping
+    /// apostrophes may be rendered as typographic closing quotes.
+    pub fn quotes(mut self, escape: bool) -> Self {
+        self.quotes = escape;
+        self
+    }
+
+    /// Whether the `~`, `^` and `` ` `` characters are escaped. Without escaping they may be
+    /// rendered as their typographic variants.
+    pub fn special_chars(mut self, escape: bool) -> Self {
+        self.special_chars = escape;
+        self
+    }
+
+    /// Whether the typographic punctuation like en and em dashes, `©`, `®`, `™` and `…` is
+    /// replaced with the roff glyphs. No-break spaces, U+00A0 and the narrow U+202F, are replaced
+    /// with the `\~` non-breaking space. Without escaping they are rendered as UTF-8 which is not
+    /// displayed consistently by all viewers.
+    pub fn punctuation(mut self, escape: bool) -> Self {
+        self.punctuation = escape;
+        self
+    }
+
+    /// Sets how tabs are rendered. By default tabs are kept in example blocks where the text is
+    /// not filled and expanded to spaces everywhere else, as in filled text and macro arguments
+    /// they produce unexpected spacing or break quoting. The text returned by
+    /// [`escape`](escape) keeps the tabs unless the policy is set.
+    pub fn tabs(mut self, tabs: TabPolicy) -> Self {
+        self.tabs = Some(tabs);
+        self
+    }
+
+    /// Returns the tab policy if one was set with [`tabs`](EscapePolicy::tabs).
+    pub fn get_tabs(&self) -> Option<TabPolicy> {
+        self.tabs
+    }
+
+    /// Whether all characters outside of ASCII are escaped with the `\[uXXXX]` escape, so that
+    /// the output is plain ASCII. Some toolchains and older `troff` implementations can't handle
+    /// UTF-8 input. A character followed by combining marks is escaped as a single composite glyph
+    /// like `\[u0065_0301]`. Disabled by default.
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Whether straight quotes and ASCII punctuation in prose are replaced with their typographic
+    /// variants: `"` and `'` with opening or closing quotes depending on the surrounding
+    /// characters, `'` between letters with an apostrophe, `--` with an em dash and `...` with an
+    /// ellipsis. A `--` at the start of a word is kept as it most likely is an option. Literal and
+    /// constant-width text and example blocks are never transformed. Disabled by default as it
+    /// changes the meaning of technical text.
+    pub fn smart_punctuation(mut self, smart: bool) -> Self {
+        self.smart_punctuation = smart;
+        self
+    }
+
+    /// Returns `true` if smart punctuation is enabled, see
+    /// [`smart_punctuation`](EscapePolicy::smart_punctuation).
+    pub fn get_smart_punctuation(&self) -> bool {
+        self.smart_punctuation
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Defines how `-` is rendered.
+///
+/// The character is used both as a hyphen in prose, like in "well-known", and as a minus sign in
+/// options, commands and code. `groff` renders a plain `-` as a hyphen, which on UTF-8 terminals
+/// may be displayed as U+2010 so that copying `--help` from the page doesn't work. Text that may
+/// be copied by the reader should always use the minus sign, while prose reads better with the
+/// hyphen.
+pub enum HyphenMode {
+    /// `-` is escaped as `\-` and rendered as a minus sign that is always copied as U+002D.
+    Minus,
+    /// `-` is written as is and rendered as a hyphen.
+    Hyphen,
+    /// `-` is written as the `\(hy` glyph, explicitly a hyphen.
+    TypographicHyphen,
+}
+
+/// Escape sequence of the hyphen glyph.
+const TYPOGRAPHIC_HYPHEN: &str = "\\(hy";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Defines how tabs are rendered, see [`EscapePolicy::tabs`](EscapePolicy::tabs).
+pub enum TabPolicy {
+    /// The tab is written as is.
+    Keep,
+    /// The tab is replaced with the given number of spaces.
+    Expand(u8),
+    /// The tab is written as the `\t` escape which moves to the next tab stop without breaking
+    /// macro arguments.
+    Escape,
+}
+
+/// Reverses the escaping done by [`escape`](escape) so that `unescape(&escape(text)) == text`.
+/// Escape sequences that [`escape`](escape) never produces are left as they are. If there is
+/// nothing to unescape the text is borrowed without allocating.
+///
+/// ```
+/// assert_eq!("it's --help", roffman::unescape(r"it\(aqs \-\-help"));
+/// ```
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains('\\') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if rest.starts_with(ELLIPSIS_TAIL) && out.ends_with('.') {
+            out.pop();
+            out.push('…');
+            rest = &rest[ELLIPSIS_TAIL.len()..];
+            continue;
         }
+        if let Some((glyph, len)) = parse_unicode_escape(rest) {
+            out.extend(glyph);
+            rest = &rest[len..];
+            continue;
+        }
+        let (unescaped, len) = match rest.as_bytes().get(1) {
+            Some(b'&') => (None, 2),
+            Some(b't') => (Some('\t'), 2),
+            Some(b'(') => match rest.get(..4).and_then(EscapeToken::from_sequence) {
+                Some(ch) => (Some(ch), 4),
+                None => (Some('\\'), 1),
+            },
+            Some(_) => match rest.get(..2).and_then(EscapeToken::from_sequence) {
+                Some(ch) => (Some(ch), 2),
+                None => (Some('\\'), 1),
+            },
+            None => (Some('\\'), 1),
+        };
+        out.extend(unescaped);
+        rest = &rest[len..];
     }
+    out.push_str(rest);
+
+    Cow::Owned(out)
+}
+
+/// The part of the escaped ellipsis `.\|.\|.` after the first dot.
+const ELLIPSIS_TAIL: &str = "\\|.\\|.";
 
-    // Escapes dots at the beginning of the line so that they don't get interpreted as
-    // roff macros.
-    out.replace("\n.", "\n\\&.")
+/// Parses a `\[uXXXX]` or composite `\[uXXXX_XXXX]` escape at the start of `text` returning
+/// the characters and the length of the escape.
+fn parse_unicode_escape(text: &str) -> Option<(Vec<char>, usize)> {
+    let end = text.find(']')?;
+    let glyph = text.get(..end)?.strip_prefix("\\[u")?;
+    let chars = glyph
+        .split('_')
+        .map(|code| {
+            if (4..=6).contains(&code.len()) {
+                u32::from_str_radix(code, 16).ok().and_then(char::from_u32)
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some((chars, end + 1))
 }
 
 enum EscapeToken {
@@ -30,7 +499,14 @@ enum EscapeToken {
     CircumflexAccent,
     ReverseSolidus,
     Tilde,
-    Unescaped(char),
+    EnDash,
+    EmDash,
+    Copyright,
+    Registered,
+    Trademark,
+    Ellipsis,
+    NoBreakSpace,
+    Unescaped,
 }
 
 impl From<char> for EscapeToken {
@@ -48,7 +524,14 @@ impl From<char> for EscapeToken {
             '^' => CircumflexAccent,
             '\\' => ReverseSolidus,
             '~' => Tilde,
-            ch => Unescaped(ch),
+            '–' => EnDash,
+            '—' => EmDash,
+            '©' => Copyright,
+            '®' => Registered,
+            '™' => Trademark,
+            '…' => Ellipsis,
+            '\u{A0}' | '\u{202F}' => NoBreakSpace,
+            _ => Unescaped,
         }
     }
 }
@@ -68,22 +551,70 @@ impl EscapeToken {
             CircumflexAccent => "\\(ha",
             ReverseSolidus => "\\e",
             Tilde => "\\(ti",
-            Unescaped(_) => "",
+            EnDash => "\\(en",
+            EmDash => "\\(em",
+            Copyright => "\\(co",
+            Registered => "\\(rg",
+            Trademark => "\\(tm",
+            // There is no ellipsis glyph in all implementations, three dots separated with
+            // sixth of an em spaces look the same.
+            Ellipsis => ".\\|.\\|.",
+            // Raw no-break spaces are handled inconsistently by the output devices.
+            NoBreakSpace => "\\~",
+            Unescaped => "",
         }
     }
 
-    fn unescaped_char(&self) -> Option<char> {
-        if let EscapeToken::Unescaped(ch) = &self {
-            Some(*ch)
-        } else {
-            None
+    /// Returns the character escaped by the given escape `sequence` if it is one of the sequences
+    /// produced by [`escape`](escape).
+    fn from_sequence(sequence: &str) -> Option<char> {
+        [
+            '-', '\'', '‘', '’', '"', '“', '”', '`', '^', '\\', '~', '–', '—', '©', '®', '™',
+            '\u{A0}',
+        ]
+        .iter()
+        .copied()
+        .find(|&ch| EscapeToken::from(ch).escape_sequence() == sequence)
+        .or_else(|| (sequence == TYPOGRAPHIC_HYPHEN).then_some('-'))
+    }
+
+    /// Returns `true` if this token is replaced with its escape sequence under the `policy`.
+    fn is_escaped_by(&self, policy: &EscapePolicy) -> bool {
+        use EscapeToken::*;
+        match self {
+            Dash => policy.hyphens == HyphenMode::Minus,
+            LatinApostrophe | OpeningQuote | ClosingQuote | DoubleQuote | LeftDoubleQuote
+            | RightDoubleQuote => policy.quotes,
+            GraveAccent | CircumflexAccent | Tilde => policy.special_chars,
+            EnDash | EmDash | Copyright | Registered | Trademark | Ellipsis | NoBreakSpace => {
+                policy.punctuation
+            }
+            ReverseSolidus => true,
+            Unescaped => false,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::escape;
+    use super::{
+        escape, escape_with_policy, named_glyph, unescape, EscapePolicy, HyphenMode, TabPolicy,
+    };
+    use proptest::prelude::*;
+    use std::borrow::Cow;
+
+    /// Characters that are likely to break the escaping, mixed with regular ones.
+    const POOL: &[char] = &[
+        'a', 'Z', '0', ' ', '\t', '\n', '.', '-', '\'', '‘', '’', '"', '“', '”', '`', '^', '\\',
+        '~', '&', '(', '[', ']', 'u', 'e', 'ą', '\u{301}', '🦀', '–', '—', '©', '®', '™', '…', '|',
+        '\u{A0}',
+    ];
+
+    /// Strings of up to 32 characters from the [`POOL`].
+    fn pool_string() -> impl Strategy<Value = String> {
+        prop::collection::vec(prop::sample::select(POOL), 0..32)
+            .prop_map(|chars| chars.into_iter().collect())
+    }
 
     #[test]
     fn it_escapes() {
@@ -98,4 +629,178 @@ mod tests {
 
         assert_eq!(escape(dot_on_new_line), "\n\\&.some dot on new line")
     }
+
+    #[test]
+    fn it_borrows_text_without_escapes() {
+        assert!(matches!(escape("nothing to escape."), Cow::Borrowed(_)));
+        assert!(matches!(escape("some-dash"), Cow::Owned(_)));
+        assert!(matches!(escape("line\n.dot"), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn it_unescapes() {
+        assert_eq!(
+            unescape("\\(ti/docs/$ bash \\-c \\(dqawk \\(aq\\(aq \\(ga\\(ga\\(dq"),
+            r#"~/docs/$ bash -c "awk '' ``""#
+        );
+        assert_eq!(
+            unescape("\n\\&.some dot on new line"),
+            "\n.some dot on new line"
+        );
+        assert_eq!(unescape("\\fBunknown\\fR \\(bu"), "\\fBunknown\\fR \\(bu");
+        assert_eq!(unescape("trailing \\"), "trailing \\");
+        assert!(matches!(unescape("nothing"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn it_unescapes_unicode_escapes() {
+        assert_eq!(unescape("caf\\[u0065_0301] \\[u1F980]"), "cafe\u{301} 🦀");
+        assert_eq!(unescape("\\[uZZZZ] \\[u12]"), "\\[uZZZZ] \\[u12]");
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+        #[test]
+        fn unescape_reverses_ascii_only_escape(text in pool_string()) {
+            let escaped = escape_with_policy(&text, &EscapePolicy::new().ascii_only(true));
+            prop_assert!(escaped.is_ascii());
+            prop_assert_eq!(unescape(&escaped), text.as_str());
+        }
+
+        #[test]
+        fn unescape_reverses_escape(text in pool_string()) {
+            let escaped = escape(&text);
+            prop_assert_eq!(unescape(&escaped), text.as_str(), "escaped: {:?}", escaped);
+        }
+
+        #[test]
+        fn escape_removes_any_control_characters(bytes in prop::collection::vec(any::<u8>(), 0..64)) {
+            let text = String::from_utf8_lossy(&bytes);
+            let escaped = escape(&text);
+            prop_assert!(
+                !escaped.chars().any(|ch| ch.is_control() && ch != '\n' && ch != '\t'),
+                "{:?} escaped to {:?}",
+                text,
+                escaped
+            );
+        }
+    }
+
+    #[test]
+    fn it_protects_line_starts() {
+        assert_eq!(escape("a\n'tis"), "a\n\\&\\(aqtis");
+        assert_eq!(escape("a\n.b\n'c"), "a\n\\&.b\n\\&\\(aqc");
+        assert_eq!(
+            escape_with_policy("a\n'tis", &EscapePolicy::new().quotes(false)),
+            "a\n\\&'tis"
+        );
+        assert_eq!(escape("it's"), "it\\(aqs");
+    }
+
+    #[test]
+    fn it_names_glyphs() {
+        assert_eq!(named_glyph('é').as_deref(), Some("\\('e"));
+        assert_eq!(named_glyph('ÿ').as_deref(), Some("\\(:y"));
+        assert_eq!(named_glyph('Ç').as_deref(), Some("\\(,C"));
+        assert_eq!(named_glyph('ß').as_deref(), Some("\\(ss"));
+        assert_eq!(named_glyph('→').as_deref(), Some("\\(->"));
+        assert_eq!(named_glyph('中'), None);
+        assert_eq!(named_glyph('e'), None);
+    }
+
+    #[test]
+    fn it_escapes_to_ascii() {
+        let policy = EscapePolicy::new().ascii_only(true);
+        assert_eq!(escape_with_policy("café", &policy), "caf\\[u00E9]");
+        assert_eq!(escape_with_policy("a → b", &policy), "a \\[u2192] b");
+        assert_eq!(escape_with_policy("中文", &policy), "\\[u4E2D]\\[u6587]");
+        assert_eq!(escape_with_policy("🦀", &policy), "\\[u1F980]");
+        assert_eq!(
+            escape_with_policy("cafe\u{301}!", &policy),
+            "caf\\[u0065_0301]!"
+        );
+        assert_eq!(escape_with_policy("‘-’", &policy), "\\(oq\\-\\(cq");
+        assert!(matches!(
+            escape_with_policy("ascii", &policy),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(escape("café"), "café");
+    }
+
+    #[test]
+    fn it_escapes_punctuation() {
+        assert_eq!(
+            escape("Pages 1–3 — see “Notes”… © 2024 Foo® Bar™"),
+            "Pages 1\\(en3 \\(em see \\(lqNotes\\(rq.\\|.\\|. \\(co 2024 Foo\\(rg Bar\\(tm"
+        );
+        assert_eq!(escape("a\n…"), "a\n\\&.\\|.\\|.");
+        assert_eq!(escape("10\u{A0}GiB 5\u{202F}%"), "10\\~GiB 5\\~%");
+        assert_eq!(
+            escape_with_policy("1–3 ©…", &EscapePolicy::new().punctuation(false)),
+            "1–3 ©…"
+        );
+    }
+
+    #[test]
+    fn it_removes_control_characters() {
+        assert_eq!(escape("line\r\nnext\r"), "line\nnext");
+        assert_eq!(escape("\u{1b}[31mred\u{1b}[0m\0"), "[31mred[0m");
+        assert_eq!(escape("c1\u{85}\u{9b}\u{7f}"), "c1");
+        assert_eq!(escape("tab\tnewline\n"), "tab\tnewline\n");
+    }
+
+    #[test]
+    fn it_applies_tab_policy() {
+        let text = "a\tb";
+        assert_eq!(escape(text), "a\tb");
+        let policy = |tabs| EscapePolicy::new().tabs(tabs);
+        assert_eq!(escape_with_policy(text, &policy(TabPolicy::Keep)), "a\tb");
+        assert_eq!(
+            escape_with_policy(text, &policy(TabPolicy::Expand(2))),
+            "a  b"
+        );
+        assert_eq!(
+            escape_with_policy(text, &policy(TabPolicy::Escape)),
+            "a\\tb"
+        );
+        assert_eq!(unescape("a\\tb"), "a\tb");
+    }
+
+    #[test]
+    fn it_applies_hyphen_mode() {
+        let text = "well-known --help";
+        let policy = |hyphens| EscapePolicy::new().hyphens(hyphens);
+        assert_eq!(escape(text), "well\\-known \\-\\-help");
+        assert_eq!(escape_with_policy(text, &policy(HyphenMode::Hyphen)), text);
+        assert_eq!(
+            escape_with_policy(text, &policy(HyphenMode::TypographicHyphen)),
+            "well\\(hyknown \\(hy\\(hyhelp"
+        );
+        assert_eq!(unescape("well\\(hyknown"), "well-known");
+    }
+
+    #[test]
+    fn it_applies_smart_punctuation() {
+        let policy = EscapePolicy::new().smart_punctuation(true);
+        let escaped = |text| escape_with_policy(text, &policy).into_owned();
+        assert_eq!(
+            escaped(r#"He said "don't say 'never' -- wait..." twice"#),
+            r"He said \(lqdon\(cqt say \(oqnever\(cq \(em wait.\|.\|.\(rq twice"
+        );
+        assert_eq!(
+            escaped(r#""'quoted'"; rock 'n' roll"#),
+            r"\(lq\(oqquoted\(cq\(rq; rock \(oqn\(cq roll"
+        );
+        assert_eq!(
+            escaped("use --help or -v---not both"),
+            r"use \-\-help or \-v\(emnot both"
+        );
+        assert_eq!(escaped("it's"), escaped("it’s"));
+        assert_eq!(escape(r#""it's""#), r"\(dqit\(aqs\(dq");
+        assert!(matches!(
+            escape_with_policy("no punctuation", &policy),
+            Cow::Borrowed(_)
+        ));
+    }
 }
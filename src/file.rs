@@ -0,0 +1,153 @@
+use crate::{Roff, RoffError};
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter making the names of temporary files unique within the process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl Roff {
+    /// Renders this roff to the file at `path`. The document is first rendered to a temporary
+    /// file in the same directory that then replaces the target, so a failed render never
+    /// truncates an existing page.
+    ///
+    /// Returns [`RoffError::MissingDirectory`](RoffError::MissingDirectory) if the parent directory
+    /// of `path` doesn't exist, use
+    /// [`write_to_file_creating_dirs`](Roff::write_to_file_creating_dirs) to create it.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), RoffError> {
+        write_atomically(path.as_ref(), |writer| self.render(writer))
+    }
+
+    /// Renders this roff to the file at `path` like [`write_to_file`](Roff::write_to_file),
+    /// creating the missing parent directories first.
+    pub fn write_to_file_creating_dirs(&self, path: impl AsRef<Path>) -> Result<(), RoffError> {
+        create_parent_dirs(path.as_ref())?;
+        self.write_to_file(path)
+    }
+}
+
+/// Creates all missing parent directories of `path`.
+pub(crate) fn create_parent_dirs(path: &Path) -> Result<(), RoffError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => Ok(fs::create_dir_all(parent)?),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the output of `render` to a temporary file next to `path` and renames it over `path`
+/// once rendering succeeds. The temporary file is removed if anything fails.
+pub(crate) fn write_atomically<F>(path: &Path, render: F) -> Result<(), RoffError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), RoffError>,
+{
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Err(RoffError::MissingDirectory(dir.to_path_buf()));
+    }
+
+    let temp = temp_path(dir, path);
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)?;
+    let result = write_and_sync(file, render).and_then(|_| Ok(fs::rename(&temp, path)?));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_and_sync<F>(file: File, render: F) -> Result<(), RoffError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), RoffError>,
+{
+    let mut writer = BufWriter::new(file);
+    render(&mut writer)?;
+    writer.flush()?;
+    let file = writer
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Returns a path for a hidden temporary file in `dir` named after the `target`.
+fn temp_path(dir: &Path, target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    dir.join(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Roff, RoffError, SectionNumber};
+    use std::fs;
+
+    fn roff(name: &str) -> Roff {
+        Roff::new("test-file", SectionNumber::UserCommands).section("NAME", [name])
+    }
+
+    #[test]
+    fn it_writes_and_overwrites_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test-file.1");
+
+        roff("first").write_to_file(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            roff("first").render_to_string().unwrap()
+        );
+
+        roff("second").write_to_file(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            roff("second").render_to_string().unwrap()
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn it_reports_missing_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("man").join("man1");
+        let path = missing.join("test-file.1");
+
+        match roff("test").write_to_file(&path) {
+            Err(RoffError::MissingDirectory(dir)) => assert_eq!(dir, missing),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(!missing.exists());
+
+        roff("test").write_to_file_creating_dirs(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            roff("test").render_to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_writes_keep_the_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test-file.1");
+        fs::write(&path, "installed").unwrap();
+
+        let result = super::write_atomically(&path, |_| {
+            Err(RoffError::StringRenderFailed("failed".to_string()))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "installed");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
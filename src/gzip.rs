@@ -0,0 +1,63 @@
+use crate::file::write_atomically;
+use crate::{Roff, RoffError};
+
+use flate2::{Compression, GzBuilder};
+use std::io::Write;
+use std::path::Path;
+
+impl Roff {
+    /// Renders this roff compressed with gzip to the given writer, the way manual pages are
+    /// usually installed. The modification time in the gzip header is zeroed so that the output
+    /// is reproducible.
+    pub fn render_gz<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        let mut encoder = GzBuilder::new()
+            .mtime(0)
+            .write(writer, Compression::default());
+        self.render(&mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    }
+
+    /// Renders this roff compressed with gzip to the file at `path`, like
+    /// [`write_to_file`](Roff::write_to_file). The path is used as is, so it should end with
+    /// `.gz`.
+    pub fn write_to_file_gz(&self, path: impl AsRef<Path>) -> Result<(), RoffError> {
+        write_atomically(path.as_ref(), |writer| self.render_gz(writer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Roff, SectionNumber};
+
+    use flate2::read::GzDecoder;
+    use std::fs;
+    use std::io::Read;
+
+    fn decompress(compressed: &[u8]) -> String {
+        let mut out = String::new();
+        GzDecoder::new(compressed).read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn it_renders_gzip() {
+        let roff = Roff::new("test-gzip", SectionNumber::UserCommands)
+            .section("NAME", ["test\\-gzip \\- compressed page"]);
+
+        let mut compressed = vec![];
+        roff.render_gz(&mut compressed).unwrap();
+        assert_eq!(decompress(&compressed), roff.render_to_string().unwrap());
+        // The mtime field of the header is zeroed.
+        assert_eq!(&compressed[4..8], &[0, 0, 0, 0]);
+
+        let mut again = vec![];
+        roff.render_gz(&mut again).unwrap();
+        assert_eq!(compressed, again);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test-gzip.1.gz");
+        roff.write_to_file_gz(&path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), compressed);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::mandir::page_file_name;
+use crate::node::RoffNodeInner;
+use crate::{IntoRoffNode, Roff, RoffNode, RoffText, StandardSection, SynopsisItem};
+
+/// The default wording used by [`Roff::reporting_bugs`](Roff::reporting_bugs).
+const REPORT_BUGS_WORDING: &str = "Report bugs to:";
+
+#[derive(Clone, Debug)]
+/// Describes where bugs should be reported to.
+pub enum BugContact {
+    /// A URL of the bug tracker.
+    Url(RoffText),
+    /// An email address accepting bug reports.
+    Email(RoffText),
+    /// Both the email address and the bug tracker URL.
+    Both { url: RoffText, email: RoffText },
+}
+
+impl BugContact {
+    /// Creates a new contact pointing to the bug tracker at `url`.
+    pub fn url(url: impl Into<RoffText>) -> Self {
+        BugContact::Url(url.into())
+    }
+
+    /// Creates a new contact pointing to the email `address`.
+    pub fn email(address: impl Into<RoffText>) -> Self {
+        BugContact::Email(address.into())
+    }
+
+    /// Creates a new contact pointing to both the bug tracker at `url` and the email `address`.
+    pub fn both(url: impl Into<RoffText>, address: impl Into<RoffText>) -> Self {
+        BugContact::Both {
+            url: url.into(),
+            email: address.into(),
+        }
+    }
+
+    fn nodes(self) -> Vec<RoffNode> {
+        match self {
+            BugContact::Url(url) => vec![RoffNode::url("", url)],
+            BugContact::Email(email) => vec![RoffNode::email("", email)],
+            BugContact::Both { url, email } => vec![
+                RoffNode::email("", email),
+                RoffNode::text("or"),
+                RoffNode::url("", url),
+            ],
+        }
+    }
+}
+
+impl Roff {
+    /// Builder method for adding an `AUTHORS` section. Each entry consists of the name of the
+    /// author and an optional email address. Authors with an email address are rendered as email
+    /// nodes, otherwise just the name is displayed. Every author is displayed on a separate line
+    /// and duplicate entries are skipped.
+    pub fn authors<I, N, E>(self, authors: I) -> Self
+    where
+        I: IntoIterator<Item = (N, Option<E>)>,
+        N: Into<RoffText>,
+        E: Into<RoffText>,
+    {
+        let mut seen: Vec<(RoffText, Option<RoffText>)> = vec![];
+        for (name, email) in authors {
+            let author = (name.into(), email.map(Into::into));
+            if !seen.contains(&author) {
+                seen.push(author);
+            }
+        }
+
+        let mut nodes = Vec::with_capacity(seen.len() * 2);
+        for (i, (name, email)) in seen.into_iter().enumerate() {
+            if i > 0 {
+                nodes.push(RoffNode::linebreak());
+            }
+            nodes.push(match email {
+                Some(email) => RoffNode::email(name, email),
+                None => RoffNode::text(name),
+            });
+        }
+
+        self.section(StandardSection::Authors, nodes)
+    }
+
+    /// Builder method for adding an `EXAMPLES` section. Each entry consists of a description and a
+    /// code snippet. The description is rendered as a paragraph (unless it already is one) followed
+    /// by the code snippet in an example block indented by 4.
+    pub fn examples<I, D, C>(self, examples: I) -> Self
+    where
+        I: IntoIterator<Item = (D, C)>,
+        D: IntoRoffNode,
+        C: Into<RoffText>,
+    {
+        let nodes: Vec<_> = examples
+            .into_iter()
+            .flat_map(|(description, code)| {
+                let description = description.into_roff();
+                let description = match description.inner_ref() {
+                    RoffNodeInner::Paragraph(_) => description,
+                    _ => RoffNode::paragraph([description]),
+                };
+                vec![
+                    description,
+                    RoffNode::nested_indented([RoffNode::example([code])], 4),
+                ]
+            })
+            .collect();
+
+        self.section(StandardSection::Examples, nodes)
+    }
+
+    /// Builder method for adding a `BUGS` section pointing to the place where bugs should be
+    /// reported, followed by optional `extra_text` paragraphs. The section starts with a GNU style
+    /// sentence `Report bugs to:`, use
+    /// [`reporting_bugs_with_wording`](Roff::reporting_bugs_with_wording) to change it.
+    pub fn reporting_bugs<I, R>(self, contact: BugContact, extra_text: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        self.reporting_bugs_with_wording(contact, REPORT_BUGS_WORDING, extra_text)
+    }
+
+    /// Same as [`reporting_bugs`](Roff::reporting_bugs) but the sentence pointing to the `contact`
+    /// is replaced with `wording`.
+    pub fn reporting_bugs_with_wording<I, R>(
+        self,
+        contact: BugContact,
+        wording: impl Into<RoffText>,
+        extra_text: I,
+    ) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        let mut contact_paragraph = vec![RoffNode::text(wording)];
+        contact_paragraph.extend(contact.nodes());
+
+        let mut nodes = vec![RoffNode::paragraph(contact_paragraph)];
+        nodes.extend(
+            extra_text
+                .into_iter()
+                .map(|text| RoffNode::paragraph([text])),
+        );
+
+        self.section(StandardSection::Bugs, nodes)
+    }
+
+    /// Builder method for adding an `OPTIONS` section listing all of the `opts`. The same options
+    /// can be used to create a [`RoffNode::synopsis`](RoffNode::synopsis) block so that every
+    /// option is only defined once. See [`RoffNode::option_list`](RoffNode::option_list) for
+    /// details.
+    pub fn options_section<O, S>(self, opts: O) -> Self
+    where
+        O: IntoIterator<Item = S>,
+        S: Into<SynopsisItem>,
+    {
+        self.section(StandardSection::Options, [RoffNode::option_list(opts)])
+    }
+
+    /// Builder method for adding a `NAME` section with the title of this roff and all of its
+    /// [`aliases`](Roff::aliases) followed by the `description`, like
+    /// `grep, egrep, fgrep \- print lines that match patterns`.
+    pub fn name_section(self, description: impl Into<RoffText>) -> Self {
+        let names = self.names();
+        self.section(
+            StandardSection::Name,
+            [RoffNode::whatis(names, description)],
+        )
+    }
+
+    /// Builder method for setting alternative names of the command documented by this roff. The
+    /// aliases are listed in the `NAME` section created with
+    /// [`name_section`](Roff::name_section) and can be used to create link pages with
+    /// [`alias_pages`](Roff::alias_pages).
+    pub fn aliases<I, R>(mut self, aliases: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<RoffText>,
+    {
+        self.aliases = aliases.into_iter().map(Into::into).collect();
+        let names = self.names();
+        for section in &mut self.sections {
+            if section.standard() != Some(StandardSection::Name) {
+                continue;
+            }
+            for node in section.get_nodes_mut() {
+                if let RoffNodeInner::Whatis { names: old, .. } = node.inner_mut() {
+                    *old = names.clone();
+                }
+            }
+        }
+        self
+    }
+
+    /// Returns the aliases of this roff.
+    pub fn get_aliases(&self) -> &[RoffText] {
+        &self.aliases
+    }
+
+    /// Returns a link page for every alias of this roff as a pair of the file name and the content
+    /// of the file. Each page sources this roff, for example the alias `egrep` of the page `grep`
+    /// in section 1 results in the file `egrep.1` containing `.so man1/grep.1`. File names are
+    /// derived like [`file_name`](Roff::file_name), so the link points at the file the page is
+    /// installed as.
+    pub fn alias_pages(&self) -> Vec<(String, String)> {
+        let target = format!(
+            ".so {}/{}\n",
+            self.section.directory_name(),
+            self.file_name()
+        );
+        self.aliases
+            .iter()
+            .map(|alias| (page_file_name(alias, &self.section), target.clone()))
+            .collect()
+    }
+
+    /// Returns the title of this roff followed by all of the aliases.
+    fn names(&self) -> Vec<RoffText> {
+        std::iter::once(self.title.clone())
+            .chain(self.aliases.iter().cloned())
+            .collect()
+    }
+}
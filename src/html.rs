@@ -0,0 +1,597 @@
+use crate::node::{ungrouped, RoffNodeInner};
+use crate::render::RenderOptions;
+use crate::synopsis::{SynopsisItem, SynopsisOpt};
+use crate::{FontStyle, Roff, RoffError, RoffNode, RoffText};
+
+use std::collections::HashSet;
+use std::io::Write;
+
+impl Roff {
+    /// Renders this roff as a fragment of semantic HTML to the given writer, without depending on
+    /// `groff -Thtml`. Useful for publishing the manual page on the project website.
+    ///
+    /// The title header becomes an `<h1>`, sections and sub headings become `<h2>` and `<h3>`
+    /// elements with ids generated from their titles for deep linking, paragraphs become `<p>`,
+    /// examples `<pre><code>` and tagged paragraphs and options are grouped into description
+    /// lists. URLs and emails become links and bold, italic and literal text is wrapped in
+    /// `<strong>`, `<em>` and `<code>`. The text is escaped for HTML starting from the content as
+    /// it was provided, and comments are skipped.
+    pub fn render_html<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        let mut html = HtmlWriter::new(writer, &self.options);
+        self.write_html(&mut html)?;
+        for page in &self.pages {
+            page.write_html(&mut html)?;
+        }
+        Ok(())
+    }
+
+    fn write_html<W: Write>(&self, html: &mut HtmlWriter<'_, W>) -> Result<(), RoffError> {
+        writeln!(
+            html.writer,
+            "<h1>{}</h1>",
+            escape_html(&self.display_title())
+        )?;
+
+        for section in &self.sections {
+            html.heading("h2", section.get_title())?;
+            if let Some(subtitle) = section.get_subtitle() {
+                html.heading("h3", subtitle)?;
+            }
+            html.nodes(section.get_nodes())?;
+            html.close_blocks()?;
+        }
+
+        if let Some(date) = &self.date {
+            write!(html.writer, "<footer>")?;
+            html.styled(date)?;
+            writeln!(html.writer, "</footer>")?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes the characters with a special meaning in HTML text and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '\u{A0}' => out.push_str("&nbsp;"),
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Returns an id for an element made of the lowercase alphanumeric characters of `title` with
+/// the runs of other characters replaced with `-`.
+fn slugify(title: &str) -> String {
+    let mut slug = String::with_capacity(title.len());
+    for ch in title.chars().flat_map(char::to_lowercase) {
+        if ch.is_alphanumeric() {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+    slug
+}
+
+/// Returns the HTML element used for text of the given `style`, or `None` for regular text.
+fn style_element(style: FontStyle) -> Option<&'static str> {
+    match style {
+        FontStyle::Bold => Some("strong"),
+        FontStyle::Italic => Some("em"),
+        FontStyle::ConstantWidth | FontStyle::Literal => Some("code"),
+        FontStyle::Roman => None,
+    }
+}
+
+/// Returns `true` if `node` is added to the description list of the preceding tagged paragraphs.
+fn continues_list(node: &RoffNodeInner) -> bool {
+    matches!(
+        node,
+        RoffNodeInner::TaggedParagraph { .. }
+            | RoffNodeInner::IndentedParagraph {
+                indentation: Some(_),
+                title: Some(_),
+                ..
+            }
+            | RoffNodeInner::OptionList(_)
+            | RoffNodeInner::Comment(_)
+    )
+}
+
+/// Writes the nodes of a document as HTML elements.
+struct HtmlWriter<'w, W: Write> {
+    writer: &'w mut W,
+    options: &'w RenderOptions,
+    /// Ids already used by the headings of the document.
+    ids: HashSet<String>,
+    /// Whether a `<p>` element is open for the inline content.
+    paragraph: bool,
+    /// Whether a `<dl>` element is open for consecutive tagged paragraphs.
+    list: bool,
+    /// Whether the next text starts a new input line, where an empty line is a paragraph break.
+    line_start: bool,
+}
+
+impl<'w, W: Write> HtmlWriter<'w, W> {
+    fn new(writer: &'w mut W, options: &'w RenderOptions) -> Self {
+        Self {
+            writer,
+            options,
+            ids: HashSet::new(),
+            paragraph: false,
+            list: false,
+            line_start: true,
+        }
+    }
+
+    /// Returns a unique id for a heading with `title`, numbering the repeated ones.
+    fn unique_id(&mut self, title: &str) -> String {
+        let slug = match slugify(title) {
+            slug if slug.is_empty() => "section".to_string(),
+            slug => slug,
+        };
+        let mut id = slug.clone();
+        let mut n = 1;
+        while !self.ids.insert(id.clone()) {
+            n += 1;
+            id = format!("{}-{}", slug, n);
+        }
+        id
+    }
+
+    fn heading(&mut self, element: &str, title: &RoffText) -> Result<(), RoffError> {
+        self.close_blocks()?;
+        let id = self.unique_id(&title.unescaped());
+        write!(self.writer, "<{} id=\"{}\">", element, escape_html(&id))?;
+        self.styled(title)?;
+        writeln!(self.writer, "</{}>", element)?;
+        Ok(())
+    }
+
+    fn close_paragraph(&mut self) -> Result<(), RoffError> {
+        if std::mem::take(&mut self.paragraph) {
+            writeln!(self.writer, "</p>")?;
+        }
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Closes the open paragraph and description list.
+    fn close_blocks(&mut self) -> Result<(), RoffError> {
+        self.close_paragraph()?;
+        if std::mem::take(&mut self.list) {
+            writeln!(self.writer, "</dl>")?;
+        }
+        Ok(())
+    }
+
+    /// Writes `text` with the element of `style` around it, opening a paragraph if there is
+    /// none. A line break is a space unless it makes an empty input line, which ends the
+    /// paragraph.
+    fn inline(&mut self, text: &str, style: FontStyle) -> Result<(), RoffError> {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                if self.line_start {
+                    self.close_paragraph()?;
+                }
+                self.line_start = true;
+            }
+            if part.is_empty() {
+                continue;
+            }
+            if !self.paragraph {
+                write!(self.writer, "<p>")?;
+                self.paragraph = true;
+            } else if self.line_start {
+                writeln!(self.writer)?;
+            }
+            self.line_start = false;
+            match style_element(style) {
+                Some(element) => write!(
+                    self.writer,
+                    "<{}>{}</{}>",
+                    element,
+                    escape_html(part),
+                    element
+                )?,
+                None => write!(self.writer, "{}", escape_html(part))?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes every segment of `text` in its own style without opening a paragraph.
+    fn styled(&mut self, text: &RoffText) -> Result<(), RoffError> {
+        for (content, style) in text.unescaped_segments() {
+            let content = escape_html(&content);
+            match style_element(style) {
+                Some(element) => write!(self.writer, "<{}>{}</{}>", element, content, element)?,
+                None => write!(self.writer, "{}", content)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &RoffText) -> Result<(), RoffError> {
+        for (content, style) in text.unescaped_segments() {
+            self.inline(&content, style)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the `content` of a block with the `tag` as a term of a description list.
+    fn tagged<F>(&mut self, tag: F, content: &[RoffNode]) -> Result<(), RoffError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), RoffError>,
+    {
+        self.close_paragraph()?;
+        if !std::mem::replace(&mut self.list, true) {
+            writeln!(self.writer, "<dl>")?;
+        }
+        write!(self.writer, "<dt>")?;
+        tag(self)?;
+        writeln!(self.writer, "</dt>")?;
+        self.block("dd", "", content)
+    }
+
+    /// Writes `content` inside of the `element` with `attributes`. The element is closed together
+    /// with everything that was opened inside of it.
+    fn block(
+        &mut self,
+        element: &str,
+        attributes: &str,
+        content: &[RoffNode],
+    ) -> Result<(), RoffError> {
+        let list = std::mem::take(&mut self.list);
+        writeln!(self.writer, "<{}{}>", element, attributes)?;
+        self.nodes(content)?;
+        self.close_blocks()?;
+        writeln!(self.writer, "</{}>", element)?;
+        self.list = list;
+        self.line_start = true;
+        Ok(())
+    }
+
+    fn nodes(&mut self, nodes: &[RoffNode]) -> Result<(), RoffError> {
+        for node in ungrouped(nodes).iter() {
+            self.node(node)?;
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, node: &RoffNode) -> Result<(), RoffError> {
+        let inner = node.inner_ref();
+        if self.list && !continues_list(inner) {
+            self.close_blocks()?;
+        }
+        match inner {
+            RoffNodeInner::Text(text) => self.text(text)?,
+            RoffNodeInner::SubHeading(title) => self.heading("h3", title)?,
+            RoffNodeInner::Whatis { names, description } => {
+                self.text(&RoffText::join(names.iter().cloned(), ", "))?;
+                self.inline(" - ", FontStyle::Roman)?;
+                self.text(description)?;
+            }
+            RoffNodeInner::Paragraph(content) => {
+                self.close_paragraph()?;
+                self.nodes(content)?;
+            }
+            RoffNodeInner::IndentedParagraph {
+                content,
+                indentation,
+                title,
+            } => match (indentation, title) {
+                (Some(_), Some(title)) => self.tagged(|html| html.styled(title), content)?,
+                _ => {
+                    self.close_paragraph()?;
+                    self.block("div", " class=\"indented\"", content)?;
+                }
+            },
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                self.tagged(|html| html.styled(title), content)?
+            }
+            RoffNodeInner::Example(content) => {
+                self.close_paragraph()?;
+                write!(self.writer, "<pre><code>")?;
+                for text in content {
+                    self.styled(text)?;
+                }
+                writeln!(self.writer, "</code></pre>")?;
+            }
+            RoffNodeInner::Columns { rows, .. } => {
+                self.close_paragraph()?;
+                writeln!(self.writer, "<table>")?;
+                for (key, value) in rows {
+                    write!(self.writer, "<tr><td>")?;
+                    self.styled(key)?;
+                    write!(self.writer, "</td><td>")?;
+                    self.styled(value)?;
+                    writeln!(self.writer, "</td></tr>")?;
+                }
+                writeln!(self.writer, "</table>")?;
+            }
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                self.close_paragraph()?;
+                writeln!(self.writer, "<div class=\"synopsis\">")?;
+                self.inline(&command.unescaped(), FontStyle::Bold)?;
+                self.line_start = true;
+                for elem in text {
+                    self.text(elem)?;
+                }
+                self.close_paragraph()?;
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.synopsis_opt(opt)?,
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.inline(&group.title.unescaped(), FontStyle::Bold)?;
+                            self.close_paragraph()?;
+                            for opt in &group.opts {
+                                self.synopsis_opt(opt)?;
+                            }
+                        }
+                    }
+                }
+                writeln!(self.writer, "</div>")?;
+            }
+            RoffNodeInner::OptionList(opts) => {
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.tagged_opt(opt)?,
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.heading("h3", &group.title)?;
+                            for opt in &group.opts {
+                                self.tagged_opt(opt)?;
+                            }
+                        }
+                    }
+                }
+            }
+            RoffNodeInner::Url { name, address } => self.link("", name, address)?,
+            RoffNodeInner::Email { name, address } => self.link("mailto:", name, address)?,
+            RoffNodeInner::RegisteredSign => self.inline("®", FontStyle::Roman)?,
+            RoffNodeInner::LeftQuote => self.inline("“", FontStyle::Roman)?,
+            RoffNodeInner::RightQuote => self.inline("”", FontStyle::Roman)?,
+            RoffNodeInner::TrademarkSign => self.inline("™", FontStyle::Roman)?,
+            RoffNodeInner::EmDash => self.inline("—", FontStyle::Roman)?,
+            RoffNodeInner::EnDash => self.inline("–", FontStyle::Roman)?,
+            RoffNodeInner::NonBreakingSpace => self.inline("\u{A0}", FontStyle::Roman)?,
+            RoffNodeInner::Nested { nodes, .. } => {
+                self.close_paragraph()?;
+                self.block("div", " class=\"indented\"", nodes)?;
+            }
+            RoffNodeInner::Group(nodes) => self.nodes(nodes)?,
+            RoffNodeInner::Break => {
+                if self.paragraph {
+                    write!(self.writer, "<br>")?;
+                }
+                self.line_start = true;
+            }
+            RoffNodeInner::Comment(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Writes a link to the `address` with the `name` as its text, or the address if the name is
+    /// empty.
+    fn link(&mut self, scheme: &str, name: &RoffText, address: &RoffText) -> Result<(), RoffError> {
+        let address = address.unescaped();
+        if !self.paragraph {
+            write!(self.writer, "<p>")?;
+            self.paragraph = true;
+        } else {
+            writeln!(self.writer)?;
+        }
+        write!(
+            self.writer,
+            "<a href=\"{}{}\">",
+            escape_html(scheme),
+            escape_html(&address)
+        )?;
+        if name.is_empty() {
+            write!(self.writer, "{}", escape_html(&address))?;
+        } else {
+            self.styled(name)?;
+        }
+        write!(self.writer, "</a>")?;
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Writes the name and the argument of `opt`.
+    fn opt_tag(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        write!(
+            self.writer,
+            "<strong>{}</strong>",
+            escape_html(&opt.name.unescaped())
+        )?;
+        if opt.inline_values && !opt.possible_values.is_empty() {
+            let values: Vec<_> = opt
+                .possible_values
+                .iter()
+                .map(|value| format!("<em>{}</em>", escape_html(&value.unescaped())))
+                .collect();
+            write!(self.writer, " {}", values.join("|"))?;
+        } else if let Some(argument) = &opt.argument {
+            write!(
+                self.writer,
+                " <em>{}</em>",
+                escape_html(&argument.unescaped())
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the description of `opt` followed by the possible values and the default value.
+    fn opt_description(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        let mut was_text = false;
+        if let Some(description) = &opt.description {
+            for elem in description {
+                self.text(elem)?;
+                was_text = was_text || !elem.is_empty();
+            }
+        }
+        if !opt.possible_values.is_empty() {
+            if was_text {
+                self.inline(" ", FontStyle::Roman)?;
+            }
+            self.inline("Possible values: ", FontStyle::Roman)?;
+            for (i, value) in opt.possible_values.iter().enumerate() {
+                if i > 0 {
+                    self.inline(", ", FontStyle::Roman)?;
+                }
+                self.inline(&value.unescaped(), FontStyle::Bold)?;
+            }
+            was_text = true;
+        }
+        if let Some(default_value) = &opt.default_value {
+            if was_text {
+                self.inline(" ", FontStyle::Roman)?;
+            }
+            self.inline("(default: ", FontStyle::Roman)?;
+            self.text(default_value)?;
+            self.inline(")", FontStyle::Roman)?;
+        }
+        self.close_paragraph()
+    }
+
+    fn synopsis_opt(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        if !opt.is_visible(self.options) {
+            return Ok(());
+        }
+        write!(self.writer, "<p>[")?;
+        self.opt_tag(opt)?;
+        write!(self.writer, "]")?;
+        self.paragraph = true;
+        self.line_start = true;
+        self.opt_description(opt)
+    }
+
+    fn tagged_opt(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        if !opt.is_visible(self.options) {
+            return Ok(());
+        }
+        self.close_paragraph()?;
+        if !std::mem::replace(&mut self.list, true) {
+            writeln!(self.writer, "<dl>")?;
+        }
+        write!(self.writer, "<dt>")?;
+        self.opt_tag(opt)?;
+        writeln!(self.writer, "</dt>")?;
+        writeln!(self.writer, "<dd>")?;
+        self.opt_description(opt)?;
+        writeln!(self.writer, "</dd>")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::slugify;
+    use crate::tests::readme_example;
+    use crate::{Roff, RoffNode, Roffable, SectionNumber, SynopsisOpt};
+
+    fn render(roff: &Roff) -> String {
+        let mut out = vec![];
+        roff.render_html(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_renders_the_readme_example() {
+        assert_eq!(
+            render(&readme_example()),
+            include_str!("../tests/golden/readme.html")
+        );
+    }
+
+    #[test]
+    fn it_escapes_html() {
+        let roff = Roff::new("<test>", SectionNumber::UserCommands)
+            .section(
+                "A & B",
+                [
+                    RoffNode::paragraph(["1 < 2 && \"quoted\" 'text'".roff().bold()]),
+                    RoffNode::url("<link>", "https://example.com/?a=1&b=\"2\""),
+                ],
+            )
+            .section("a & b", [RoffNode::text("")]);
+        assert_eq!(
+            render(&roff),
+            r#"<h1>&lt;test&gt;(1)</h1>
+<h2 id="a-b">A &amp; B</h2>
+<p><strong>1 &lt; 2 &amp;&amp; &quot;quoted&quot; &#39;text&#39;</strong>
+<a href="https://example.com/?a=1&amp;b=&quot;2&quot;">&lt;link&gt;</a></p>
+<h2 id="a-b-2">a &amp; b</h2>
+"#
+        );
+    }
+
+    #[test]
+    fn it_groups_tagged_paragraphs() {
+        let roff = Roff::new("test", SectionNumber::UserCommands).section(
+            "OPTIONS",
+            [
+                RoffNode::tagged_paragraph(["first"], "-a"),
+                RoffNode::comment("between"),
+                RoffNode::option_list([SynopsisOpt::new("--color")
+                    .argument("WHEN")
+                    .possible_values(["auto", "never"])]),
+                RoffNode::nested([
+                    RoffNode::text("nested"),
+                    RoffNode::linebreak(),
+                    RoffNode::text("text"),
+                ]),
+                RoffNode::email("Me", "me@example.com"),
+            ],
+        );
+        assert_eq!(
+            render(&roff),
+            r#"<h1>test(1)</h1>
+<h2 id="options">OPTIONS</h2>
+<dl>
+<dt>-a</dt>
+<dd>
+<p>first</p>
+</dd>
+<dt><strong>--color</strong> <em>WHEN</em></dt>
+<dd>
+<p>Possible values: <strong>auto</strong>, <strong>never</strong></p>
+</dd>
+</dl>
+<div class="indented">
+<p>nested<br>
+text</p>
+</div>
+<p><a href="mailto:me@example.com">Me</a></p>
+"#
+        );
+    }
+
+    #[test]
+    fn slugify_works() {
+        assert_eq!(slugify("EXIT STATUS"), "exit-status");
+        assert_eq!(slugify(" --Options & flags-- "), "options-flags");
+        assert_eq!(slugify("Zażółć"), "zażółć");
+    }
+}
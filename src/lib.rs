@@ -3,7 +3,7 @@
 //!
 //! ## Example usage
 //! ```
-//! use roffman::{Roff, RoffNode, Roffable, SectionNumber, SynopsisOpt};
+//! use roffman::{Roff, RoffNode, RoffStyleExt, Roffable, SectionNumber, SynopsisOpt};
 //!
 //! let roff = Roff::new("roffman", SectionNumber::Miscellaneous)
 //! .date("August 2021")
@@ -16,7 +16,7 @@
 //!        RoffNode::indented_paragraph(
 //!            [
 //!                "This line should be slightly indented to the ".roff(),
-//!                "right.".roff().bold(),
+//!                "right.".bold(),
 //!            ],
 //!            Some(4),
 //!            Some("optional-title")
@@ -25,14 +25,14 @@
 //!                 "roffman-command",
 //!                 [
 //!                 "This is the description of this command. It will be displayed right next to".roff(),
-//! " it".roff().italic()
+//! " it".italic()
 //! ]                     ,
 //!                 [
 //!                 SynopsisOpt::new("--opt").description(["some simple opt"]),
 //!                 SynopsisOpt::new("--opt-with-arg").argument("ARG").description(["opt with an argument"]),
 //!                 SynopsisOpt::new("--bold")
 //!        ]),
-//!        RoffNode::paragraph(["Example:".roff().bold()]),
+//!        RoffNode::paragraph(["Example:".bold()]),
 //!        RoffNode::example([
 //!             r#"
 //! impl Roffable for u8 {
@@ -47,25 +47,20 @@
 //!     ],
 //! );
 //!
-//! let rendered = roff.to_string().unwrap();
+//! let rendered = roff.render_to_string().unwrap();
 //!
 //! let output = r#".TH roffman 7 "August 2021"
 //! .SH "BASIC USAGE"
-//! .P
 //! This is how you create a basic paragraph using roffman.
 //! .IP optional\-title 4
 //! This line should be slightly indented to the \fBright.\fR
 //! .SY roffman\-command
 //! This is the description of this command. It will be displayed right next to\fI it\fR
-//!
 //! .OP \-\-opt
 //! some simple opt
-//!
 //! .OP \-\-opt\-with\-arg ARG
 //! opt with an argument
-//!
 //! .OP \-\-bold
-//!
 //! .YS
 //! .P
 //! \fBExample:\fR
@@ -81,7 +76,7 @@
 //! GitHub
 //! .UE
 //!
-//! vv9k\*(Tm
+//! vv9k\(tm
 //! "#;
 //!
 //! assert_eq!(rendered.trim(), output.trim());
@@ -117,21 +112,73 @@
 //!                                                               August 2021                                             roffman(7)
 //! ```
 
+#[cfg(feature = "async")]
+mod async_write;
+mod cargo;
+#[cfg(feature = "clap")]
+mod clap_command;
+mod date;
+#[cfg(feature = "descriptor")]
+mod descriptor;
+mod diagnostic;
+mod diff;
+mod display;
 mod escape;
+mod file;
+#[cfg(feature = "gzip")]
+mod gzip;
+mod helpers;
+#[cfg(feature = "html")]
+mod html;
+mod lint;
+mod mandir;
+mod markdown;
+mod mdoc;
+mod merge;
 mod node;
+mod plain;
+mod render;
 mod section;
+mod stream;
+mod synopsis;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 mod text;
+mod transform;
+mod validation;
+mod verify;
+mod visit;
 
-pub use node::RoffNode;
-pub use section::Section;
+#[cfg(feature = "descriptor")]
+pub use descriptor::DescriptorFormat;
+pub use diagnostic::{Diagnostic, NodePath, Severity};
+pub use diff::{diff, ChangeKind, DocumentChange, RoffDiff};
+pub use display::{display, styled, DisplayRoff};
+pub use escape::{escape, escape_with_policy, unescape, EscapePolicy, HyphenMode, TabPolicy};
+pub use helpers::BugContact;
+pub use lint::{LintFinding, LintKind};
+pub use mandir::ManDirWriter;
+pub use merge::MergeOptions;
+pub use node::{RoffNode, RoffNodeKind};
+pub use render::{CompatLevel, LiteralFont, OutputEncoding, RenderOptions};
+pub use section::{Section, StandardSection};
+pub use stream::RoffStream;
+pub use synopsis::{SynopsisItem, SynopsisOpt, SynopsisOptGroup};
 pub use text::{FontStyle, RoffText};
+pub use validation::ValidationKind;
+pub use verify::{verify_output, OutputIssue, OutputIssueKind};
+pub use visit::{CollectText, Visitor};
 
-use escape::escape;
+use render::FmtWriter;
 
+use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 use std::fmt::Formatter;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 mod _macro {
     pub(crate) const SPACE: &[u8] = b" ";
@@ -139,6 +186,10 @@ mod _macro {
     pub(crate) const ENDL: &[u8] = b"\n";
     pub(crate) const BOLD: &[u8] = b"\\fB";
     pub(crate) const ITALIC: &[u8] = b"\\fI";
+    pub(crate) const CONSTANT_WIDTH: &[u8] = b"\\f(CW";
+    pub(crate) const SIZE_RESET: &[u8] = b"\\s0";
+    pub(crate) const NO_HYPHENATION: &[u8] = b"\\%";
+    pub(crate) const ZERO_WIDTH: &[u8] = b"\\&";
     pub(crate) const FONT_END: &[u8] = b"\\fR";
     pub(crate) const SECTION_HEADER: &[u8] = b".SH";
     pub(crate) const SUB_HEADER: &[u8] = b".SS";
@@ -166,14 +217,200 @@ mod _macro {
     pub(crate) const EN_DASH: &[u8] = b"\\(en";
     pub(crate) const NON_BREAKING_SPACE: &[u8] = b"\\~";
     pub(crate) const COMMENT: &[u8] = b"\\\"";
+    pub(crate) const COMMENT_LINE: &[u8] = b".\\\"";
+    pub(crate) const TAB_STOPS: &[u8] = b".ta";
+    pub(crate) const NO_FILL: &[u8] = b".nf";
+    pub(crate) const FILL: &[u8] = b".fi";
+    pub(crate) const CONSTANT_WIDTH_FONT: &[u8] = b".ft CW";
+    pub(crate) const ROMAN_FONT: &[u8] = b".ft R";
+    pub(crate) const TAB: &[u8] = b"\t";
 }
-use _macro::{ENDL, QUOTE, SPACE, TITLE_HEADER};
+use _macro::{COMMENT_LINE, ENDL, QUOTE, SPACE, TITLE_HEADER};
+
+/// The standard preamble of pages generated by `pod2man`, with fallbacks for fonts and strings
+/// defining accent marks, written verbatim before the title header by
+/// [`classic_preamble`](Roff::classic_preamble).
+pub(crate) const CLASSIC_PREAMBLE: &str = r##".de Sp \" Vertical space (when we can't use .PP)
+.if t .sp .5v
+.if n .sp
+..
+.de Vb \" Begin verbatim text
+.ft CW
+.nf
+.ne \\$1
+..
+.de Ve \" End verbatim text
+.ft R
+.fi
+..
+.\" Set up some character translations and predefined strings.  \*(-- will
+.\" give an unbreakable dash, \*(PI will give pi, \*(L" will give a left
+.\" double quote, and \*(R" will give a right double quote.  \*(C+ will
+.\" give a nicer C++.  Capital omega is used to do unbreakable dashes and
+.\" therefore won't be available.  \*(C` and \*(C' expand to `' in nroff,
+.\" nothing in troff, for use with C<>.
+.tr \(*W-
+.ds C+ C\v'-.1v'\h'-1p'\s-2+\h'-1p'+\s0\v'.1v'\h'-1p'
+.ie n \{\
+.    ds -- \(*W-
+.    ds PI pi
+.    if (\n(.H=4u)&(1m=24u) .ds -- \(*W\h'-12u'\(*W\h'-12u'-\" diablo 10 pitch
+.    if (\n(.H=4u)&(1m=20u) .ds -- \(*W\h'-12u'\(*W\h'-8u'-\"  diablo 12 pitch
+.    ds L" ""
+.    ds R" ""
+.    ds C` ""
+.    ds C' ""
+'br\}
+.el\{\
+.    ds -- \|\(em\|
+.    ds PI \(*p
+.    ds L" ``
+.    ds R" ''
+.    ds C`
+.    ds C'
+'br\}
+.\"
+.\" Escape single quotes in literal strings from groff's Unicode transform.
+.ie \n(.g .ds Aq \(aq
+.el       .ds Aq '
+.\"
+.\" If the F register is >0, we'll generate index entries on stderr for
+.\" titles (.TH), headers (.SH), subsections (.SS), items (.Ip), and index
+.\" entries marked with X<> in POD.  Of course, you'll have to process the
+.\" output yourself in some meaningful fashion.
+.\"
+.\" Avoid warning from groff about undefined register 'F'.
+.de IX
+..
+.nr rF 0
+.if \n(.g .if rF .nr rF 1
+.if (\n(rF:(\n(.g==0)) \{\
+.    if \nF \{\
+.        de IX
+.        tm Index:\\$1\t\\n%\t"\\$2"
+..
+.        if !\nF==2 \{\
+.            nr % 0
+.            nr F 2
+.        \}
+.    \}
+.\}
+.rr rF
+.\"
+.\" Accent mark definitions (@(#)ms.acc 1.5 88/02/08 SMI; from UCB 4.2).
+.\" Fear.  Run.  Save yourself.  No user-serviceable parts.
+.    \" fudge factors for nroff and troff
+.if n \{\
+.    ds #H 0
+.    ds #V .8m
+.    ds #F .3m
+.    ds #[ \f1
+.    ds #] \fP
+.\}
+.if t \{\
+.    ds #H ((1u-(\\\\n(.fu%2u))*.13m)
+.    ds #V .6m
+.    ds #F 0
+.    ds #[ \&
+.    ds #] \&
+.\}
+.    \" simple accents for nroff and troff
+.if n \{\
+.    ds ' \&
+.    ds ` \&
+.    ds ^ \&
+.    ds , \&
+.    ds ~ ~
+.    ds /
+.\}
+.if t \{\
+.    ds ' \\k:\h'-(\\n(.wu*8/10-\*(#H)'\'\h"|\\n:u"
+.    ds ` \\k:\h'-(\\n(.wu*8/10-\*(#H)'\`\h'|\\n:u'
+.    ds ^ \\k:\h'-(\\n(.wu*10/11-\*(#H)'^\h'|\\n:u'
+.    ds , \\k:\h'-(\\n(.wu*8/10)',\h'|\\n:u'
+.    ds ~ \\k:\h'-(\\n(.wu-\*(#H-.1m)'~\h'|\\n:u'
+.    ds / \\k:\h'-(\\n(.wu*8/10-\*(#H)'\z\(sl\h'|\\n:u'
+.\}
+.    \" troff and (daisy-wheel) nroff accents
+.ds : \\k:\h'-(\\n(.wu*8/10-\*(#H+.1m+\*(#F)'\v'-\*(#V'\z.\h'.2m+\*(#F'.\h'|\\n:u'\v'\*(#V'
+.ds 8 \h'\*(#H'\(*b\h'-\*(#H'
+.ds o \\k:\h'-(\\n(.wu+\w'\(de'u-\*(#H)/2u'\v'-.3n'\*(#[\z\(de\v'.3n'\h'|\\n:u'\*(#]
+.ds d- \h'\*(#H'\(pd\h'-\w'~'u'\v'-.25m'\f2\(hy\fP\v'.25m'\h'-\*(#H'
+.ds D- D\\k:\h'-\w'D'u'\v'-.11m'\z\(hy\v'.11m'\h'|\\n:u'
+.ds th \*(#[\v'.3m'\s+1I\s-1\v'-.3m'\h'-(\w'I'u*2/3)'\s-1o\s+1\*(#]
+.ds Th \*(#[\s+2I\s-2\h'-\w'I'u*3/5'\v'-.3m'o\v'.3m'\*(#]
+.ds ae a\h'-(\w'a'u*4/10)'e
+.ds Ae A\h'-(\w'A'u*4/10)'E
+.    \" corrections for vroff
+.if v .ds ~ \\k:\h'-(\\n(.wu*9/10-\*(#H)'\v'-\*(#V'\s-2\(ti\s+2\v'\*(#V'\h'|\\n:u'
+.if v .ds ^ \\k:\h'-(\\n(.wu*10/11-\*(#H)'\v'-\*(#V'\s-2\(ha\s+2\v'\*(#V'\h'|\\n:u'
+.    \" for low resolution devices (crt and lpr)
+.if \n(.H>23 .if \n(.V>19 \
+\{\
+.    ds : e
+.    ds 8 ss
+.    ds o a
+.    ds d- d\h'-1'\(ga
+.    ds D- D\h'-1'\(hy
+.    ds th \o'bp'
+.    ds Th \o'LP'
+.    ds ae ae
+.    ds Ae AE
+.\}
+.rm #[ #] #H #V #F C
+.\" ========================================================================
+"##;
 
 #[derive(Debug)]
+#[non_exhaustive]
 /// An error type returned by the functions used in this crate.
 pub enum RoffError {
     StringRenderFailed(String),
     RenderFailed(io::Error),
+    /// Writing the node or the section at `path` failed.
+    NodeRenderFailed {
+        path: NodePath,
+        error: io::Error,
+    },
+    /// The directory a file should be written to doesn't exist.
+    MissingDirectory(PathBuf),
+    /// Writing to a [`fmt::Write`](fmt::Write) sink failed.
+    FormatFailed(fmt::Error),
+    /// The output contains `characters` that can't be represented in the selected
+    /// [`OutputEncoding`](OutputEncoding). Contains every such character once, in the order they
+    /// first occur, with its position, the number of the output line it first occurs on. Text
+    /// outside of comments is replaced with escapes instead, so only comments can trigger this
+    /// error.
+    Encoding {
+        encoding: OutputEncoding,
+        characters: Vec<(char, usize)>,
+    },
+    /// The title of a page can't be used as a file name.
+    InvalidFileName(String),
+    /// A page with the same name and section was already written to this path.
+    DuplicatePage(PathBuf),
+    /// A descriptor read by `Roff::from_descriptor` is invalid. Contains the `path` of the invalid
+    /// value, empty for the whole descriptor, and the description of the problem.
+    InvalidDescriptor {
+        path: String,
+        message: String,
+    },
+    /// Documents merged in strict mode have conflicting title headers. Contains the name of the
+    /// conflicting `field` and its values in both documents.
+    MergeConflict {
+        field: String,
+        ours: String,
+        theirs: String,
+    },
+    /// A section number is `0` or is not a number.
+    InvalidSectionNumber(String),
+    /// The document is not valid. Contains the `kind` and the location of the problem and its
+    /// description.
+    Validation {
+        kind: ValidationKind,
+        path: NodePath,
+        message: String,
+    },
 }
 
 impl fmt::Display for RoffError {
@@ -183,6 +420,62 @@ impl fmt::Display for RoffError {
                 write!(f, "Failed to render ROFF to string - `{}`", err)
             }
             RoffError::RenderFailed(err) => write!(f, "Failed to render ROFF - `{}`", err),
+            RoffError::NodeRenderFailed { path, error } => {
+                write!(f, "Failed to render ROFF at {} - `{}`", path, error)
+            }
+            RoffError::MissingDirectory(dir) => {
+                write!(f, "Directory `{}` doesn't exist", dir.display())
+            }
+            RoffError::FormatFailed(err) => write!(f, "Failed to format ROFF - `{}`", err),
+            RoffError::Encoding {
+                encoding,
+                characters,
+            } => {
+                write!(
+                    f,
+                    "Output contains characters that can't be represented in {} -",
+                    encoding
+                )?;
+                for (i, (character, position)) in characters.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(
+                        f,
+                        "{} `{}` (U+{:04X}) on line {}",
+                        separator,
+                        character,
+                        u32::from(*character),
+                        position
+                    )?;
+                }
+                Ok(())
+            }
+            RoffError::InvalidFileName(name) => {
+                write!(f, "`{}` is not a valid file name for a page", name)
+            }
+            RoffError::DuplicatePage(path) => {
+                write!(f, "Page `{}` was already written", path.display())
+            }
+            RoffError::InvalidDescriptor { path, message } if path.is_empty() => {
+                write!(f, "Invalid descriptor - {}", message)
+            }
+            RoffError::InvalidDescriptor { path, message } => {
+                write!(f, "Invalid descriptor at `{}` - {}", path, message)
+            }
+            RoffError::MergeConflict {
+                field,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "Conflicting {} when merging documents - `{}` and `{}`",
+                field, ours, theirs
+            ),
+            RoffError::InvalidSectionNumber(section) => {
+                write!(f, "`{}` is not a valid section number", section)
+            }
+            RoffError::Validation { path, message, .. } => {
+                write!(f, "Invalid document at {} - {}", path, message)
+            }
         }
     }
 }
@@ -190,87 +483,501 @@ impl fmt::Display for RoffError {
 impl Error for RoffError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            RoffError::RenderFailed(err) => Some(err),
+            RoffError::RenderFailed(err) | RoffError::NodeRenderFailed { error: err, .. } => {
+                Some(err)
+            }
+            RoffError::FormatFailed(err) => Some(err),
             _ => None,
         }
     }
 }
 
+impl RoffError {
+    /// Attaches the location returned by `path` to a failed write that has no location yet.
+    pub(crate) fn at(self, path: impl FnOnce() -> NodePath) -> Self {
+        match self {
+            RoffError::RenderFailed(error) => RoffError::NodeRenderFailed {
+                path: path(),
+                error,
+            },
+            err => err,
+        }
+    }
+}
+
 impl From<io::Error> for RoffError {
     fn from(err: io::Error) -> Self {
         Self::RenderFailed(err)
     }
 }
 
-fn write_quoted(roff: &RoffText, writer: &mut impl Write) -> Result<(), RoffError> {
+impl From<fmt::Error> for RoffError {
+    fn from(err: fmt::Error) -> Self {
+        Self::FormatFailed(err)
+    }
+}
+
+/// Renders `roff` as a macro argument. Arguments can't span multiple lines so line breaks are
+/// replaced with spaces.
+fn render_argument(roff: &RoffText, options: &RenderOptions) -> Result<Vec<u8>, RoffError> {
+    let mut rendered = vec![];
+    roff.render(&mut rendered, options)?;
+    rendered
+        .iter_mut()
+        .filter(|byte| **byte == b'\n')
+        .for_each(|byte| *byte = b' ');
+    Ok(rendered)
+}
+
+/// Writes a rendered macro argument in quotes. Quotes inside of the argument are doubled so that
+/// they don't end the argument early.
+fn write_quoted(rendered: &[u8], writer: &mut impl Write) -> Result<(), RoffError> {
     writer.write_all(QUOTE)?;
-    roff.render(writer)?;
+    for (i, part) in rendered.split(|byte| *byte == b'"').enumerate() {
+        if i > 0 {
+            writer.write_all(QUOTE)?;
+            writer.write_all(QUOTE)?;
+        }
+        writer.write_all(part)?;
+    }
     writer.write_all(QUOTE)?;
     Ok(())
 }
 
-fn write_quoted_if_whitespace(roff: &RoffText, writer: &mut impl Write) -> Result<(), RoffError> {
-    if roff
-        .content()
-        .as_bytes()
+/// Writes `roff` as a macro argument, quoted if it contains whitespace or quotes that would
+/// otherwise split it or start a quoted argument.
+fn write_quoted_if_whitespace(
+    roff: &RoffText,
+    writer: &mut impl Write,
+    options: &RenderOptions,
+) -> Result<(), RoffError> {
+    let rendered = render_argument(roff, options)?;
+    if rendered
         .iter()
-        .any(u8::is_ascii_whitespace)
+        .any(|byte| byte.is_ascii_whitespace() || *byte == b'"')
     {
-        write_quoted(roff, writer)
+        write_quoted(&rendered, writer)
     } else {
-        roff.render(writer)
+        writer.write_all(&rendered)?;
+        Ok(())
+    }
+}
+
+/// Makes a single line of a comment safe to render. Backslashes are escaped and control
+/// characters are removed so that the content can't escape the comment.
+fn sanitize_comment_line(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for ch in line.chars() {
+        match ch {
+            '\\' => out.push_str("\\e"),
+            '\t' => out.push(ch),
+            ch if ch.is_control() => {}
+            ch => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Splits `comment` into sanitized lines that can be rendered as separate comment lines.
+fn comment_lines(comment: &str) -> impl Iterator<Item = String> + '_ {
+    comment.split('\n').map(sanitize_comment_line)
+}
+
+/// Writes every line of `comment` as a separate `.\"` comment line. Empty lines are rendered as
+/// a bare `.\"`.
+fn write_comment_lines(comment: &str, writer: &mut impl Write) -> Result<(), RoffError> {
+    for line in comment_lines(comment) {
+        writer.write_all(COMMENT_LINE)?;
+        if !line.is_empty() {
+            writer.write_all(SPACE)?;
+            writer.write_all(line.as_bytes())?;
+        }
+        writer.write_all(ENDL)?;
     }
+    Ok(())
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Defines how the title of the document is displayed in the title header.
+pub enum TitleCase {
+    /// The title is displayed exactly as it was provided.
+    #[default]
+    AsIs,
+    /// The title is converted to uppercase, like `.TH GREP 1`. This is the convention used by most
+    /// man pages.
+    Upper,
+    /// The title is converted to lowercase.
+    Lower,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Represents a ROFF document that can be rendered and displayed
 /// with tools like [`man`](https://man7.org/linux/man-pages/man1/man.1.html).
+///
+/// Two documents are equal if they have the same structure, content and render options. Text is
+/// compared as it was provided, before escaping.
 pub struct Roff {
     title: RoffText,
+    title_case: TitleCase,
+    aliases: Vec<RoffText>,
+    generated_by: Vec<String>,
+    preamble: Vec<String>,
+    classic_preamble: bool,
     date: Option<RoffText>,
-    section: SectionNumber,
+    source: Option<RoffText>,
+    manual: Option<RoffText>,
+    section: SectionId,
     sections: Vec<Section>,
+    options: RenderOptions,
+    pages: Vec<Roff>,
 }
 
 impl Roff {
-    /// Create a new `Roff` with a `title` and a `section`.
-    pub fn new(title: impl Roffable, section: SectionNumber) -> Self {
+    /// Create a new `Roff` with a `title` and a `section`, either a
+    /// [`SectionNumber`](SectionNumber) or a [`SectionId`](SectionId) like `3pm`.
+    pub fn new(title: impl Into<RoffText>, section: impl Into<SectionId>) -> Self {
         Self {
-            title: title.roff(),
+            title: title.into(),
+            title_case: TitleCase::default(),
+            aliases: vec![],
+            generated_by: vec![],
+            preamble: vec![],
+            classic_preamble: false,
             date: None,
-            section,
+            source: None,
+            manual: None,
+            section: section.into(),
             sections: vec![],
+            options: RenderOptions::default(),
+            pages: vec![],
         }
     }
 
+    /// Returns the title of this document.
+    pub fn get_title(&self) -> &RoffText {
+        &self.title
+    }
+
+    /// Returns the title of this document as it was provided, before escaping.
+    pub fn get_title_original(&self) -> Cow<'_, str> {
+        self.title.original()
+    }
+
+    /// Replaces the title of this document.
+    pub fn set_title(&mut self, title: impl Into<RoffText>) {
+        self.title = title.into();
+    }
+
+    /// Returns the date of this document if it was set.
+    pub fn get_date(&self) -> Option<&RoffText> {
+        self.date.as_ref()
+    }
+
+    /// Replaces the date of this document.
+    pub fn set_date(&mut self, date: impl Into<RoffText>) {
+        self.date = Some(date.into());
+    }
+
+    /// Returns the source of this document, like the name and version of the project, if it was
+    /// set.
+    pub fn get_source(&self) -> Option<&RoffText> {
+        self.source.as_ref()
+    }
+
+    /// Returns the title of the manual this document belongs to if it was set.
+    pub fn get_manual(&self) -> Option<&RoffText> {
+        self.manual.as_ref()
+    }
+
+    /// Returns the section of the manual this document belongs to.
+    pub fn get_section_id(&self) -> &SectionId {
+        &self.section
+    }
+
+    /// Returns the sections of this document in the order they are rendered.
+    pub fn get_sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Returns the sections of this document for modification.
+    pub fn get_sections_mut(&mut self) -> &mut Vec<Section> {
+        &mut self.sections
+    }
+
     /// Renders this roff to a `String` returning an error if a write fails or the rendered
-    /// output contains invalid UTF-8 byte sequences.
+    /// output contains invalid UTF-8 byte sequences. The [`Display`](fmt::Display)
+    /// implementation produces the same output.
+    pub fn render_to_string(&self) -> Result<String, RoffError> {
+        let mut out = String::with_capacity(self.estimated_size());
+        self.render_fmt(&mut out)?;
+        Ok(out)
+    }
+
+    /// Renders this roff to a byte vector. Unlike [`render_to_string`](Roff::render_to_string)
+    /// the output is not validated as UTF-8, which is not needed when it's written to a file or
+    /// hashed.
+    pub fn render_to_vec(&self) -> Result<Vec<u8>, RoffError> {
+        let mut out = Vec::with_capacity(self.estimated_size());
+        self.render_into(&mut out)?;
+        Ok(out)
+    }
+
+    /// Returns an estimate of the number of bytes this roff takes when rendered, including the
+    /// appended pages. The estimate is computed from the length of the content and the macros
+    /// used by every node, so it can be used to reserve space for the output in a buffer, but it
+    /// is not exact.
+    pub fn estimated_size(&self) -> usize {
+        let comments: usize = self
+            .generated_by
+            .iter()
+            .chain(&self.preamble)
+            .map(|comment| comment.len() + 4 * (comment.matches('\n').count() + 1))
+            .sum();
+        let preamble = if self.classic_preamble {
+            CLASSIC_PREAMBLE.len()
+        } else {
+            0
+        };
+        let header = 10
+            + self.title.estimated_size()
+            + [&self.date, &self.source, &self.manual]
+                .iter()
+                .filter_map(|field| field.as_ref())
+                .map(|field| 3 + field.estimated_size())
+                .sum::<usize>();
+        let sections: usize = self.sections.iter().map(Section::estimated_size).sum();
+        let pages: usize = self.pages.iter().map(Roff::estimated_size).sum();
+        comments + preamble + header + sections + pages
+    }
+
+    /// Renders this roff appending the output to `buf`, so that the buffer can be reused between
+    /// documents.
+    pub fn render_into(&self, buf: &mut Vec<u8>) -> Result<(), RoffError> {
+        buf.reserve(self.estimated_size());
+        self.render(buf)
+    }
+
+    /// Renders this roff to a `String`, see [`render_to_string`](Roff::render_to_string).
+    #[deprecated(
+        since = "0.5.0",
+        note = "use `render_to_string` or the `Display` implementation instead"
+    )]
+    #[allow(clippy::inherent_to_string_shadow_display)]
     pub fn to_string(&self) -> Result<String, RoffError> {
-        let mut writer = std::io::BufWriter::new(vec![]);
-        self.render(&mut writer)
-            .map_err(|e| RoffError::StringRenderFailed(e.to_string()))?;
-        String::from_utf8(
-            writer
-                .into_inner()
-                .map_err(|e| RoffError::StringRenderFailed(e.to_string()))?,
-        )
-        .map_err(|e| RoffError::StringRenderFailed(e.to_string()))
+        self.render_to_string()
+    }
+
+    /// Builder method for setting the case of the title displayed in the title header. The title
+    /// is only converted when rendering the header, the conversion happens before escaping.
+    pub fn title_case(mut self, title_case: TitleCase) -> Self {
+        self.title_case = title_case;
+        self
+    }
+
+    /// Builder method for adding a comment describing how this document was generated. The comment
+    /// is rendered at the very top of the output, before the title header. Calling this method
+    /// multiple times adds multiple comments, every line of the text is rendered as a separate
+    /// comment line.
+    pub fn generated_by(mut self, text: impl AsRef<str>) -> Self {
+        self.generated_by.push(text.as_ref().to_string());
+        self
+    }
+
+    /// Same as [`generated_by`](Roff::generated_by) with the text `Generated by roffman x.y.z`
+    /// containing the version of this crate.
+    pub fn generated_by_roffman(self) -> Self {
+        self.generated_by(concat!("Generated by roffman ", env!("CARGO_PKG_VERSION")))
+    }
+
+    /// Builder method for adding comment lines, like a copyright and license notice, at the top of
+    /// the output. The comments are rendered before the title header and after the comments added
+    /// with [`generated_by`](Roff::generated_by). Empty lines are preserved as bare comment lines.
+    pub fn preamble_comments<I, L>(mut self, lines: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: AsRef<str>,
+    {
+        self.preamble
+            .extend(lines.into_iter().map(|line| line.as_ref().to_string()));
+        self
+    }
+
+    /// Builder method for writing the standard preamble of `pod2man`, which defines fallbacks for
+    /// fonts and strings for accent marks so that the page renders acceptably on old `troff`
+    /// implementations and matches what tools in the Perl ecosystem expect. The preamble is
+    /// written after the comments and before the title header. Disabled by default.
+    pub fn classic_preamble(mut self, enabled: bool) -> Self {
+        self.classic_preamble = enabled;
+        self
+    }
+
+    /// Builder method for appending another page to this roff. Appended pages are rendered in
+    /// order after this page, each with its own title header and sections, so that multiple man
+    /// pages can be rendered into a single output.
+    pub fn append_page(mut self, mut other: Roff) -> Self {
+        let pages = std::mem::take(&mut other.pages);
+        self.pages.push(other);
+        self.pages.extend(pages);
+        self
     }
 
     /// Builder method for adding a date to this roff.
-    pub fn date(mut self, date: impl Roffable) -> Self {
-        self.date = Some(date.roff());
+    pub fn date(mut self, date: impl Into<RoffText>) -> Self {
+        self.date = Some(date.into());
+        self
+    }
+
+    /// Builder method for setting the source of this roff, usually the name and version of the
+    /// project like `roffman 0.4.0`. Most pagers display it in the left corner of the footer.
+    pub fn source(mut self, source: impl Into<RoffText>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Builder method for setting the title of the manual this roff belongs to, like
+    /// `User Commands`. Most pagers display it in the center of the header.
+    pub fn manual(mut self, manual: impl Into<RoffText>) -> Self {
+        self.manual = Some(manual.into());
+        self
+    }
+
+    /// Builder method for selecting the font used for [`literal`](RoffText::literal) text. Defaults
+    /// to [`LiteralFont::ConstantWidth`](LiteralFont::ConstantWidth).
+    pub fn literal_font(mut self, font: LiteralFont) -> Self {
+        self.options.literal_font = font;
+        self
+    }
+
+    /// Builder method for selecting the set of macros the output relies on. Defaults to
+    /// [`CompatLevel::Groff`](CompatLevel::Groff), use [`CompatLevel::Portable`] for pages that
+    /// should render on older `troff` implementations.
+    pub fn compat(mut self, compat: CompatLevel) -> Self {
+        self.options.compat = compat;
+        self
+    }
+
+    /// Builder method for restricting the output to the character set of `encoding`, for
+    /// packaging targets that require ASCII or Latin-1 man pages. Defaults to
+    /// [`OutputEncoding::Utf8`](OutputEncoding::Utf8).
+    pub fn encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.options.encoding = encoding;
+        self
+    }
+
+    /// Builder method for replacing all of the options that alter the way this document is
+    /// rendered.
+    pub fn render_options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Builder method for setting the policy used to escape all text of this document that doesn't
+    /// define its own with [`RoffText::with_policy`](RoffText::with_policy).
+    pub fn escape_policy(mut self, policy: EscapePolicy) -> Self {
+        self.options.escape_policy = policy;
+        self
+    }
+
+    /// Builder method that forces hidden options to be rendered. Useful for internal builds of the
+    /// documentation.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.options.include_hidden = include_hidden;
         self
     }
 
     /// Add an already defined section to this roff.
-    pub fn add_section(mut self, section: Section) -> Self {
-        self.sections.push(section);
+    pub fn add_section(mut self, section: impl Into<Section>) -> Self {
+        self.sections.push(section.into());
+        self
+    }
+
+    /// Builder method for adding a new section to this roff only if `condition` is `true`. The
+    /// `content` closure is only called when the section is added, so generating the content is
+    /// skipped otherwise.
+    ///
+    /// ```
+    /// use roffman::{Roff, RoffNode, SectionNumber};
+    ///
+    /// let examples = vec![("List files:", "foo ls")];
+    /// let env_vars: Vec<(&str, &str)> = vec![];
+    ///
+    /// let roff = Roff::new("foo", SectionNumber::UserCommands)
+    ///     .section("NAME", ["foo"])
+    ///     .section_if(!examples.is_empty(), "EXAMPLES", || {
+    ///         examples
+    ///             .iter()
+    ///             .flat_map(|(text, example)| {
+    ///                 [RoffNode::paragraph([*text]), RoffNode::example([*example])]
+    ///             })
+    ///             .collect::<Vec<_>>()
+    ///     })
+    ///     .section_if(!env_vars.is_empty(), "ENVIRONMENT", || {
+    ///         env_vars
+    ///             .iter()
+    ///             .map(|(name, description)| RoffNode::tagged_paragraph([*description], *name))
+    ///             .collect::<Vec<_>>()
+    ///     });
+    ///
+    /// let titles: Vec<_> = roff.get_sections().iter().map(|s| s.get_title().original()).collect();
+    /// assert_eq!(titles, ["NAME", "EXAMPLES"]);
+    /// ```
+    pub fn section_if<F, I, R>(
+        self,
+        condition: bool,
+        title: impl Into<RoffText>,
+        content: F,
+    ) -> Self
+    where
+        F: FnOnce() -> I,
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        if condition {
+            self.section(title, content())
+        } else {
+            self
+        }
+    }
+
+    /// Builder method for applying `f` to this roff only if `condition` is `true`, so that
+    /// optional parts of a document can be added without breaking the chain of builder calls.
+    /// `f` is not called otherwise.
+    ///
+    /// ```
+    /// use roffman::{Roff, SectionNumber};
+    ///
+    /// let date = Some("August 2021");
+    /// let roff = Roff::new("foo", SectionNumber::UserCommands)
+    ///     .apply_if(date.is_some(), |roff| roff.date(date.unwrap()))
+    ///     .apply_if(false, |roff| roff.section("BUGS", ["None."]));
+    ///
+    /// assert_eq!(roff.render_to_string()?, ".TH foo 1 \"August 2021\"\n");
+    /// # Ok::<(), roffman::RoffError>(())
+    /// ```
+    pub fn apply_if(self, condition: bool, f: impl FnOnce(Self) -> Self) -> Self {
+        if condition {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    /// Builder method for adding multiple sections to this roff in order, like a `Vec<Section>`
+    /// produced by a generator or `(title, content)` pairs. Use the [`Extend`](Extend)
+    /// implementation to add sections to an existing roff in place.
+    pub fn add_sections<I, S>(mut self, sections: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Section>,
+    {
+        self.extend(sections);
         self
     }
 
     /// Builder method for adding a new section to this roff.
-    pub fn section<I, R>(self, title: impl Roffable, content: I) -> Self
+    pub fn section<I, R>(self, title: impl Into<RoffText>, content: I) -> Self
     where
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
@@ -278,20 +985,134 @@ impl Roff {
         self.add_section(Section::new(title, content))
     }
 
+    /// Reorders the sections of this roff so that they follow the conventional order of sections
+    /// described by [`StandardSection`](StandardSection). Sections with a title that is not a
+    /// standard one are placed right after the `DESCRIPTION` section keeping their relative order.
+    /// The sort is stable and doesn't modify the contents of sections.
+    pub fn sort_sections_conventionally(self) -> Self {
+        self.sort_sections_conventionally_after(StandardSection::Description)
+    }
+
+    /// Same as [`sort_sections_conventionally`](Roff::sort_sections_conventionally) but sections
+    /// with a non standard title are placed right after the `position` section.
+    pub fn sort_sections_conventionally_after(mut self, position: StandardSection) -> Self {
+        self.sections
+            .sort_by_key(|section| match section.standard() {
+                Some(standard) => (standard.order_index(), false),
+                None => (position.order_index(), true),
+            });
+        self
+    }
+
+    /// Merges sections with equal titles (compared case insensitively) into a single section placed
+    /// at the position of the first occurrence. The nodes of the later sections are appended to the
+    /// first one in order. The subtitle of the first section is kept while subtitles of the later
+    /// sections are converted to sub headings preceding their nodes.
+    pub fn merge_duplicate_sections(mut self) -> Self {
+        let mut merged: Vec<Section> = Vec::with_capacity(self.sections.len());
+        for section in self.sections.drain(..) {
+            merge::push_merged(&mut merged, section);
+        }
+        self.sections = merged;
+        self
+    }
+
+    /// Returns the index of the first section with `title`. Titles are compared case insensitively
+    /// as they were provided, before escaping.
+    pub fn position_of(&self, title: impl AsRef<str>) -> Option<usize> {
+        let title = title.as_ref().to_lowercase();
+        self.sections
+            .iter()
+            .position(|section| section.get_title().unescaped().to_lowercase() == title)
+    }
+
+    /// Inserts `section` at `index` shifting the later sections. The position is kept as is, call
+    /// [`sort_sections_conventionally`](Roff::sort_sections_conventionally) afterwards to move the
+    /// section to its conventional place instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of sections.
+    pub fn insert_section(&mut self, index: usize, section: Section) {
+        self.sections.insert(index, section);
+    }
+
+    /// Removes the first section with `title`, compared like in
+    /// [`position_of`](Roff::position_of), and returns it. Returns `None` if there is no such
+    /// section.
+    pub fn remove_section(&mut self, title: impl AsRef<str>) -> Option<Section> {
+        let index = self.position_of(title)?;
+        Some(self.sections.remove(index))
+    }
+
+    /// Replaces the first section with `title`, compared like in
+    /// [`position_of`](Roff::position_of), with `section` at the same position and returns the
+    /// replaced section. Returns `None` and leaves the document unchanged if there is no such
+    /// section.
+    pub fn replace_section(&mut self, title: impl AsRef<str>, section: Section) -> Option<Section> {
+        let index = self.position_of(title)?;
+        Some(std::mem::replace(&mut self.sections[index], section))
+    }
+
     fn write_title(&self, writer: &mut impl Write) -> Result<(), RoffError> {
         writer.write_all(SPACE)?;
-        write_quoted_if_whitespace(&self.title, writer)
+        match self.title_case {
+            TitleCase::AsIs => write_quoted_if_whitespace(&self.title, writer, &self.options),
+            TitleCase::Upper => write_quoted_if_whitespace(
+                &self.title.map_original(str::to_uppercase),
+                writer,
+                &self.options,
+            ),
+            TitleCase::Lower => write_quoted_if_whitespace(
+                &self.title.map_original(str::to_lowercase),
+                writer,
+                &self.options,
+            ),
+        }
+    }
+
+    /// Returns the title of this document with the title case applied, followed by the section
+    /// number in parentheses like `grep(1)`.
+    pub(crate) fn display_title(&self) -> String {
+        let title = self.title.unescaped();
+        let title = match self.title_case {
+            TitleCase::AsIs => title.into_owned(),
+            TitleCase::Upper => title.to_uppercase(),
+            TitleCase::Lower => title.to_lowercase(),
+        };
+        format!("{}({})", title, self.section.roff().unescaped())
     }
 
     fn write_section(&self, writer: &mut impl Write) -> Result<(), RoffError> {
         writer.write_all(SPACE)?;
-        write_quoted_if_whitespace(&self.section.roff(), writer)
+        write_quoted_if_whitespace(&self.section.roff(), writer, &self.options)
     }
 
-    fn write_date(&self, writer: &mut impl Write) -> Result<(), RoffError> {
-        if let Some(date) = &self.date {
+    /// Writes the date, source and manual fields of the title header. Fields that are not set are
+    /// written as empty arguments when a later field is set, so that the arguments keep their
+    /// positions.
+    fn write_header_fields(&self, writer: &mut impl Write) -> Result<(), RoffError> {
+        let default_manual = match (&self.manual, &self.source) {
+            (None, Some(_)) if !self.options.no_default_manual => {
+                self.section.manual_name().map(|name| name.roff())
+            }
+            _ => None,
+        };
+        let fields = [
+            self.date.as_ref(),
+            self.source.as_ref(),
+            self.manual.as_ref().or(default_manual.as_ref()),
+        ];
+        let count = fields
+            .iter()
+            .rposition(|field| field.is_some())
+            .map_or(0, |last| last + 1);
+        for field in &fields[..count] {
             writer.write_all(SPACE)?;
-            write_quoted_if_whitespace(date, writer)?;
+            match field {
+                Some(field) => write_quoted_if_whitespace(field, writer, &self.options)?,
+                None => write_quoted(&[], writer)?,
+            }
         }
         Ok(())
     }
@@ -300,25 +1121,67 @@ impl Roff {
         writer.write_all(TITLE_HEADER)?;
         self.write_title(writer)?;
         self.write_section(writer)?;
-        self.write_date(writer)?;
+        self.write_header_fields(writer)?;
         writer.write_all(ENDL)?;
         Ok(())
     }
 
+    /// Renders this `Roff` to a [`fmt::Write`](fmt::Write) sink like a `String` or a `Formatter`
+    /// without buffering the whole output. Returns [`RoffError::FormatFailed`] if the sink fails
+    /// and [`RoffError::StringRenderFailed`] if the output is not valid UTF-8.
+    pub fn render_fmt<W: fmt::Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        let mut sink = FmtWriter::new(writer, self.options.encoding);
+        self.render(&mut sink).map_err(|err| sink.map_error(err))?;
+        sink.finish()
+    }
+
     /// Renders this `Roff` to a `writer` returning an error if any of the writes fails.
     pub fn render<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
-        self.write_title_header(writer)?;
+        let mut stream = RoffStream::start(writer, &self.options);
+        stream.write_page(self)?;
+        for page in &self.pages {
+            stream.write_page(page)?;
+        }
+        stream.finish()?;
+        Ok(())
+    }
 
-        let mut was_text = false;
-        for section in &self.sections {
-            was_text = section.render(writer, was_text)?;
+    /// Writes the comments and the title header that start this page.
+    fn write_header<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        for comment in self.generated_by.iter().chain(&self.preamble) {
+            write_comment_lines(comment, writer)?;
+        }
+        if self.classic_preamble {
+            writer.write_all(CLASSIC_PREAMBLE.as_bytes())?;
         }
+        self.write_title_header(writer)
+    }
+}
 
-        Ok(())
+/// Renders the document, same as [`render_to_string`](Roff::render_to_string). Errors are
+/// reported as [`fmt::Error`](fmt::Error).
+///
+/// ```
+/// use roffman::{Roff, SectionNumber};
+///
+/// let roff = Roff::new("test", SectionNumber::UserCommands).section("NAME", ["test"]);
+/// assert_eq!(format!("{}", roff), roff.render_to_string().unwrap());
+/// ```
+impl fmt::Display for Roff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.render_fmt(f).map_err(|_| fmt::Error)
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Appends the sections to the roff in order.
+impl<S: Into<Section>> Extend<S> for Roff {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, sections: I) {
+        self.sections.extend(sections.into_iter().map(Into::into));
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Defines the section to which the given ROFF belongs.
 pub enum SectionNumber {
     ///Commands that can be executed by the user from within a shell.
@@ -359,44 +1222,176 @@ impl From<SectionNumber> for u8 {
     }
 }
 
+impl SectionNumber {
+    /// Returns the conventional name of the manual this section belongs to, like `User Commands`
+    /// for section 1. Custom sections have no name.
+    pub fn description(&self) -> Option<&'static str> {
+        use SectionNumber::*;
+        match self {
+            UserCommands => Some("User Commands"),
+            SystemCalls => Some("System Calls Manual"),
+            LibraryCalls => Some("Library Functions Manual"),
+            Devices => Some("Kernel Interfaces Manual"),
+            FileFormatsAndConfigurationFiles => Some("File Formats Manual"),
+            Games => Some("Games Manual"),
+            Miscellaneous => Some("Miscellaneous Information Manual"),
+            SystemManagementCommands => Some("System Administration"),
+            Custom(_) => None,
+        }
+    }
+
+    /// Returns the name of the manual pages of this section are part of. It is written to the
+    /// title header when the source is set without a manual, see
+    /// [`RenderOptions::default_manual`](RenderOptions::default_manual). This is the
+    /// [`description`](SectionNumber::description) of the section, custom sections with numbers
+    /// `1` to `8` use the one of the matching section. Other custom sections have no name.
+    pub fn manual_name(&self) -> Option<&'static str> {
+        SectionNumber::try_from(u8::from(*self)).ok()?.description()
+    }
+}
+
+/// Converts a section number to the matching section, numbers other than `1` to `8` are custom
+/// sections. Fails for `0` which is not a valid section.
+impl TryFrom<u8> for SectionNumber {
+    type Error = RoffError;
+
+    fn try_from(n: u8) -> Result<Self, Self::Error> {
+        use SectionNumber::*;
+        Ok(match n {
+            0 => return Err(RoffError::InvalidSectionNumber(n.to_string())),
+            1 => UserCommands,
+            2 => SystemCalls,
+            3 => LibraryCalls,
+            4 => Devices,
+            5 => FileFormatsAndConfigurationFiles,
+            6 => Games,
+            7 => Miscellaneous,
+            8 => SystemManagementCommands,
+            n => Custom(n),
+        })
+    }
+}
+
+/// Parses a section number like `1` or `8`, the same way as
+/// [`SectionNumber::try_from`](SectionNumber::try_from). Surrounding whitespace is ignored.
+impl FromStr for SectionNumber {
+    type Err = RoffError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .parse::<u8>()
+            .map_err(|_| RoffError::InvalidSectionNumber(s.to_string()))
+            .and_then(SectionNumber::try_from)
+    }
+}
+
+/// Displays the numeric form of the section, like `8`.
+impl fmt::Display for SectionNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", u8::from(*self))
+    }
+}
+
 impl Roffable for SectionNumber {
     fn roff(&self) -> RoffText {
         u8::from(*self).roff()
     }
 }
 
-#[derive(Clone, Debug)]
-/// An option used by the [`RoffNode::synopsis`](RoffNode::synopsis) block.
-pub struct SynopsisOpt {
-    name: RoffText,
-    argument: Option<RoffText>,
-    description: Option<Vec<RoffText>>,
+impl From<SectionNumber> for RoffText {
+    fn from(item: SectionNumber) -> Self {
+        item.roff()
+    }
 }
 
-impl SynopsisOpt {
-    /// Creates a new option used in a synopsis block.
-    pub fn new<R: Roffable>(name: R) -> Self {
-        Self {
-            name: name.roff(),
-            argument: None,
-            description: None,
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+/// Identifies the section of the manual a document belongs to, either by one of the numbered
+/// [`SectionNumber`s](SectionNumber) or by a string identifier like `3pm`. Accepted by
+/// [`Roff::new`](Roff::new) in place of a `SectionNumber`.
+pub enum SectionId {
+    /// A numbered section like `1` or `8`.
+    Number(SectionNumber),
+    /// A section identifier rendered verbatim, like `3pm` for Perl modules, `1p` for POSIX
+    /// commands or `n` for Tcl.
+    Custom(String),
+}
+
+impl SectionId {
+    /// Returns the number of this section. For [`Custom`](SectionId::Custom) sections this is
+    /// the leading number like `3` of `3pm`, if there is one.
+    pub fn number(&self) -> Option<u8> {
+        match self {
+            SectionId::Number(section) => Some(u8::from(*section)),
+            SectionId::Custom(id) => {
+                let end = id.find(|c: char| !c.is_ascii_digit()).unwrap_or(id.len());
+                id[..end].parse().ok()
+            }
         }
     }
 
-    /// Set the name of the argument that this option takes.
-    pub fn argument<R: Roffable>(mut self, argument: R) -> Self {
-        self.argument = Some(argument.roff());
-        self
+    /// Returns the name of the directory pages of this section are installed in, like `man3` for
+    /// both `3` and `3pm`. Sections without a leading number use the whole identifier, like
+    /// `mann`.
+    pub fn directory_name(&self) -> String {
+        match (self, self.number()) {
+            (_, Some(number)) => format!("man{}", number),
+            (id, None) => format!("man{}", id),
+        }
     }
 
-    /// Set the description for this command synopsis.
-    pub fn description<I, R>(mut self, description: I) -> Self
-    where
-        I: IntoIterator<Item = R>,
-        R: Roffable,
-    {
-        self.description = Some(description.into_iter().map(|item| item.roff()).collect());
-        self
+    /// Returns the [`manual name`](SectionNumber::manual_name) of the numbered section or, for
+    /// string sections like `3pm`, of their leading number.
+    pub fn manual_name(&self) -> Option<&'static str> {
+        SectionNumber::try_from(self.number()?).ok()?.manual_name()
+    }
+}
+
+impl From<SectionNumber> for SectionId {
+    fn from(section: SectionNumber) -> Self {
+        SectionId::Number(section)
+    }
+}
+
+/// Parses a section number like `1`, the same way as
+/// [`SectionNumber::from_str`](SectionNumber::from_str), or an alphanumeric identifier like `3pm`
+/// or `n` as a [`Custom`](SectionId::Custom) section. Surrounding whitespace is ignored.
+impl FromStr for SectionId {
+    type Err = RoffError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let id = s.trim();
+        if !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()) {
+            s.parse().map(SectionId::Number)
+        } else if !id.is_empty() && id.chars().all(char::is_alphanumeric) {
+            Ok(SectionId::Custom(id.to_string()))
+        } else {
+            Err(RoffError::InvalidSectionNumber(s.to_string()))
+        }
+    }
+}
+
+/// Displays the numeric form of the section, like `8`, or the identifier of a
+/// [`Custom`](SectionId::Custom) section as is.
+impl fmt::Display for SectionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SectionId::Number(section) => section.fmt(f),
+            SectionId::Custom(id) => f.write_str(id),
+        }
+    }
+}
+
+impl Roffable for SectionId {
+    fn roff(&self) -> RoffText {
+        self.to_string().roff()
+    }
+}
+
+impl From<SectionId> for RoffText {
+    fn from(item: SectionId) -> Self {
+        item.roff()
     }
 }
 
@@ -412,9 +1407,23 @@ impl IntoRoffNode for RoffNode {
     }
 }
 
-impl<R: Roffable> IntoRoffNode for R {
+impl IntoRoffNode for Vec<RoffNode> {
+    /// Converts the nodes into a [`group`](RoffNode::group).
+    fn into_roff(self) -> RoffNode {
+        RoffNode::group(self)
+    }
+}
+
+impl<const N: usize> IntoRoffNode for [RoffNode; N] {
+    /// Converts the nodes into a [`group`](RoffNode::group).
+    fn into_roff(self) -> RoffNode {
+        RoffNode::group(self)
+    }
+}
+
+impl<R: Into<RoffText>> IntoRoffNode for R {
     fn into_roff(self) -> RoffNode {
-        RoffNode::text(self.roff())
+        RoffNode::text(self)
     }
 }
 
@@ -424,21 +1433,91 @@ pub trait Roffable {
     fn roff(&self) -> RoffText;
 }
 
+/// Items that can be converted into [`RoffText`](RoffText) by value. It is implemented for every
+/// [`Roffable`](Roffable) item through [`roff`](Roffable::roff).
+///
+/// The builder methods take `impl Into<RoffText>` instead, which is implemented for all of the
+/// `Roffable` types of this crate. The conversion of an owned `String`, `Cow` or `RoffText` moves
+/// its content into the text instead of copying it. Other `Roffable` items can be passed after
+/// calling [`roff`](Roffable::roff) on them.
+///
+/// ```
+/// use roffman::{Roff, SectionNumber};
+///
+/// let version = format!("foo {}", "1.0");
+/// // The string is moved into the document.
+/// let roff = Roff::new("foo", SectionNumber::UserCommands).source(version);
+/// assert_eq!(roff.render_to_string().unwrap(), ".TH foo 1 \"\" \"foo 1.0\" \"User Commands\"\n");
+/// ```
+pub trait IntoRoffText {
+    /// Converts this item into [`RoffText`](RoffText).
+    fn into_roff_text(self) -> RoffText;
+}
+
+impl<R: Roffable> IntoRoffText for R {
+    fn into_roff_text(self) -> RoffText {
+        self.roff()
+    }
+}
+
+/// Extension trait to style [`Roffable`](Roffable) items directly, so that `"text".bold()` can be
+/// written instead of `"text".roff().bold()`. Each method converts the item with
+/// [`roff`](Roffable::roff) and applies the style of the matching [`RoffText`](RoffText) method.
+///
+/// Calling these methods on a `RoffText` value still uses the inherent methods, which take the text
+/// by value and don't clone it.
+///
+/// ```
+/// use roffman::{FontStyle, RoffStyleExt};
+///
+/// assert_eq!("special".bold().style(), FontStyle::Bold);
+/// assert_eq!(String::from("path").italic().style(), FontStyle::Italic);
+/// ```
+pub trait RoffStyleExt: Roffable {
+    /// Returns this item as bold text.
+    fn bold(&self) -> RoffText {
+        self.roff().bold()
+    }
+
+    /// Returns this item as italic text.
+    fn italic(&self) -> RoffText {
+        self.roff().italic()
+    }
+
+    /// Returns this item as constant-width text, see [`RoffText::code`](RoffText::code).
+    fn code(&self) -> RoffText {
+        self.roff().code()
+    }
+
+    /// Returns this item as literal text, see [`RoffText::literal`](RoffText::literal).
+    fn literal(&self) -> RoffText {
+        self.roff().literal()
+    }
+}
+
+impl<T: Roffable + ?Sized> RoffStyleExt for T {}
+
 impl Roffable for String {
     fn roff(&self) -> RoffText {
-        RoffText::new(self.clone(), None)
+        RoffText::new(self, None)
     }
 }
 
 impl Roffable for &String {
     fn roff(&self) -> RoffText {
-        RoffText::new((*self).clone(), None)
+        RoffText::new(self, None)
+    }
+}
+
+impl From<&String> for RoffText {
+    fn from(item: &String) -> Self {
+        item.roff()
     }
 }
 
 impl Roffable for &str {
     fn roff(&self) -> RoffText {
-        RoffText::new(self.to_string(), None)
+        RoffText::new(self, None)
     }
 }
 
@@ -448,21 +1527,117 @@ impl Roffable for &&str {
     }
 }
 
-impl Roffable for std::borrow::Cow<'_, str> {
+impl From<&&str> for RoffText {
+    fn from(item: &&str) -> Self {
+        item.roff()
+    }
+}
+
+impl Roffable for Cow<'_, str> {
     fn roff(&self) -> RoffText {
         self.as_ref().roff()
     }
 }
 
-impl Roffable for u8 {
+/// Paths are converted with [`Path::to_string_lossy`](Path::to_string_lossy), so any part of the
+/// path that is not valid unicode is replaced with `U+FFFD REPLACEMENT CHARACTER`. The path is not
+/// styled, by convention it should usually be displayed in italics.
+impl Roffable for &Path {
     fn roff(&self) -> RoffText {
-        RoffText::new(self.to_string(), None)
+        self.to_string_lossy().roff()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl From<&Path> for RoffText {
+    fn from(item: &Path) -> Self {
+        item.roff()
+    }
+}
+
+/// See the implementation for [`&Path`](Path).
+impl Roffable for PathBuf {
+    fn roff(&self) -> RoffText {
+        self.as_path().roff()
+    }
+}
+
+impl From<PathBuf> for RoffText {
+    fn from(item: PathBuf) -> Self {
+        item.roff()
+    }
+}
+
+/// Implements [`Roffable`](Roffable) and the conversion into [`RoffText`](RoffText) for types
+/// whose [`Display`](fmt::Display) output is used as the unstyled text.
+macro_rules! impl_roffable_display {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Roffable for $ty {
+                fn roff(&self) -> RoffText {
+                    RoffText::new(self.to_string(), None)
+                }
+            }
+
+            impl From<$ty> for RoffText {
+                fn from(item: $ty) -> Self {
+                    item.roff()
+                }
+            }
+        )*
+    };
+}
+
+impl_roffable_display!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The document from the example in the crate documentation and the README.
+    pub(crate) fn readme_example() -> Roff {
+        Roff::new("roffman", SectionNumber::Miscellaneous)
+            .date("August 2021")
+            .section(
+                "BASIC USAGE",
+                [
+                    RoffNode::paragraph(["This is how you create a basic paragraph using roffman."]),
+                    RoffNode::indented_paragraph(
+                        [
+                            "This line should be slightly indented to the ".roff(),
+                            "right.".roff().bold(),
+                        ],
+                        Some(4),
+                        Some("optional-title"),
+                    ),
+                    RoffNode::synopsis(
+                        "roffman-command",
+                        [
+                            "This is the description of this command. It will be displayed right next to".roff(),
+                            " it".roff().italic(),
+                        ],
+                        [
+                            SynopsisOpt::new("--opt").description(["some simple opt"]),
+                            SynopsisOpt::new("--opt-with-arg")
+                                .argument("ARG")
+                                .description(["opt with an argument"]),
+                            SynopsisOpt::new("--bold"),
+                        ],
+                    ),
+                    RoffNode::paragraph(["Example:".roff().bold()]),
+                    RoffNode::example([r#"
+impl Roffable for u8 {
+    fn roff(&self) -> RoffText {
+        self.to_string().roff()
+    }
+}"#]),
+                    RoffNode::url("GitHub", "https://github.com/vv9k/roffman"),
+                    RoffNode::text("\nvv9k"),
+                    RoffNode::trademark_sign(),
+                ],
+            )
+    }
 
     #[test]
     fn it_roffs() {
@@ -515,11 +1690,10 @@ mod tests {
             )
             ;
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             r#".TH test 1
 .SH "test section 1"
-.P
 this is some very \fBspecial\fR text
 .SH "test section 2"
 .IP "" 4
@@ -560,13 +1734,12 @@ Another indented paragraph
             .subtitle("with some subtitle..."),
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             rendered,
             r#".TH test 1
 .SH "BASE SECTION"
 .SS "with some subtitle..."
-.P
 some text in first paragraph.
 .RS
 .P
@@ -598,7 +1771,7 @@ back two levels left without roffs"#,
             ],
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             r#".TH test\-examples 3
 .SH "BASE SECTION"
@@ -631,19 +1804,16 @@ if x.len() > 0 {
             ],
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             r#".TH test\-synopsis 7
 .SH SYNOPSIS
 .SY ls
 lists files in the given\fIpath\fR.
-
 .OP \-l
 use a long listing format
-
 .OP "\-L, \-\-dereference"
 when showing file information for a symbolic link, show information for the file the link references rather than for the link itself
-
 .OP \-\-block\-size SIZE
 with \-l, scale sizes by SIZE when printing them
 .YS
@@ -668,7 +1838,7 @@ with \-l, scale sizes by SIZE when printing them
             ],
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             r#".TH test\-urls 7
 .SH URLS
@@ -731,7 +1901,7 @@ John Test
             ],
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             rendered,
             r#".TH test\-strings 7
@@ -747,9 +1917,9 @@ some text\(eminterupted sentence in the middle\(emmore text...
 \(en
 .P
 paragraph after special sequence\"that was interesting indeed...
-\"this should span
-\"over multiple
-\"lines correctly.
+.\" this should span
+.\" over multiple
+.\" lines correctly.
 "#
         )
     }
@@ -768,7 +1938,7 @@ paragraph after special sequence\"that was interesting indeed...
             )
             .section("THIRD", vec![RoffNode::text("this is some example text.")]);
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             r#".TH test\-sections 7
 .SH TEXTS
@@ -796,7 +1966,7 @@ this is some example text."#,
             ],
         );
 
-        let rendered = roff.to_string().unwrap();
+        let rendered = roff.render_to_string().unwrap();
         assert_eq!(
             rendered,
             r#".TH test\-breaks 7
@@ -808,4 +1978,2958 @@ this is some example text on second line.
 this is some example text on third line."#
         )
     }
+
+    #[test]
+    fn columns_work() {
+        let roff = Roff::new("test-columns", SectionNumber::Miscellaneous).section(
+            "SIGNALS",
+            vec![
+                RoffNode::text("Supported signals:"),
+                RoffNode::columns(
+                    [
+                        (1, "SIGHUP"),
+                        (2, "SIGINT"),
+                        (9, "SIGKILL"),
+                        (15, "SIG\tTERM"),
+                        (19, "SIGSTOP\nSIGTSTP"),
+                    ],
+                    "2i",
+                ),
+                RoffNode::text("Other signals are ignored."),
+            ],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            rendered,
+            ".TH test\\-columns 7
+.SH SIGNALS
+Supported signals:
+.ta 2i
+.nf
+1\tSIGHUP
+2\tSIGINT
+9\tSIGKILL
+15\tSIG TERM
+19\tSIGSTOP SIGTSTP
+.fi
+.ta
+Other signals are ignored."
+        )
+    }
+
+    #[test]
+    fn synopsis_opt_getters_work() {
+        let opt = SynopsisOpt::new("--color")
+            .argument("WHEN")
+            .description(["colorize the output", "disabled when piped"])
+            .default_value("auto")
+            .possible_values(["auto", "always", "never"])
+            .inline_values(true)
+            .hidden(true);
+
+        assert_eq!(opt.get_name().original(), "--color");
+        assert_eq!(opt.get_argument().unwrap().original(), "WHEN");
+        assert_eq!(
+            opt.get_description()
+                .iter()
+                .map(|line| line.original())
+                .collect::<Vec<_>>(),
+            ["colorize the output", "disabled when piped"]
+        );
+        assert_eq!(opt.get_default_value().unwrap().original(), "auto");
+        assert_eq!(opt.get_possible_values().len(), 3);
+        assert!(opt.has_inline_values());
+        assert!(opt.is_hidden());
+
+        let (name, argument, description) = opt.parts();
+        assert_eq!(name, opt.get_name());
+        assert_eq!(argument, opt.get_argument());
+        assert_eq!(description, opt.get_description());
+
+        let bare = SynopsisOpt::new("-l");
+        assert_eq!(bare.parts(), (&"-l".roff().minus_hyphens(), None, &[][..]));
+        assert!(!bare.has_inline_values());
+
+        let group = SynopsisOptGroup::new("Output options", [opt.clone(), bare]);
+        assert_eq!(group.get_title().original(), "Output options");
+        assert_eq!(group.get_opts().len(), 2);
+        assert_eq!(group.get_opts()[0], opt);
+    }
+
+    #[test]
+    fn synopsis_default_values_work() {
+        let opt = SynopsisOpt::new("--color").default_value("auto");
+        assert_eq!(opt.get_default_value(), Some(&"auto".roff()));
+
+        let roff = Roff::new("test-defaults", SectionNumber::Miscellaneous).section(
+            "SYNOPSIS",
+            vec![RoffNode::synopsis(
+                "ls",
+                ["lists files"],
+                vec![
+                    SynopsisOpt::new("--block-size")
+                        .argument("SIZE")
+                        .description(["scale sizes by SIZE"])
+                        .default_value("1024"),
+                    opt,
+                    SynopsisOpt::new("-l"),
+                ],
+            )],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-defaults 7
+.SH SYNOPSIS
+.SY ls
+lists files
+.OP \-\-block\-size SIZE
+scale sizes by SIZE
+.br
+(default: 1024)
+.OP \-\-color
+(default: auto)
+.OP \-l
+.YS
+"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn synopsis_possible_values_work() {
+        let opt = SynopsisOpt::new("--color")
+            .argument("WHEN")
+            .description(["colorize the output"])
+            .possible_values(["auto", "always", "never"])
+            .default_value("auto");
+        assert_eq!(opt.get_possible_values().len(), 3);
+
+        let roff = Roff::new("test-values", SectionNumber::Miscellaneous).section(
+            "SYNOPSIS",
+            vec![RoffNode::synopsis(
+                "ls",
+                ["lists files"],
+                vec![
+                    opt.clone(),
+                    opt.inline_values(true),
+                    SynopsisOpt::new("--format")
+                        .argument("WORD")
+                        .possible_values(["long", "single column"])
+                        .inline_values(true),
+                ],
+            )],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-values 7
+.SH SYNOPSIS
+.SY ls
+lists files
+.OP \-\-color WHEN
+colorize the output
+.br
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.br
+(default: auto)
+.OP \-\-color auto|always|never
+colorize the output
+.br
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.br
+(default: auto)
+.OP \-\-format "long|single column"
+Possible values: \fBlong\fR, \fBsingle column\fR
+.YS
+"#,
+            rendered
+        );
+
+        // A description that already ends its line is not followed by an empty one.
+        let opt = SynopsisOpt::new("-c")
+            .description(["colorize\n"])
+            .possible_values(["auto"]);
+        let roff = Roff::new("test", SectionNumber::Miscellaneous)
+            .section("SYNOPSIS", [RoffNode::synopsis("ls", [""], [opt])]);
+        assert!(roff
+            .render_to_string()
+            .unwrap()
+            .contains(".OP \\-c\ncolorize\n.br\nPossible values: \\fBauto\\fR\n"));
+    }
+
+    #[test]
+    fn synopsis_groups_work() {
+        let roff = Roff::new("test-groups", SectionNumber::Miscellaneous).section(
+            "SYNOPSIS",
+            vec![RoffNode::synopsis(
+                "grep",
+                ["print lines that match patterns"],
+                vec![
+                    SynopsisOptGroup::new(
+                        "Output options",
+                        vec![
+                            SynopsisOpt::new("-c").description(["print a count of matching lines"]),
+                            SynopsisOpt::new("--color").argument("WHEN"),
+                        ],
+                    ),
+                    SynopsisOptGroup::new(
+                        "Filtering options",
+                        vec![
+                            SynopsisOpt::new("-v").description(["invert the sense of matching"]),
+                            SynopsisOpt::new("-i").description(["ignore case distinctions"]),
+                        ],
+                    ),
+                ],
+            )],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-groups 7
+.SH SYNOPSIS
+.SY grep
+print lines that match patterns
+.br
+\fBOutput options\fR
+.OP \-c
+print a count of matching lines
+.OP \-\-color WHEN
+.br
+\fBFiltering options\fR
+.OP \-v
+invert the sense of matching
+.OP \-i
+ignore case distinctions
+.YS
+"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn hidden_synopsis_opts_are_skipped() {
+        let roff = Roff::new("test-hidden", SectionNumber::Miscellaneous).section(
+            "SYNOPSIS",
+            vec![RoffNode::synopsis(
+                "app",
+                ["runs the app"],
+                vec![
+                    SynopsisItem::from(SynopsisOpt::new("--verbose")),
+                    SynopsisOpt::new("--debug-dump").hidden(true).into(),
+                    SynopsisOptGroup::new(
+                        "Internal options",
+                        vec![SynopsisOpt::new("--trace").hidden(true)],
+                    )
+                    .into(),
+                ],
+            )],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-hidden 7
+.SH SYNOPSIS
+.SY app
+runs the app
+.OP \-\-verbose
+.YS
+"#,
+            rendered
+        );
+
+        let rendered = roff.include_hidden(true).render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-hidden 7
+.SH SYNOPSIS
+.SY app
+runs the app
+.OP \-\-verbose
+.OP \-\-debug\-dump
+.br
+\fBInternal options\fR
+.OP \-\-trace
+.YS
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn authors_section_works() {
+        let roff = Roff::new("test-authors", SectionNumber::Miscellaneous).authors(vec![
+            ("John Test", Some("john@invalid.domain")),
+            ("Jane Test", None),
+            ("John Test", Some("john@invalid.domain")),
+            ("Bob Test", Some("bob@invalid.domain")),
+            ("Alice Test", None),
+        ]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-authors 7
+.SH AUTHORS
+.MT john@invalid.domain
+John Test
+.ME
+.br
+Jane Test
+.br
+.MT bob@invalid.domain
+Bob Test
+.ME
+.br
+Alice Test"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn examples_section_works() {
+        let roff = Roff::new("test-examples", SectionNumber::UserCommands).examples(vec![
+            (RoffNode::text("List all files:"), "ls -a"),
+            (
+                RoffNode::paragraph(["Use the ".roff(), "long".roff().bold(), " format:".roff()]),
+                "ls -l",
+            ),
+            ("Sort by size:".into_roff(), "ls -S"),
+        ]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-examples 1
+.SH EXAMPLES
+List all files:
+.RS 4
+.EX
+ls \-a
+.EE
+.RE
+.P
+Use the \fBlong\fR format:
+.RS 4
+.EX
+ls \-l
+.EE
+.RE
+.P
+Sort by size:
+.RS 4
+.EX
+ls \-S
+.EE
+.RE
+"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn reporting_bugs_section_works() {
+        let roff = Roff::new("test-bugs", SectionNumber::UserCommands).reporting_bugs(
+            BugContact::url("https://github.com/vv9k/roffman/issues"),
+            ["Please include the version of the program in the report."],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-bugs 1
+.SH BUGS
+Report bugs to:
+.UR https://github.com/vv9k/roffman/issues
+.UE
+.P
+Please include the version of the program in the report."#,
+            rendered
+        );
+
+        let roff = Roff::new("test-bugs", SectionNumber::UserCommands).reporting_bugs_with_wording(
+            BugContact::both(
+                "https://github.com/vv9k/roffman/issues",
+                "bugs@invalid.domain",
+            ),
+            "Found a bug? Let us know at",
+            None::<&str>,
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-bugs 1
+.SH BUGS
+Found a bug? Let us know at
+.MT bugs@invalid.domain
+.ME
+or
+.UR https://github.com/vv9k/roffman/issues
+.UE
+"#,
+            rendered
+        );
+
+        let roff = Roff::new("test-bugs", SectionNumber::UserCommands)
+            .reporting_bugs(BugContact::email("bugs@invalid.domain"), None::<&str>);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-bugs 1
+.SH BUGS
+Report bugs to:
+.MT bugs@invalid.domain
+.ME
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn options_section_works() {
+        let opts = vec![
+            SynopsisOpt::new("-l").description(["use a long listing format"]),
+            SynopsisOpt::new("--color")
+                .argument("WHEN")
+                .description(["colorize the output"])
+                .possible_values(["auto", "always", "never"])
+                .default_value("auto"),
+            SynopsisOpt::new("--block-size").argument("SIZE"),
+            SynopsisOpt::new("--debug").hidden(true),
+        ];
+        let roff = Roff::new("ls", SectionNumber::UserCommands)
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis("ls", None::<&str>, opts.clone())],
+            )
+            .options_section(opts);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH ls 1
+.SH SYNOPSIS
+.SY ls
+.OP \-l
+use a long listing format
+.OP \-\-color WHEN
+colorize the output
+.br
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.br
+(default: auto)
+.OP \-\-block\-size SIZE
+.YS
+.SH OPTIONS
+.TP
+\fB\-l\fR
+use a long listing format
+.TP
+\fB\-\-color\fR \fIWHEN\fR
+colorize the output
+.br
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.br
+(default: auto)
+.TP
+\fB\-\-block\-size\fR \fISIZE\fR
+"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn grouped_options_section_works() {
+        let roff = Roff::new("grep", SectionNumber::UserCommands).options_section(vec![
+            SynopsisOptGroup::new(
+                "Output options",
+                vec![
+                    SynopsisOpt::new("-c").description(["print a count of matching lines"]),
+                    SynopsisOpt::new("--color")
+                        .argument("WHEN")
+                        .possible_values(["auto", "always", "never"])
+                        .inline_values(true),
+                ],
+            ),
+            SynopsisOptGroup::new(
+                "Filtering options",
+                vec![
+                    SynopsisOpt::new("-v").description(["invert the sense of matching"]),
+                    SynopsisOpt::new("-i").description(["ignore case distinctions"]),
+                ],
+            ),
+            SynopsisOptGroup::new(
+                "Internal options",
+                vec![SynopsisOpt::new("--trace").hidden(true)],
+            ),
+        ]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH grep 1
+.SH OPTIONS
+.SS "Output options"
+.TP
+\fB\-c\fR
+print a count of matching lines
+.TP
+\fB\-\-color\fR \fIauto\fR|\fIalways\fR|\fInever\fR
+Possible values: \fBauto\fR, \fBalways\fR, \fBnever\fR
+.SS "Filtering options"
+.TP
+\fB\-v\fR
+invert the sense of matching
+.TP
+\fB\-i\fR
+ignore case distinctions
+"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn standard_sections_work() {
+        assert_eq!(StandardSection::Name.order_index(), 0);
+        assert!(
+            StandardSection::Description.order_index() < StandardSection::Options.order_index()
+        );
+        assert!(StandardSection::Examples.order_index() < StandardSection::SeeAlso.order_index());
+        assert_eq!(
+            StandardSection::SeeAlso.order_index(),
+            StandardSection::ALL.len() - 1
+        );
+
+        let roff = Roff::new("test-standard", SectionNumber::UserCommands)
+            .section(StandardSection::Name, ["test-standard - does things"])
+            .section(StandardSection::ExitStatus, ["0 on success"])
+            .section(StandardSection::SeeAlso, ["ls(1)"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-standard 1
+.SH NAME
+test\-standard \- does things
+.SH "EXIT STATUS"
+0 on success
+.SH "SEE ALSO"
+ls(1)"#,
+            rendered
+        )
+    }
+
+    #[test]
+    fn sorts_sections_conventionally() {
+        let titles = |roff: &Roff| -> Vec<String> {
+            roff.render_to_string()
+                .unwrap()
+                .lines()
+                .filter_map(|line| line.strip_prefix(".SH "))
+                .map(|title| title.trim_matches('"').to_string())
+                .collect()
+        };
+
+        let roff = Roff::new("test-sort", SectionNumber::UserCommands)
+            .section(StandardSection::SeeAlso, ["ls(1)"])
+            .section("CUSTOM ONE", ["first custom"])
+            .section(StandardSection::Options, ["options"])
+            .section("synopsis", ["synopsis"])
+            .section(StandardSection::Authors, ["authors"])
+            .section("CUSTOM TWO", ["second custom"])
+            .section(StandardSection::Description, ["description"])
+            .section(StandardSection::Name, ["name"]);
+
+        let sorted = roff.clone().sort_sections_conventionally();
+        assert_eq!(
+            titles(&sorted),
+            vec![
+                "NAME",
+                "synopsis",
+                "DESCRIPTION",
+                "CUSTOM ONE",
+                "CUSTOM TWO",
+                "OPTIONS",
+                "AUTHORS",
+                "SEE ALSO"
+            ]
+        );
+        assert_eq!(
+            sorted.render_to_string().unwrap(),
+            r#".TH test\-sort 1
+.SH NAME
+name
+.SH synopsis
+synopsis
+.SH DESCRIPTION
+description
+.SH "CUSTOM ONE"
+first custom
+.SH "CUSTOM TWO"
+second custom
+.SH OPTIONS
+options
+.SH AUTHORS
+authors
+.SH "SEE ALSO"
+ls(1)"#
+        );
+
+        let sorted = roff.sort_sections_conventionally_after(StandardSection::Authors);
+        assert_eq!(
+            titles(&sorted),
+            vec![
+                "NAME",
+                "synopsis",
+                "DESCRIPTION",
+                "OPTIONS",
+                "AUTHORS",
+                "CUSTOM ONE",
+                "CUSTOM TWO",
+                "SEE ALSO"
+            ]
+        );
+    }
+
+    #[test]
+    fn section_number_conversions_work() {
+        for n in 1..=u8::MAX {
+            let section = SectionNumber::try_from(n).unwrap();
+            assert_eq!(u8::from(section), n);
+            assert_eq!(SectionId::from(section).number(), Some(n));
+            assert_eq!(section.to_string(), n.to_string());
+            assert_eq!(
+                section.to_string().parse::<SectionNumber>().unwrap(),
+                section
+            );
+            assert_eq!(section.roff().as_str(), n.to_string());
+            assert_eq!(section.description().is_some(), n <= 8);
+            assert_eq!(section.manual_name(), section.description());
+        }
+        assert_eq!(
+            "8".parse::<SectionNumber>().unwrap(),
+            SectionNumber::SystemManagementCommands
+        );
+        assert_eq!(
+            " 9\n".parse::<SectionNumber>().unwrap(),
+            SectionNumber::Custom(9)
+        );
+        assert_eq!(SectionNumber::Custom(3).to_string(), "3");
+        assert_eq!(
+            SectionNumber::UserCommands.description(),
+            Some("User Commands")
+        );
+        assert_eq!(
+            SectionNumber::SystemCalls.description(),
+            Some("System Calls Manual")
+        );
+        assert_eq!(SectionNumber::Custom(9).description(), None);
+
+        for invalid in ["0", "", " ", "-1", "256", "3pm", "3.1"] {
+            assert!(matches!(
+                invalid.parse::<SectionNumber>(),
+                Err(RoffError::InvalidSectionNumber(section)) if section == invalid
+            ));
+        }
+        let err = SectionNumber::try_from(0).unwrap_err();
+        assert_eq!(err.to_string(), "`0` is not a valid section number");
+    }
+
+    #[test]
+    fn string_sections_work() {
+        let section: SectionId = "3pm".parse().unwrap();
+        assert_eq!(section, SectionId::Custom("3pm".to_string()));
+        assert_eq!(section.to_string(), "3pm");
+        assert_eq!(section.number(), Some(3));
+        assert_eq!(section.directory_name(), "man3");
+        assert_eq!(section.manual_name(), Some("Library Functions Manual"));
+        let tcl: SectionId = "n".parse().unwrap();
+        assert_eq!(tcl.number(), None);
+        assert_eq!(tcl.directory_name(), "mann");
+        assert_eq!(tcl.manual_name(), None);
+        assert_eq!("1p".parse::<SectionId>().unwrap().number(), Some(1));
+        assert_eq!(
+            " 8".parse::<SectionId>().unwrap(),
+            SectionId::Number(SectionNumber::SystemManagementCommands)
+        );
+        for invalid in ["0", "", "3 pm", "3.1"] {
+            assert!(invalid.parse::<SectionId>().is_err());
+        }
+
+        let roff = Roff::new("foo", section)
+            .date("August 2021")
+            .aliases(["bar"])
+            .section("NAME", ["foo - a Perl module"]);
+        assert_eq!(roff.file_name(), "foo.3pm");
+        assert_eq!(
+            roff.alias_pages(),
+            [("bar.3pm".to_string(), ".so man3/foo.3pm\n".to_string())]
+        );
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 3pm \"August 2021\"\n.SH NAME\nfoo \\- a Perl module"
+        );
+
+        let roff = Roff::new("foo", SectionId::Custom("3 x".to_string()));
+        assert_eq!(roff.render_to_string().unwrap(), ".TH foo \"3 x\"\n");
+    }
+
+    #[test]
+    fn header_fields_work() {
+        let roff = Roff::new("foo", SectionNumber::UserCommands)
+            .date("August 2021")
+            .source("foo 1.0.0")
+            .manual("User Commands");
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 \"August 2021\" \"foo 1.0.0\" \"User Commands\"\n"
+        );
+        assert_eq!(roff.get_source().unwrap().original(), "foo 1.0.0");
+        assert_eq!(roff.get_manual().unwrap().original(), "User Commands");
+
+        // Fields that are not set keep the positions of the following ones.
+        let roff = Roff::new("foo", SectionNumber::UserCommands).manual("Foo Manual");
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 \"\" \"\" \"Foo Manual\"\n"
+        );
+        let roff = Roff::new("foo", SectionNumber::UserCommands)
+            .source("foo")
+            .render_options(RenderOptions::new().default_manual(false));
+        assert_eq!(roff.render_to_string().unwrap(), ".TH foo 1 \"\" foo\n");
+        assert_eq!(roff.get_manual(), None);
+    }
+
+    #[test]
+    fn default_manual_works() {
+        let expected: [(SectionId, &str); 9] = [
+            (SectionNumber::UserCommands.into(), "\"User Commands\""),
+            (SectionNumber::SystemCalls.into(), "\"System Calls Manual\""),
+            (
+                SectionNumber::LibraryCalls.into(),
+                "\"Library Functions Manual\"",
+            ),
+            (
+                SectionNumber::Devices.into(),
+                "\"Kernel Interfaces Manual\"",
+            ),
+            (
+                SectionNumber::FileFormatsAndConfigurationFiles.into(),
+                "\"File Formats Manual\"",
+            ),
+            (SectionNumber::Games.into(), "\"Games Manual\""),
+            (
+                SectionNumber::Miscellaneous.into(),
+                "\"Miscellaneous Information Manual\"",
+            ),
+            (
+                SectionNumber::SystemManagementCommands.into(),
+                "\"System Administration\"",
+            ),
+            ("3pm".parse().unwrap(), "\"Library Functions Manual\""),
+        ];
+        for (section, manual) in expected {
+            let roff = Roff::new("foo", section.clone()).source("foo 1.0");
+            assert_eq!(
+                roff.render_to_string().unwrap(),
+                format!(".TH foo {} \"\" \"foo 1.0\" {}\n", section, manual)
+            );
+        }
+
+        for section in [
+            SectionNumber::Custom(9).into(),
+            "9x".parse::<SectionId>().unwrap(),
+            "n".parse().unwrap(),
+        ] {
+            let roff = Roff::new("foo", section.clone()).source("foo 1.0");
+            assert_eq!(
+                roff.render_to_string().unwrap(),
+                format!(".TH foo {} \"\" \"foo 1.0\"\n", section)
+            );
+            assert_eq!(section.manual_name(), None);
+        }
+
+        // The manual is only defaulted when the source is set and is never overridden.
+        let roff = Roff::new("foo", SectionNumber::UserCommands).date("August 2021");
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 \"August 2021\"\n"
+        );
+        let roff = Roff::new("foo", SectionNumber::UserCommands)
+            .source("foo 1.0")
+            .manual("Foo Manual");
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 \"\" \"foo 1.0\" \"Foo Manual\"\n"
+        );
+    }
+
+    #[test]
+    fn from_cargo_metadata_works() {
+        let roff =
+            Roff::from_cargo_metadata("foo", "1.2.3", "does things", SectionNumber::UserCommands);
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            r#".TH foo 1 "" "foo 1.2.3" "User Commands"
+.SH NAME
+foo \- does things"#
+        );
+
+        let roff = Roff::from_cargo_metadata("foo", "1.2.3", "", SectionNumber::UserCommands);
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            ".TH foo 1 \"\" \"foo 1.2.3\" \"User Commands\"\n.SH NAME\nfoo"
+        );
+    }
+
+    #[test]
+    fn title_case_works() {
+        let roff = Roff::new("my-app", SectionNumber::UserCommands)
+            .title_case(TitleCase::Upper)
+            .section("NAME", ["my-app - does things"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH MY\-APP 1
+.SH NAME
+my\-app \- does things"#,
+            rendered
+        );
+
+        let roff = Roff::new("Straße ÉÀ's", SectionNumber::UserCommands);
+        let rendered = roff
+            .clone()
+            .title_case(TitleCase::Upper)
+            .render_to_string()
+            .unwrap();
+        assert_eq!(".TH \"STRASSE ÉÀ\\(aqS\" 1\n", rendered);
+        let rendered = roff
+            .clone()
+            .title_case(TitleCase::Lower)
+            .render_to_string()
+            .unwrap();
+        assert_eq!(".TH \"straße éà\\(aqs\" 1\n", rendered);
+        let rendered = roff.title_case(TitleCase::AsIs).render_to_string().unwrap();
+        assert_eq!(".TH \"Straße ÉÀ\\(aqs\" 1\n", rendered);
+    }
+
+    #[test]
+    fn generated_by_comments_work() {
+        let roff = Roff::new("test-generated", SectionNumber::UserCommands)
+            .generated_by_roffman()
+            .generated_by("Source: templates/app.toml\nDO NOT EDIT\r\n\n.SH \\fBINJECTED")
+            .section("NAME", ["test"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            format!(
+                r#".\" Generated by roffman {}
+.\" Source: templates/app.toml
+.\" DO NOT EDIT
+.\"
+.\" .SH \efBINJECTED
+.TH test\-generated 1
+.SH NAME
+test"#,
+                env!("CARGO_PKG_VERSION")
+            ),
+            rendered
+        );
+    }
+
+    #[test]
+    fn comment_content_is_inert() {
+        let roff = Roff::new("test-comment", SectionNumber::UserCommands).section(
+            "NAME",
+            [
+                RoffNode::text("test"),
+                RoffNode::comment("\\fBbold\n.SH INJECTED\r\n\\"),
+            ],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-comment 1
+.SH NAME
+test\"\efBbold
+.\" .SH INJECTED
+.\" \e
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn preamble_comments_work() {
+        let license = r#"Copyright (c) 2021 Wojciech Kępka
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software")."#;
+        let roff = Roff::new("test-preamble", SectionNumber::UserCommands)
+            .preamble_comments(license.lines())
+            .generated_by("Generated by a test")
+            .section("NAME", ["test"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".\" Generated by a test
+.\" Copyright (c) 2021 Wojciech Kępka
+.\"
+.\" Permission is hereby granted, free of charge, to any person obtaining a copy
+.\" of this software and associated documentation files (the "Software").
+.TH test\-preamble 1
+.SH NAME
+test"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn classic_preamble_works() {
+        let roff = Roff::new("test-classic", SectionNumber::UserCommands)
+            .generated_by("Generated by a test")
+            .classic_preamble(true)
+            .section("NAME", ["test-classic - a page with the pod2man preamble"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(rendered.matches(CLASSIC_PREAMBLE).count(), 1);
+        let preamble = rendered.find(CLASSIC_PREAMBLE).unwrap();
+        assert!(rendered.starts_with(".\\\" Generated by a test\n"));
+        assert!(preamble < rendered.find("\n.TH ").unwrap());
+        assert!(rendered[preamble..].ends_with(
+            r#".\" ========================================================================
+.TH test\-classic 1
+.SH NAME
+test\-classic \- a page with the pod2man preamble"#
+        ));
+        assert!(verify_output(&rendered).is_empty());
+
+        let wrapped = roff
+            .render_options(RenderOptions::default().max_line_width(20))
+            .render_to_string()
+            .unwrap();
+        assert_eq!(wrapped.matches(CLASSIC_PREAMBLE).count(), 1);
+    }
+
+    #[test]
+    fn no_paragraph_macros_around_headings() {
+        let roff = Roff::new("test-edges", SectionNumber::UserCommands)
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::comment("first"),
+                    RoffNode::paragraph(["after the heading"]),
+                    RoffNode::subheading("Sub"),
+                    RoffNode::linebreak(),
+                    RoffNode::paragraph(["after the sub heading"]),
+                    RoffNode::linebreak(),
+                ],
+            )
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis(
+                    "test",
+                    Vec::<&str>::new(),
+                    [SynopsisOpt::new("-v")],
+                )],
+            );
+
+        let expected = r#".TH test\-edges 1
+.SH DESCRIPTION
+.\" first
+after the heading
+.SS Sub
+after the sub heading
+.SH SYNOPSIS
+"#;
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            format!("{}.SY test\n.OP \\-v\n.YS\n", expected)
+        );
+        assert_eq!(
+            roff.compat(CompatLevel::Portable)
+                .render_to_string()
+                .unwrap(),
+            format!("{}\\fBtest\\fR\n[\\fB\\-v\\fR]\n", expected)
+        );
+    }
+
+    #[test]
+    fn output_encoding_works() {
+        let roff = |encoding| {
+            Roff::new("café", SectionNumber::UserCommands)
+                .date("été 2021")
+                .encoding(encoding)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("input → output, naïve cafe\u{301} 🦀"),
+                        RoffNode::comment("plain \\ \"comment\""),
+                    ],
+                )
+        };
+
+        assert_eq!(
+            roff(OutputEncoding::Ascii).render_to_string().unwrap(),
+            r#".TH caf\('e 1 "\('et\('e 2021"
+.SH DESCRIPTION
+input \(-> output, na\(:ive caf\[u0065_0301] \[u1F980]\"plain \e "comment"
+"#
+        );
+        let latin1 = r#".TH café 1 "été 2021"
+.SH DESCRIPTION
+input \(-> output, naïve caf\[u0065_0301] \[u1F980]\"plain \e "comment"
+"#;
+        assert_eq!(
+            roff(OutputEncoding::Latin1).render_to_string().unwrap(),
+            latin1
+        );
+        let bytes: Vec<u8> = latin1.chars().map(|ch| u32::from(ch) as u8).collect();
+        assert_eq!(roff(OutputEncoding::Latin1).render_to_vec().unwrap(), bytes);
+
+        let commented = |encoding| {
+            roff(encoding)
+                .section("NOTES", [RoffNode::comment("é → ⇒ →\nü → é")])
+                .render_to_vec()
+        };
+        match commented(OutputEncoding::Ascii) {
+            Err(RoffError::Encoding {
+                encoding: OutputEncoding::Ascii,
+                characters,
+            }) => assert_eq!(characters, [('é', 5), ('→', 5), ('⇒', 5), ('ü', 6)]),
+            other => panic!("unexpected result {:?}", other),
+        }
+        let err = commented(OutputEncoding::Latin1).unwrap_err();
+        match &err {
+            RoffError::Encoding {
+                encoding: OutputEncoding::Latin1,
+                characters,
+            } => assert_eq!(characters, &[('→', 5), ('⇒', 5)]),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Output contains characters that can't be represented in Latin-1 - `→` (U+2192) on \
+             line 5, `⇒` (U+21D2) on line 5"
+        );
+        assert!(commented(OutputEncoding::Utf8).is_ok());
+    }
+
+    #[test]
+    fn portable_compat_avoids_groff_macros() {
+        let roff = Roff::new("test-compat", SectionNumber::UserCommands).section(
+            "SYNOPSIS",
+            [
+                RoffNode::synopsis(
+                    "test-compat",
+                    ["does things"],
+                    [
+                        SynopsisItem::from(SynopsisOpt::new("-v").description(["be verbose"])),
+                        SynopsisOptGroup::new(
+                            "Output:",
+                            [SynopsisOpt::new("--format")
+                                .argument("FMT")
+                                .possible_values(["json", "text"])],
+                        )
+                        .into(),
+                    ],
+                ),
+                RoffNode::text("See"),
+                RoffNode::url("the homepage", "https://example.com"),
+                RoffNode::text("or write to"),
+                RoffNode::email("", "me@example.com"),
+                RoffNode::text("."),
+                RoffNode::example(["$ test-compat -v\n.hidden"]),
+            ],
+        );
+
+        let groff = roff.clone().render_to_string().unwrap();
+        for request in [
+            ".SY", ".OP", ".YS", ".UR", ".UE", ".MT", ".ME", ".EX", ".EE",
+        ] {
+            assert!(groff.contains(&format!("\n{}", request)), "{}", request);
+        }
+
+        let portable = roff
+            .compat(CompatLevel::Portable)
+            .render_to_string()
+            .unwrap();
+        assert_eq!(
+            portable,
+            r#".TH test\-compat 1
+.SH SYNOPSIS
+\fBtest\-compat\fR
+does things
+[\fB\-v\fR]
+be verbose
+\fBOutput:\fR
+[\fB\-\-format\fR \fIFMT\fR]
+Possible values: \fBjson\fR, \fBtext\fR
+.br
+See
+the homepage <https://example.com>
+or write to
+<me@example.com>
+\&.
+.nf
+.ft CW
+$ test\-compat \-v
+\&.hidden
+.ft R
+.fi
+"#
+        );
+        assert!(verify_output(&portable).is_empty());
+    }
+
+    #[test]
+    fn render_errors_have_context() {
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let roff = Roff::new("foo", SectionNumber::UserCommands)
+            .section("NAME", ["foo"])
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::group(["short", "text"]),
+                    RoffNode::text("x".repeat(2 * render::BATCH_SIZE)),
+                ],
+            );
+        let err = roff.render(&mut FailingWriter).unwrap_err();
+        match &err {
+            RoffError::NodeRenderFailed { path, error } => {
+                assert_eq!(path.section_index(), Some(1));
+                assert_eq!(path.section_title(), Some("DESCRIPTION"));
+                assert_eq!(path.node_indices(), [1]);
+                assert_eq!(error.to_string(), "disk full");
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Failed to render ROFF at sections[1] (DESCRIPTION).nodes[1] - `disk full`"
+        );
+        assert_eq!(err.source().unwrap().to_string(), "disk full");
+
+        let err = RoffError::Validation {
+            kind: ValidationKind::EmptyTitle,
+            path: NodePath::section(0, "NAME").node(2),
+            message: "the title is empty".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Invalid document at sections[0] (NAME).nodes[2] - the title is empty"
+        );
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn render_fmt_works() {
+        struct FailingSink;
+
+        impl fmt::Write for FailingSink {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let roff = readme_example();
+        let mut rendered = String::from("already written\n");
+        roff.render_fmt(&mut rendered).unwrap();
+        assert_eq!(
+            rendered
+                .strip_prefix("already written\n")
+                .unwrap()
+                .as_bytes(),
+            roff.render_to_vec().unwrap()
+        );
+        assert_eq!(format!("{}", roff), roff.render_to_string().unwrap());
+
+        assert!(matches!(
+            roff.render_fmt(&mut FailingSink),
+            Err(RoffError::FormatFailed(_))
+        ));
+
+        let mut out = String::new();
+        let mut sink = FmtWriter::new(&mut out, OutputEncoding::Utf8);
+        let bytes = "Kępka".as_bytes();
+        sink.write_all(&bytes[..2]).unwrap();
+        sink.write_all(&bytes[2..]).unwrap();
+        sink.finish().unwrap();
+        assert_eq!(out, "Kępka");
+
+        let mut sink = FmtWriter::new(&mut out, OutputEncoding::Utf8);
+        sink.write_all(&bytes[..2]).unwrap();
+        assert!(matches!(
+            sink.finish(),
+            Err(RoffError::StringRenderFailed(_))
+        ));
+    }
+
+    #[test]
+    fn output_larger_than_a_batch_renders() {
+        let paragraph = RoffNode::paragraph([
+            "Zażółć gęślą jaźń, the quick brown fox ".roff(),
+            "jumps".roff().bold(),
+            " over the lazy dog. Then it sleeps.".roff(),
+        ]);
+        let options = RenderOptions::new()
+            .max_line_width(20)
+            .sentence_per_line(true)
+            .strip_trailing_whitespace(true);
+        let single = Roff::new("test", SectionNumber::UserCommands)
+            .render_options(options.clone())
+            .section("DESCRIPTION", vec![paragraph.clone(); 2])
+            .render_to_string()
+            .unwrap();
+        let (header, body) = single.split_at(single.find(".P\n").unwrap());
+
+        let roff = Roff::new("test", SectionNumber::UserCommands)
+            .render_options(options)
+            .section("DESCRIPTION", vec![paragraph; 1000]);
+        let rendered = roff.render_to_string().unwrap();
+        assert!(rendered.len() > 10 * render::BATCH_SIZE);
+        assert_eq!(
+            rendered,
+            format!("{}{}", header, vec![body; 999].join("\n"))
+        );
+    }
+
+    #[test]
+    fn estimated_size_is_close() {
+        fn assert_close(roff: &Roff) {
+            let estimate = roff.estimated_size();
+            let actual = roff.render_to_vec().unwrap().len();
+            assert!(
+                actual / 2 <= estimate && estimate <= actual * 2,
+                "estimate {} is not close to {}",
+                estimate,
+                actual
+            );
+        }
+
+        assert_close(&readme_example());
+
+        let nodes = (0..2000).map(|i| {
+            RoffNode::paragraph([
+                "Zażółć gęślą jaźń, the quick brown fox ".roff(),
+                i.to_string().roff().bold(),
+                " jumps over the lazy dog.".roff(),
+            ])
+        });
+        let opts = (0..500).map(|i| {
+            SynopsisOpt::new(format!("--option-{}", i))
+                .argument("VALUE")
+                .description([format!("Sets the option number {} to VALUE.", i)])
+        });
+        let roff = Roff::new("large", SectionNumber::Miscellaneous)
+            .generated_by_roffman()
+            .section("DESCRIPTION", nodes)
+            .section("OPTIONS", [RoffNode::option_list::<_, SynopsisOpt>(opts)])
+            .append_page(readme_example());
+        assert_close(&roff);
+    }
+
+    #[test]
+    fn appended_pages_render() {
+        let first = Roff::new("first", SectionNumber::UserCommands)
+            .section("NAME", ["first - the first page"]);
+        let second = Roff::new("second", SectionNumber::FileFormatsAndConfigurationFiles)
+            .date("2021-08-01")
+            .section("NAME", [RoffNode::paragraph(["second - the second page"])]);
+        let third = Roff::new("third", SectionNumber::Miscellaneous)
+            .section("NAME", ["third - the third page"]);
+
+        let roff = first.append_page(second.append_page(third));
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH first 1
+.SH NAME
+first \- the first page
+.TH second 5 2021\-08\-01
+.SH NAME
+second \- the second page
+.TH third 7
+.SH NAME
+third \- the third page"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn merges_duplicate_sections() {
+        let roff = Roff::new("test-merge", SectionNumber::UserCommands)
+            .section("NAME", ["test - merges sections"])
+            .add_section(Section::new("ENVIRONMENT", ["HOME"]).subtitle("core"))
+            .section("FILES", ["~/.config"])
+            .add_section(Section::new("Environment", ["PLUGIN_DIR"]).subtitle("plugins"))
+            .section("environment", [RoffNode::paragraph(["EDITOR"])]);
+
+        let rendered = roff.merge_duplicate_sections().render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-merge 1
+.SH NAME
+test \- merges sections
+.SH ENVIRONMENT
+.SS core
+HOME
+.SS plugins
+PLUGIN_DIR
+.P
+EDITOR
+.SH FILES
+\(ti/.config"#,
+            rendered
+        );
+
+        let roff = Roff::new("test-merge", SectionNumber::UserCommands)
+            .section("FILES", [RoffNode::paragraph(["first"])])
+            .section("FILES", [RoffNode::paragraph(["second"])]);
+        let rendered = roff.merge_duplicate_sections().render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-merge 1
+.SH FILES
+first
+.P
+second"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn merging_without_duplicates_is_a_noop() {
+        let roff = Roff::new("test-merge", SectionNumber::UserCommands)
+            .section("NAME", ["test - merges sections"])
+            .add_section(Section::new("ENVIRONMENT", ["HOME"]).subtitle("core"))
+            .section("FILES", ["~/.config"]);
+
+        assert_eq!(
+            roff.clone()
+                .merge_duplicate_sections()
+                .render_to_string()
+                .unwrap(),
+            roff.render_to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn name_section_with_aliases_works() {
+        let roff = Roff::new("grep", SectionNumber::UserCommands)
+            .name_section("print lines that match patterns")
+            .aliases(["egrep", "fgrep"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH grep 1
+.SH NAME
+grep, egrep, fgrep \- print lines that match patterns"#,
+            rendered
+        );
+        assert_eq!(roff.get_aliases(), &["egrep".roff(), "fgrep".roff()]);
+        assert_eq!(
+            roff.alias_pages(),
+            vec![
+                ("egrep.1".to_string(), ".so man1/grep.1\n".to_string()),
+                ("fgrep.1".to_string(), ".so man1/grep.1\n".to_string()),
+            ]
+        );
+
+        let roff = Roff::new("my-app", SectionNumber::UserCommands)
+            .aliases(["my-alias"])
+            .name_section("does things");
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH my\-app 1
+.SH NAME
+my\-app, my\-alias \- does things"#,
+            rendered
+        );
+        assert!(roff
+            .validate()
+            .iter()
+            .all(|finding| finding.kind() != LintKind::NameNotWhatis));
+    }
+
+    #[test]
+    fn constant_width_text_works() {
+        let roff = Roff::new("test-code", SectionNumber::UserCommands).section(
+            "FILES",
+            [RoffNode::paragraph([
+                "The configuration is read from ".roff(),
+                "~/.config/app.toml".roff().code(),
+                " on startup.".roff(),
+            ])],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-code 1
+.SH FILES
+The configuration is read from \f(CW\(ti/.config/app.toml\fR on startup."#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn text_size_works() {
+        let roff = Roff::new("test-size", SectionNumber::Miscellaneous).section(
+            "SIZES",
+            [RoffNode::paragraph([
+                "BANNER".roff().bold().size(4),
+                " normal ".roff(),
+                "small print".roff().size(-2),
+                " ".roff(),
+                "huge".roff().italic().size(12),
+                " ".roff(),
+                "tiny".roff().size(-120),
+                " ".roff(),
+                "unchanged".roff().size(0),
+            ])],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-size 7
+.SH SIZES
+\s+4\fBBANNER\fR\s0 normal \s-2small print\s0 \s+(12\fIhuge\fR\s0 \s[-120]tiny\s0 unchanged"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn appended_text_works() {
+        let tag = "-v"
+            .roff()
+            .bold()
+            .append(", ")
+            .append("--verbose".roff().bold())
+            .append("=")
+            .append("WHEN".roff().italic());
+        let roff = Roff::new("test-append", SectionNumber::Miscellaneous).section(
+            "OPTIONS",
+            [RoffNode::tagged_paragraph(["Be verbose."], tag.clone())],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-append 7
+.SH OPTIONS
+.TP
+\fB\-v\fR, \fB\-\-verbose\fR=\fIWHEN\fR
+Be verbose.
+"#,
+            rendered
+        );
+        assert_eq!(r#"\-v, \-\-verbose=WHEN"#, tag.as_str());
+
+        let bold = "a".roff().append("b".roff().italic()).bold();
+        let mut out = vec![];
+        bold.render(&mut out, &RenderOptions::default()).unwrap();
+        assert_eq!(r#"\fBa\fR\fBb\fR"#, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn roff_text_macro_works() {
+        fn render(text: RoffText) -> String {
+            let mut out = vec![];
+            text.render(&mut out, &RenderOptions::default()).unwrap();
+            String::from_utf8(out).unwrap()
+        }
+
+        assert_eq!(
+            r#"listening on \fBlocal\-host\fR port 8080"#,
+            render(roff_text!(
+                "listening on {} port {}",
+                "local-host".roff().bold(),
+                8080u16.to_string()
+            ))
+        );
+        assert_eq!(
+            r#"\fB\-\-color\fR\fIWHEN\fR"#,
+            render(roff_text!(
+                "{}{}",
+                "--color".roff().bold(),
+                "WHEN".roff().italic()
+            ))
+        );
+        assert_eq!(
+            r#"{\-x} \fBy\fR"#,
+            render(roff_text!("{{-x}} {}", "y".roff().bold()))
+        );
+        assert_eq!(r#"a\eb"#, render(roff_text!("a\\b")));
+        assert_eq!(r#"5 items"#, render(roff_text!("{} items", 5u8)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn roff_text_macro_panics_on_missing_argument() {
+        roff_text!("{} and {}", "one");
+    }
+
+    #[test]
+    fn text_display_and_accessors_work() {
+        let text = "--color".roff().bold() + "=" + "a\\b".roff().italic().size(-1);
+
+        assert_eq!(r#"\fB\-\-color\fR=\s-1\fIa\eb\fR\s0"#, text.to_string());
+        assert_eq!(r#"\-\-color=a\eb"#, text.as_str());
+        assert_eq!(r#"\-\-color=a\eb"#, AsRef::<str>::as_ref(&text));
+        assert_eq!(r#"--color=a\b"#, text.plain());
+        assert_eq!("plain", format!("{}", "plain".roff()));
+    }
+
+    #[test]
+    fn raw_text_works() {
+        let roff = Roff::new("test-raw", SectionNumber::Miscellaneous).section(
+            "DESCRIPTION",
+            [RoffNode::paragraph([
+                "Made with a \\ and a ".roff(),
+                RoffText::raw(r"\[u1F980]").bold(),
+                " - crab.".roff(),
+            ])],
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-raw 7
+.SH DESCRIPTION
+Made with a \e and a \fB\[u1F980]\fR \- crab."#,
+            rendered
+        );
+
+        let raw = RoffText::raw(r"\(em text");
+        assert!(format!("{:?}", raw).contains("raw: true"));
+        assert!(!format!("{:?}", "text".roff()).contains("raw: true"));
+
+        let mut quoted = vec![];
+        write_quoted_if_whitespace(&raw, &mut quoted, &RenderOptions::default()).unwrap();
+        assert_eq!(r#""\(em text""#, String::from_utf8(quoted).unwrap());
+    }
+
+    #[test]
+    fn original_content_round_trips() {
+        let roff = Roff::new("my-app", SectionNumber::UserCommands);
+        assert_eq!("my-app", roff.get_title_original());
+
+        let text = "it's a \\ test".roff().bold() + " - more";
+        assert_eq!("it's a \\ test - more", text.original());
+        assert_eq!(r#"it\(aqs a \e test \- more"#, text.as_str());
+        assert_eq!(text, "it's a \\ test".roff().bold() + " - more");
+    }
+
+    #[test]
+    fn static_text_works() {
+        let text = RoffText::from_static("static - text").bold();
+        assert_eq!(r#"\fBstatic \- text\fR"#, text.to_string());
+        assert_eq!("static - text", text.original());
+        assert_eq!(text, "static - text".roff().bold());
+    }
+
+    #[test]
+    fn primitives_are_roffable() {
+        assert_eq!("42", 42u64.roff().as_str());
+        assert_eq!(r#"\-1"#, (-1i32).roff().as_str());
+        assert_eq!(r#"\-128"#, i8::MIN.roff().as_str());
+        assert_eq!(
+            "340282366920938463463374607431768211455",
+            u128::MAX.roff().as_str()
+        );
+        assert_eq!("1.5", 1.5f64.roff().as_str());
+        assert_eq!(r#"\-0.25"#, (-0.25f32).roff().as_str());
+        assert_eq!("true", true.roff().as_str());
+        assert_eq!("false", false.roff().as_str());
+        assert_eq!("x", 'x'.roff().as_str());
+        assert_eq!(r#"\e"#, '\\'.roff().as_str());
+        assert_eq!(r#"\(aq"#, '\''.roff().as_str());
+        assert_eq!("7".roff(), 7usize.roff());
+    }
+
+    #[test]
+    fn paths_are_roffable() {
+        let path = Path::new("~/.config/my-app/config.toml");
+        assert_eq!(r#"\(ti/.config/my\-app/config.toml"#, path.roff().as_str());
+        assert_eq!(path.roff(), path.to_path_buf().roff());
+        assert_eq!(path.roff().as_str(), path.roff().to_string());
+    }
+
+    #[test]
+    fn non_unicode_paths_are_converted_lossily() {
+        #[cfg(unix)]
+        let os_string = {
+            use std::os::unix::ffi::OsStringExt;
+            std::ffi::OsString::from_vec(vec![b'a', 0xff, b'-', b'b'])
+        };
+        #[cfg(windows)]
+        let os_string = {
+            use std::os::windows::ffi::OsStringExt;
+            std::ffi::OsString::from_wide(&[
+                u16::from(b'a'),
+                0xd800,
+                u16::from(b'-'),
+                u16::from(b'b'),
+            ])
+        };
+
+        let path = PathBuf::from(os_string);
+        assert_eq!("a\u{fffd}\\-b", path.roff().as_str());
+    }
+
+    #[test]
+    fn joining_text_works() {
+        let empty = RoffText::join(Vec::<RoffText>::new(), ", ");
+        assert!(empty.to_string().is_empty());
+
+        let single = RoffText::join(["only".roff().bold()], ", ");
+        assert_eq!(r#"\fBonly\fR"#, single.to_string());
+
+        let mixed = RoffText::join(
+            [
+                "ls".roff().bold(),
+                "dir-colors".roff().italic(),
+                "plain".roff(),
+            ],
+            " | ".roff().code(),
+        );
+        assert_eq!(
+            r#"\fBls\fR\f(CW | \fR\fIdir\-colors\fR\f(CW | \fRplain"#,
+            mixed.to_string()
+        );
+
+        let collected: RoffText = vec!["a".roff().bold(), "-b".roff()].into_iter().collect();
+        assert_eq!(r#"\fBa\fR\-b"#, collected.to_string());
+        assert!(std::iter::empty::<RoffText>()
+            .collect::<RoffText>()
+            .to_string()
+            .is_empty());
+    }
+
+    #[test]
+    fn text_mutation_and_inspection_works() {
+        let mut text = "it's".roff().bold();
+        assert!(!text.is_empty());
+        assert_eq!(4, text.len());
+        assert_eq!(FontStyle::Bold, text.style());
+
+        text.push_str(" a-b ~");
+        assert_eq!(r#"\fBit\(aqs a\-b \(ti\fR"#, text.to_string());
+        assert_eq!(10, text.len());
+
+        text.set_style(FontStyle::Italic);
+        assert_eq!(FontStyle::Italic, text.style());
+        assert_eq!(r#"\fIit\(aqs a\-b \(ti\fR"#, text.to_string());
+
+        let mut raw = RoffText::raw(r"\(em");
+        raw.push_str("-");
+        assert_eq!(r#"\(em\-"#, raw.as_str());
+        assert_eq!(5, raw.len());
+
+        let mut raw = RoffText::raw(r"\(em")
+            .prose()
+            .with_policy(EscapePolicy::new().quotes(false));
+        raw.push_str("it's well-known");
+        assert_eq!(r#"\(emit's well-known"#, raw.as_str());
+        raw.push_str("-");
+        assert_eq!(r#"\(emit's well-known-"#, raw.as_str());
+
+        let mut empty = RoffText::default();
+        assert!(empty.is_empty());
+        assert!("".roff().is_empty());
+        assert_eq!(0, empty.len());
+        empty.push_str("zażółć");
+        assert_eq!(6, empty.len());
+    }
+
+    #[test]
+    fn literal_text_works() {
+        let roff = |font| {
+            Roff::new("test-literal", SectionNumber::Miscellaneous)
+                .literal_font(font)
+                .section(
+                    "FILES",
+                    [RoffNode::paragraph([
+                        "Read ".roff(),
+                        "config.toml".roff().literal(),
+                        " or run ".roff(),
+                        "my-app init.".roff().literal(),
+                    ])],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            r#".TH test\-literal 7
+.SH FILES
+Read \f(CW\%config.toml\fR or run \f(CW\%my\-app init.\&\fR"#,
+            roff(LiteralFont::ConstantWidth)
+        );
+        assert_eq!(
+            r#".TH test\-literal 7
+.SH FILES
+Read \fB\%config.toml\fR or run \fB\%my\-app init.\&\fR"#,
+            roff(LiteralFont::Bold)
+        );
+    }
+
+    #[test]
+    fn strips_trailing_whitespace() {
+        let roff = |options| {
+            Roff::new("test-whitespace", SectionNumber::Miscellaneous)
+                .render_options(options)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::paragraph(["Trailing spaces   \nin text\t \n".roff()]),
+                        RoffNode::text("text  with  inner spaces  "),
+                        RoffNode::linebreak(),
+                        RoffNode::text(" last "),
+                    ],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            ".TH test\\-whitespace 7\n.SH DESCRIPTION\nTrailing spaces   \nin text     \ntext  with  inner spaces  \n.br\n last ",
+            roff(RenderOptions::new())
+        );
+        assert_eq!(
+            ".TH test\\-whitespace 7\n.SH DESCRIPTION\nTrailing spaces\nin text\ntext  with  inner spaces\n.br\n last ",
+            roff(RenderOptions::new().strip_trailing_whitespace(true))
+        );
+
+        let text = "a  \nb\t\nc ".roff().trim_trailing_whitespace();
+        assert_eq!("a\nb\nc ", text.original());
+    }
+
+    #[test]
+    fn escape_policy_works() {
+        let roff = |policy| {
+            Roff::new("test-policy", SectionNumber::Miscellaneous)
+                .escape_policy(policy)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("It's a well-known ~/.config ^ `trick` \\ "),
+                        RoffNode::text("--always-escaped".roff().with_policy(EscapePolicy::new())),
+                    ],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            r#".TH test\-policy 7
+.SH DESCRIPTION
+It\(aqs a well\-known \(ti/.config \(ha \(gatrick\(ga \e \-\-always\-escaped"#,
+            roff(EscapePolicy::default())
+        );
+        assert_eq!(
+            r#".TH test-policy 7
+.SH DESCRIPTION
+It's a well-known ~/.config ^ `trick` \e \-\-always\-escaped"#,
+            roff(
+                EscapePolicy::new()
+                    .dashes(false)
+                    .quotes(false)
+                    .special_chars(false)
+            )
+        );
+        assert_eq!(
+            r#".TH test-policy 7
+.SH DESCRIPTION
+It\(aqs a well-known \(ti/.config \(ha \(gatrick\(ga \e \-\-always\-escaped"#,
+            roff(EscapePolicy::new().dashes(false))
+        );
+    }
+
+    #[test]
+    fn leading_dots_are_protected() {
+        let roff = Roff::new("test-dots", SectionNumber::Miscellaneous)
+            .section(
+                "FILES",
+                [
+                    RoffNode::text(".config directory is used"),
+                    RoffNode::linebreak(),
+                    RoffNode::text(".cache".roff()),
+                    RoffNode::text(" and .local are not"),
+                ],
+            )
+            .section(
+                "EXAMPLES",
+                [
+                    RoffNode::tagged_paragraph(["Hidden files."], ".hidden"),
+                    RoffNode::example([".profile is read first"]),
+                ],
+            );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-dots 7
+.SH FILES
+\&.config directory is used
+.br
+\&.cache and .local are not
+.SH EXAMPLES
+.TP
+\&.hidden
+Hidden files.
+.EX
+\&.profile is read first
+.EE
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn leading_apostrophes_are_protected() {
+        let roff = |policy| {
+            Roff::new("test-apostrophes", SectionNumber::Miscellaneous)
+                .escape_policy(policy)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("'tis the season"),
+                        RoffNode::linebreak(),
+                        RoffNode::text("'quoted' and\n'another'"),
+                    ],
+                )
+                .section("EXAMPLES", [RoffNode::example(["'single quoted' example"])])
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            r#".TH test\-apostrophes 7
+.SH DESCRIPTION
+\&\(aqtis the season
+.br
+\&\(aqquoted\(aq and
+\&\(aqanother\(aq
+.SH EXAMPLES
+.EX
+\&\(aqsingle quoted\(aq example
+.EE
+"#,
+            roff(EscapePolicy::default())
+        );
+        assert_eq!(
+            r#".TH test\-apostrophes 7
+.SH DESCRIPTION
+\&'tis the season
+.br
+\&'quoted' and
+\&'another'
+.SH EXAMPLES
+.EX
+\&'single quoted' example
+.EE
+"#,
+            roff(EscapePolicy::new().quotes(false))
+        );
+    }
+
+    #[test]
+    fn tab_policy_works() {
+        let roff = |policy| {
+            Roff::new("test-tabs", SectionNumber::Miscellaneous)
+                .escape_policy(policy)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::paragraph(["a\tparagraph"]),
+                        RoffNode::tagged_paragraph(["body"], "--tab\ttag"),
+                        RoffNode::example(["key\tvalue"]),
+                    ],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            ".TH test\\-tabs 7
+.SH DESCRIPTION
+a    paragraph
+.TP
+\\-\\-tab    tag
+body
+.EX
+key\tvalue
+.EE
+",
+            roff(EscapePolicy::default())
+        );
+        assert_eq!(
+            ".TH test\\-tabs 7
+.SH DESCRIPTION
+a\\tparagraph
+.TP
+\\-\\-tab\\ttag
+body
+.EX
+key\\tvalue
+.EE
+",
+            roff(EscapePolicy::new().tabs(TabPolicy::Escape))
+        );
+    }
+
+    #[test]
+    fn hyphen_mode_works() {
+        let roff = |policy| {
+            Roff::new("test-hyphens", SectionNumber::Miscellaneous)
+                .escape_policy(policy)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("re-run with --force, "),
+                        RoffNode::text("re-run with ".roff().prose()),
+                        RoffNode::text("--force".roff().literal()),
+                    ],
+                )
+                .section(
+                    "OPTIONS",
+                    [RoffNode::option_list([
+                        SynopsisOpt::new("--force").description(["re-run everything"])
+                    ])],
+                )
+                .section("EXAMPLES", [RoffNode::example(["re-run --force"])])
+                .render_to_string()
+                .unwrap()
+        };
+
+        assert_eq!(
+            r#".TH test\-hyphens 7
+.SH DESCRIPTION
+re\-run with \-\-force, re-run with \f(CW\%\-\-force\fR
+.SH OPTIONS
+.TP
+\fB\-\-force\fR
+re\-run everything
+.SH EXAMPLES
+.EX
+re\-run \-\-force
+.EE
+"#,
+            roff(EscapePolicy::default())
+        );
+        assert_eq!(
+            r#".TH test-hyphens 7
+.SH DESCRIPTION
+re-run with --force, re-run with \f(CW\%\-\-force\fR
+.SH OPTIONS
+.TP
+\fB\-\-force\fR
+re-run everything
+.SH EXAMPLES
+.EX
+re\-run \-\-force
+.EE
+"#,
+            roff(EscapePolicy::new().hyphens(HyphenMode::Hyphen))
+        );
+        assert_eq!(
+            r#".TH test\(hyhyphens 7
+.SH DESCRIPTION
+re\(hyrun with \(hy\(hyforce, re-run with \f(CW\%\-\-force\fR
+.SH OPTIONS
+.TP
+\fB\-\-force\fR
+re\(hyrun everything
+.SH EXAMPLES
+.EX
+re\-run \-\-force
+.EE
+"#,
+            roff(EscapePolicy::new().hyphens(HyphenMode::TypographicHyphen))
+        );
+    }
+
+    #[test]
+    fn node_escape_policy_works() {
+        let verbatim = EscapePolicy::new()
+            .dashes(false)
+            .quotes(false)
+            .special_chars(false);
+        let roff = Roff::new("test-node-policy", SectionNumber::Miscellaneous)
+            .escape_policy(EscapePolicy::new().quotes(false))
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::text("it's ~/.config-dir"),
+                    RoffNode::nested([
+                        RoffNode::paragraph(["it's ~/.config-dir"]),
+                        RoffNode::paragraph(["it's ~/.config-dir"])
+                            .with_policy(EscapePolicy::default()),
+                        RoffNode::example([
+                            "it's ~/.config-dir".roff(),
+                            "\nit's ~/.config-dir"
+                                .roff()
+                                .with_policy(EscapePolicy::default()),
+                        ]),
+                    ])
+                    .with_policy(verbatim),
+                ],
+            );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-node\-policy 7
+.SH DESCRIPTION
+it's \(ti/.config\-dir
+.RS
+.P
+it's ~/.config-dir
+.P
+it\(aqs \(ti/.config\-dir
+.EX
+it's ~/.config\-dir
+it\(aqs \(ti/.config\-dir
+.EE
+.RE
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn smart_punctuation_skips_code() {
+        let roff = Roff::new("test-smart", SectionNumber::Miscellaneous)
+            .escape_policy(EscapePolicy::new().smart_punctuation(true))
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::text("Don't \"quote\" -- run "),
+                    RoffNode::text("echo \"it's\"".roff().literal()),
+                    RoffNode::text(" or "),
+                    RoffNode::text("a -- b...".roff().code()),
+                    RoffNode::example(["echo 'it's' -- \"done\"..."]),
+                ],
+            );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(
+            r#".TH test\-smart 7
+.SH DESCRIPTION
+Don\(cqt \(lqquote\(rq \(em run \f(CW\%echo \(dqit\(aqs\(dq\fR or \f(CWa \-\- b...\fR
+.EX
+echo \(aqit\(aqs\(aq \-\- \(dqdone\(dq...
+.EE
+"#,
+            rendered
+        );
+    }
+
+    #[test]
+    fn pasted_no_break_spaces_render_like_nodes() {
+        let roff = |nodes| {
+            Roff::new("test-nbsp", SectionNumber::Miscellaneous)
+                .section("DESCRIPTION", nodes)
+                .render_to_string()
+                .unwrap()
+        };
+
+        let pasted = roff(vec![RoffNode::text("10\u{A0}GiB")]);
+        assert_eq!(
+            r#".TH test\-nbsp 7
+.SH DESCRIPTION
+10\~GiB"#,
+            pasted
+        );
+        assert_eq!(
+            pasted,
+            roff(vec![
+                RoffNode::text("10"),
+                RoffNode::non_breaking_space(),
+                RoffNode::text("GiB"),
+            ])
+        );
+    }
+
+    #[test]
+    fn display_matches_render() {
+        let roff = Roff::new("test-display", SectionNumber::UserCommands)
+            .generated_by_roffman()
+            .render_options(RenderOptions::new().strip_trailing_whitespace(true))
+            .section("NAME", ["test \\ display – zażółć "])
+            .append_page(Roff::new("second", SectionNumber::Miscellaneous));
+
+        let mut written = vec![];
+        roff.render(&mut written).unwrap();
+        assert_eq!(format!("{}", roff), roff.render_to_string().unwrap());
+        assert_eq!(format!("{}", roff).into_bytes(), written);
+        #[allow(deprecated)]
+        let deprecated = roff.to_string().unwrap();
+        assert_eq!(format!("{}", roff), deprecated);
+    }
+
+    #[test]
+    fn render_to_vec_matches_string() {
+        let roff = Roff::new("test-vec", SectionNumber::UserCommands)
+            .section("NAME", ["test\\-vec \\- bytes – zażółć"]);
+
+        let rendered = roff.render_to_string().unwrap();
+        assert_eq!(roff.render_to_vec().unwrap(), rendered.clone().into_bytes());
+
+        let mut buf = b"previous\n".to_vec();
+        roff.render_into(&mut buf).unwrap();
+        assert_eq!(buf, format!("previous\n{}", rendered).into_bytes());
+    }
+
+    #[test]
+    fn long_lines_are_wrapped() {
+        let roff = |options| {
+            Roff::new("test-wrap", SectionNumber::Miscellaneous)
+                .render_options(options)
+                .section(
+                    "DESCRIPTION",
+                    [RoffNode::paragraph([
+                        "Some quite long text with ".roff(),
+                        "bold words that straddle".roff().bold(),
+                        " the wrap point and a ".roff(),
+                        "size change".roff().size(2),
+                        " then finally x . a dot and more words to fill xx ' a quote, ".roff(),
+                        "a_very_long_unbreakable_word_exceeding_the_width".roff(),
+                        " end.".roff(),
+                    ])],
+                )
+                .section(
+                    "OPTIONS",
+                    [RoffNode::tagged_paragraph(
+                        ["the body of the tagged paragraph is wrapped as well"],
+                        "--tag with many words that is never wrapped",
+                    )],
+                )
+                .section(
+                    "EXAMPLES",
+                    [RoffNode::example([
+                        "an example line that is longer than the width but kept",
+                    ])],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        let unwrapped = roff(RenderOptions::new());
+        let wrapped = roff(RenderOptions::new().max_line_width(24));
+        assert_eq!(
+            r#".TH test\-wrap 7
+.SH DESCRIPTION
+Some quite long text
+with \fBbold words that
+straddle\fR the wrap
+point and a \s+2size
+change\s0 then finally x
+\&. a dot and more words
+to fill xx \(aq a quote,
+a_very_long_unbreakable_word_exceeding_the_width
+end.
+.SH OPTIONS
+.TP
+\-\-tag with many words that is never wrapped
+the body of the tagged
+paragraph is wrapped as
+well
+.SH EXAMPLES
+.EX
+an example line that is longer than the width but kept
+.EE
+"#,
+            wrapped
+        );
+        assert_eq!(
+            wrapped.replace('\n', " "),
+            unwrapped.replace('\n', " ").replace(" . ", " \\&. ")
+        );
+        assert!(verify_output(&wrapped).is_empty());
+    }
+
+    #[test]
+    fn output_is_normalized() {
+        let roff = |options| {
+            Roff::new("test-normalize", SectionNumber::Miscellaneous)
+                .render_options(options)
+                .section(
+                    "SYNOPSIS",
+                    [RoffNode::synopsis(
+                        "cmd",
+                        ["desc"],
+                        [
+                            SynopsisOpt::new("--a"),
+                            SynopsisOpt::new("--b"),
+                            SynopsisOpt::new("--c").description(["opt"]),
+                        ],
+                    )],
+                )
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::paragraph(["first\n\n\nsecond"]),
+                        RoffNode::example(["kept\n\n\nas is"]),
+                        RoffNode::text("end"),
+                    ],
+                )
+                .render_to_string()
+                .unwrap()
+        };
+
+        let rendered = roff(RenderOptions::new());
+        assert!(rendered.contains("first\n\n\nsecond"));
+        assert!(!rendered.ends_with('\n'));
+
+        assert_eq!(
+            r#".TH test\-normalize 7
+.SH SYNOPSIS
+.SY cmd
+desc
+.OP \-\-a
+.OP \-\-b
+.OP \-\-c
+opt
+.YS
+.SH DESCRIPTION
+first
+
+second
+.EX
+kept
+
+
+as is
+.EE
+end
+"#,
+            roff(RenderOptions::new().normalize_output(true))
+        );
+    }
+
+    #[test]
+    fn sentences_start_on_new_lines() {
+        let roff = |options| {
+            Roff::new("test-sentences", SectionNumber::Miscellaneous)
+                .render_options(options)
+                .section(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::paragraph([
+                            "The first sentence. Is it the second? Yes! ".roff(),
+                            "Use e.g. this or Dr. Who, see J. Doe and fig. 2. Done.".roff(),
+                        ]),
+                        RoffNode::paragraph([
+                            "Styled ".roff(),
+                            "text. Spans".roff().bold(),
+                            " sentences. ".roff(),
+                            "Quoted".roff().italic(),
+                            " too (really.) \"Next\" one. 'Quote' here. ".roff(),
+                            "'Unescaped' quote."
+                                .roff()
+                                .with_policy(EscapePolicy::new().quotes(false)),
+                        ]),
+                        RoffNode::paragraph([
+                            "Kept etc.".roff(),
+                            RoffText::raw("\\&"),
+                            " Together and".roff(),
+                        ]),
+                    ],
+                )
+                .section(
+                    "OPTIONS",
+                    [RoffNode::tagged_paragraph(
+                        ["Body one. Body two."],
+                        "Tag one. Tag two.",
+                    )],
+                )
+                .section("EXAMPLES", [RoffNode::example(["Code one. Code two."])])
+                .render_to_string()
+                .unwrap()
+        };
+
+        let split = roff(RenderOptions::new().sentence_per_line(true));
+        assert_eq!(
+            r#".TH test\-sentences 7
+.SH DESCRIPTION
+The first sentence.
+Is it the second?
+Yes!
+Use e.g. this or Dr. Who, see J. Doe and fig. 2.
+Done.
+.P
+Styled \fBtext.
+Spans\fR sentences.
+\fIQuoted\fR too (really.)
+\(dqNext\(dq one.
+\(aqQuote\(aq here.
+\&'Unescaped' quote.
+.P
+Kept etc.\& Together and
+.SH OPTIONS
+.TP
+Tag one. Tag two.
+Body one.
+Body two.
+.SH EXAMPLES
+.EX
+Code one. Code two.
+.EE
+"#,
+            split
+        );
+        assert!(verify_output(&split).is_empty());
+
+        let wrapped = roff(
+            RenderOptions::new()
+                .sentence_per_line(true)
+                .max_line_width(24),
+        );
+        assert!(wrapped.contains("Use e.g. this or Dr.\nWho, see J. Doe and fig.\n2.\nDone.\n"));
+    }
+
+    #[test]
+    fn accessors_work() {
+        let mut roff = Roff::new("test-accessors", SectionNumber::UserCommands)
+            .date("August 2021")
+            .section("NAME", ["test"])
+            .section("DESCRIPTION", [RoffNode::paragraph(["First."])]);
+
+        assert_eq!(roff.get_title(), &"test-accessors".roff());
+        assert_eq!(roff.get_date(), Some(&"August 2021".roff()));
+        assert_eq!(
+            roff.get_section_id(),
+            &SectionId::Number(SectionNumber::UserCommands)
+        );
+        assert_eq!(
+            roff.get_sections()
+                .iter()
+                .map(|section| section.get_title().as_str())
+                .collect::<Vec<_>>(),
+            ["NAME", "DESCRIPTION"]
+        );
+
+        roff.set_title("test-changed");
+        roff.set_date("September 2021");
+        roff.get_sections_mut()[1]
+            .get_nodes_mut()
+            .push(RoffNode::paragraph(["Second."]));
+        roff.get_sections_mut().remove(0);
+
+        assert_eq!(
+            r#".TH test\-changed 1 "September 2021"
+.SH DESCRIPTION
+First.
+.P
+Second."#,
+            roff.render_to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn section_introspection_works() {
+        fn count_urls(nodes: &[RoffNode]) -> usize {
+            nodes
+                .iter()
+                .map(|node| (node.kind_name() == "url") as usize + count_urls(node.children()))
+                .sum()
+        }
+
+        let mut roff = Roff::new("test-introspection", SectionNumber::UserCommands)
+            .section("NAME", ["test"])
+            .add_section(
+                Section::new(
+                    "SEE ALSO",
+                    [
+                        RoffNode::url("GitHub", "https://github.com/vv9k/roffman"),
+                        RoffNode::paragraph([
+                            RoffNode::text("Docs at "),
+                            RoffNode::url("docs.rs", "https://docs.rs/roffman"),
+                            RoffNode::text(" and "),
+                            RoffNode::email("the author", "wojciech@wkepka.dev"),
+                        ]),
+                    ],
+                )
+                .subtitle("links"),
+            );
+
+        let see_also = roff
+            .get_sections()
+            .iter()
+            .find(|section| section.get_title().as_str() == "SEE ALSO")
+            .unwrap();
+        assert_eq!(see_also.get_subtitle(), Some(&"links".roff()));
+        assert_eq!(count_urls(see_also.get_nodes()), 2);
+        assert_eq!(
+            see_also.get_nodes()[1].text_content(),
+            "Docs at docs.rs and the author"
+        );
+        assert_eq!(see_also.get_nodes()[1].kind_name(), "paragraph");
+
+        roff.get_sections_mut()[1]
+            .get_nodes_mut()
+            .retain(|node| node.kind_name() != "url");
+        assert_eq!(count_urls(roff.get_sections()[1].get_nodes()), 1);
+    }
+
+    #[test]
+    fn bulk_section_addition_works() {
+        let table = [
+            ("FILES", "~/.config/foo.toml", "The configuration file."),
+            ("ENVIRONMENT", "FOO_DIR", "Directory with the data."),
+        ];
+        let sections: Vec<Section> = table
+            .iter()
+            .map(|(title, tag, body)| {
+                Section::new(title, [RoffNode::tagged_paragraph([body], tag)])
+            })
+            .collect();
+        let pairs = table
+            .iter()
+            .map(|(title, tag, body)| (title, [RoffNode::tagged_paragraph([body], tag)]));
+
+        let one_by_one = Roff::new("test-bulk", SectionNumber::UserCommands)
+            .section(
+                "FILES",
+                [RoffNode::tagged_paragraph(
+                    ["The configuration file."],
+                    "~/.config/foo.toml",
+                )],
+            )
+            .section(
+                "ENVIRONMENT",
+                [RoffNode::tagged_paragraph(
+                    ["Directory with the data."],
+                    "FOO_DIR",
+                )],
+            );
+        let roff = Roff::new("test-bulk", SectionNumber::UserCommands).add_sections(sections);
+        assert_eq!(roff, one_by_one);
+        let roff = Roff::new("test-bulk", SectionNumber::UserCommands).add_sections(pairs.clone());
+        assert_eq!(roff, one_by_one);
+
+        let mut roff = Roff::new("test-bulk", SectionNumber::UserCommands)
+            .add_section(("NAME", ["test-bulk"]));
+        roff.extend(pairs);
+        assert_eq!(
+            roff.get_sections()
+                .iter()
+                .map(|section| section.get_title().original())
+                .collect::<Vec<_>>(),
+            ["NAME", "FILES", "ENVIRONMENT"]
+        );
+        assert_eq!(&roff.get_sections()[1..], one_by_one.get_sections());
+    }
+
+    #[test]
+    fn conditional_builders_work() {
+        let roff = Roff::new("test-conditional", SectionNumber::UserCommands)
+            .section_if(false, "SKIPPED", || -> Vec<RoffNode> { unreachable!() })
+            .section_if(true, "EXAMPLES", || vec![RoffNode::example(["$ foo"])])
+            .apply_if(false, |_| unreachable!())
+            .apply_if(true, |roff| {
+                roff.add_section(
+                    Section::new("NOTES", None::<RoffNode>)
+                        .node_if(false, || -> RoffNode { unreachable!() })
+                        .node_if(true, || "kept")
+                        .apply_if(false, |_| unreachable!())
+                        .apply_if(true, |section| section.linebreak()),
+                )
+            });
+
+        let expected = Roff::new("test-conditional", SectionNumber::UserCommands)
+            .section("EXAMPLES", [RoffNode::example(["$ foo"])])
+            .section("NOTES", [RoffNode::text("kept"), RoffNode::linebreak()]);
+        assert_eq!(roff, expected);
+    }
+
+    #[test]
+    fn section_builder_works() {
+        let built = Section::new("DESCRIPTION", None::<RoffNode>)
+            .text("Some text.")
+            .paragraph(["A paragraph."])
+            .subheading("Details")
+            .indented_paragraph(["Indented."], Some(4), Some("title"))
+            .tagged("--opt", ["An option."])
+            .example(["$ foo --opt"])
+            .linebreak()
+            .url("GitHub", "https://github.com/vv9k/roffman")
+            .email("the author", "author@example.com")
+            .node(RoffNode::em_dash());
+        let constructed = Section::new(
+            "DESCRIPTION",
+            [
+                RoffNode::text("Some text."),
+                RoffNode::paragraph(["A paragraph."]),
+                RoffNode::subheading("Details"),
+                RoffNode::indented_paragraph(["Indented."], Some(4), Some("title")),
+                RoffNode::tagged_paragraph(["An option."], "--opt"),
+                RoffNode::example(["$ foo --opt"]),
+                RoffNode::linebreak(),
+                RoffNode::url("GitHub", "https://github.com/vv9k/roffman"),
+                RoffNode::email("the author", "author@example.com"),
+                RoffNode::em_dash(),
+            ],
+        );
+        assert_eq!(built, constructed);
+
+        let page =
+            |section| Roff::new("test-builder", SectionNumber::UserCommands).add_section(section);
+        assert_eq!(
+            page(built).render_to_string().unwrap(),
+            page(constructed).render_to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn section_editing_works() {
+        fn titles(roff: &Roff) -> Vec<&str> {
+            roff.get_sections()
+                .iter()
+                .map(|section| section.get_title().as_str())
+                .collect()
+        }
+
+        let mut roff = Roff::new("test-editing", SectionNumber::UserCommands)
+            .section("NAME", ["test"])
+            .section("DESCRIPTION", ["description"])
+            .section("SEE ALSO", ["roff(7)"]);
+
+        assert_eq!(roff.position_of("see also"), Some(2));
+        assert_eq!(roff.position_of("EXAMPLES"), None);
+        let index = roff.position_of("SEE ALSO").unwrap();
+        roff.insert_section(
+            index,
+            Section::new("EXAMPLES", [RoffNode::example(["test"])]),
+        );
+        assert_eq!(
+            titles(&roff),
+            ["NAME", "DESCRIPTION", "EXAMPLES", "SEE ALSO"]
+        );
+
+        assert!(roff.remove_section("BUGS").is_none());
+        assert!(roff
+            .replace_section("BUGS", Section::new("BUGS", ["none"]))
+            .is_none());
+        assert_eq!(roff.get_sections().len(), 4);
+
+        let replaced = roff
+            .replace_section("Description", Section::new("DESCRIPTION", ["replaced"]))
+            .unwrap();
+        assert_eq!(replaced.get_nodes(), &[RoffNode::text("description")]);
+        assert_eq!(
+            titles(&roff),
+            ["NAME", "DESCRIPTION", "EXAMPLES", "SEE ALSO"]
+        );
+
+        let removed = roff.remove_section("name").unwrap();
+        assert_eq!(removed.get_title().as_str(), "NAME");
+        roff.insert_section(3, removed);
+        let roff = roff.sort_sections_conventionally();
+        assert_eq!(
+            titles(&roff),
+            ["NAME", "DESCRIPTION", "EXAMPLES", "SEE ALSO"]
+        );
+        assert!(roff
+            .render_to_string()
+            .unwrap()
+            .contains(".SH DESCRIPTION\nreplaced\n"));
+    }
+
+    #[test]
+    fn structural_equality_works() {
+        fn roff(option: &str) -> Roff {
+            readme_example()
+                .section(
+                    "OPTIONS",
+                    [RoffNode::option_list([SynopsisOpt::new(option)])],
+                )
+                .append_page(Roff::new("second", SectionNumber::UserCommands))
+        }
+
+        let page = roff("--opt");
+        // Rendering escapes the text, which doesn't affect equality.
+        page.render_to_string().unwrap();
+        assert_eq!(page, roff("--opt"));
+        assert_ne!(page, roff("--opr"));
+        assert_ne!(page, page.clone().date("August 2022"));
+        assert_ne!(page, page.clone().title_case(TitleCase::Upper));
+        assert_ne!(page, page.clone().compat(CompatLevel::Portable));
+        assert_ne!(
+            RoffNode::text("text".roff().bold()),
+            RoffNode::text("text".roff().italic())
+        );
+        assert_eq!("a-b".roff(), RoffText::new("a-b", None));
+        assert_ne!("a-b".roff(), RoffText::raw("a\\-b"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_works() {
+        let opts = || {
+            vec![
+                SynopsisItem::from(SynopsisOpt::new("-v").description(["verbose"])),
+                SynopsisOptGroup::new(
+                    "Output",
+                    [
+                        SynopsisOpt::new("--color")
+                            .argument("WHEN")
+                            .possible_values(["auto", "never"])
+                            .default_value("auto"),
+                        SynopsisOpt::new("--internal").hidden(true),
+                    ],
+                )
+                .into(),
+            ]
+        };
+        let roff = Roff::new("test-serde", SectionNumber::Custom(9))
+            .title_case(TitleCase::Upper)
+            .date("August 2021")
+            .aliases(["test-alias"])
+            .generated_by_roffman()
+            .preamble_comments(["a comment"])
+            .compat(CompatLevel::Portable)
+            .section("NAME", [RoffNode::whatis(["test-serde"], "round trips")])
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis("test-serde", ["[OPTIONS]"], opts())],
+            )
+            .add_section(
+                Section::new(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("plain ".roff() + "bold".roff().bold().size(2)),
+                        RoffNode::text(RoffText::raw("\\[u1F980]")),
+                        RoffNode::text("well-known".roff().hyphens(HyphenMode::Hyphen)),
+                        RoffNode::subheading("Nodes"),
+                        RoffNode::paragraph([
+                            RoffNode::left_quote(),
+                            RoffNode::text("quoted"),
+                            RoffNode::right_quote(),
+                            RoffNode::registered_sign(),
+                            RoffNode::trademark_sign(),
+                            RoffNode::em_dash(),
+                            RoffNode::en_dash(),
+                            RoffNode::non_breaking_space(),
+                            RoffNode::linebreak(),
+                        ]),
+                        RoffNode::indented_paragraph(["indented"], Some(4), Some("title")),
+                        RoffNode::indented_paragraph(["indented"], None, None::<&str>),
+                        RoffNode::tagged_paragraph(["tagged"], "tag"),
+                        RoffNode::example(["$ test-serde -v"]),
+                        RoffNode::columns([("key", "value")], "2i"),
+                        RoffNode::nested_indented([RoffNode::text("nested")], 2),
+                        RoffNode::url("GitHub", "https://github.com/vv9k/roffman"),
+                        RoffNode::email("author", "wojciech@wkepka.dev"),
+                        RoffNode::comment("a comment"),
+                        RoffNode::text("'quotes'")
+                            .with_policy(EscapePolicy::new().smart_punctuation(true)),
+                    ],
+                )
+                .subtitle("subtitle"),
+            )
+            .section("OPTIONS", [RoffNode::option_list(opts())])
+            .append_page(Roff::new("second", SectionNumber::UserCommands))
+            .append_page(Roff::new("third", SectionId::Custom("3pm".to_string())));
+
+        let json = serde_json::to_string(&roff).unwrap();
+        let deserialized: Roff = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, roff);
+        assert!(json.contains(r#""section":"UserCommands""#));
+        assert!(json.contains(r#""section":"3pm""#));
+        assert_eq!(
+            deserialized.render_to_string().unwrap(),
+            roff.render_to_string().unwrap()
+        );
+
+        assert_eq!(
+            serde_json::to_string(&RoffNode::text("bold".roff().bold())).unwrap(),
+            r#"{"Text":[{"text":"bold","style":"Bold"}]}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&RoffNode::linebreak().with_policy(EscapePolicy::new())).unwrap(),
+            r#"{"node":"Break","escape_policy":{"hyphens":"Minus","quotes":true,"special_chars":true,"punctuation":true,"ascii_only":false,"smart_punctuation":false,"tabs":null}}"#
+        );
+    }
+
+    #[test]
+    fn node_kind_works() {
+        let text = RoffNode::text("some text".roff().bold());
+        assert!(matches!(
+            text.kind(),
+            RoffNodeKind::Text(content) if content == &"some text".roff().bold()
+        ));
+
+        let url = RoffNode::url("docs", "https://example.com");
+        match url.kind() {
+            RoffNodeKind::Url { name, address } => {
+                assert_eq!(name.original(), "docs");
+                assert_eq!(address.original(), "https://example.com");
+            }
+            other => panic!("unexpected kind {:?}", other),
+        }
+
+        let paragraph = RoffNode::indented_paragraph([text.clone(), url], Some(4), Some("tag"));
+        match paragraph.kind() {
+            RoffNodeKind::IndentedParagraph {
+                content,
+                indentation,
+                title,
+            } => {
+                assert_eq!(content, paragraph.children());
+                assert_eq!(indentation, Some(4));
+                assert_eq!(title.unwrap().original(), "tag");
+            }
+            other => panic!("unexpected kind {:?}", other),
+        }
+
+        let opt = SynopsisOpt::new("-v");
+        let synopsis = RoffNode::synopsis("foo", ["does things"], [opt.clone()]);
+        match synopsis.kind() {
+            RoffNodeKind::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                assert_eq!(command.original(), "foo");
+                assert_eq!(text, ["does things".roff()]);
+                assert_eq!(opts, [SynopsisItem::Opt(opt)]);
+            }
+            other => panic!("unexpected kind {:?}", other),
+        }
+
+        let whatis = RoffNode::whatis(["grep", "egrep"], "print lines");
+        assert!(matches!(
+            whatis.kind(),
+            RoffNodeKind::Whatis { names, .. } if names.len() == 2
+        ));
+        let group = RoffNode::group([text.clone(), RoffNode::em_dash()]);
+        assert!(matches!(
+            group.kind(),
+            RoffNodeKind::Group([first, dash])
+                if first == &text && dash.kind() == RoffNodeKind::EmDash
+        ));
+        assert_eq!(
+            RoffNode::comment("a note").kind(),
+            RoffNodeKind::Comment("a note")
+        );
+        assert_eq!(RoffNode::linebreak().kind(), RoffNodeKind::Break);
+        assert_eq!(
+            RoffNode::example(["cargo build"]).kind(),
+            RoffNodeKind::Example(&["cargo build".roff().minus_hyphens()])
+        );
+    }
+
+    #[test]
+    fn group_works() {
+        fn option_entry(name: &str) -> RoffNode {
+            RoffNode::group([
+                RoffNode::tagged_paragraph([format!("sets {}", name)], format!("--{}", name)),
+                RoffNode::text("Since 1.0. "),
+                RoffNode::text("well-known"),
+            ])
+        }
+
+        let policy = EscapePolicy::new().dashes(false);
+        let grouped = Roff::new("test-group", SectionNumber::UserCommands)
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::group([RoffNode::paragraph(["first"]), RoffNode::text(" and")]),
+                    RoffNode::text(" more "),
+                    RoffNode::paragraph([
+                        RoffNode::text("inline "),
+                        RoffNode::group(vec![
+                            RoffNode::text("grouped-text "),
+                            RoffNode::group([RoffNode::em_dash()]),
+                        ]),
+                        RoffNode::text(" continued"),
+                    ]),
+                    RoffNode::group([
+                        RoffNode::text("not-escaped"),
+                        RoffNode::text(" escaped-again").with_policy(EscapePolicy::new()),
+                    ])
+                    .with_policy(policy),
+                ],
+            )
+            .section(
+                "OPTIONS",
+                [
+                    option_entry("color"),
+                    option_entry("width"),
+                    [RoffNode::example(["$ foo --color"]), RoffNode::linebreak()].into_roff(),
+                ],
+            )
+            .section("EMPTY", [RoffNode::group(Vec::<RoffNode>::new())]);
+
+        let inline = Roff::new("test-group", SectionNumber::UserCommands)
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::paragraph(["first"]),
+                    RoffNode::text(" and"),
+                    RoffNode::text(" more "),
+                    RoffNode::paragraph([
+                        RoffNode::text("inline "),
+                        RoffNode::text("grouped-text "),
+                        RoffNode::em_dash(),
+                        RoffNode::text(" continued"),
+                    ]),
+                    RoffNode::text("not-escaped").with_policy(policy),
+                    RoffNode::text(" escaped-again").with_policy(EscapePolicy::new()),
+                ],
+            )
+            .section(
+                "OPTIONS",
+                [
+                    RoffNode::tagged_paragraph(["sets color"], "--color"),
+                    RoffNode::text("Since 1.0. "),
+                    RoffNode::text("well-known"),
+                    RoffNode::tagged_paragraph(["sets width"], "--width"),
+                    RoffNode::text("Since 1.0. "),
+                    RoffNode::text("well-known"),
+                    RoffNode::example(["$ foo --color"]),
+                    RoffNode::linebreak(),
+                ],
+            )
+            .section("EMPTY", Vec::<RoffNode>::new());
+
+        let rendered = grouped.render_to_string().unwrap();
+        assert_eq!(rendered, inline.render_to_string().unwrap());
+        assert_eq!(
+            rendered,
+            r#".TH test\-group 1
+.SH DESCRIPTION
+first and more 
+.P
+inline grouped\-text \(em continuednot-escaped escaped\-again
+.SH OPTIONS
+.TP
+\-\-color
+sets color
+Since 1.0. well\-known
+.TP
+\-\-width
+sets width
+Since 1.0. well\-known
+.EX
+$ foo \-\-color
+.EE
+.SH EMPTY
+"#
+        );
+        assert_eq!(grouped.render_plain(80), inline.render_plain(80));
+        let markdown = |roff: &Roff| {
+            let mut out = vec![];
+            roff.render_markdown(&mut out).unwrap();
+            out
+        };
+        assert_eq!(markdown(&grouped), markdown(&inline));
+        let mdoc = |roff: &Roff| {
+            let mut out = vec![];
+            roff.render_mdoc(&mut out).unwrap();
+            out
+        };
+        assert_eq!(mdoc(&grouped), mdoc(&inline));
+
+        let group = option_entry("color");
+        assert_eq!(group.kind_name(), "group");
+        assert_eq!(group.children().len(), 3);
+        assert_eq!(
+            group.text_content(),
+            "--color sets colorSince 1.0. well-known"
+        );
+    }
+
+    #[test]
+    fn style_ext_works() {
+        assert_eq!("special".bold(), "special".roff().bold());
+        assert_eq!("a-b".italic().as_str(), r"a\-b");
+        assert_eq!("a-b".italic().style(), FontStyle::Italic);
+
+        let owned = String::from("file.txt");
+        assert_eq!(owned.literal(), owned.roff().literal());
+        assert_eq!(owned.code().style(), FontStyle::ConstantWidth);
+
+        assert_eq!(7u8.bold(), "7".roff().bold());
+        assert_eq!(7u8.bold().to_string(), r"\fB7\fR");
+
+        // Inherent methods take precedence on `RoffText` and keep the existing segments.
+        let text = "plain ".roff() + "bold".roff().bold();
+        let italic: RoffText = text.clone().italic();
+        assert_eq!(italic, text.italic());
+        assert_eq!(italic.style(), FontStyle::Italic);
+
+        let rendered = Roff::new("foo", SectionNumber::UserCommands)
+            .section(
+                "NAME",
+                [RoffNode::paragraph(["foo".bold(), " - bar".roff()])],
+            )
+            .render_to_string()
+            .unwrap();
+        assert!(rendered.contains("\\fBfoo\\fR \\- bar"));
+    }
+
+    #[test]
+    fn owned_text_conversions_work() {
+        assert_eq!(RoffText::from("a-b"), "a-b".roff());
+        assert_eq!(RoffText::from(String::from("a-b")), "a-b".roff());
+        assert_eq!(RoffText::from(Cow::Borrowed("a-b")), "a-b".roff());
+        assert_eq!(
+            RoffText::from(Cow::Owned(String::from("a-b"))),
+            "a-b".roff()
+        );
+        assert_eq!(String::from("a-b").into_roff_text().as_str(), r"a\-b");
+
+        // The content of owned strings is moved, not copied.
+        let content = String::from("moved");
+        let ptr = content.as_ptr();
+        assert_eq!(RoffText::from(content).original().as_ptr(), ptr);
+        let content = String::from("moved");
+        let ptr = content.as_ptr();
+        let opt = SynopsisOpt::new("-a").argument(content);
+        assert_eq!(opt.get_argument().unwrap().original().as_ptr(), ptr);
+
+        let bold = "bold".roff().bold();
+        assert_eq!(bold.clone().into_roff_text(), bold);
+        assert_eq!(7u8.into_roff_text(), 7u8.roff());
+
+        let borrowed = Roff::new("foo", SectionNumber::UserCommands)
+            .date("August 2021")
+            .source("foo 1.0")
+            .manual("Foo Manual")
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::text("some-text"),
+                    RoffNode::synopsis("foo", ["does things"], [SynopsisOpt::new("-v")]),
+                ],
+            )
+            .add_section(Section::new("NOTES", ["note"]).subtitle("sub"));
+        let owned = Roff::new(String::from("foo"), SectionNumber::UserCommands)
+            .date(String::from("August 2021"))
+            .source(Cow::Owned::<str>(String::from("foo 1.0")))
+            .manual(RoffText::from("Foo Manual"))
+            .section(
+                String::from("DESCRIPTION"),
+                [
+                    RoffNode::text(String::from("some-text")),
+                    RoffNode::synopsis(
+                        String::from("foo"),
+                        [String::from("does things")],
+                        [SynopsisOpt::new(String::from("-v"))],
+                    ),
+                ],
+            )
+            .add_section(
+                Section::new(String::from("NOTES"), [String::from("note")])
+                    .subtitle(String::from("sub")),
+            );
+        assert_eq!(owned, borrowed);
+        assert_eq!(
+            owned.render_to_string().unwrap(),
+            borrowed.render_to_string().unwrap()
+        );
+    }
 }
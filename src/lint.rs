@@ -0,0 +1,354 @@
+use crate::node::RoffNodeInner;
+use crate::{Diagnostic, NodePath, Roff, RoffNode, RoffText, Severity, StandardSection};
+
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// The kind of a problem found by [`Roff::validate`](Roff::validate).
+pub enum LintKind {
+    /// The document has no `NAME` section.
+    MissingName,
+    /// The `NAME` section is not in the `name \- description` format expected by `whatis`.
+    NameNotWhatis,
+    /// A page in section 1 or 8 has no `SYNOPSIS` section.
+    MissingSynopsis,
+    /// A standard section appears after a section that conventionally follows it.
+    SectionOutOfOrder,
+    /// A section has no content.
+    EmptySection,
+    /// A section title contains lowercase characters.
+    TitleNotUppercase,
+    /// The document has no date.
+    MissingDate,
+    /// Text contains control characters like carriage returns or ANSI escape sequences. They are
+    /// removed from the rendered document.
+    ControlCharacters,
+}
+
+impl LintKind {
+    /// Returns the severity of findings of this kind.
+    pub fn severity(&self) -> Severity {
+        match self {
+            LintKind::MissingName => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A single problem found by [`Roff::validate`](Roff::validate).
+pub struct LintFinding {
+    kind: LintKind,
+    diagnostic: Diagnostic,
+}
+
+impl LintFinding {
+    fn new(kind: LintKind, message: impl Into<String>, path: NodePath) -> Self {
+        Self {
+            kind,
+            diagnostic: Diagnostic::new(kind.severity(), message, path),
+        }
+    }
+
+    /// Returns the kind of this finding.
+    pub fn kind(&self) -> LintKind {
+        self.kind
+    }
+
+    /// Returns the human-readable description of this finding.
+    pub fn message(&self) -> &str {
+        self.diagnostic.message()
+    }
+
+    /// Returns the diagnostic with the severity and location of this finding.
+    pub fn diagnostic(&self) -> &Diagnostic {
+        &self.diagnostic
+    }
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.diagnostic.fmt(f)
+    }
+}
+
+/// Collects the escaped text content of `nodes` including the text nested in paragraphs.
+fn text_content(nodes: &[RoffNode], out: &mut String) {
+    for node in nodes {
+        match node.inner_ref() {
+            RoffNodeInner::Text(text) => out.push_str(text.as_str()),
+            RoffNodeInner::Whatis { names, description } => {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(name.as_str());
+                }
+                out.push_str(" \\- ");
+                out.push_str(description.as_str());
+            }
+            RoffNodeInner::Paragraph(content)
+            | RoffNodeInner::IndentedParagraph { content, .. }
+            | RoffNodeInner::TaggedParagraph { content, .. }
+            | RoffNodeInner::Group(content) => text_content(content, out),
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if any text of `node` or its children contains control characters.
+fn has_control_chars(node: &RoffNode) -> bool {
+    let found = match node.inner_ref() {
+        RoffNodeInner::Text(text) | RoffNodeInner::SubHeading(text) => text.has_control_chars(),
+        RoffNodeInner::Whatis { names, description } => {
+            names.iter().any(RoffText::has_control_chars) || description.has_control_chars()
+        }
+        RoffNodeInner::IndentedParagraph { title, .. } => {
+            title.as_ref().is_some_and(RoffText::has_control_chars)
+        }
+        RoffNodeInner::TaggedParagraph { title, .. } => title.has_control_chars(),
+        RoffNodeInner::Example(lines) => lines.iter().any(RoffText::has_control_chars),
+        RoffNodeInner::Columns { rows, .. } => rows
+            .iter()
+            .any(|(key, value)| key.has_control_chars() || value.has_control_chars()),
+        RoffNodeInner::Synopsis { command, text, .. } => {
+            command.has_control_chars() || text.iter().any(RoffText::has_control_chars)
+        }
+        RoffNodeInner::Url { address, name } | RoffNodeInner::Email { address, name } => {
+            address.has_control_chars() || name.has_control_chars()
+        }
+        _ => false,
+    };
+    found || node.children().iter().any(has_control_chars)
+}
+
+impl Roff {
+    /// Checks this document against the conventions described in
+    /// [`man-pages(7)`](https://man7.org/linux/man-pages/man7/man-pages.7.html) and returns all of
+    /// the problems found. An empty list means that the document follows the conventions. The
+    /// document can be rendered regardless of the findings.
+    pub fn validate(&self) -> Vec<LintFinding> {
+        let mut findings = vec![];
+
+        if self.date.is_none() {
+            findings.push(LintFinding::new(
+                LintKind::MissingDate,
+                "the document has no date",
+                NodePath::document(),
+            ));
+        }
+
+        let name = self
+            .sections
+            .iter()
+            .enumerate()
+            .find(|(_, section)| section.standard() == Some(StandardSection::Name));
+        match name {
+            Some((i, name)) => {
+                let mut content = String::new();
+                text_content(name.get_nodes(), &mut content);
+                if !content.contains(" \\- ") {
+                    findings.push(LintFinding::new(
+                        LintKind::NameNotWhatis,
+                        format!(
+                            "the NAME section should be in the `name \\- description` format, found `{}`",
+                            content
+                        ),
+                        NodePath::section(i, name.get_title().as_str()),
+                    ));
+                }
+            }
+            None => findings.push(LintFinding::new(
+                LintKind::MissingName,
+                "the document has no NAME section",
+                NodePath::document(),
+            )),
+        }
+
+        let needs_synopsis = matches!(self.section.number(), Some(1 | 8));
+        if needs_synopsis
+            && !self
+                .sections
+                .iter()
+                .any(|section| section.standard() == Some(StandardSection::Synopsis))
+        {
+            findings.push(LintFinding::new(
+                LintKind::MissingSynopsis,
+                format!(
+                    "pages in section {} should have a SYNOPSIS section",
+                    self.section
+                ),
+                NodePath::document(),
+            ));
+        }
+
+        let mut previous: Option<StandardSection> = None;
+        for (i, section) in self.sections.iter().enumerate() {
+            let title = section.get_title().as_str();
+            let path = NodePath::section(i, title);
+            if section.get_nodes().is_empty() {
+                findings.push(LintFinding::new(
+                    LintKind::EmptySection,
+                    format!("the section `{}` is empty", title),
+                    path.clone(),
+                ));
+            }
+            if title.chars().any(char::is_lowercase) {
+                findings.push(LintFinding::new(
+                    LintKind::TitleNotUppercase,
+                    format!("the section title `{}` should be uppercase", title),
+                    path.clone(),
+                ));
+            }
+            for (j, node) in section.get_nodes().iter().enumerate() {
+                if has_control_chars(node) {
+                    findings.push(LintFinding::new(
+                        LintKind::ControlCharacters,
+                        "the text contains control characters that will be removed",
+                        path.clone().node(j),
+                    ));
+                }
+            }
+            if let Some(standard) = section.standard() {
+                match previous {
+                    Some(previous) if previous.order_index() > standard.order_index() => {
+                        findings.push(LintFinding::new(
+                            LintKind::SectionOutOfOrder,
+                            format!(
+                                "the section `{}` should come before the section `{}`",
+                                standard.title(),
+                                previous.title()
+                            ),
+                            path,
+                        ));
+                    }
+                    _ => previous = Some(standard),
+                }
+            }
+        }
+
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SectionNumber, SynopsisOpt};
+
+    fn kinds(roff: &Roff) -> Vec<LintKind> {
+        roff.validate().iter().map(LintFinding::kind).collect()
+    }
+
+    #[test]
+    fn valid_document_has_no_findings() {
+        let roff = Roff::new("ls", SectionNumber::UserCommands)
+            .date("2021-08-01")
+            .section(StandardSection::Name, ["ls - list directory contents"])
+            .section(
+                StandardSection::Synopsis,
+                [RoffNode::synopsis(
+                    "ls",
+                    None::<&str>,
+                    [SynopsisOpt::new("-l")],
+                )],
+            )
+            .section(
+                StandardSection::Description,
+                [RoffNode::paragraph(["List information about the FILEs."])],
+            );
+
+        assert!(roff.validate().is_empty());
+    }
+
+    #[test]
+    fn finds_missing_sections_and_date() {
+        let roff = Roff::new("ls", SectionNumber::SystemManagementCommands)
+            .section(StandardSection::Description, ["description"]);
+
+        assert_eq!(
+            kinds(&roff),
+            vec![
+                LintKind::MissingDate,
+                LintKind::MissingName,
+                LintKind::MissingSynopsis
+            ]
+        );
+
+        let roff = Roff::new("ls", SectionNumber::Miscellaneous).date("2021-08-01");
+        assert_eq!(kinds(&roff), vec![LintKind::MissingName]);
+    }
+
+    #[test]
+    fn finds_name_not_in_whatis_format() {
+        let roff = Roff::new("ls", SectionNumber::Miscellaneous)
+            .date("2021-08-01")
+            .section(
+                StandardSection::Name,
+                [RoffNode::paragraph(["ls lists directory contents"])],
+            );
+
+        let findings = roff.validate();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind(), LintKind::NameNotWhatis);
+        assert_eq!(
+            findings[0].message(),
+            "the NAME section should be in the `name \\- description` format, found `ls lists directory contents`"
+        );
+    }
+
+    #[test]
+    fn finds_section_problems() {
+        let roff = Roff::new("ls", SectionNumber::Miscellaneous)
+            .date("2021-08-01")
+            .section(StandardSection::Name, ["ls - list directory contents"])
+            .section(StandardSection::SeeAlso, ["dir(1)"])
+            .section("Custom", ["custom"])
+            .section(StandardSection::Description, None::<&str>);
+
+        let findings = roff.validate();
+        assert_eq!(
+            findings.iter().map(LintFinding::kind).collect::<Vec<_>>(),
+            vec![
+                LintKind::TitleNotUppercase,
+                LintKind::EmptySection,
+                LintKind::SectionOutOfOrder
+            ]
+        );
+        assert_eq!(
+            findings[2].message(),
+            "the section `DESCRIPTION` should come before the section `SEE ALSO`"
+        );
+        assert_eq!(
+            findings[2].to_string(),
+            "warning: the section `DESCRIPTION` should come before the section `SEE ALSO` at sections[3] (DESCRIPTION)"
+        );
+        assert_eq!(findings[0].diagnostic().path().section_index(), Some(2));
+    }
+
+    #[test]
+    fn finds_control_characters() {
+        let roff = Roff::new("ls", SectionNumber::Miscellaneous)
+            .date("2021-08-01")
+            .section(StandardSection::Name, ["ls - list directory contents"])
+            .section(
+                StandardSection::Description,
+                [
+                    RoffNode::text("clean"),
+                    RoffNode::paragraph(["from a database\r\n", "\u{1b}[1mbold"]),
+                ],
+            );
+
+        let findings = roff.validate();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind(), LintKind::ControlCharacters);
+        assert_eq!(
+            findings[0].diagnostic().path().to_string(),
+            "sections[1] (DESCRIPTION).nodes[1]"
+        );
+        assert!(roff
+            .render_to_string()
+            .unwrap()
+            .ends_with(".P\nfrom a database\n[1mbold"));
+    }
+}
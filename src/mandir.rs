@@ -0,0 +1,251 @@
+use crate::file::create_parent_dirs;
+use crate::{Roff, RoffError, RoffText, SectionId};
+
+use std::path::{Path, PathBuf};
+
+impl Roff {
+    /// Returns the name of the file this page is installed as, like `grep.1`. The name is the
+    /// title with any escapes of raw text reversed, lowercased and followed by the section number.
+    pub fn file_name(&self) -> String {
+        page_file_name(&self.title, &self.section)
+    }
+}
+
+/// Returns the name of the file a page called `name` is installed as in `section`, see
+/// [`Roff::file_name`](Roff::file_name).
+pub(crate) fn page_file_name(name: &RoffText, section: &SectionId) -> String {
+    format!("{}.{}", name.unescaped().to_lowercase(), section)
+}
+
+#[derive(Debug)]
+/// Writes pages into a man directory tree, like `share/man`, placing each page in the `man<N>`
+/// subdirectory of its section under the name returned by [`Roff::file_name`](Roff::file_name).
+///
+/// ```no_run
+/// use roffman::{ManDirWriter, Roff, SectionNumber};
+///
+/// let mut writer = ManDirWriter::new("target/man");
+/// writer.add(&Roff::new("foo", SectionNumber::UserCommands))?;
+/// writer.add(&Roff::new("foo.conf", SectionNumber::FileFormatsAndConfigurationFiles))?;
+/// for path in writer.paths() {
+///     println!("cargo:warning=generated {}", path.display());
+/// }
+/// # Ok::<(), roffman::RoffError>(())
+/// ```
+pub struct ManDirWriter {
+    root: PathBuf,
+    gzip: bool,
+    paths: Vec<PathBuf>,
+}
+
+impl ManDirWriter {
+    /// Creates a writer placing pages under `root`. The directories are created as pages are added.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            gzip: false,
+            paths: vec![],
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    /// Builder method for compressing the written pages with gzip, adding `.gz` to their names.
+    /// Disabled by default.
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Renders `roff` to `<root>/man<N>/<file name>` and returns the path of the written file.
+    ///
+    /// Returns [`RoffError::InvalidFileName`](RoffError::InvalidFileName) if the title can't be
+    /// used as a file name and [`RoffError::DuplicatePage`](RoffError::DuplicatePage) if a page
+    /// with the same name and section was already added to this writer.
+    pub fn add(&mut self, roff: &Roff) -> Result<PathBuf, RoffError> {
+        let mut name = roff.file_name();
+        let title = roff.title.unescaped();
+        if title.is_empty() || title.contains(&['/', '\\'][..]) {
+            return Err(RoffError::InvalidFileName(name));
+        }
+        if self.gzip {
+            name.push_str(".gz");
+        }
+
+        let path = self.root.join(roff.section.directory_name()).join(name);
+        if self.paths.contains(&path) {
+            return Err(RoffError::DuplicatePage(path));
+        }
+
+        create_parent_dirs(&path)?;
+        self.write(roff, &path)?;
+        self.paths.push(path.clone());
+        Ok(path)
+    }
+
+    #[cfg(feature = "gzip")]
+    fn write(&self, roff: &Roff, path: &Path) -> Result<(), RoffError> {
+        match self.gzip {
+            true => roff.write_to_file_gz(path),
+            false => roff.write_to_file(path),
+        }
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn write(&self, roff: &Roff, path: &Path) -> Result<(), RoffError> {
+        roff.write_to_file(path)
+    }
+
+    /// Returns the paths of all pages written so far, in the order they were added.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Consumes the writer returning the paths of all written pages.
+    pub fn into_paths(self) -> Vec<PathBuf> {
+        self.paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ManDirWriter;
+    use crate::{Roff, RoffError, RoffText, SectionId, SectionNumber};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn it_writes_pages_into_section_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("share").join("man");
+        let foo = Roff::new("Foo", SectionNumber::UserCommands).section("NAME", ["foo"]);
+        let conf = Roff::new("foo.conf", SectionNumber::FileFormatsAndConfigurationFiles);
+        let raw = Roff::new(RoffText::raw("foo\\-ctl"), SectionNumber::Custom(9));
+
+        let mut writer = ManDirWriter::new(&root);
+        assert_eq!(writer.add(&foo).unwrap(), root.join("man1").join("foo.1"));
+        writer.add(&conf).unwrap();
+        writer.add(&raw).unwrap();
+
+        assert_eq!(
+            writer.into_paths(),
+            vec![
+                root.join("man1").join("foo.1"),
+                root.join("man5").join("foo.conf.5"),
+                root.join("man9").join("foo-ctl.9"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("man1").join("foo.1")).unwrap(),
+            foo.render_to_string().unwrap()
+        );
+        let mut sections: Vec<_> = fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        sections.sort();
+        assert_eq!(sections, ["man1", "man5", "man9"]);
+    }
+
+    #[test]
+    fn it_writes_string_sections_into_numbered_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ManDirWriter::new(dir.path());
+        let perl = Roff::new("Foo::Bar", SectionId::Custom("3pm".to_string()));
+        let tcl = Roff::new("foo", SectionId::Custom("n".to_string()));
+
+        assert_eq!(
+            writer.add(&perl).unwrap(),
+            dir.path().join("man3").join("foo::bar.3pm")
+        );
+        assert_eq!(
+            writer.add(&tcl).unwrap(),
+            dir.path().join("mann").join("foo.n")
+        );
+    }
+
+    #[test]
+    fn alias_pages_link_to_installed_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ManDirWriter::new(dir.path());
+        let pages = [
+            Roff::new("Grep", SectionNumber::UserCommands).aliases(["EGrep", "fgrep"]),
+            Roff::new(
+                RoffText::raw("foo\\-ctl"),
+                SectionNumber::SystemManagementCommands,
+            )
+            .aliases([RoffText::raw("foo\\-admin")]),
+            Roff::new("Foo::Bar", SectionId::Custom("3pm".to_string())).aliases(["Foo"]),
+        ];
+
+        let mut links = vec![];
+        for roff in &pages {
+            let path = writer.add(roff).unwrap();
+            for (name, content) in roff.alias_pages() {
+                let link = path.with_file_name(name);
+                fs::write(&link, &content).unwrap();
+                links.push((link, content));
+            }
+        }
+
+        let names: Vec<_> = links
+            .iter()
+            .map(|(link, _)| link.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                Path::new("man1/egrep.1"),
+                Path::new("man1/fgrep.1"),
+                Path::new("man8/foo-admin.8"),
+                Path::new("man3/foo.3pm"),
+            ]
+        );
+        for (link, content) in links {
+            let target = content.strip_prefix(".so ").unwrap().trim_end();
+            assert!(
+                dir.path().join(target).is_file(),
+                "{} links to the missing page {}",
+                link.display(),
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn it_rejects_duplicate_and_invalid_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = ManDirWriter::new(dir.path());
+        writer
+            .add(&Roff::new("foo", SectionNumber::UserCommands))
+            .unwrap();
+        writer
+            .add(&Roff::new("foo", SectionNumber::Miscellaneous))
+            .unwrap();
+
+        match writer.add(&Roff::new("FOO", SectionNumber::UserCommands)) {
+            Err(RoffError::DuplicatePage(path)) => {
+                assert_eq!(path, dir.path().join("man1").join("foo.1"))
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        match writer.add(&Roff::new("../foo", SectionNumber::UserCommands)) {
+            Err(RoffError::InvalidFileName(name)) => assert_eq!(name, "../foo.1"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(writer.paths().len(), 2);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn it_writes_compressed_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let roff = Roff::new("foo", SectionNumber::UserCommands);
+        let mut writer = ManDirWriter::new(dir.path()).gzip(true);
+
+        let path = writer.add(&roff).unwrap();
+        assert_eq!(path, dir.path().join("man1").join("foo.1.gz"));
+        let mut compressed = vec![];
+        roff.render_gz(&mut compressed).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), compressed);
+    }
+}
@@ -0,0 +1,549 @@
+use crate::node::{ungrouped, RoffNodeInner};
+use crate::render::RenderOptions;
+use crate::synopsis::{SynopsisItem, SynopsisOpt};
+use crate::{FontStyle, Roff, RoffError, RoffNode, RoffText};
+
+use std::io::Write;
+
+impl Roff {
+    /// Renders this roff as a CommonMark document to the given writer, so that the same content
+    /// can be published as a man page and on a documentation site.
+    ///
+    /// Sections and sub headings become `##` and `###` headings, examples fenced code blocks, bold
+    /// and italic text `**bold**` and `*italic*`, literal text code spans and URLs and emails
+    /// `[name](address)` links. Tagged paragraphs and options are approximated with definition
+    /// lists: the bold term on its own line followed by the body indented after `:`. Characters
+    /// with a special meaning in Markdown are escaped and comments are skipped.
+    pub fn render_markdown<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        let mut markdown = MarkdownWriter::new(&self.options);
+        self.write_markdown(&mut markdown);
+        for page in &self.pages {
+            page.write_markdown(&mut markdown);
+        }
+        writer.write_all(markdown.finish().as_bytes())?;
+        Ok(())
+    }
+
+    fn write_markdown(&self, markdown: &mut MarkdownWriter<'_>) {
+        markdown.push_block(format!("# {}", escape_markdown(&self.display_title())));
+        for section in &self.sections {
+            markdown.heading("##", section.get_title());
+            if let Some(subtitle) = section.get_subtitle() {
+                markdown.heading("###", subtitle);
+            }
+            markdown.nodes(section.get_nodes());
+            markdown.finish_text();
+        }
+    }
+}
+
+/// Escapes the characters that have a special meaning anywhere in Markdown text.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>') {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// Escapes the characters that start a block like a heading, a list or a quote when `text` is at
+/// the start of a line.
+fn escape_line_start(text: &str) -> String {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let marker = &text[digits..];
+    if (digits > 0 && (marker.starts_with('.') || marker.starts_with(')')))
+        || (digits == 0 && text.starts_with(['#', '-', '+', '=', '|', '~']))
+    {
+        let (start, rest) = text.split_at(digits);
+        format!("{}\\{}", start, rest)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Returns the longest run of backticks in `text` plus one, the length of a fence that can
+/// enclose it.
+fn fence_len(text: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    longest + 1
+}
+
+/// Returns `text` marked up with `style`. Surrounding whitespace is kept outside of the markup,
+/// where emphasis can't start or end.
+fn styled(text: &str, style: FontStyle) -> String {
+    let core = text.trim_matches(' ');
+    if core.is_empty() {
+        return text.to_string();
+    }
+    let start = text.len() - text.trim_start_matches(' ').len();
+    let (before, after) = (&text[..start], &text[start + core.len()..]);
+    let core = match style {
+        FontStyle::Roman => escape_markdown(core),
+        FontStyle::Bold => format!("**{}**", escape_markdown(core)),
+        FontStyle::Italic => format!("*{}*", escape_markdown(core)),
+        FontStyle::ConstantWidth | FontStyle::Literal => {
+            let fence = "`".repeat(fence_len(core));
+            let padding = if core.starts_with('`') || core.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            format!("{}{}{}{}{}", fence, padding, core, padding, fence)
+        }
+    };
+    format!("{}{}{}", before, core, after)
+}
+
+/// Returns the destination of a link to `address`, enclosed in `<>` if it contains characters
+/// that would end it.
+fn link_destination(address: &str) -> String {
+    if address.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", address.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        address.to_string()
+    }
+}
+
+/// Lays out the nodes of a document as Markdown blocks.
+struct MarkdownWriter<'o> {
+    options: &'o RenderOptions,
+    out: String,
+    /// Inline Markdown of the current paragraph.
+    text: String,
+    /// Whether the next block follows the previous one on the next line instead of after a blank
+    /// line.
+    attached: bool,
+    /// Set in the body of a definition, `true` until its first block is written.
+    definition: Option<bool>,
+    /// Whether the next text starts a new input line, where an empty line is a paragraph break.
+    line_start: bool,
+}
+
+impl<'o> MarkdownWriter<'o> {
+    fn new(options: &'o RenderOptions) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            text: String::new(),
+            attached: false,
+            definition: None,
+            line_start: true,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.finish_text();
+        self.out.push('\n');
+        self.out
+    }
+
+    /// Adds a complete `block`. The lines of a definition body are indented and the first one
+    /// starts with `:`.
+    fn push_block(&mut self, block: String) {
+        if !self.out.is_empty() {
+            self.out.push_str(if std::mem::take(&mut self.attached) {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        match self.definition {
+            Some(first) => {
+                for (i, line) in block.split('\n').enumerate() {
+                    if i > 0 {
+                        self.out.push('\n');
+                    }
+                    if first && i == 0 {
+                        self.out.push_str(":   ");
+                    } else if !line.is_empty() {
+                        self.out.push_str("    ");
+                    }
+                    self.out.push_str(line);
+                }
+                self.definition = Some(false);
+            }
+            None => self.out.push_str(&block),
+        }
+    }
+
+    /// Ends the current paragraph.
+    fn finish_text(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim_end();
+        if !text.is_empty() {
+            self.push_block(text.to_string());
+        }
+        self.line_start = true;
+    }
+
+    /// Appends `text` in the given `style` to the current paragraph. A line break is a space
+    /// unless it makes an empty input line, which ends the paragraph.
+    fn inline(&mut self, text: &str, style: FontStyle) {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                if self.line_start {
+                    self.finish_text();
+                }
+                self.line_start = true;
+            }
+            if part.is_empty() {
+                continue;
+            }
+            if self.text.is_empty() {
+                let part = part.trim_start_matches(' ');
+                if part.is_empty() {
+                    continue;
+                }
+                self.text.push_str(&escape_line_start(&styled(part, style)));
+            } else {
+                if self.line_start {
+                    self.text.push(' ');
+                }
+                self.text.push_str(&styled(part, style));
+            }
+            self.line_start = false;
+        }
+    }
+
+    fn text(&mut self, text: &RoffText) {
+        for (content, style) in text.unescaped_segments() {
+            self.inline(&content, style);
+        }
+    }
+
+    /// Returns the inline Markdown of `text` without adding it to the paragraph.
+    fn inline_markdown(text: &RoffText) -> String {
+        text.unescaped_segments()
+            .map(|(content, style)| styled(&content.replace('\n', " "), style))
+            .collect()
+    }
+
+    /// Appends a hard line break to the current paragraph.
+    fn line_break(&mut self) {
+        if !self.text.is_empty() {
+            self.text.push_str("\\\n");
+        }
+        self.line_start = false;
+    }
+
+    fn heading(&mut self, level: &str, title: &RoffText) {
+        self.finish_text();
+        self.definition = None;
+        self.push_block(format!(
+            "{} {}",
+            level,
+            escape_markdown(&title.unescaped().replace('\n', " "))
+        ));
+    }
+
+    /// Writes a definition with the bold `term` followed by the body added by `body`.
+    fn definition<F>(&mut self, term: String, body: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.finish_text();
+        let outer = self.definition.take();
+        self.push_block(term);
+        self.attached = true;
+        self.definition = Some(true);
+        body(self);
+        self.finish_text();
+        if self.definition == Some(true) {
+            self.attached = false;
+        }
+        self.definition = outer;
+    }
+
+    fn nodes(&mut self, nodes: &[RoffNode]) {
+        for node in ungrouped(nodes).iter() {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &RoffNode) {
+        match node.inner_ref() {
+            RoffNodeInner::Text(text) => self.text(text),
+            RoffNodeInner::SubHeading(title) => self.heading("###", title),
+            RoffNodeInner::Whatis { names, description } => {
+                self.text(&RoffText::join(names.iter().cloned(), ", "));
+                self.inline(" - ", FontStyle::Roman);
+                self.text(description);
+            }
+            RoffNodeInner::Paragraph(content) => {
+                self.finish_text();
+                self.nodes(content);
+            }
+            RoffNodeInner::IndentedParagraph {
+                content,
+                indentation: Some(_),
+                title: Some(title),
+            }
+            | RoffNodeInner::TaggedParagraph { content, title } => {
+                let term = format!("**{}**", escape_markdown(&title.unescaped()));
+                self.definition(term, |markdown| markdown.nodes(content));
+            }
+            RoffNodeInner::IndentedParagraph { content, .. } => {
+                self.finish_text();
+                self.nodes(content);
+                self.finish_text();
+            }
+            RoffNodeInner::Example(content) => {
+                self.finish_text();
+                let content: String = content.iter().map(RoffText::unescaped).collect();
+                let fence = "`".repeat(fence_len(&content).max(3));
+                self.push_block(format!("{}\n{}\n{}", fence, content, fence));
+            }
+            RoffNodeInner::Columns { rows, .. } => {
+                self.finish_text();
+                for (i, (key, value)) in rows.iter().enumerate() {
+                    if i > 0 {
+                        self.line_break();
+                    }
+                    self.text(key);
+                    self.inline(" ", FontStyle::Roman);
+                    self.text(value);
+                }
+                self.finish_text();
+            }
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                self.finish_text();
+                self.inline(&command.unescaped(), FontStyle::Bold);
+                self.line_start = true;
+                for elem in text {
+                    self.text(elem);
+                }
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.synopsis_opt(opt),
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.line_break();
+                            self.inline(&group.title.unescaped(), FontStyle::Bold);
+                            for opt in &group.opts {
+                                self.synopsis_opt(opt);
+                            }
+                        }
+                    }
+                }
+                self.finish_text();
+            }
+            RoffNodeInner::OptionList(opts) => {
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.option(opt),
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.heading("###", &group.title);
+                            for opt in &group.opts {
+                                self.option(opt);
+                            }
+                        }
+                    }
+                }
+            }
+            RoffNodeInner::Url { name, address } => self.link("", name, address),
+            RoffNodeInner::Email { name, address } => self.link("mailto:", name, address),
+            RoffNodeInner::RegisteredSign => self.inline("®", FontStyle::Roman),
+            RoffNodeInner::LeftQuote => self.inline("“", FontStyle::Roman),
+            RoffNodeInner::RightQuote => self.inline("”", FontStyle::Roman),
+            RoffNodeInner::TrademarkSign => self.inline("™", FontStyle::Roman),
+            RoffNodeInner::EmDash => self.inline("—", FontStyle::Roman),
+            RoffNodeInner::EnDash => self.inline("–", FontStyle::Roman),
+            RoffNodeInner::NonBreakingSpace => self.inline("\u{A0}", FontStyle::Roman),
+            RoffNodeInner::Nested { nodes, .. } => {
+                self.finish_text();
+                self.nodes(nodes);
+                self.finish_text();
+            }
+            RoffNodeInner::Group(nodes) => self.nodes(nodes),
+            RoffNodeInner::Break => self.line_break(),
+            RoffNodeInner::Comment(_) => {}
+        }
+    }
+
+    /// Appends a link to the `address` with the `name` as its text. An autolink is used if the
+    /// name is empty.
+    fn link(&mut self, scheme: &str, name: &RoffText, address: &RoffText) {
+        let address = address.unescaped().replace('\n', "");
+        let link = if name.is_empty() {
+            format!("<{}{}>", scheme, address.replace(['<', '>', ' '], ""))
+        } else {
+            format!(
+                "[{}]({})",
+                Self::inline_markdown(name),
+                link_destination(&format!("{}{}", scheme, address))
+            )
+        };
+        if !self.text.is_empty() {
+            self.text.push(' ');
+        }
+        self.text.push_str(&link);
+        self.line_start = true;
+    }
+
+    /// Returns the name and the argument of `opt` in bold and italic.
+    fn opt_term(opt: &SynopsisOpt) -> String {
+        let mut term = styled(&opt.name.unescaped(), FontStyle::Bold);
+        if opt.inline_values && !opt.possible_values.is_empty() {
+            let values: Vec<_> = opt
+                .possible_values
+                .iter()
+                .map(|value| styled(&value.unescaped(), FontStyle::Italic))
+                .collect();
+            term.push(' ');
+            term.push_str(&values.join("|"));
+        } else if let Some(argument) = &opt.argument {
+            term.push(' ');
+            term.push_str(&styled(&argument.unescaped(), FontStyle::Italic));
+        }
+        term
+    }
+
+    /// Appends the description of `opt` followed by the possible values and the default value.
+    fn opt_description(&mut self, opt: &SynopsisOpt) {
+        let mut was_text = false;
+        if let Some(description) = &opt.description {
+            for elem in description {
+                self.text(elem);
+                was_text = was_text || !elem.is_empty();
+            }
+        }
+        if !opt.possible_values.is_empty() {
+            if was_text {
+                self.inline(" ", FontStyle::Roman);
+            }
+            self.inline("Possible values: ", FontStyle::Roman);
+            for (i, value) in opt.possible_values.iter().enumerate() {
+                if i > 0 {
+                    self.inline(", ", FontStyle::Roman);
+                }
+                self.inline(&value.unescaped(), FontStyle::Bold);
+            }
+            was_text = true;
+        }
+        if let Some(default_value) = &opt.default_value {
+            if was_text {
+                self.inline(" ", FontStyle::Roman);
+            }
+            self.inline("(default: ", FontStyle::Roman);
+            self.text(default_value);
+            self.inline(")", FontStyle::Roman);
+        }
+    }
+
+    /// Appends a single option of a synopsis on its own line as `[name argument] description`.
+    fn synopsis_opt(&mut self, opt: &SynopsisOpt) {
+        if !opt.is_visible(self.options) {
+            return;
+        }
+        self.line_break();
+        self.text
+            .push_str(&format!("\\[{}\\]", Self::opt_term(opt)));
+        self.line_start = true;
+        self.opt_description(opt);
+    }
+
+    /// Writes a single option of an options list as a definition.
+    fn option(&mut self, opt: &SynopsisOpt) {
+        if !opt.is_visible(self.options) {
+            return;
+        }
+        self.definition(Self::opt_term(opt), |markdown| {
+            markdown.opt_description(opt)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_line_start, escape_markdown, styled};
+    use crate::tests::readme_example;
+    use crate::{FontStyle, Roff, RoffNode, RoffText, Roffable, SectionNumber, SynopsisOpt};
+
+    fn render(roff: &Roff) -> String {
+        let mut out = vec![];
+        roff.render_markdown(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_renders_the_readme_example() {
+        assert_eq!(
+            render(&readme_example()),
+            include_str!("../tests/golden/readme.md")
+        );
+    }
+
+    #[test]
+    fn it_escapes_markdown() {
+        assert_eq!(
+            escape_markdown("a*b_c`d[e]f\\"),
+            "a\\*b\\_c\\`d\\[e\\]f\\\\"
+        );
+        assert_eq!(escape_line_start("# not a heading"), "\\# not a heading");
+        assert_eq!(escape_line_start("1. not a list"), "1\\. not a list");
+        assert_eq!(escape_line_start("a # b"), "a # b");
+        assert_eq!(styled(" it ", FontStyle::Italic), " *it* ");
+        assert_eq!(styled("a `b`", FontStyle::Literal), "`` a `b` ``");
+    }
+
+    #[test]
+    fn it_renders_definitions() {
+        let roff = Roff::new("test", SectionNumber::UserCommands).section(
+            "OPTIONS",
+            [
+                RoffNode::tagged_paragraph(
+                    [RoffNode::text("first"), RoffNode::text("\n\nsecond")],
+                    "-a",
+                ),
+                RoffNode::option_list([SynopsisOpt::new("--color")
+                    .argument("WHEN")
+                    .possible_values(["auto", "never"])]),
+                RoffNode::paragraph([
+                    "Use ".roff(),
+                    RoffText::new("*glob*", None).literal(),
+                    " - see".roff(),
+                ]),
+                RoffNode::url("", "https://example.com"),
+                RoffNode::email("Me", "me@example.com"),
+            ],
+        );
+        assert_eq!(
+            render(&roff),
+            r#"# test(1)
+
+## OPTIONS
+
+**-a**
+:   first
+
+    second
+
+**--color** *WHEN*
+:   Possible values: **auto**, **never**
+
+Use `*glob*` - see <https://example.com> [Me](mailto:me@example.com)
+"#
+        );
+    }
+}
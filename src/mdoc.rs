@@ -0,0 +1,889 @@
+use crate::node::{ungrouped, RoffNodeInner};
+use crate::render::RenderOptions;
+use crate::synopsis::{SynopsisItem, SynopsisOpt};
+use crate::{escape_with_policy, FontStyle, Roff, RoffError, RoffNode, RoffText, Roffable};
+
+use std::io::Write;
+
+/// Callable mdoc macros. An argument of a parsed macro that is one of these names is preceded
+/// with `\&` so that it is displayed instead of being called.
+const CALLABLE_MACROS: &[&str] = &[
+    "Ac", "Ad", "An", "Ao", "Ap", "Aq", "Ar", "At", "Bc", "Bo", "Bq", "Brc", "Bro", "Brq", "Bsx",
+    "Bx", "Cd", "Cm", "Dc", "Do", "Dq", "Dv", "Dx", "Ec", "Em", "En", "Eo", "Er", "Ev", "Fa", "Fc",
+    "Fl", "Fn", "Fo", "Fr", "Ft", "Fx", "Ic", "Li", "Lk", "Ms", "Mt", "Nm", "No", "Ns", "Nx", "Oc",
+    "Oo", "Op", "Ox", "Pa", "Pc", "Pf", "Po", "Pq", "Qc", "Ql", "Qo", "Qq", "Sc", "So", "Sq", "St",
+    "Sx", "Sy", "Ta", "Tn", "Ux", "Va", "Vt", "Xc", "Xo", "Xr",
+];
+
+/// Returns `true` if `word` consists of the delimiters that mdoc macros attach to the preceding
+/// argument without a space, like the punctuation ending a sentence.
+fn is_delimiter(word: &str) -> bool {
+    !word.is_empty() && word.chars().all(|ch| ".,;:?!)]".contains(ch))
+}
+
+/// Splits a cross reference like `grep(1),` into the name, the section and the trailing
+/// delimiters.
+fn split_xref(word: &str) -> Option<(&str, &str, &str)> {
+    let open = word.find('(')?;
+    let close = open + word[open..].find(')')?;
+    let (name, section, trailing) = (&word[..open], &word[open + 1..close], &word[close + 1..]);
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || "._-+:".contains(ch));
+    let valid_section = section.starts_with(|ch: char| ch.is_ascii_digit())
+        && section.chars().all(char::is_alphanumeric);
+    if valid_name && valid_section && (trailing.is_empty() || is_delimiter(trailing)) {
+        Some((name, section, trailing))
+    } else {
+        None
+    }
+}
+
+impl Roff {
+    /// Renders this roff with the semantic `mdoc` macros preferred on BSD systems instead of the
+    /// `man` macros to the given writer.
+    ///
+    /// The title header becomes the `.Dd`, `.Dt` and `.Os` prologue, sections and sub headings
+    /// `.Sh` and `.Ss`, the names in a `whatis` node `.Nm` and `.Nd`, synopsis blocks `.Nm` with
+    /// `.Op` options, tagged paragraphs and options `.Bl -tag` lists, examples literal `.Bd`
+    /// displays, URLs `.Lk` and emails `.Mt` links. Bold, italic and literal text becomes `.Sy`,
+    /// `.Em` and `.Li` and references to other pages like `grep(1)` become `.Xr` macros.
+    pub fn render_mdoc<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+        let mut mdoc = MdocWriter::new(writer, &self.options);
+        self.write_mdoc(&mut mdoc)?;
+        for page in &self.pages {
+            page.write_mdoc(&mut mdoc)?;
+        }
+        mdoc.flush()
+    }
+
+    fn write_mdoc<W: Write>(&self, mdoc: &mut MdocWriter<'_, W>) -> Result<(), RoffError> {
+        mdoc.flush()?;
+        for comment in self.generated_by.iter().chain(&self.preamble) {
+            mdoc.comment(comment)?;
+        }
+        let date = match &self.date {
+            Some(date) => date.unescaped().replace('\n', " "),
+            None => "$Mdocdate$".to_string(),
+        };
+        mdoc.macro_line("Dd", date.split_whitespace())?;
+        let title = self.title.unescaped().to_uppercase();
+        let section = self.section.roff().unescaped().into_owned();
+        mdoc.macro_line("Dt", [title.as_str(), section.as_str()])?;
+        let source = self
+            .source
+            .as_ref()
+            .map(|source| source.unescaped().replace('\n', " "))
+            .unwrap_or_default();
+        mdoc.macro_line("Os", source.split_whitespace())?;
+
+        for section in &self.sections {
+            mdoc.heading("Sh", section.get_title())?;
+            if let Some(subtitle) = section.get_subtitle() {
+                mdoc.heading("Ss", subtitle)?;
+            }
+            mdoc.nodes(section.get_nodes())?;
+            mdoc.close_list()?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the nodes of a document as mdoc macros.
+struct MdocWriter<'w, W: Write> {
+    writer: &'w mut W,
+    options: &'w RenderOptions,
+    /// The text line being written.
+    line: String,
+    /// A macro line that following delimiters can still be attached to.
+    pending: Option<String>,
+    /// Whether a paragraph break is written before the next text line.
+    paragraph: bool,
+    /// Whether a `.Bl -tag` list is open for consecutive tagged paragraphs.
+    list: bool,
+    /// Whether nothing was written since the start of a section or a block, where a paragraph
+    /// break would be redundant.
+    fresh: bool,
+    /// Whether the next text is separated from the previous one by whitespace.
+    space: bool,
+    /// Whether the next text starts a new input line, where an empty line is a paragraph break.
+    line_start: bool,
+}
+
+impl<'w, W: Write> MdocWriter<'w, W> {
+    fn new(writer: &'w mut W, options: &'w RenderOptions) -> Self {
+        Self {
+            writer,
+            options,
+            line: String::new(),
+            pending: None,
+            paragraph: false,
+            list: false,
+            fresh: true,
+            space: true,
+            line_start: true,
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        escape_with_policy(text, &self.options.escape_policy).into_owned()
+    }
+
+    /// Returns `text` escaped as a single macro argument.
+    fn arg(&self, text: &str) -> String {
+        let escaped = self.escape(text);
+        if escaped.is_empty() || escaped.contains([' ', '\t', '"']) {
+            format!("\"{}\"", escaped.replace('"', "\"\""))
+        } else if CALLABLE_MACROS.contains(&escaped.as_str()) || is_delimiter(&escaped) {
+            format!("\\&{}", escaped)
+        } else {
+            escaped
+        }
+    }
+
+    /// Writes the pending macro line or the text line.
+    fn flush(&mut self) -> Result<(), RoffError> {
+        if let Some(line) = self.pending.take() {
+            writeln!(self.writer, "{}", line)?;
+            self.fresh = false;
+        }
+        let line = std::mem::take(&mut self.line);
+        let line = line.trim_end();
+        if !line.is_empty() {
+            writeln!(self.writer, "{}", line)?;
+            self.fresh = false;
+        }
+        self.space = true;
+        Ok(())
+    }
+
+    /// Ends the current block of text, the next text starts a new paragraph.
+    fn start_paragraph(&mut self) -> Result<(), RoffError> {
+        self.flush()?;
+        self.paragraph = true;
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Ends the text before a block macro, which breaks the line by itself.
+    fn start_block(&mut self) -> Result<(), RoffError> {
+        self.flush()?;
+        self.paragraph = false;
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Writes a macro line with every word of `args` as a separate argument.
+    fn macro_line<'a, I>(&mut self, name: &str, args: I) -> Result<(), RoffError>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.flush()?;
+        let mut line = format!(".{}", name);
+        for arg in args {
+            line.push(' ');
+            line.push_str(&self.arg(arg));
+        }
+        writeln!(self.writer, "{}", line)?;
+        Ok(())
+    }
+
+    /// Starts a macro line with the words of `text` as arguments that following delimiters can
+    /// be attached to.
+    fn pending_macro(&mut self, name: &str, text: &str) -> Result<(), RoffError> {
+        self.write_paragraph()?;
+        let mut line = format!(".{}", name);
+        for word in text.split_whitespace() {
+            line.push(' ');
+            line.push_str(&self.arg(word));
+        }
+        self.pending = Some(line);
+        Ok(())
+    }
+
+    /// Writes the paragraph break requested before the next text.
+    fn write_paragraph(&mut self) -> Result<(), RoffError> {
+        self.flush()?;
+        if std::mem::take(&mut self.paragraph) && !self.fresh {
+            writeln!(self.writer, ".Pp")?;
+        }
+        self.fresh = false;
+        Ok(())
+    }
+
+    fn comment(&mut self, comment: &str) -> Result<(), RoffError> {
+        self.flush()?;
+        for line in crate::comment_lines(comment) {
+            if line.is_empty() {
+                writeln!(self.writer, ".\\\"")?;
+            } else {
+                writeln!(self.writer, ".\\\" {}", line)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn heading(&mut self, name: &str, title: &RoffText) -> Result<(), RoffError> {
+        self.close_list()?;
+        let title = title.unescaped();
+        self.macro_line(name, title.split_whitespace())?;
+        self.fresh = true;
+        self.paragraph = false;
+        self.line_start = true;
+        Ok(())
+    }
+
+    fn close_list(&mut self) -> Result<(), RoffError> {
+        self.flush()?;
+        if std::mem::take(&mut self.list) {
+            writeln!(self.writer, ".El")?;
+        }
+        self.paragraph = false;
+        Ok(())
+    }
+
+    /// Appends a word of regular text to the text line. A `glued` word continues the previous
+    /// text without a space in between.
+    fn word(&mut self, word: &str, glued: bool) -> Result<(), RoffError> {
+        if glued {
+            if self.pending.is_some() {
+                let attached = if is_delimiter(word) {
+                    self.escape(word)
+                } else {
+                    format!("Ns {}", self.arg(word))
+                };
+                if let Some(line) = &mut self.pending {
+                    line.push(' ');
+                    line.push_str(&attached);
+                }
+                return Ok(());
+            }
+            if !self.line.is_empty() {
+                let escaped = self.escape(word);
+                self.line.push_str(&escaped);
+                return Ok(());
+            }
+        }
+        if let Some((name, section, trailing)) = split_xref(word) {
+            self.write_paragraph()?;
+            let mut line = format!(".Xr {} {}", self.arg(name), self.arg(section));
+            if !trailing.is_empty() {
+                line.push(' ');
+                line.push_str(&self.escape(trailing));
+            }
+            self.pending = Some(line);
+            return Ok(());
+        }
+        if self.pending.is_some() || (self.line.is_empty() && self.paragraph) {
+            self.write_paragraph()?;
+        }
+        let escaped = self.escape(word);
+        if self.line.is_empty() {
+            if escaped.starts_with(['.', '\'']) {
+                self.line.push_str("\\&");
+            }
+        } else {
+            self.line.push(' ');
+        }
+        self.line.push_str(&escaped);
+        Ok(())
+    }
+
+    /// Appends text in the given `style`. A line break is a space unless it makes an empty input
+    /// line, which ends the paragraph.
+    fn inline(&mut self, text: &str, style: FontStyle) -> Result<(), RoffError> {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                if self.line_start {
+                    self.start_paragraph()?;
+                }
+                self.line_start = true;
+                self.space = true;
+            }
+            if part.trim().is_empty() {
+                self.space |= !part.is_empty();
+                continue;
+            }
+            self.line_start = false;
+            let name = match style {
+                FontStyle::Roman => None,
+                FontStyle::Bold => Some("Sy"),
+                FontStyle::Italic => Some("Em"),
+                FontStyle::ConstantWidth | FontStyle::Literal => Some("Li"),
+            };
+            if let Some(name) = name {
+                self.pending_macro(name, part)?;
+            } else {
+                let mut glued = !self.space && !part.starts_with([' ', '\t']);
+                for word in part.split([' ', '\t']).filter(|word| !word.is_empty()) {
+                    self.word(word, glued)?;
+                    glued = false;
+                }
+            }
+            self.space = part.ends_with([' ', '\t']);
+        }
+        Ok(())
+    }
+
+    /// Appends an escape sequence of a special character to the text.
+    fn special(&mut self, sequence: &str) -> Result<(), RoffError> {
+        self.line_start = false;
+        match &mut self.pending {
+            Some(line) => {
+                line.push_str(if self.space { " " } else { " Ns " });
+                line.push_str(sequence);
+            }
+            None => {
+                if self.line.is_empty() {
+                    self.write_paragraph()?;
+                } else if self.space {
+                    self.line.push(' ');
+                }
+                self.line.push_str(sequence);
+            }
+        }
+        self.space = false;
+        Ok(())
+    }
+
+    fn text(&mut self, text: &RoffText) -> Result<(), RoffError> {
+        for (content, style) in text.unescaped_segments() {
+            self.inline(&content, style)?;
+        }
+        Ok(())
+    }
+
+    /// Writes `content` as the body of a `.It` item with the arguments written by `tag`.
+    fn item<F>(&mut self, tag: F, content: &[RoffNode]) -> Result<(), RoffError>
+    where
+        F: FnOnce(&Self) -> String,
+    {
+        self.start_block()?;
+        if !std::mem::replace(&mut self.list, true) {
+            writeln!(self.writer, ".Bl -tag -width Ds")?;
+        }
+        let tag = tag(self);
+        if tag.is_empty() {
+            writeln!(self.writer, ".It")?;
+        } else {
+            writeln!(self.writer, ".It {}", tag)?;
+        }
+        self.list = false;
+        self.nodes(content)?;
+        self.close_list()?;
+        self.list = true;
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Writes `content` in a display indented from the surrounding text.
+    fn display(&mut self, content: &[RoffNode]) -> Result<(), RoffError> {
+        self.start_block()?;
+        writeln!(self.writer, ".Bd -ragged -offset indent")?;
+        let list = std::mem::take(&mut self.list);
+        self.nodes(content)?;
+        self.close_list()?;
+        self.list = list;
+        writeln!(self.writer, ".Ed")?;
+        self.line_start = true;
+        Ok(())
+    }
+
+    /// Returns the words of `text` escaped as separate macro arguments.
+    fn args(&self, text: &RoffText) -> String {
+        text.unescaped()
+            .split_whitespace()
+            .map(|word| self.arg(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns the arguments of the `.Fl` and `.Ar` macros describing `opt`.
+    fn opt_args(&self, opt: &SynopsisOpt) -> String {
+        let name = opt.name.unescaped();
+        let mut args = match name.strip_prefix('-') {
+            Some(flag) if !flag.is_empty() => format!("Fl {}", self.arg(flag)),
+            Some(_) => "Fl".to_string(),
+            None => format!("Cm {}", self.arg(&name)),
+        };
+        if opt.inline_values && !opt.possible_values.is_empty() {
+            let values: Vec<_> = opt
+                .possible_values
+                .iter()
+                .map(|value| value.unescaped().into_owned())
+                .collect();
+            args.push_str(" Ar ");
+            args.push_str(&self.arg(&values.join("|")));
+        } else if let Some(argument) = &opt.argument {
+            args.push_str(" Ar ");
+            args.push_str(&self.arg(&argument.unescaped()));
+        }
+        args
+    }
+
+    /// Appends the description of `opt` followed by the possible values and the default value.
+    fn opt_description(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        if let Some(description) = &opt.description {
+            for elem in description {
+                self.text(elem)?;
+            }
+        }
+        if !opt.possible_values.is_empty() {
+            self.inline(" Possible values:", FontStyle::Roman)?;
+            for (i, value) in opt.possible_values.iter().enumerate() {
+                self.inline(&value.unescaped(), FontStyle::Bold)?;
+                if i + 1 < opt.possible_values.len() {
+                    self.inline(",", FontStyle::Roman)?;
+                }
+            }
+        }
+        if let Some(default_value) = &opt.default_value {
+            self.inline(" (default: ", FontStyle::Roman)?;
+            self.text(default_value)?;
+            self.inline(")", FontStyle::Roman)?;
+        }
+        Ok(())
+    }
+
+    fn nodes(&mut self, nodes: &[RoffNode]) -> Result<(), RoffError> {
+        for node in ungrouped(nodes).iter() {
+            self.node(node)?;
+        }
+        Ok(())
+    }
+
+    fn node(&mut self, node: &RoffNode) -> Result<(), RoffError> {
+        let inner = node.inner_ref();
+        let continues_list = matches!(
+            inner,
+            RoffNodeInner::TaggedParagraph { .. }
+                | RoffNodeInner::IndentedParagraph {
+                    indentation: Some(_),
+                    title: Some(_),
+                    ..
+                }
+                | RoffNodeInner::OptionList(_)
+                | RoffNodeInner::Comment(_)
+        );
+        if self.list && !continues_list {
+            self.close_list()?;
+            self.paragraph = true;
+        }
+        match inner {
+            RoffNodeInner::Text(text) => self.text(text)?,
+            RoffNodeInner::SubHeading(title) => self.heading("Ss", title)?,
+            RoffNodeInner::Whatis { names, description } => {
+                self.start_block()?;
+                for (i, name) in names.iter().enumerate() {
+                    let mut line = format!(".Nm {}", self.args(name));
+                    if i + 1 < names.len() {
+                        line.push_str(" ,");
+                    }
+                    writeln!(self.writer, "{}", line)?;
+                }
+                self.macro_line("Nd", description.unescaped().split_whitespace())?;
+            }
+            RoffNodeInner::Paragraph(content) => {
+                self.start_paragraph()?;
+                self.nodes(content)?;
+            }
+            RoffNodeInner::IndentedParagraph {
+                content,
+                indentation,
+                title,
+            } => match (indentation, title) {
+                (Some(_), Some(title)) => self.item(|mdoc| mdoc.args(title), content)?,
+                _ => self.display(content)?,
+            },
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                self.item(|mdoc| mdoc.args(title), content)?
+            }
+            RoffNodeInner::Example(content) => {
+                self.start_block()?;
+                writeln!(self.writer, ".Bd -literal -offset indent")?;
+                let options = RenderOptions {
+                    no_fill: true,
+                    ..self.options.clone()
+                };
+                let content: String = content.iter().map(RoffText::unescaped).collect();
+                let content = content.trim_matches('\n');
+                if !content.is_empty() {
+                    content.roff().render_line_start(self.writer, &options)?;
+                    writeln!(self.writer)?;
+                }
+                writeln!(self.writer, ".Ed")?;
+                self.line_start = true;
+            }
+            RoffNodeInner::Columns { rows, tab_stop } => {
+                self.start_block()?;
+                let tab_stop = tab_stop.unescaped();
+                let width = if !tab_stop.is_empty() && tab_stop.chars().all(|c| c.is_ascii_digit())
+                {
+                    format!("{}n", tab_stop)
+                } else {
+                    self.arg(&tab_stop)
+                };
+                writeln!(self.writer, ".Bl -tag -width {}", width)?;
+                for (key, value) in rows {
+                    writeln!(self.writer, ".It {}", self.args(key))?;
+                    self.text(value)?;
+                    self.flush()?;
+                }
+                writeln!(self.writer, ".El")?;
+                self.line_start = true;
+            }
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                self.start_paragraph()?;
+                self.write_paragraph()?;
+                writeln!(self.writer, ".Nm {}", self.args(command))?;
+                self.line_start = true;
+                for elem in text {
+                    self.text(elem)?;
+                }
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.synopsis_opt(opt)?,
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.pending_macro("Sy", &group.title.unescaped())?;
+                            for opt in &group.opts {
+                                self.synopsis_opt(opt)?;
+                            }
+                        }
+                    }
+                }
+                self.start_paragraph()?;
+            }
+            RoffNodeInner::OptionList(opts) => {
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.option(opt)?,
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.heading("Ss", &group.title)?;
+                            for opt in &group.opts {
+                                self.option(opt)?;
+                            }
+                        }
+                    }
+                }
+            }
+            RoffNodeInner::Url { name, address } => {
+                self.write_paragraph()?;
+                let mut line = format!(".Lk {}", self.arg(&address.unescaped()));
+                if !name.is_empty() {
+                    line.push(' ');
+                    line.push_str(&self.args(name));
+                }
+                self.pending = Some(line);
+                self.space = false;
+                self.line_start = true;
+            }
+            RoffNodeInner::Email { name, address } => {
+                let address = self.arg(&address.unescaped());
+                if name.is_empty() {
+                    self.write_paragraph()?;
+                    self.pending = Some(format!(".Mt {}", address));
+                } else {
+                    self.text(name)?;
+                    self.write_paragraph()?;
+                    self.pending = Some(format!(".Aq Mt {}", address));
+                }
+                self.space = false;
+                self.line_start = true;
+            }
+            RoffNodeInner::RegisteredSign => self.special("\\(rg")?,
+            RoffNodeInner::LeftQuote => self.special("\\(lq")?,
+            RoffNodeInner::RightQuote => self.special("\\(rq")?,
+            RoffNodeInner::TrademarkSign => self.special("\\(tm")?,
+            RoffNodeInner::EmDash => self.special("\\(em")?,
+            RoffNodeInner::EnDash => self.special("\\(en")?,
+            RoffNodeInner::NonBreakingSpace => self.special("\\~")?,
+            RoffNodeInner::Nested { nodes, .. } => self.display(nodes)?,
+            RoffNodeInner::Group(nodes) => self.nodes(nodes)?,
+            RoffNodeInner::Break => {
+                self.flush()?;
+                writeln!(self.writer, ".br")?;
+                self.line_start = true;
+            }
+            RoffNodeInner::Comment(comment) => self.comment(comment)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a single option of a synopsis as an `.Op` macro followed by its description.
+    fn synopsis_opt(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        if !opt.is_visible(self.options) {
+            return Ok(());
+        }
+        self.flush()?;
+        writeln!(self.writer, ".Op {}", self.opt_args(opt))?;
+        self.fresh = false;
+        self.line_start = true;
+        self.opt_description(opt)
+    }
+
+    /// Writes a single option of an options list as an item of a tag list.
+    fn option(&mut self, opt: &SynopsisOpt) -> Result<(), RoffError> {
+        if !opt.is_visible(self.options) {
+            return Ok(());
+        }
+        self.start_block()?;
+        if !std::mem::replace(&mut self.list, true) {
+            writeln!(self.writer, ".Bl -tag -width Ds")?;
+        }
+        writeln!(self.writer, ".It {}", self.opt_args(opt))?;
+        self.opt_description(opt)?;
+        self.flush()?;
+        self.line_start = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_xref;
+    use crate::{Roff, RoffNode, Roffable, SectionNumber, SynopsisOpt};
+
+    fn render(roff: &Roff) -> String {
+        let mut out = Vec::new();
+        roff.render_mdoc(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn it_renders_a_realistic_page() {
+        let roff = Roff::new("grepper", SectionNumber::UserCommands)
+            .date("August 18, 2021")
+            .section(
+                "NAME",
+                [RoffNode::whatis(
+                    ["grepper", "egrepper"],
+                    "search files for patterns",
+                )],
+            )
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis(
+                    "grepper",
+                    ["Searches every ".roff(), "FILE".roff().italic(), ".".roff()],
+                    [
+                        SynopsisOpt::new("-i").description(["ignore case"]),
+                        SynopsisOpt::new("--color")
+                            .argument("WHEN")
+                            .description(["colorize matches"]),
+                    ],
+                )],
+            )
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::paragraph([
+                        "The ".roff(),
+                        "grepper".roff().bold(),
+                        " utility works like grep(1), but faster.".roff(),
+                    ]),
+                    RoffNode::paragraph([".dotfiles are searched too"]),
+                ],
+            )
+            .section(
+                "OPTIONS",
+                [RoffNode::option_list([
+                    SynopsisOpt::new("-i").description(["Ignore case."]),
+                    SynopsisOpt::new("--color")
+                        .argument("WHEN")
+                        .possible_values(["auto", "never"])
+                        .default_value("auto")
+                        .description(["Colorize matches."]),
+                ])],
+            )
+            .section(
+                "EXAMPLES",
+                [
+                    RoffNode::text("Search for a word:"),
+                    RoffNode::example(["grepper -i word .\n"]),
+                ],
+            )
+            .section(
+                "SEE ALSO",
+                [
+                    RoffNode::text("grep(1), sed(1)"),
+                    RoffNode::linebreak(),
+                    RoffNode::url("Homepage", "https://example.com"),
+                ],
+            )
+            .section(
+                "AUTHORS",
+                [
+                    RoffNode::email("Jane Doe", "jane@example.com"),
+                    RoffNode::text(", maintainer."),
+                ],
+            );
+        assert_eq!(
+            render(&roff),
+            r#".Dd August 18, 2021
+.Dt GREPPER 1
+.Os
+.Sh NAME
+.Nm grepper ,
+.Nm egrepper
+.Nd search files for patterns
+.Sh SYNOPSIS
+.Nm grepper
+Searches every
+.Em FILE .
+.Op Fl i
+ignore case
+.Op Fl \-color Ar WHEN
+colorize matches
+.Sh DESCRIPTION
+The
+.Sy grepper
+utility works like
+.Xr grep 1 ,
+but faster.
+.Pp
+\&.dotfiles are searched too
+.Sh OPTIONS
+.Bl -tag -width Ds
+.It Fl i
+Ignore case.
+.It Fl \-color Ar WHEN
+Colorize matches. Possible values:
+.Sy auto ,
+.Sy never
+(default: auto)
+.El
+.Sh EXAMPLES
+Search for a word:
+.Bd -literal -offset indent
+grepper \-i word .
+.Ed
+.Sh SEE ALSO
+.Xr grep 1 ,
+.Xr sed 1
+.br
+.Lk https://example.com Homepage
+.Sh AUTHORS
+Jane Doe
+.Aq Mt jane@example.com ,
+maintainer.
+"#
+        );
+    }
+
+    #[test]
+    fn it_renders_every_node() {
+        let roff = Roff::new("every", SectionNumber::Miscellaneous)
+            .section(
+                "NODES",
+                [
+                    RoffNode::subheading("Sub heading"),
+                    RoffNode::comment("a comment\nover two lines"),
+                    RoffNode::text("Some "),
+                    RoffNode::registered_sign(),
+                    RoffNode::text(" and "),
+                    RoffNode::left_quote(),
+                    RoffNode::text("quoted"),
+                    RoffNode::right_quote(),
+                    RoffNode::trademark_sign(),
+                    RoffNode::en_dash(),
+                    RoffNode::non_breaking_space(),
+                    RoffNode::text("text."),
+                    RoffNode::tagged_paragraph(["tagged body"], "Tag"),
+                    RoffNode::indented_paragraph(["indented body"], Some(4), Some("Title")),
+                    RoffNode::indented_paragraph(["untitled body"], None, None::<&str>),
+                    RoffNode::nested([RoffNode::text("nested text")]),
+                    RoffNode::columns([("key", "value"), ("other", "more")], "8"),
+                    RoffNode::url("", "https://example.com"),
+                    RoffNode::text(", then"),
+                    RoffNode::email("", "me@example.com"),
+                    RoffNode::text(".\n\nNew "),
+                    RoffNode::text("paragraph".roff().literal()),
+                ],
+            )
+            .section("SEE ALSO", [RoffNode::text("No")]);
+        assert_eq!(
+            render(&roff),
+            r#".Dd $Mdocdate$
+.Dt EVERY 7
+.Os
+.Sh NODES
+.Ss Sub heading
+.\" a comment
+.\" over two lines
+Some \(rg and \(lqquoted\(rq\(tm\(en\~text.
+.Bl -tag -width Ds
+.It Tag
+tagged body
+.It Title
+indented body
+.El
+.Bd -ragged -offset indent
+untitled body
+.Ed
+.Bd -ragged -offset indent
+nested text
+.Ed
+.Bl -tag -width 8n
+.It key
+value
+.It other
+more
+.El
+.Lk https://example.com ,
+then
+.Mt me@example.com .
+.Pp
+New
+.Li paragraph
+.Sh SEE ALSO
+No
+"#
+        );
+    }
+
+    #[test]
+    fn it_detects_cross_references() {
+        assert_eq!(split_xref("grep(1)"), Some(("grep", "1", "")));
+        assert_eq!(split_xref("git-commit(1),"), Some(("git-commit", "1", ",")));
+        assert_eq!(split_xref("open(3p)."), Some(("open", "3p", ".")));
+        assert_eq!(split_xref("f(x)"), None);
+        assert_eq!(split_xref("(1)"), None);
+        assert_eq!(split_xref("grep(1)s"), None);
+    }
+
+    #[test]
+    fn it_escapes_macro_arguments() {
+        let roff = Roff::new("test", SectionNumber::UserCommands).section(
+            "Sh Op",
+            [
+                RoffNode::tagged_paragraph(["body"], "say \"hi\" ."),
+                RoffNode::text("Sy".roff().bold()),
+            ],
+        );
+        assert_eq!(
+            render(&roff),
+            r#".Dd $Mdocdate$
+.Dt TEST 1
+.Os
+.Sh Sh \&Op
+.Bl -tag -width Ds
+.It say \(dqhi\(dq \&.
+body
+.El
+.Pp
+.Sy \&Sy
+"#
+        );
+    }
+}
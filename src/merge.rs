@@ -0,0 +1,220 @@
+use crate::{Roff, RoffError, RoffText, Section};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+/// Options that alter the way documents are combined with [`Roff::merge`](Roff::merge) and
+/// [`Roff::extend_from`](Roff::extend_from). By default the sections of the other document are
+/// appended and its title header is ignored.
+pub struct MergeOptions {
+    /// Whether the title header of the other document must be compatible.
+    pub(crate) strict: bool,
+    /// Whether sections with the title of an existing section are merged into it.
+    pub(crate) merge_duplicates: bool,
+}
+
+impl MergeOptions {
+    /// Creates the default options that append the sections and ignore the title header.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the title header of the other document to be compatible. A title that is not
+    /// empty must match the title and the section number, and the date, source and manual must
+    /// match if they are set in both documents. Conflicts are reported as
+    /// [`RoffError::MergeConflict`](RoffError::MergeConflict).
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Merges sections with the title of an existing section into it instead of appending them,
+    /// the same way as [`Roff::merge_duplicate_sections`](Roff::merge_duplicate_sections).
+    /// Duplicate sections that are already in the document are left as they are.
+    pub fn merge_duplicates(mut self, merge_duplicates: bool) -> Self {
+        self.merge_duplicates = merge_duplicates;
+        self
+    }
+}
+
+impl Roff {
+    /// Adds the sections and appended pages of `other` to this roff, for example partial
+    /// documents returned by plugins that only contain sections. The title header of `other` is
+    /// ignored unless [`strict`](MergeOptions::strict) mode is requested, in which case a conflict
+    /// is returned as an error and this roff is left unchanged.
+    pub fn extend_from(&mut self, other: Roff, options: MergeOptions) -> Result<(), RoffError> {
+        if options.strict {
+            self.check_compatible(&other)?;
+        }
+        for section in other.sections {
+            match options.merge_duplicates {
+                true => push_merged(&mut self.sections, section),
+                false => self.sections.push(section),
+            }
+        }
+        self.pages.extend(other.pages);
+        Ok(())
+    }
+
+    /// Consuming form of [`extend_from`](Roff::extend_from).
+    pub fn merge(mut self, other: Roff, options: MergeOptions) -> Result<Self, RoffError> {
+        self.extend_from(other, options)?;
+        Ok(self)
+    }
+
+    fn check_compatible(&self, other: &Roff) -> Result<(), RoffError> {
+        if !other.title.is_empty() {
+            check_field("title", Some(&self.title), Some(&other.title))?;
+            let (ours, theirs) = (self.section.to_string(), other.section.to_string());
+            if ours != theirs {
+                return Err(RoffError::MergeConflict {
+                    field: "section".to_string(),
+                    ours,
+                    theirs,
+                });
+            }
+        }
+        check_field("date", self.date.as_ref(), other.date.as_ref())?;
+        check_field("source", self.source.as_ref(), other.source.as_ref())?;
+        check_field("manual", self.manual.as_ref(), other.manual.as_ref())
+    }
+}
+
+/// Returns a conflict if the field named `what` is set in both documents to different text.
+fn check_field(
+    what: &str,
+    ours: Option<&RoffText>,
+    theirs: Option<&RoffText>,
+) -> Result<(), RoffError> {
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) if ours.original() != theirs.original() => {
+            Err(RoffError::MergeConflict {
+                field: what.to_string(),
+                ours: ours.original().into_owned(),
+                theirs: theirs.original().into_owned(),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Merges `section` into the first of `sections` with an equal title, compared case
+/// insensitively, or appends it if there is none.
+pub(crate) fn push_merged(sections: &mut Vec<Section>, section: Section) {
+    let title = section.get_title().as_str().to_lowercase();
+    match sections
+        .iter_mut()
+        .find(|existing| existing.get_title().as_str().to_lowercase() == title)
+    {
+        Some(first) => first.merge(section),
+        None => sections.push(section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MergeOptions;
+    use crate::{Roff, RoffError, RoffNode, SectionNumber};
+
+    fn base() -> Roff {
+        Roff::new("foo", SectionNumber::UserCommands)
+            .date("August 2021")
+            .section("NAME", ["foo - does things"])
+            .section("OPTIONS", [RoffNode::tagged_paragraph(["verbose"], "-v")])
+    }
+
+    fn plugin() -> Roff {
+        Roff::new("", SectionNumber::UserCommands)
+            .section(
+                "options",
+                [RoffNode::tagged_paragraph(["plugin"], "--plugin")],
+            )
+            .section(
+                "ENVIRONMENT",
+                [RoffNode::tagged_paragraph(["data"], "FOO_DIR")],
+            )
+    }
+
+    fn titles(roff: &Roff) -> Vec<String> {
+        roff.get_sections()
+            .iter()
+            .map(|section| section.get_title().original().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn it_appends_sections() {
+        let mut roff = base();
+        roff.extend_from(plugin(), MergeOptions::new()).unwrap();
+        assert_eq!(titles(&roff), ["NAME", "OPTIONS", "options", "ENVIRONMENT"]);
+        assert_eq!(roff.get_title().original(), "foo");
+        assert_eq!(roff.get_date().unwrap().original(), "August 2021");
+
+        let other = Roff::new("bar", SectionNumber::Miscellaneous)
+            .date("2022")
+            .section("NAME", ["bar"])
+            .append_page(Roff::new("baz", SectionNumber::UserCommands));
+        let merged = base().merge(other, MergeOptions::new()).unwrap();
+        assert_eq!(titles(&merged), ["NAME", "OPTIONS", "NAME"]);
+        assert_eq!(merged.get_date().unwrap().original(), "August 2021");
+        assert!(merged.render_to_string().unwrap().contains("\n.TH baz 1\n"));
+    }
+
+    #[test]
+    fn it_merges_duplicate_sections() {
+        let merged = base()
+            .merge(plugin(), MergeOptions::new().merge_duplicates(true))
+            .unwrap();
+        assert_eq!(titles(&merged), ["NAME", "OPTIONS", "ENVIRONMENT"]);
+        assert_eq!(
+            merged.get_sections()[1].get_nodes(),
+            [
+                RoffNode::tagged_paragraph(["verbose"], "-v"),
+                RoffNode::tagged_paragraph(["plugin"], "--plugin"),
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_mode_reports_conflicts() {
+        let strict = MergeOptions::new().strict(true);
+        let merged = base().merge(plugin(), strict).unwrap();
+        assert_eq!(merged.get_sections().len(), 4);
+
+        let compatible = Roff::new("foo", SectionNumber::UserCommands)
+            .date("August 2021")
+            .source("foo 1.0");
+        assert!(base().merge(compatible, strict).is_ok());
+
+        let mut roff = base();
+        let other = Roff::new("foo", SectionNumber::UserCommands)
+            .date("September 2021")
+            .section("BUGS", ["none"]);
+        match roff.extend_from(other, strict) {
+            Err(RoffError::MergeConflict {
+                field,
+                ours,
+                theirs,
+            }) => {
+                assert_eq!(field, "date");
+                assert_eq!(ours, "August 2021");
+                assert_eq!(theirs, "September 2021");
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(roff, base());
+
+        let err = base()
+            .merge(Roff::new("bar", SectionNumber::UserCommands), strict)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Conflicting title when merging documents - `foo` and `bar`"
+        );
+        let err = base()
+            .merge(Roff::new("foo", SectionNumber::Miscellaneous), strict)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Conflicting section when merging documents - `1` and `7`"
+        );
+    }
+}
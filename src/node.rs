@@ -1,16 +1,88 @@
 use crate::_macro::*;
-use crate::{write_quoted_if_whitespace, IntoRoffNode, RoffError, RoffText, Roffable, SynopsisOpt};
+use crate::render::{CompatLevel, RenderOptions};
+use crate::text::render_texts;
+use crate::{
+    comment_lines, write_comment_lines, write_quoted_if_whitespace, EscapePolicy, IntoRoffNode,
+    RoffError, RoffText, Roffable, SynopsisItem,
+};
 
+use std::borrow::Cow;
 use std::io::Write;
+use std::sync::Arc;
 
-#[derive(Clone, Debug)]
-/// Building block of ROFF documents.
-pub struct RoffNode(RoffNodeInner);
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "NodeRepr", into = "NodeRepr")
+)]
+/// Building block of ROFF documents. The content of a node is shared between its clones, so
+/// cloning is cheap.
+pub struct RoffNode(Arc<RoffNodeInner>, Option<EscapePolicy>);
+
+#[cfg(feature = "serde")]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+/// Serialized form of [`RoffNode`](RoffNode). Nodes without their own escape policy are
+/// serialized as the bare node, the others together with the policy.
+enum NodeRepr {
+    Node(RoffNodeInner),
+    WithPolicy {
+        node: RoffNodeInner,
+        escape_policy: EscapePolicy,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl From<NodeRepr> for RoffNode {
+    fn from(repr: NodeRepr) -> Self {
+        match repr {
+            NodeRepr::Node(node) => Self(Arc::new(node), None),
+            NodeRepr::WithPolicy {
+                node,
+                escape_policy,
+            } => Self(Arc::new(node), Some(escape_policy)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<RoffNode> for NodeRepr {
+    fn from(node: RoffNode) -> Self {
+        let inner = Arc::try_unwrap(node.0).unwrap_or_else(|inner| (*inner).clone());
+        match node.1 {
+            None => NodeRepr::Node(inner),
+            Some(escape_policy) => NodeRepr::WithPolicy {
+                node: inner,
+                escape_policy,
+            },
+        }
+    }
+}
 
 impl RoffNode {
     /// Creates a simple text node.
-    pub fn text(content: impl Roffable) -> Self {
-        Self(RoffNodeInner::Text(content.roff()))
+    pub fn text(content: impl Into<RoffText>) -> Self {
+        Self::from_inner(RoffNodeInner::Text(content.into()))
+    }
+
+    /// Creates a sub heading inside of a section.
+    pub fn subheading(title: impl Into<RoffText>) -> Self {
+        Self::from_inner(RoffNodeInner::SubHeading(title.into()))
+    }
+
+    /// Creates a line in the format expected by `whatis` listing all of the `names` followed by a
+    /// short `description`, like `grep, egrep, fgrep \- print lines that match patterns`. Used in the
+    /// `NAME` section.
+    pub fn whatis<I, R>(names: I, description: impl Into<RoffText>) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<RoffText>,
+    {
+        Self::from_inner(RoffNodeInner::Whatis {
+            names: names.into_iter().map(Into::into).collect(),
+            description: description.into(),
+        })
     }
 
     /// Creates a new paragraph. When a new paragraph is created the indentation is reset to the
@@ -20,11 +92,8 @@ impl RoffNode {
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
     {
-        Self(RoffNodeInner::Paragraph(
-            content
-                .into_iter()
-                .map(|item| item.into_roff().into_inner())
-                .collect(),
+        Self::from_inner(RoffNodeInner::Paragraph(
+            content.into_iter().map(IntoRoffNode::into_roff).collect(),
         ))
     }
 
@@ -32,76 +101,119 @@ impl RoffNode {
     pub fn indented_paragraph<I, R>(
         content: I,
         indentation: Option<u8>,
-        title: Option<impl Roffable>,
+        title: Option<impl Into<RoffText>>,
     ) -> Self
     where
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
     {
-        Self(RoffNodeInner::IndentedParagraph {
-            content: content
-                .into_iter()
-                .map(|item| item.into_roff().into_inner())
-                .collect(),
+        Self::from_inner(RoffNodeInner::IndentedParagraph {
+            content: content.into_iter().map(IntoRoffNode::into_roff).collect(),
             indentation,
-            title: title.map(|t| t.roff()),
+            title: title.map(Into::into),
         })
     }
 
     /// Creates a new paragraph with a leading tag and the remainder of the paragraph indented.
-    pub fn tagged_paragraph<I, R>(content: I, title: impl Roffable) -> Self
+    pub fn tagged_paragraph<I, R>(content: I, title: impl Into<RoffText>) -> Self
     where
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
     {
-        Self(RoffNodeInner::TaggedParagraph {
-            content: content
-                .into_iter()
-                .map(|item| item.into_roff().into_inner())
-                .collect(),
-            title: title.roff(),
+        Self::from_inner(RoffNodeInner::TaggedParagraph {
+            content: content.into_iter().map(IntoRoffNode::into_roff).collect(),
+            title: title.into(),
         })
     }
 
     /// Creates a new example node. An example block usually has the font set to monospaced but that
-    /// behavior depends on the viewer used.
+    /// behavior depends on the viewer used. `-` is rendered as a minus sign so that the code can be
+    /// copied from the page.
     ///
     /// This is an extension introduced in Version 9 Unix, to the original `man` package. Many systems
     /// running AT&T or Plan 9 `troff` support them.
     pub fn example<I, R>(content: I) -> Self
     where
         I: IntoIterator<Item = R>,
-        R: Roffable,
+        R: Into<RoffText>,
     {
-        Self(RoffNodeInner::Example(
-            content.into_iter().map(|item| item.roff()).collect(),
+        Self::from_inner(RoffNodeInner::Example(
+            content
+                .into_iter()
+                .map(|item| item.into().minus_hyphens())
+                .collect(),
         ))
     }
 
+    /// Creates a two column listing aligned with a tab stop at `tab_stop`. This is a lightweight
+    /// alternative to a full table for short key/value listings. The text is rendered without
+    /// filling so that every row ends up on its own line, and the default tab stops are restored
+    /// after the listing.
+    ///
+    /// The tab stop is a distance from the start of the line: an unsigned number, optionally with a
+    /// fraction, followed by an optional unit like `i` (inches), `c` (centimeters), `m` (ems) or
+    /// `n` (ens), for example `2i`, `1.5c` or `20n`. Without a unit the number is in ens. It is
+    /// written to the `.ta` request as it is, [`Roff::validated`](crate::Roff::validated) rejects
+    /// anything else.
+    ///
+    /// Tab characters and newlines inside of the cells are replaced with spaces so that they don't
+    /// shift the columns or split the rows.
+    pub fn columns<I, K, V>(rows: I, tab_stop: impl Into<RoffText>) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<RoffText>,
+        V: Into<RoffText>,
+    {
+        Self::from_inner(RoffNodeInner::Columns {
+            rows: rows
+                .into_iter()
+                .map(|(key, value)| (key.into().single_line(), value.into().single_line()))
+                .collect(),
+            tab_stop: tab_stop.into(),
+        })
+    }
+
     /// Creates a new synopsis node explaining the given `command` with `description` and `opts`.
+    /// The options can be either plain [`SynopsisOpt`s](crate::SynopsisOpt) or
+    /// [`SynopsisOptGroup`s](crate::SynopsisOptGroup) which display a bold title before their options.
     ///
     /// This is a GNU extension not defined on systems runing AT&T, Plan 9, or Solaris `troff`.
-    pub fn synopsis<I, R, O>(command: impl Roffable, description: I, opts: O) -> Self
+    pub fn synopsis<I, R, O, S>(command: impl Into<RoffText>, description: I, opts: O) -> Self
     where
         I: IntoIterator<Item = R>,
-        R: Roffable,
-        O: IntoIterator<Item = SynopsisOpt>,
+        R: Into<RoffText>,
+        O: IntoIterator<Item = S>,
+        S: Into<SynopsisItem>,
     {
-        Self(RoffNodeInner::Synopsis {
-            command: command.roff(),
-            text: description.into_iter().map(|item| item.roff()).collect(),
-            opts: opts.into_iter().collect(),
+        Self::from_inner(RoffNodeInner::Synopsis {
+            command: command.into().minus_hyphens(),
+            text: description.into_iter().map(Into::into).collect(),
+            opts: opts.into_iter().map(S::into).collect(),
         })
     }
 
+    /// Creates a list of options where each option is rendered as a tagged paragraph with the bold
+    /// name of the option and its italic argument as the tag followed by the description, possible
+    /// values and the default value. Groups of options are preceded by a sub heading with the title
+    /// of the group. Hidden options are skipped.
+    pub fn option_list<O, S>(opts: O) -> Self
+    where
+        O: IntoIterator<Item = S>,
+        S: Into<SynopsisItem>,
+    {
+        Self::from_inner(RoffNodeInner::OptionList(
+            opts.into_iter().map(S::into).collect(),
+        ))
+    }
+
     /// Creates a new URL node that will take the form of `[name](address)` where `name` is the
     /// visible part of the URL and address is where it points to.
     ///
     /// This is a GNU extension not defined on systems runing AT&T, Plan 9, or Solaris `troff`.
-    pub fn url(name: impl Roffable, address: impl Roffable) -> Self {
-        Self(RoffNodeInner::Url {
-            name: name.roff(),
-            address: address.roff(),
+    pub fn url(name: impl Into<RoffText>, address: impl Into<RoffText>) -> Self {
+        Self::from_inner(RoffNodeInner::Url {
+            name: name.into(),
+            address: address.into(),
         })
     }
 
@@ -109,31 +221,31 @@ impl RoffNode {
     /// visible URL text. `address` may not be visible if the man page is being viewed as HTML.
     ///
     /// This is a GNU extension not defined on systems runing AT&T, Plan 9, or Solaris `troff`.
-    pub fn email(name: impl Roffable, address: impl Roffable) -> Self {
-        Self(RoffNodeInner::Email {
-            name: name.roff(),
-            address: address.roff(),
+    pub fn email(name: impl Into<RoffText>, address: impl Into<RoffText>) -> Self {
+        Self::from_inner(RoffNodeInner::Email {
+            name: name.into(),
+            address: address.into(),
         })
     }
 
     /// Returns a node that will be rendered as a registered sign `®`.
     pub fn registered_sign() -> Self {
-        Self(RoffNodeInner::RegisteredSign)
+        Self::from_inner(RoffNodeInner::RegisteredSign)
     }
 
     /// Returns a node that will be rendered as a left quote `“`.
     pub fn left_quote() -> Self {
-        Self(RoffNodeInner::LeftQuote)
+        Self::from_inner(RoffNodeInner::LeftQuote)
     }
 
     /// Returns a node that will be rendered as a right quote `”`.
     pub fn right_quote() -> Self {
-        Self(RoffNodeInner::RightQuote)
+        Self::from_inner(RoffNodeInner::RightQuote)
     }
 
     /// Returns a node that will be rendered as a trademark sign `™`.
     pub fn trademark_sign() -> Self {
-        Self(RoffNodeInner::TrademarkSign)
+        Self::from_inner(RoffNodeInner::TrademarkSign)
     }
 
     /// Nest nodes by indenting all of the nodes inside. Creating a paragraph inside of this structure
@@ -143,7 +255,34 @@ impl RoffNode {
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
     {
-        Self(RoffNodeInner::Nested(
+        Self::from_inner(RoffNodeInner::Nested {
+            nodes: nodes.into_iter().map(R::into_roff).collect(),
+            indentation: None,
+        })
+    }
+
+    /// Same as [`nested`](RoffNode::nested) but the nodes are indented by `indentation` instead of
+    /// the default indentation.
+    pub fn nested_indented<I, R>(nodes: I, indentation: u8) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        Self::from_inner(RoffNodeInner::Nested {
+            nodes: nodes.into_iter().map(R::into_roff).collect(),
+            indentation: Some(indentation),
+        })
+    }
+
+    /// Groups `nodes` into a single node that renders them in place, exactly as if they were
+    /// provided one by one, without any surrounding macros or indentation. Useful for helpers
+    /// returning several nodes that make up a logical chunk of a document.
+    pub fn group<I, R>(nodes: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        Self::from_inner(RoffNodeInner::Group(
             nodes.into_iter().map(R::into_roff).collect(),
         ))
     }
@@ -151,68 +290,415 @@ impl RoffNode {
     /// Breaks the line in text. Use this instead of adding raw `\n` characters to actually render
     /// linebreaks.
     pub fn linebreak() -> Self {
-        Self(RoffNodeInner::Break)
+        Self::from_inner(RoffNodeInner::Break)
     }
 
     /// A long dash `—`. Used for an interruption—such as this one—in a sentence.
     pub fn em_dash() -> Self {
-        Self(RoffNodeInner::EmDash)
+        Self::from_inner(RoffNodeInner::EmDash)
     }
 
     /// A long dash `–`. Used to separate the ends of a range, particularly between number like "1–9".
     pub fn en_dash() -> Self {
-        Self(RoffNodeInner::EnDash)
+        Self::from_inner(RoffNodeInner::EnDash)
     }
 
     /// Adjustable non-breaking space.  Use this to prevent a break inside a short phrase or
     /// between a numerical quantity and its corresponding unit(s).
     pub fn non_breaking_space() -> Self {
-        Self(RoffNodeInner::NonBreakingSpace)
+        Self::from_inner(RoffNodeInner::NonBreakingSpace)
     }
 
     /// Adds a comment to the generated roff. You can add multiple lines in a single comment and
-    /// they will automatically get converted to multiple comment lines.
+    /// they will automatically get converted to multiple comment lines. Backslashes are escaped
+    /// and control characters removed so that the content can't end the comment early.
     pub fn comment<C: AsRef<str>>(comment: C) -> Self {
-        Self(RoffNodeInner::Comment(comment.as_ref().to_string()))
+        Self::from_inner(RoffNodeInner::Comment(comment.as_ref().to_string()))
     }
 
-    #[inline]
-    pub(crate) fn into_inner(self) -> RoffNodeInner {
-        self.0
+    /// Sets the policy used to escape the text of this node and all of its children, overriding the
+    /// one set for the whole document with [`Roff::escape_policy`](crate::Roff::escape_policy) or
+    /// by an enclosing node. Text with its own policy set with
+    /// [`RoffText::with_policy`](crate::RoffText::with_policy) is not affected.
+    ///
+    /// Useful for documents that mix prose with verbatim snippets, like configuration files in an
+    /// example block.
+    pub fn with_policy(mut self, policy: EscapePolicy) -> Self {
+        self.1 = Some(policy);
+        self
+    }
+
+    fn from_inner(inner: RoffNodeInner) -> Self {
+        Self(Arc::new(inner), None)
     }
 
     #[inline]
     pub(crate) fn inner_ref(&self) -> &RoffNodeInner {
         &self.0
     }
+
+    /// Returns the inner node for modification.
+    #[inline]
+    pub(crate) fn inner_mut(&mut self) -> &mut RoffNodeInner {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Returns a read-only view of what this node contains, for code that needs to tell the kinds
+    /// of nodes apart, like custom renderers or checks.
+    ///
+    /// ```
+    /// use roffman::{Roff, RoffNode, RoffNodeKind, SectionNumber};
+    ///
+    /// /// Returns the addresses of links that don't use HTTPS.
+    /// fn insecure_links(nodes: &[RoffNode], out: &mut Vec<String>) {
+    ///     for node in nodes {
+    ///         match node.kind() {
+    ///             RoffNodeKind::Url { address, .. } if !address.original().starts_with("https://") => {
+    ///                 out.push(address.original().into_owned())
+    ///             }
+    ///             RoffNodeKind::Paragraph(content) | RoffNodeKind::Group(content) => {
+    ///                 insecure_links(content, out)
+    ///             }
+    ///             _ => insecure_links(node.children(), out),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let roff = Roff::new("foo", SectionNumber::UserCommands).section(
+    ///     "SEE ALSO",
+    ///     [
+    ///         RoffNode::url("docs", "https://example.com/docs"),
+    ///         RoffNode::paragraph([RoffNode::url("mirror", "http://example.org")]),
+    ///     ],
+    /// );
+    /// let mut links = vec![];
+    /// for section in roff.get_sections() {
+    ///     insecure_links(section.get_nodes(), &mut links);
+    /// }
+    /// assert_eq!(links, ["http://example.org"]);
+    /// ```
+    pub fn kind(&self) -> RoffNodeKind<'_> {
+        match &*self.0 {
+            RoffNodeInner::Text(text) => RoffNodeKind::Text(text),
+            RoffNodeInner::SubHeading(title) => RoffNodeKind::SubHeading(title),
+            RoffNodeInner::Whatis { names, description } => {
+                RoffNodeKind::Whatis { names, description }
+            }
+            RoffNodeInner::Paragraph(content) => RoffNodeKind::Paragraph(content),
+            RoffNodeInner::IndentedParagraph {
+                content,
+                indentation,
+                title,
+            } => RoffNodeKind::IndentedParagraph {
+                content,
+                indentation: *indentation,
+                title: title.as_ref(),
+            },
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                RoffNodeKind::TaggedParagraph { content, title }
+            }
+            RoffNodeInner::Example(lines) => RoffNodeKind::Example(lines),
+            RoffNodeInner::Columns { rows, tab_stop } => RoffNodeKind::Columns { rows, tab_stop },
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => RoffNodeKind::Synopsis {
+                command,
+                text,
+                opts,
+            },
+            RoffNodeInner::OptionList(opts) => RoffNodeKind::OptionList(opts),
+            RoffNodeInner::Url { name, address } => RoffNodeKind::Url { name, address },
+            RoffNodeInner::Email { name, address } => RoffNodeKind::Email { name, address },
+            RoffNodeInner::RegisteredSign => RoffNodeKind::RegisteredSign,
+            RoffNodeInner::LeftQuote => RoffNodeKind::LeftQuote,
+            RoffNodeInner::RightQuote => RoffNodeKind::RightQuote,
+            RoffNodeInner::TrademarkSign => RoffNodeKind::TrademarkSign,
+            RoffNodeInner::Nested { nodes, indentation } => RoffNodeKind::Nested {
+                nodes,
+                indentation: *indentation,
+            },
+            RoffNodeInner::Group(nodes) => RoffNodeKind::Group(nodes),
+            RoffNodeInner::Break => RoffNodeKind::Break,
+            RoffNodeInner::EmDash => RoffNodeKind::EmDash,
+            RoffNodeInner::EnDash => RoffNodeKind::EnDash,
+            RoffNodeInner::NonBreakingSpace => RoffNodeKind::NonBreakingSpace,
+            RoffNodeInner::Comment(comment) => RoffNodeKind::Comment(comment),
+        }
+    }
+
+    /// Returns the nodes nested inside of paragraphs and nested blocks. Other nodes have no
+    /// children.
+    pub fn children(&self) -> &[RoffNode] {
+        match &*self.0 {
+            RoffNodeInner::Paragraph(content)
+            | RoffNodeInner::IndentedParagraph { content, .. }
+            | RoffNodeInner::TaggedParagraph { content, .. }
+            | RoffNodeInner::Nested { nodes: content, .. }
+            | RoffNodeInner::Group(content) => content,
+            _ => &[],
+        }
+    }
+
+    /// Returns an estimate of the number of bytes this node takes when rendered.
+    pub(crate) fn estimated_size(&self) -> usize {
+        self.0.estimated_size()
+    }
+
+    /// Returns a short human-readable name of the kind of this node, like `paragraph` or `url`.
+    pub fn kind_name(&self) -> &'static str {
+        match &*self.0 {
+            RoffNodeInner::Text(_) => "text",
+            RoffNodeInner::SubHeading(_) => "sub heading",
+            RoffNodeInner::Whatis { .. } => "whatis line",
+            RoffNodeInner::Paragraph(_) => "paragraph",
+            RoffNodeInner::IndentedParagraph { .. } => "indented paragraph",
+            RoffNodeInner::TaggedParagraph { .. } => "tagged paragraph",
+            RoffNodeInner::Example(_) => "example",
+            RoffNodeInner::Columns { .. } => "columns",
+            RoffNodeInner::Synopsis { .. } => "synopsis",
+            RoffNodeInner::OptionList(_) => "option list",
+            RoffNodeInner::Url { .. } => "url",
+            RoffNodeInner::Email { .. } => "email",
+            RoffNodeInner::RegisteredSign => "registered sign",
+            RoffNodeInner::LeftQuote => "left quote",
+            RoffNodeInner::RightQuote => "right quote",
+            RoffNodeInner::TrademarkSign => "trademark sign",
+            RoffNodeInner::Nested { .. } => "nested block",
+            RoffNodeInner::Group(_) => "group",
+            RoffNodeInner::Break => "line break",
+            RoffNodeInner::EmDash => "em dash",
+            RoffNodeInner::EnDash => "en dash",
+            RoffNodeInner::NonBreakingSpace => "non-breaking space",
+            RoffNodeInner::Comment(_) => "comment",
+        }
+    }
+
+    /// Returns the text displayed by this node and the nodes nested in it, without styling and
+    /// escapes. Links contribute their names without the addresses, while comments, special
+    /// characters, synopses and columns contribute nothing.
+    pub fn text_content(&self) -> String {
+        let mut out = String::new();
+        self.push_text_content(&mut out);
+        out
+    }
+
+    fn push_text_content(&self, out: &mut String) {
+        match &*self.0 {
+            RoffNodeInner::Text(text)
+            | RoffNodeInner::SubHeading(text)
+            | RoffNodeInner::Url { name: text, .. }
+            | RoffNodeInner::Email { name: text, .. } => out.push_str(&text.unescaped()),
+            RoffNodeInner::Whatis { names, description } => {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    out.push_str(&name.unescaped());
+                }
+                out.push_str(" - ");
+                out.push_str(&description.unescaped());
+            }
+            RoffNodeInner::IndentedParagraph {
+                title: Some(title), ..
+            }
+            | RoffNodeInner::TaggedParagraph { title, .. } => {
+                out.push_str(&title.unescaped());
+                out.push(' ');
+            }
+            RoffNodeInner::Example(lines) => {
+                for line in lines {
+                    out.push_str(&line.unescaped());
+                }
+            }
+            _ => {}
+        }
+        for child in self.children() {
+            child.push_text_content(out);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        was_text: bool,
+        options: &RenderOptions,
+    ) -> Result<bool, RoffError> {
+        match self.1 {
+            Some(policy) => {
+                let options = RenderOptions {
+                    escape_policy: policy,
+                    ..options.clone()
+                };
+                self.0.render(writer, was_text, &options)
+            }
+            None => self.0.render(writer, was_text, options),
+        }
+    }
+
+    /// Renders this node as a part of a section, `after_heading` if it directly follows the section
+    /// or a sub heading and `last` if it ends the section. A paragraph begins after a heading
+    /// anyway and a new one begins with the next section, so the paragraph macros and line breaks
+    /// at these positions are left out, as formatters warn about them.
+    pub(crate) fn render_in_section<W: Write>(
+        &self,
+        writer: &mut W,
+        was_text: bool,
+        after_heading: bool,
+        last: bool,
+        options: &RenderOptions,
+    ) -> Result<bool, RoffError> {
+        let policy_options;
+        let options = match self.1 {
+            Some(policy) => {
+                policy_options = RenderOptions {
+                    escape_policy: policy,
+                    ..options.clone()
+                };
+                &policy_options
+            }
+            None => options,
+        };
+        let was_text = was_text && !after_heading;
+        match &*self.0 {
+            RoffNodeInner::Paragraph(content) if after_heading => {
+                let mut was_text = false;
+                for node in content {
+                    was_text = node.render(writer, was_text, options)?;
+                }
+                Ok(was_text)
+            }
+            RoffNodeInner::Break if after_heading || last => Ok(was_text),
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                if was_text {
+                    writer.write_all(ENDL)?;
+                }
+                render_synopsis(command, text, opts, !after_heading, !last, writer, options)?;
+                Ok(false)
+            }
+            inner => inner.render(writer, was_text, options),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+/// A read-only view of the content of a [`RoffNode`](RoffNode) returned by
+/// [`RoffNode::kind`](RoffNode::kind). Each variant matches one of the constructors of
+/// `RoffNode`. The text is borrowed as it was provided, before escaping.
+pub enum RoffNodeKind<'a> {
+    /// A [`text`](RoffNode::text) node.
+    Text(&'a RoffText),
+    /// A [`subheading`](RoffNode::subheading) of a section.
+    SubHeading(&'a RoffText),
+    /// A [`whatis`](RoffNode::whatis) line with the names and the description.
+    Whatis {
+        names: &'a [RoffText],
+        description: &'a RoffText,
+    },
+    /// A [`paragraph`](RoffNode::paragraph) with its content.
+    Paragraph(&'a [RoffNode]),
+    /// An [`indented paragraph`](RoffNode::indented_paragraph).
+    IndentedParagraph {
+        content: &'a [RoffNode],
+        indentation: Option<u8>,
+        title: Option<&'a RoffText>,
+    },
+    /// A [`tagged paragraph`](RoffNode::tagged_paragraph).
+    TaggedParagraph {
+        content: &'a [RoffNode],
+        title: &'a RoffText,
+    },
+    /// The lines of an [`example`](RoffNode::example) block.
+    Example(&'a [RoffText]),
+    /// Rows of [`columns`](RoffNode::columns) aligned with the tab stop.
+    Columns {
+        rows: &'a [(RoffText, RoffText)],
+        tab_stop: &'a RoffText,
+    },
+    /// A [`synopsis`](RoffNode::synopsis) block of a command.
+    Synopsis {
+        command: &'a RoffText,
+        text: &'a [RoffText],
+        opts: &'a [SynopsisItem],
+    },
+    /// An [`option list`](RoffNode::option_list).
+    OptionList(&'a [SynopsisItem]),
+    /// A [`url`](RoffNode::url) with its name.
+    Url {
+        name: &'a RoffText,
+        address: &'a RoffText,
+    },
+    /// An [`email`](RoffNode::email) address with its name.
+    Email {
+        name: &'a RoffText,
+        address: &'a RoffText,
+    },
+    RegisteredSign,
+    LeftQuote,
+    RightQuote,
+    TrademarkSign,
+    /// A [`nested`](RoffNode::nested) block.
+    Nested {
+        nodes: &'a [RoffNode],
+        indentation: Option<u8>,
+    },
+    /// A [`group`](RoffNode::group) of nodes rendered in place.
+    Group(&'a [RoffNode]),
+    Break,
+    EmDash,
+    EnDash,
+    NonBreakingSpace,
+    /// The text of a [`comment`](RoffNode::comment).
+    Comment(&'a str),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Base struct used to create ROFFs.
 pub(crate) enum RoffNodeInner {
     /// The most basic node type, contains only text with style.
     Text(RoffText),
+    /// A sub heading of a section.
+    SubHeading(RoffText),
+    /// Names of the documented commands followed by a short description.
+    Whatis {
+        names: Vec<RoffText>,
+        description: RoffText,
+    },
     /// A simple paragraph that can contain nested items.
-    Paragraph(Vec<RoffNodeInner>),
+    Paragraph(Vec<RoffNode>),
     /// Indented paragraph that can contain nested items. If no indentation is provided the default
     /// is `4`.
     IndentedParagraph {
-        content: Vec<RoffNodeInner>,
+        content: Vec<RoffNode>,
         indentation: Option<u8>,
         title: Option<RoffText>,
     },
     /// Paragraph with a title.
     TaggedParagraph {
-        content: Vec<RoffNodeInner>,
+        content: Vec<RoffNode>,
         title: RoffText,
     },
     /// An example block where text is monospaced.
     Example(Vec<RoffText>),
+    /// Two columns of text aligned with a tab stop.
+    Columns {
+        rows: Vec<(RoffText, RoffText)>,
+        tab_stop: RoffText,
+    },
     Synopsis {
         command: RoffText,
         text: Vec<RoffText>,
-        opts: Vec<SynopsisOpt>,
+        opts: Vec<SynopsisItem>,
     },
+    OptionList(Vec<SynopsisItem>),
     Url {
         name: RoffText,
         address: RoffText,
@@ -225,7 +711,12 @@ pub(crate) enum RoffNodeInner {
     LeftQuote,
     RightQuote,
     TrademarkSign,
-    Nested(Vec<RoffNode>),
+    Nested {
+        nodes: Vec<RoffNode>,
+        indentation: Option<u8>,
+    },
+    /// Nodes rendered in place without any surrounding macros.
+    Group(Vec<RoffNode>),
     Break,
     EmDash,
     EnDash,
@@ -233,11 +724,119 @@ pub(crate) enum RoffNodeInner {
     Comment(String),
 }
 
+/// Returns `nodes` with the children of [`group`s](RoffNode::group) in place of the groups, the
+/// way they are rendered. Children without an escape policy of their own take the one of their
+/// group. Borrows `nodes` if there are no groups.
+pub(crate) fn ungrouped(nodes: &[RoffNode]) -> Cow<'_, [RoffNode]> {
+    if !nodes
+        .iter()
+        .any(|node| matches!(*node.0, RoffNodeInner::Group(_)))
+    {
+        return Cow::Borrowed(nodes);
+    }
+    let mut out = Vec::with_capacity(nodes.len());
+    push_ungrouped(nodes, None, &mut out);
+    Cow::Owned(out)
+}
+
+fn push_ungrouped(nodes: &[RoffNode], policy: Option<EscapePolicy>, out: &mut Vec<RoffNode>) {
+    for node in nodes {
+        let policy = node.1.or(policy);
+        match &*node.0 {
+            RoffNodeInner::Group(children) => push_ungrouped(children, policy, out),
+            _ => out.push(RoffNode(Arc::clone(&node.0), policy)),
+        }
+    }
+}
+
+/// Returns the estimated size of all `nodes`.
+fn nodes_size(nodes: &[RoffNode]) -> usize {
+    nodes.iter().map(RoffNode::estimated_size).sum()
+}
+
+/// Returns the estimated size of all `texts`.
+fn texts_size(texts: &[RoffText]) -> usize {
+    texts.iter().map(RoffText::estimated_size).sum()
+}
+
 impl RoffNodeInner {
-    pub fn render<W: Write>(&self, writer: &mut W, mut was_text: bool) -> Result<bool, RoffError> {
+    /// Returns an estimate of the number of bytes this node takes when rendered, the size of the
+    /// content plus the macros surrounding it.
+    pub(crate) fn estimated_size(&self) -> usize {
+        match self {
+            RoffNodeInner::Text(text) => text.estimated_size(),
+            RoffNodeInner::SubHeading(title) => 6 + title.estimated_size(),
+            RoffNodeInner::Whatis { names, description } => {
+                texts_size(names) + 2 * names.len() + 4 + description.estimated_size()
+            }
+            RoffNodeInner::Paragraph(content) => 4 + nodes_size(content),
+            RoffNodeInner::IndentedParagraph { content, title, .. } => {
+                10 + title.as_ref().map(RoffText::estimated_size).unwrap_or(2) + nodes_size(content)
+            }
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                6 + title.estimated_size() + nodes_size(content)
+            }
+            RoffNodeInner::Example(content) => 8 + texts_size(content),
+            RoffNodeInner::Columns { rows, tab_stop } => {
+                14 + tab_stop.estimated_size()
+                    + rows
+                        .iter()
+                        .map(|(key, value)| key.estimated_size() + value.estimated_size() + 2)
+                        .sum::<usize>()
+            }
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                10 + command.estimated_size()
+                    + texts_size(text)
+                    + opts.iter().map(SynopsisItem::estimated_size).sum::<usize>()
+            }
+            RoffNodeInner::OptionList(opts) => opts.iter().map(SynopsisItem::estimated_size).sum(),
+            RoffNodeInner::Url { name, address } | RoffNodeInner::Email { name, address } => {
+                12 + name.estimated_size() + address.estimated_size()
+            }
+            RoffNodeInner::Nested { nodes, .. } => 10 + nodes_size(nodes),
+            RoffNodeInner::Group(nodes) => nodes_size(nodes),
+            RoffNodeInner::Comment(comment) => {
+                comment.len() + 4 * (comment.matches('\n').count() + 1)
+            }
+            RoffNodeInner::RegisteredSign
+            | RoffNodeInner::LeftQuote
+            | RoffNodeInner::RightQuote
+            | RoffNodeInner::TrademarkSign
+            | RoffNodeInner::Break
+            | RoffNodeInner::EmDash
+            | RoffNodeInner::EnDash
+            | RoffNodeInner::NonBreakingSpace => 4,
+        }
+    }
+
+    pub fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        mut was_text: bool,
+        options: &RenderOptions,
+    ) -> Result<bool, RoffError> {
         match self {
             RoffNodeInner::Text(text) => {
-                text.render(writer)?;
+                was_text = !render_texts([text], !was_text, writer, options)?;
+            }
+            RoffNodeInner::SubHeading(title) => {
+                if was_text {
+                    writer.write_all(ENDL)?;
+                }
+                writer.write_all(SUB_HEADER)?;
+                writer.write_all(SPACE)?;
+                write_quoted_if_whitespace(title, writer, options)?;
+                writer.write_all(ENDL)?;
+                was_text = false;
+            }
+            RoffNodeInner::Whatis { names, description } => {
+                RoffText::join(names.iter().cloned(), ", ").render_line_start(writer, options)?;
+                " - ".roff().render(writer, options)?;
+                description.render(writer, options)?;
                 was_text = true;
             }
             RoffNodeInner::Paragraph(content) => {
@@ -246,8 +845,9 @@ impl RoffNodeInner {
                 }
                 writer.write_all(PARAGRAPH)?;
                 writer.write_all(ENDL)?;
+                was_text = false;
                 for node in content {
-                    was_text = node.render(writer, was_text)?;
+                    was_text = node.render(writer, was_text, options)?;
                 }
             }
             RoffNodeInner::IndentedParagraph {
@@ -262,19 +862,22 @@ impl RoffNodeInner {
                 if let Some(indentation) = indentation {
                     writer.write_all(SPACE)?;
                     if let Some(title) = title {
-                        write_quoted_if_whitespace(title, writer)?;
+                        write_quoted_if_whitespace(title, writer, options)?;
                     } else {
                         writer.write_all(QUOTE)?;
                         writer.write_all(QUOTE)?;
                     }
                     writer.write_all(SPACE)?;
-                    indentation.roff().render(writer)?;
+                    indentation.roff().render(writer, options)?;
                 }
                 writer.write_all(ENDL)?;
+                was_text = false;
                 for node in content {
-                    was_text = node.render(writer, was_text)?;
+                    was_text = node.render(writer, was_text, options)?;
+                }
+                if was_text {
+                    writer.write_all(ENDL)?;
                 }
-                writer.write_all(ENDL)?;
                 was_text = false;
             }
             RoffNodeInner::TaggedParagraph {
@@ -286,26 +889,65 @@ impl RoffNodeInner {
                 }
                 writer.write_all(TAGGED_PARAGRAPH)?;
                 writer.write_all(ENDL)?;
-                tag.render(writer)?;
+                tag.render_line_start(writer, options)?;
                 writer.write_all(ENDL)?;
-
+                was_text = false;
                 for node in content {
-                    was_text = node.render(writer, was_text)?;
+                    was_text = node.render(writer, was_text, options)?;
+                }
+                if was_text {
+                    writer.write_all(ENDL)?;
                 }
-                writer.write_all(ENDL)?;
                 was_text = false;
             }
             RoffNodeInner::Example(content) => {
                 if was_text {
                     writer.write_all(ENDL)?;
                 }
-                writer.write_all(EXAMPLE_START)?;
+                let portable = options.compat == CompatLevel::Portable;
+                if portable {
+                    writer.write_all(NO_FILL)?;
+                    writer.write_all(ENDL)?;
+                    writer.write_all(CONSTANT_WIDTH_FONT)?;
+                } else {
+                    writer.write_all(EXAMPLE_START)?;
+                }
                 writer.write_all(ENDL)?;
-                for node in content {
-                    node.render(writer)?;
+                let options = &RenderOptions {
+                    no_fill: true,
+                    ..options.clone()
+                };
+                render_texts(content, true, writer, options)?;
+                writer.write_all(ENDL)?;
+                if portable {
+                    writer.write_all(ROMAN_FONT)?;
+                    writer.write_all(ENDL)?;
+                    writer.write_all(FILL)?;
+                } else {
+                    writer.write_all(EXAMPLE_END)?;
                 }
                 writer.write_all(ENDL)?;
-                writer.write_all(EXAMPLE_END)?;
+                was_text = false;
+            }
+            RoffNodeInner::Columns { rows, tab_stop } => {
+                if was_text {
+                    writer.write_all(ENDL)?;
+                }
+                writer.write_all(TAB_STOPS)?;
+                writer.write_all(SPACE)?;
+                tab_stop.render(writer, options)?;
+                writer.write_all(ENDL)?;
+                writer.write_all(NO_FILL)?;
+                writer.write_all(ENDL)?;
+                for (key, value) in rows {
+                    key.render_line_start(writer, options)?;
+                    writer.write_all(TAB)?;
+                    value.render(writer, options)?;
+                    writer.write_all(ENDL)?;
+                }
+                writer.write_all(FILL)?;
+                writer.write_all(ENDL)?;
+                writer.write_all(TAB_STOPS)?;
                 writer.write_all(ENDL)?;
                 was_text = false;
             }
@@ -317,78 +959,45 @@ impl RoffNodeInner {
                 if was_text {
                     writer.write_all(ENDL)?;
                 }
-                writer.write_all(SYNOPSIS_START)?;
-                writer.write_all(SPACE)?;
-                write_quoted_if_whitespace(command, writer)?;
-                writer.write_all(ENDL)?;
-                for elem in text {
-                    elem.render(writer)?;
-                }
-                if !text.is_empty() {
+                render_synopsis(command, text, opts, true, true, writer, options)?;
+                was_text = false;
+            }
+            RoffNodeInner::OptionList(opts) => {
+                if was_text {
                     writer.write_all(ENDL)?;
                 }
-                for op in opts {
-                    writer.write_all(ENDL)?;
-                    writer.write_all(SYNOPSIS_OPT)?;
-                    writer.write_all(SPACE)?;
-                    write_quoted_if_whitespace(&op.name, writer)?;
-                    if let Some(arg) = &op.argument {
-                        writer.write_all(SPACE)?;
-                        write_quoted_if_whitespace(arg, writer)?;
-                    }
-                    writer.write_all(ENDL)?;
-                    if let Some(description) = &op.description {
-                        for elem in description {
-                            elem.render(writer)?;
-                        }
-                    }
-                    writer.write_all(ENDL)?;
+                for item in opts {
+                    item.render_tagged(writer, options)?;
                 }
-                writer.write_all(SYNOPSIS_END)?;
-                writer.write_all(ENDL)?;
                 was_text = false;
             }
             RoffNodeInner::Url { address, name } => {
                 if was_text {
                     writer.write_all(ENDL)?;
                 }
-                writer.write_all(URL_START)?;
-                writer.write_all(SPACE)?;
-                address.render(writer)?;
-                writer.write_all(ENDL)?;
-                name.render(writer)?;
-                if !name.content().is_empty() {
-                    writer.write_all(ENDL)?;
-                }
-                writer.write_all(URL_END)?;
-                writer.write_all(ENDL)?;
+                render_link(URL_START, URL_END, name, address, writer, options)?;
                 was_text = false;
             }
             RoffNodeInner::Email { address, name } => {
                 if was_text {
                     writer.write_all(ENDL)?;
                 }
-                writer.write_all(MAIL_START)?;
-                writer.write_all(SPACE)?;
-                address.render(writer)?;
-                writer.write_all(ENDL)?;
-                name.render(writer)?;
-                if !name.content().is_empty() {
-                    writer.write_all(ENDL)?;
-                }
-                writer.write_all(MAIL_END)?;
-                writer.write_all(ENDL)?;
+                render_link(MAIL_START, MAIL_END, name, address, writer, options)?;
                 was_text = false;
             }
-            RoffNodeInner::Nested(nodes) => {
+            RoffNodeInner::Nested { nodes, indentation } => {
                 if was_text {
                     writer.write_all(ENDL)?;
                 }
                 writer.write_all(NESTED_START)?;
+                if let Some(indentation) = indentation {
+                    writer.write_all(SPACE)?;
+                    indentation.roff().render(writer, options)?;
+                }
                 writer.write_all(ENDL)?;
                 was_text = false;
                 for node in nodes {
-                    was_text = node.inner_ref().render(writer, was_text)?;
+                    was_text = node.render(writer, was_text, options)?;
                 }
 
                 if was_text {
@@ -398,10 +1007,18 @@ impl RoffNodeInner {
                 writer.write_all(ENDL)?;
                 was_text = false;
             }
+            RoffNodeInner::Group(nodes) => {
+                for node in nodes {
+                    was_text = node.render(writer, was_text, options)?;
+                }
+            }
             RoffNodeInner::Break => {
-                writer.write_all(ENDL)?;
+                if was_text {
+                    writer.write_all(ENDL)?;
+                }
                 writer.write_all(BREAK)?;
                 writer.write_all(ENDL)?;
+                was_text = false;
             }
             RoffNodeInner::RegisteredSign => {
                 writer.write_all(REGISTERED_SIGN)?;
@@ -432,10 +1049,23 @@ impl RoffNodeInner {
                 was_text = true;
             }
             RoffNodeInner::Comment(comment) => {
-                for line in comment.split('\n') {
-                    writer.write_all(COMMENT)?;
-                    writer.write_all(line.as_bytes())?;
-                    writer.write_all(ENDL)?;
+                // Only the first line can continue a text line, formatters treat the lines
+                // starting with a bare `\"` as blank lines.
+                let mut rest = Some(comment.as_str());
+                if was_text {
+                    let (first, other) = match comment.split_once('\n') {
+                        Some((first, other)) => (first, Some(other)),
+                        None => (comment.as_str(), None),
+                    };
+                    for line in comment_lines(first) {
+                        writer.write_all(COMMENT)?;
+                        writer.write_all(line.as_bytes())?;
+                        writer.write_all(ENDL)?;
+                    }
+                    rest = other;
+                }
+                if let Some(rest) = rest {
+                    write_comment_lines(rest, writer)?;
                 }
                 was_text = false
             }
@@ -445,8 +1075,98 @@ impl RoffNodeInner {
     }
 }
 
+/// Returns `true` if `tab_stop` is a valid distance for the tab stop of a
+/// [`columns`](RoffNode::columns) node, like `2i` or `1.5c`.
+pub(crate) fn is_valid_tab_stop(tab_stop: &str) -> bool {
+    let number = tab_stop.trim_end_matches(['c', 'i', 'm', 'M', 'n', 'p', 'P', 'u', 'v']);
+    if tab_stop.len() - number.len() > 1 {
+        return false;
+    }
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole.chars().all(|ch| ch.is_ascii_digit())
+        && fraction.chars().all(|ch| ch.is_ascii_digit())
+}
+
+/// Renders a synopsis block of the `command`. Output that can't rely on the synopsis macros gets
+/// plain lines instead, preceded by a line break if `break_before` is set and followed by one if
+/// `break_after` is set.
+fn render_synopsis<W: Write>(
+    command: &RoffText,
+    text: &[RoffText],
+    opts: &[SynopsisItem],
+    break_before: bool,
+    break_after: bool,
+    writer: &mut W,
+    options: &RenderOptions,
+) -> Result<(), RoffError> {
+    let portable = options.compat == CompatLevel::Portable;
+    if portable {
+        if break_before {
+            writer.write_all(BREAK)?;
+            writer.write_all(ENDL)?;
+        }
+        command.clone().bold().render_line_start(writer, options)?;
+    } else {
+        writer.write_all(SYNOPSIS_START)?;
+        writer.write_all(SPACE)?;
+        write_quoted_if_whitespace(command, writer, options)?;
+    }
+    writer.write_all(ENDL)?;
+    render_texts(text, true, writer, options)?;
+    if !text.is_empty() {
+        writer.write_all(ENDL)?;
+    }
+    for item in opts {
+        item.render(writer, options)?;
+    }
+    if !portable {
+        writer.write_all(SYNOPSIS_END)?;
+        writer.write_all(ENDL)?;
+    } else if break_after {
+        writer.write_all(BREAK)?;
+        writer.write_all(ENDL)?;
+    }
+    Ok(())
+}
+
+/// Renders a link between the `start` and `end` macros. Output that can't rely on the link
+/// macros gets a `name <address>` line instead, separated from the following text the same way.
+fn render_link<W: Write>(
+    start: &[u8],
+    end: &[u8],
+    name: &RoffText,
+    address: &RoffText,
+    writer: &mut W,
+    options: &RenderOptions,
+) -> Result<(), RoffError> {
+    if options.compat == CompatLevel::Portable {
+        let mut text = name.clone();
+        if !name.is_empty() {
+            text += " ".roff();
+        }
+        text += "<".roff();
+        text += address.clone();
+        text += ">".roff();
+        text.render_line_start(writer, options)?;
+        writer.write_all(ENDL)?;
+        return Ok(());
+    }
+    writer.write_all(start)?;
+    writer.write_all(SPACE)?;
+    write_quoted_if_whitespace(address, writer, options)?;
+    writer.write_all(ENDL)?;
+    name.render_line_start(writer, options)?;
+    if !name.is_empty() {
+        writer.write_all(ENDL)?;
+    }
+    writer.write_all(end)?;
+    writer.write_all(ENDL)?;
+    Ok(())
+}
+
 impl IntoRoffNode for RoffNodeInner {
     fn into_roff(self) -> RoffNode {
-        RoffNode(self)
+        RoffNode::from_inner(self)
     }
 }
@@ -0,0 +1,586 @@
+use crate::node::{ungrouped, RoffNodeInner};
+use crate::render::RenderOptions;
+use crate::synopsis::{SynopsisItem, SynopsisOpt};
+use crate::{Roff, RoffNode, RoffText};
+
+/// Indentation of the content of sections, the same as the one used by `man` on terminals.
+const SECTION_INDENT: usize = 7;
+/// Indentation of sub headings.
+const SUB_HEADING_INDENT: usize = 3;
+/// Indentation of tagged paragraphs and nested blocks.
+const DEFAULT_INDENT: usize = 7;
+/// Indentation of indented paragraphs that don't define their own.
+const PARAGRAPH_INDENT: usize = 4;
+
+impl Roff {
+    /// Renders this roff as formatted plain text with lines at most `width` characters long where
+    /// possible, roughly the way `man` displays the page on a terminal. Useful for quick
+    /// inspection in tests or to reuse the content of the page in other output.
+    ///
+    /// Section titles are uppercase and unindented, filled text is wrapped and indented by 7
+    /// spaces, tagged paragraphs have a hanging indent, examples are copied verbatim and URLs are
+    /// displayed as `name <address>`. Special characters are converted to their Unicode
+    /// equivalents and comments are skipped. The output is not meant to match the one of `groff`
+    /// exactly, but it is deterministic.
+    pub fn render_plain(&self, width: usize) -> String {
+        let mut writer = PlainWriter::new(width, &self.options);
+        self.write_plain(&mut writer);
+        for page in &self.pages {
+            writer.start_paragraph();
+            page.write_plain(&mut writer);
+        }
+        writer.finish()
+    }
+
+    fn write_plain(&self, writer: &mut PlainWriter<'_>) {
+        let id = self.display_title();
+        let manual = unescaped_or_empty(&self.manual);
+        writer.push_line(writer.header_line(&id, &manual, &id));
+        writer.separate = true;
+
+        for section in &self.sections {
+            writer.start_paragraph();
+            writer.push_line(section.get_title().unescaped().to_uppercase());
+            writer.heading = true;
+            if let Some(subtitle) = section.get_subtitle() {
+                writer.sub_heading(subtitle);
+            }
+            writer.indent = SECTION_INDENT;
+            writer.nodes(section.get_nodes());
+            writer.finish_text();
+            writer.indent = 0;
+        }
+
+        writer.start_paragraph();
+        let source = unescaped_or_empty(&self.source);
+        let date = unescaped_or_empty(&self.date);
+        writer.push_line(writer.header_line(&source, &date, &id));
+    }
+}
+
+/// Returns the text of an optional header field, or an empty string if it is not set.
+fn unescaped_or_empty(field: &Option<RoffText>) -> String {
+    field
+        .as_ref()
+        .map(|field| field.unescaped().into_owned())
+        .unwrap_or_default()
+}
+
+/// Lays out the nodes of a document as lines of plain text.
+struct PlainWriter<'o> {
+    width: usize,
+    options: &'o RenderOptions,
+    lines: Vec<String>,
+    /// Filled text waiting to be wrapped.
+    text: String,
+    /// Tag displayed in front of the filled text and the column at which it starts.
+    tag: Option<(usize, String)>,
+    /// Column at which the filled text starts.
+    indent: usize,
+    /// Whether a blank line separates the next line from the previous ones.
+    separate: bool,
+    /// Whether the last line is a heading that is never separated from the content.
+    heading: bool,
+    /// Whether the next text starts a new input line, where an empty line is a paragraph break.
+    line_start: bool,
+}
+
+impl<'o> PlainWriter<'o> {
+    fn new(width: usize, options: &'o RenderOptions) -> Self {
+        Self {
+            width,
+            options,
+            lines: vec![],
+            text: String::new(),
+            tag: None,
+            indent: 0,
+            separate: false,
+            heading: false,
+            line_start: true,
+        }
+    }
+
+    /// Returns all of the lines written so far terminated with newlines.
+    fn finish(mut self) -> String {
+        self.finish_text();
+        let mut out = self.lines.join("\n");
+        out.push('\n');
+        out
+    }
+
+    /// Returns a line with `left` and `right` at the edges and `center` in the middle.
+    fn header_line(&self, left: &str, center: &str, right: &str) -> String {
+        let used = left.chars().count() + center.chars().count() + right.chars().count();
+        let space = self.width.saturating_sub(used);
+        let before = (space / 2).max(1);
+        let after = (space - space / 2).max(1);
+        format!(
+            "{}{}{}{}{}",
+            left,
+            " ".repeat(before),
+            center,
+            " ".repeat(after),
+            right
+        )
+    }
+
+    /// Adds a complete `line`, preceded by a blank line if the previous block should be
+    /// separated.
+    fn push_line(&mut self, line: String) {
+        let heading = std::mem::take(&mut self.heading);
+        if std::mem::take(&mut self.separate) && !heading && !self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+        self.lines
+            .push(line.replace('\u{A0}', " ").trim_end().to_string());
+    }
+
+    /// Ends the current block of filled text, the next one is separated with a blank line.
+    fn start_paragraph(&mut self) {
+        self.finish_text();
+        self.separate = true;
+        self.line_start = true;
+    }
+
+    /// Ends the current line of filled text.
+    fn start_line(&mut self) {
+        self.finish_text();
+        self.line_start = true;
+    }
+
+    /// Appends `text` to the filled text. A line break is a space unless it makes an empty input
+    /// line, which ends the paragraph.
+    fn text(&mut self, text: &str) {
+        for ch in text.chars() {
+            if ch == '\n' {
+                if self.line_start {
+                    self.start_paragraph();
+                } else {
+                    self.text.push(' ');
+                }
+                self.line_start = true;
+                continue;
+            }
+            if self.line_start && !self.text.is_empty() {
+                self.text.push(' ');
+            }
+            self.text.push(ch);
+            self.line_start = false;
+        }
+    }
+
+    /// Wraps the filled text at the current indentation, preceded by the tag if there is one.
+    fn finish_text(&mut self) {
+        let text = std::mem::take(&mut self.text);
+        let tag = self.tag.take();
+        let mut words = text
+            .split([' ', '\t', '\n'])
+            .filter(|word| !word.is_empty())
+            .peekable();
+        if words.peek().is_none() && tag.as_ref().map_or(true, |(_, tag)| tag.is_empty()) {
+            return;
+        }
+
+        let mut line = String::new();
+        let mut column = 0;
+        if let Some((tag_column, tag)) = tag {
+            line = format!("{}{}", " ".repeat(tag_column), tag);
+            column = tag_column + tag.chars().count();
+            if column >= self.indent && words.peek().is_some() {
+                self.push_line(std::mem::take(&mut line));
+                column = 0;
+            }
+        }
+        for word in words {
+            let len = word.chars().count();
+            if line.is_empty() || column < self.indent {
+                line.push_str(&" ".repeat(self.indent - column));
+                column = self.indent;
+            } else if column + 1 + len > self.width {
+                self.push_line(std::mem::take(&mut line));
+                line.push_str(&" ".repeat(self.indent));
+                column = self.indent;
+            } else {
+                line.push(' ');
+                column += 1;
+            }
+            line.push_str(word);
+            column += len;
+        }
+        self.push_line(line);
+    }
+
+    fn sub_heading(&mut self, title: &RoffText) {
+        self.start_paragraph();
+        self.push_line(format!(
+            "{}{}",
+            " ".repeat(SUB_HEADING_INDENT),
+            title.unescaped()
+        ));
+        self.heading = true;
+        self.line_start = true;
+    }
+
+    /// Lays out the filled text of `nodes` with `tag` in front of it and a hanging indent.
+    fn tagged(&mut self, tag: String, hang: usize, nodes: &[RoffNode]) {
+        self.start_paragraph();
+        let indent = self.indent;
+        self.tag = Some((indent, tag));
+        self.indent += hang;
+        self.nodes(nodes);
+        self.finish_text();
+        self.indent = indent;
+        self.line_start = true;
+    }
+
+    fn nodes(&mut self, nodes: &[RoffNode]) {
+        for node in ungrouped(nodes).iter() {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &RoffNode) {
+        match node.inner_ref() {
+            RoffNodeInner::Text(text) => self.text(&text.unescaped()),
+            RoffNodeInner::SubHeading(title) => self.sub_heading(title),
+            RoffNodeInner::Whatis { names, description } => {
+                let names: Vec<_> = names.iter().map(RoffText::unescaped).collect();
+                self.text(&format!(
+                    "{} - {}",
+                    names.join(", "),
+                    description.unescaped()
+                ));
+            }
+            RoffNodeInner::Paragraph(content) => {
+                self.start_paragraph();
+                self.nodes(content);
+            }
+            RoffNodeInner::IndentedParagraph {
+                content,
+                indentation,
+                title,
+            } => {
+                let hang = indentation.map_or(PARAGRAPH_INDENT, usize::from);
+                let tag = match (indentation, title) {
+                    (Some(_), Some(title)) => title.unescaped().into_owned(),
+                    _ => String::new(),
+                };
+                self.tagged(tag, hang, content);
+            }
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                self.tagged(title.unescaped().into_owned(), DEFAULT_INDENT, content);
+            }
+            RoffNodeInner::Example(content) => {
+                self.start_line();
+                let content: String = content.iter().map(RoffText::unescaped).collect();
+                for line in content.split('\n') {
+                    self.push_line(format!("{}{}", " ".repeat(self.indent), line));
+                }
+            }
+            RoffNodeInner::Columns { rows, tab_stop } => {
+                self.start_line();
+                let tab_stop = tab_stop.unescaped();
+                let digits = tab_stop
+                    .find(|ch: char| !ch.is_ascii_digit())
+                    .unwrap_or(tab_stop.len());
+                let column = tab_stop[..digits].parse::<usize>().unwrap_or_else(|_| {
+                    rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0) + 2
+                });
+                for (key, value) in rows {
+                    let key = key.unescaped();
+                    let padding = column.saturating_sub(key.chars().count()).max(1);
+                    self.push_line(format!(
+                        "{}{}{}{}",
+                        " ".repeat(self.indent),
+                        key,
+                        " ".repeat(padding),
+                        value.unescaped()
+                    ));
+                }
+            }
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                self.start_paragraph();
+                let indent = self.indent;
+                let command = command.unescaped();
+                self.tag = Some((indent, command.to_string()));
+                self.indent += command.chars().count() + 1;
+                for elem in text {
+                    self.text(&elem.unescaped());
+                }
+                self.start_line();
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.synopsis_opt(opt),
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.text(&group.title.unescaped());
+                            self.start_line();
+                            for opt in &group.opts {
+                                self.synopsis_opt(opt);
+                            }
+                        }
+                    }
+                }
+                self.indent = indent;
+            }
+            RoffNodeInner::OptionList(opts) => {
+                for item in opts {
+                    match item {
+                        SynopsisItem::Opt(opt) => self.tagged_opt(opt),
+                        SynopsisItem::Group(group) => {
+                            if !group.opts.iter().any(|opt| opt.is_visible(self.options)) {
+                                continue;
+                            }
+                            self.sub_heading(&group.title);
+                            for opt in &group.opts {
+                                self.tagged_opt(opt);
+                            }
+                        }
+                    }
+                }
+            }
+            RoffNodeInner::Url { name, address } | RoffNodeInner::Email { name, address } => {
+                self.line_start = true;
+                if name.is_empty() {
+                    self.text(&address.unescaped());
+                } else {
+                    self.text(&format!("{} <{}>", name.unescaped(), address.unescaped()));
+                }
+                self.line_start = true;
+            }
+            RoffNodeInner::RegisteredSign => self.text("®"),
+            RoffNodeInner::LeftQuote => self.text("“"),
+            RoffNodeInner::RightQuote => self.text("”"),
+            RoffNodeInner::TrademarkSign => self.text("™"),
+            RoffNodeInner::EmDash => self.text("—"),
+            RoffNodeInner::EnDash => self.text("–"),
+            RoffNodeInner::NonBreakingSpace => self.text("\u{A0}"),
+            RoffNodeInner::Nested { nodes, indentation } => {
+                self.start_line();
+                let indent = self.indent;
+                self.indent += indentation.map_or(DEFAULT_INDENT, usize::from);
+                self.nodes(nodes);
+                self.start_line();
+                self.indent = indent;
+            }
+            RoffNodeInner::Group(nodes) => self.nodes(nodes),
+            RoffNodeInner::Break => self.start_line(),
+            RoffNodeInner::Comment(_) => {}
+        }
+    }
+
+    /// Lays out a single option of a synopsis as `[name argument] description`.
+    fn synopsis_opt(&mut self, opt: &SynopsisOpt) {
+        if !opt.is_visible(self.options) {
+            return;
+        }
+        self.text(&format!("[{}] {}", opt_tag(opt), opt_description(opt)));
+        self.start_line();
+    }
+
+    /// Lays out a single option of an options list as a tagged paragraph.
+    fn tagged_opt(&mut self, opt: &SynopsisOpt) {
+        if !opt.is_visible(self.options) {
+            return;
+        }
+        self.tagged(
+            opt_tag(opt),
+            DEFAULT_INDENT,
+            &[RoffNode::text(opt_description(opt))],
+        );
+    }
+}
+
+/// Returns the name of `opt` followed by its argument or the inlined values.
+fn opt_tag(opt: &SynopsisOpt) -> String {
+    let mut tag = opt.name.unescaped().into_owned();
+    let argument = if opt.inline_values && !opt.possible_values.is_empty() {
+        let values: Vec<_> = opt
+            .possible_values
+            .iter()
+            .map(RoffText::unescaped)
+            .collect();
+        Some(values.join("|"))
+    } else {
+        opt.argument
+            .as_ref()
+            .map(|argument| argument.unescaped().into_owned())
+    };
+    if let Some(argument) = argument {
+        tag.push(' ');
+        tag.push_str(&argument);
+    }
+    tag
+}
+
+/// Returns the description of `opt` followed by the possible values and the default value.
+fn opt_description(opt: &SynopsisOpt) -> String {
+    let mut parts = vec![];
+    if let Some(description) = &opt.description {
+        let description: String = description.iter().map(RoffText::unescaped).collect();
+        if !description.is_empty() {
+            parts.push(description);
+        }
+    }
+    if !opt.possible_values.is_empty() {
+        let values: Vec<_> = opt
+            .possible_values
+            .iter()
+            .map(RoffText::unescaped)
+            .collect();
+        parts.push(format!("Possible values: {}", values.join(", ")));
+    }
+    if let Some(default_value) = &opt.default_value {
+        parts.push(format!("(default: {})", default_value.unescaped()));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::readme_example;
+    use crate::{
+        Roff, RoffNode, RoffText, Section, SectionNumber, SynopsisItem, SynopsisOpt,
+        SynopsisOptGroup,
+    };
+
+    #[test]
+    fn it_renders_the_readme_example() {
+        assert_eq!(
+            readme_example().render_plain(80),
+            r#"roffman(7)                                                            roffman(7)
+
+BASIC USAGE
+       This is how you create a basic paragraph using roffman.
+
+       optional-title
+           This line should be slightly indented to the right.
+
+       roffman-command This is the description of this command. It will be
+                       displayed right next to it
+                       [--opt] some simple opt
+                       [--opt-with-arg ARG] opt with an argument
+                       [--bold]
+
+       Example:
+
+       impl Roffable for u8 {
+           fn roff(&self) -> RoffText {
+               self.to_string().roff()
+           }
+       }
+       GitHub <https://github.com/vv9k/roffman>
+
+       vv9k™
+
+                             August 2021                              roffman(7)
+"#
+        );
+    }
+
+    #[test]
+    fn it_renders_every_node() {
+        let roff = Roff::new("plain", SectionNumber::UserCommands)
+            .section(
+                "name",
+                [RoffNode::whatis(["plain", "preview"], "render a preview")],
+            )
+            .add_section(
+                Section::new(
+                    "DESCRIPTION",
+                    [
+                        RoffNode::text("Text with a"),
+                        RoffNode::non_breaking_space(),
+                        RoffNode::text("no-break space, "),
+                        RoffNode::left_quote(),
+                        RoffNode::text("quotes"),
+                        RoffNode::right_quote(),
+                        RoffNode::em_dash(),
+                        RoffNode::text("dashes"),
+                        RoffNode::en_dash(),
+                        RoffNode::text("and signs"),
+                        RoffNode::registered_sign(),
+                        RoffNode::comment("not displayed"),
+                        RoffNode::linebreak(),
+                        RoffNode::text(RoffText::raw("raw\\(em\\[u2713]")),
+                    ],
+                )
+                .subtitle("Subtitle"),
+            )
+            .section(
+                "OPTIONS",
+                [
+                    RoffNode::tagged_paragraph(["tagged body"], "-t"),
+                    RoffNode::tagged_paragraph(["long tag body"], "--long-tag"),
+                    RoffNode::option_list::<_, SynopsisItem>([
+                        SynopsisOpt::new("--color")
+                            .argument("WHEN")
+                            .description(["colorize"])
+                            .possible_values(["auto", "never"])
+                            .default_value("auto")
+                            .into(),
+                        SynopsisOptGroup::new(
+                            "Hidden",
+                            [SynopsisOpt::new("--secret").hidden(true)],
+                        )
+                        .into(),
+                        SynopsisOptGroup::new("Group", [SynopsisOpt::new("-v")]).into(),
+                    ]),
+                    RoffNode::subheading("Columns"),
+                    RoffNode::columns([("a", "first"), ("longer", "second")], "8"),
+                    RoffNode::nested([RoffNode::text("nested text")]),
+                    RoffNode::email("Me", "me@example.com"),
+                    RoffNode::url("", "https://example.com"),
+                ],
+            )
+            .append_page(Roff::new("appended", SectionNumber::UserCommands));
+
+        assert_eq!(
+            roff.render_plain(40),
+            r#"plain(1)                        plain(1)
+
+NAME
+       plain, preview - render a preview
+
+DESCRIPTION
+   Subtitle
+       Text with a no-break space,
+       “quotes”—dashes–and signs®
+       raw—✓
+
+OPTIONS
+       -t     tagged body
+
+       --long-tag
+              long tag body
+
+       --color WHEN
+              colorize Possible values:
+              auto, never (default:
+              auto)
+
+   Group
+       -v
+
+   Columns
+       a       first
+       longer  second
+              nested text
+       Me <me@example.com>
+       https://example.com
+
+                                plain(1)
+
+appended(1)                  appended(1)
+
+                             appended(1)
+"#
+        );
+    }
+}
@@ -0,0 +1,986 @@
+use crate::escape::{is_combining, named_glyph};
+use crate::{EscapePolicy, RoffError};
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Write};
+use std::iter::Peekable;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Options that alter the way a document is rendered. Set them on a document with
+/// [`Roff::render_options`](crate::Roff::render_options).
+pub struct RenderOptions {
+    /// Whether hidden synopsis options should be rendered.
+    pub(crate) include_hidden: bool,
+    /// Font used for [`FontStyle::Literal`](crate::FontStyle::Literal) text.
+    pub(crate) literal_font: LiteralFont,
+    /// Whether spaces and tabs at the end of the rendered lines should be removed.
+    pub(crate) strip_trailing_whitespace: bool,
+    /// Policy used to escape text that doesn't define its own.
+    pub(crate) escape_policy: EscapePolicy,
+    /// Whether the text is rendered in a no-fill block like an example, where the lines are
+    /// displayed as they are.
+    pub(crate) no_fill: bool,
+    /// Column at which the source lines of filled text are wrapped.
+    pub(crate) max_line_width: Option<usize>,
+    /// Whether the output ends with a single newline and has no runs of blank lines.
+    pub(crate) normalize_output: bool,
+    /// Whether each sentence of filled text starts on a new source line.
+    pub(crate) sentence_per_line: bool,
+    /// Set of macros that the output can rely on.
+    pub(crate) compat: CompatLevel,
+    /// Character set the output is restricted to.
+    pub(crate) encoding: OutputEncoding,
+    /// Whether the title header is left without a manual when only the source is set.
+    pub(crate) no_default_manual: bool,
+}
+
+impl RenderOptions {
+    /// Creates the default options that render the document exactly as it was defined.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forces hidden options to be rendered. Useful for internal builds of the documentation.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Selects the font used for [`literal`](crate::RoffText::literal) text.
+    pub fn literal_font(mut self, font: LiteralFont) -> Self {
+        self.literal_font = font;
+        self
+    }
+
+    /// Sets the policy used to escape text. Text with its own policy set with
+    /// [`RoffText::with_policy`](crate::RoffText::with_policy) is not affected.
+    pub fn escape_policy(mut self, policy: EscapePolicy) -> Self {
+        self.escape_policy = policy;
+        self
+    }
+
+    /// Removes spaces and tabs at the end of each rendered line. Trailing whitespace may produce
+    /// extra space in justified output and is reported by `mandoc -Tlint`.
+    pub fn strip_trailing_whitespace(mut self, strip: bool) -> Self {
+        self.strip_trailing_whitespace = strip;
+        self
+    }
+
+    /// Wraps the source lines of filled text at word boundaries so that they are at most `width`
+    /// characters long where possible. Only the generated source is affected, the lines are
+    /// joined again when the page is formatted. Control lines, the tags of tagged paragraphs and
+    /// example blocks are never wrapped, and neither are escape sequences or comments. Lines are
+    /// not wrapped by default.
+    pub fn max_line_width(mut self, width: usize) -> Self {
+        self.max_line_width = Some(width);
+        self
+    }
+
+    /// Normalizes the rendered output so that it ends with exactly one newline and runs of blank
+    /// lines are collapsed into a single one, except in example blocks where the lines are
+    /// displayed as they are. Both are reported by `mandoc -Tlint` and make diffs of the generated
+    /// pages noisy. Disabled by default.
+    pub fn normalize_output(mut self, normalize: bool) -> Self {
+        self.normalize_output = normalize;
+        self
+    }
+
+    /// Starts each sentence of filled text on a new source line, as recommended by
+    /// man-pages(7), so that the formatter puts the inter-sentence space after it and diffs of
+    /// the pages stay small. A sentence ends with `.`, `?` or `!`, optionally followed by closing
+    /// quotes or parentheses, before a space and a capital letter. A period after an initial or a
+    /// common abbreviation like `e.g.` or `Dr.` doesn't end a sentence. Others can be kept
+    /// together with a [`non_breaking_space`](crate::RoffNode::non_breaking_space) or by
+    /// following the period with a raw `\&`. Control lines, the tags of tagged paragraphs and
+    /// example blocks are not affected. Disabled by default.
+    pub fn sentence_per_line(mut self, split: bool) -> Self {
+        self.sentence_per_line = split;
+        self
+    }
+
+    /// Selects the set of macros the output relies on. Defaults to
+    /// [`CompatLevel::Groff`](CompatLevel::Groff).
+    pub fn compat(mut self, compat: CompatLevel) -> Self {
+        self.compat = compat;
+        self
+    }
+
+    /// Restricts the whole output, including the title, the date and the comments, to the
+    /// character set of `encoding`. Defaults to [`OutputEncoding::Utf8`](OutputEncoding::Utf8).
+    pub fn encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Whether the title header of a document with a source but no manual gets the
+    /// [`manual name`](crate::SectionNumber::manual_name) of its section, like `User Commands`
+    /// for section 1. Enabled by default, disable it to leave the manual out.
+    pub fn default_manual(mut self, enabled: bool) -> Self {
+        self.no_default_manual = !enabled;
+        self
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Font used to render [`literal`](crate::RoffText::literal) text.
+pub enum LiteralFont {
+    /// Constant-width font. Best suited for typeset output like PDF, on terminals `man` displays
+    /// it like regular text.
+    #[default]
+    ConstantWidth,
+    /// Bold font. Stands out on terminals which don't have a constant-width font.
+    Bold,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Set of macros that the rendered output relies on.
+pub enum CompatLevel {
+    /// Uses the `.SY`/`.OP`/`.YS` synopsis, `.UR`/`.UE` and `.MT`/`.ME` link and `.EX`/`.EE`
+    /// example macros, supported by groff, mandoc and most of the current `man` implementations.
+    #[default]
+    Groff,
+    /// Avoids the macros missing from older `troff` implementations, like the ones of Solaris or
+    /// embedded systems. Synopsis blocks are rendered as bold and italic text with the options in
+    /// brackets, links as `name <address>` text and examples with the `.nf` and `.ft CW`
+    /// requests.
+    Portable,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Character set of the rendered output. Characters outside of it are replaced with groff named
+/// glyphs like `\('e`, or `\[uXXXX]` escapes if they have no name. Comments can't contain
+/// escapes, so rendering fails with [`RoffError::Encoding`](crate::RoffError::Encoding) if a comment
+/// contains such characters.
+pub enum OutputEncoding {
+    /// The output is written as it is, encoded as UTF-8.
+    #[default]
+    Utf8,
+    /// The output is restricted to ASCII.
+    Ascii,
+    /// The output is encoded as ISO 8859-1, every character that has a Latin-1 byte is written as
+    /// that byte. [`Roff::render_to_string`](crate::Roff::render_to_string) and the other
+    /// renderers to `fmt::Write` sinks return the same characters as a string.
+    Latin1,
+}
+
+impl OutputEncoding {
+    /// Returns `true` if `ch` can be written as it is.
+    fn represents(self, ch: char) -> bool {
+        match self {
+            OutputEncoding::Utf8 => true,
+            OutputEncoding::Ascii => ch.is_ascii(),
+            OutputEncoding::Latin1 => u32::from(ch) <= 0xFF,
+        }
+    }
+}
+
+impl fmt::Display for OutputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputEncoding::Utf8 => "UTF-8",
+            OutputEncoding::Ascii => "ASCII",
+            OutputEncoding::Latin1 => "Latin-1",
+        })
+    }
+}
+
+/// Writer that passes the rendered output on to a [`fmt::Write`] sink, like a `String` or a
+/// `Formatter`. The bytes of a character split between two writes are held back until the rest of
+/// it arrives.
+pub(crate) struct FmtWriter<'w, F: fmt::Write> {
+    inner: &'w mut F,
+    pending: Vec<u8>,
+    failed: bool,
+    /// Whether the output is Latin-1, where every byte is a character.
+    latin1: bool,
+}
+
+impl<'w, F: fmt::Write> FmtWriter<'w, F> {
+    pub(crate) fn new(inner: &'w mut F, encoding: OutputEncoding) -> Self {
+        Self {
+            inner,
+            pending: vec![],
+            failed: false,
+            latin1: encoding == OutputEncoding::Latin1,
+        }
+    }
+
+    /// Maps an error returned while rendering to this writer, so that a failure of the sink is
+    /// reported as [`RoffError::FormatFailed`](RoffError::FormatFailed).
+    pub(crate) fn map_error(&self, err: RoffError) -> RoffError {
+        match err {
+            _ if self.failed => RoffError::FormatFailed(fmt::Error),
+            RoffError::RenderFailed(err) if err.kind() == io::ErrorKind::InvalidData => {
+                RoffError::StringRenderFailed(err.to_string())
+            }
+            RoffError::NodeRenderFailed { path, error }
+                if error.kind() == io::ErrorKind::InvalidData =>
+            {
+                RoffError::StringRenderFailed(format!("{} at {}", error, path))
+            }
+            err => err,
+        }
+    }
+
+    /// Returns an error if the output ended in the middle of a character.
+    pub(crate) fn finish(self) -> Result<(), RoffError> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(RoffError::StringRenderFailed(
+                "incomplete utf-8 byte sequence at the end of the output".to_string(),
+            ))
+        }
+    }
+}
+
+impl<F: fmt::Write> Write for FmtWriter<'_, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.latin1 {
+            let text: String = buf.iter().map(|byte| char::from(*byte)).collect();
+            if self.inner.write_str(&text).is_err() {
+                self.failed = true;
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "failed to write to the formatter",
+                ));
+            }
+            return Ok(buf.len());
+        }
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+        let text = std::str::from_utf8(&self.pending[..valid])
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if self.inner.write_str(text).is_err() {
+            self.failed = true;
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "failed to write to the formatter",
+            ));
+        }
+        self.pending.drain(..valid);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer batching the rendered output and applying the post-processing selected by the render
+/// options.
+pub(crate) type Output<W> =
+    BatchWriter<OutputNormalizer<LineWrapper<TrailingWhitespaceStripper<Encoder<W>>>>>;
+
+/// Wraps `writer` with the post-processing selected by `options`. The stages that are not
+/// selected pass the output through.
+pub(crate) fn output<W: Write>(writer: W, options: &RenderOptions) -> Output<W> {
+    let writer = Encoder::new(writer, options.encoding);
+    let writer = TrailingWhitespaceStripper::new(writer, options.strip_trailing_whitespace);
+    let writer = LineWrapper::new(writer, options.max_line_width, options.sentence_per_line);
+    BatchWriter::new(OutputNormalizer::new(writer, options.normalize_output))
+}
+
+impl<W: Write> Output<W> {
+    /// Returns the writer passed to [`output`], below all of the post-processing stages.
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        self.get_mut().get_mut().get_mut().get_mut().get_mut()
+    }
+
+    /// Flushes all of the stages and returns the writer passed to [`output`], or the error of a
+    /// character that couldn't be encoded.
+    pub(crate) fn into_writer(mut self) -> Result<W, RoffError> {
+        self.flush()?;
+        self.into_inner()
+            .into_inner()
+            .into_inner()
+            .into_inner()
+            .finish()
+    }
+}
+
+/// Size of the chunks in which [`BatchWriter`] passes on the output.
+pub(crate) const BATCH_SIZE: usize = 8 * 1024;
+
+/// Writer that collects the many small writes of the renderer, like a macro name followed by a
+/// space, in a buffer that is passed on in chunks of [`BATCH_SIZE`] bytes or on flush.
+pub(crate) struct BatchWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> BatchWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(BATCH_SIZE),
+        }
+    }
+
+    /// Passes on the buffered output without flushing the inner writer.
+    pub(crate) fn write_buffered(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for BatchWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(buf);
+        if self.buf.len() >= BATCH_SIZE {
+            self.write_buffered()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.inner.flush()
+    }
+}
+
+/// Writer that drops spaces and tabs written right before a line break. Whitespace that is not
+/// followed by a line break is written once more content arrives or on flush.
+pub(crate) struct TrailingWhitespaceStripper<W: Write> {
+    inner: W,
+    enabled: bool,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> TrailingWhitespaceStripper<W> {
+    pub(crate) fn new(inner: W, enabled: bool) -> Self {
+        Self {
+            inner,
+            enabled,
+            pending: vec![],
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for TrailingWhitespaceStripper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled {
+            return self.inner.write(buf);
+        }
+        let mut start = 0;
+        for (i, byte) in buf.iter().enumerate() {
+            match byte {
+                b' ' | b'\t' => {
+                    self.inner.write_all(&buf[start..i])?;
+                    self.pending.push(*byte);
+                    start = i + 1;
+                }
+                b'\n' => self.pending.clear(),
+                _ => {
+                    if !self.pending.is_empty() {
+                        self.inner.write_all(&self.pending)?;
+                        self.pending.clear();
+                    }
+                }
+            }
+        }
+        self.inner.write_all(&buf[start..])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.write_all(&self.pending)?;
+        self.pending.clear();
+        self.inner.flush()
+    }
+}
+
+/// Writer that restricts the output to an [`OutputEncoding`]. Characters outside of it are
+/// replaced with escapes, except in comments where they are replaced with `?` and reported by
+/// [`finish`](Encoder::finish). Every character is held back until the next one arrives, so that
+/// the combining marks following it end up in the same escape.
+pub(crate) struct Encoder<W: Write> {
+    inner: W,
+    encoding: OutputEncoding,
+    /// Bytes of a character split between two writes.
+    pending: Vec<u8>,
+    /// The last character with the combining marks following it.
+    held: Vec<char>,
+    comment: bool,
+    backslash: bool,
+    /// Number of the output line the held character is on.
+    line: usize,
+    /// The distinct characters of comments that couldn't be represented and the line each of them
+    /// first occurs on.
+    unrepresentable: Vec<(char, usize)>,
+}
+
+impl<W: Write> Encoder<W> {
+    pub(crate) fn new(inner: W, encoding: OutputEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            pending: vec![],
+            held: vec![],
+            comment: false,
+            backslash: false,
+            line: 1,
+            unrepresentable: vec![],
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the inner writer, or an error with all of the characters of comments that couldn't
+    /// be represented.
+    pub(crate) fn finish(self) -> Result<W, RoffError> {
+        if self.unrepresentable.is_empty() {
+            Ok(self.inner)
+        } else {
+            Err(RoffError::Encoding {
+                encoding: self.encoding,
+                characters: self.unrepresentable,
+            })
+        }
+    }
+
+    /// Encodes the held character with its combining marks to `out`.
+    fn encode_held(&mut self, out: &mut Vec<u8>) {
+        let (ch, marks) = match self.held.split_first() {
+            Some((ch, marks)) => (*ch, marks),
+            None => return,
+        };
+        let backslash = std::mem::take(&mut self.backslash);
+        match ch {
+            '\n' => {
+                self.comment = false;
+                self.line += 1;
+            }
+            _ if self.comment => {}
+            '\\' => self.backslash = !backslash,
+            '"' => self.comment = backslash,
+            _ => {}
+        }
+
+        let representable = self.held.iter().all(|ch| self.encoding.represents(*ch));
+        if representable {
+            for ch in &self.held {
+                match self.encoding {
+                    OutputEncoding::Latin1 => out.push(u32::from(*ch) as u8),
+                    _ => out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes()),
+                }
+            }
+        } else if self.comment {
+            for ch in &self.held {
+                let known = self.unrepresentable.iter().any(|(other, _)| other == ch);
+                if !known && !self.encoding.represents(*ch) {
+                    self.unrepresentable.push((*ch, self.line));
+                }
+            }
+            out.push(b'?');
+        } else if let Some(glyph) = named_glyph(ch).filter(|_| marks.is_empty()) {
+            out.extend_from_slice(glyph.as_bytes());
+        } else {
+            let codes: Vec<_> = self
+                .held
+                .iter()
+                .map(|ch| format!("{:04X}", u32::from(*ch)))
+                .collect();
+            out.extend_from_slice(format!("\\[u{}]", codes.join("_")).as_bytes());
+        }
+        self.held.clear();
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoding == OutputEncoding::Utf8 {
+            return self.inner.write(buf);
+        }
+        self.pending.extend_from_slice(buf);
+        let valid = match std::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+        };
+        let pending = std::mem::take(&mut self.pending);
+        let text = std::str::from_utf8(&pending[..valid])
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut out = Vec::with_capacity(text.len());
+        for ch in text.chars() {
+            if !is_combining(ch) || self.held.is_empty() {
+                self.encode_held(&mut out);
+            }
+            self.held.push(ch);
+        }
+        self.pending.extend_from_slice(&pending[valid..]);
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut out = vec![];
+        self.encode_held(&mut out);
+        self.inner.write_all(&out)?;
+        self.inner.flush()
+    }
+}
+
+/// Returns the name of the request on a control `line`, or `None` if it's not a control line.
+fn request_name(line: &[u8]) -> Option<&[u8]> {
+    match line.first() {
+        Some(b'.') | Some(b'\'') => {
+            let name = &line[1..];
+            let start = name
+                .iter()
+                .position(|byte| *byte != b' ' && *byte != b'\t')
+                .unwrap_or(name.len());
+            let name = &name[start..];
+            let end = name
+                .iter()
+                .position(|byte| *byte == b' ' || *byte == b'\t')
+                .unwrap_or(name.len());
+            Some(&name[..end])
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether the text is filled after the request `name`, or `None` if the request
+/// doesn't change it.
+fn no_fill_after(name: &[u8]) -> Option<bool> {
+    match name {
+        b"EX" | b"nf" => Some(true),
+        b"EE" | b"fi" => Some(false),
+        _ => None,
+    }
+}
+
+/// Writer that makes the output end with exactly one newline and collapses runs of blank lines
+/// outside of no-fill blocks. Blank lines are held back until more content arrives, so that the
+/// blank lines at the end of the output are dropped on flush.
+pub(crate) struct OutputNormalizer<W: Write> {
+    inner: W,
+    enabled: bool,
+    line: Vec<u8>,
+    blank_pending: bool,
+    no_fill: bool,
+}
+
+impl<W: Write> OutputNormalizer<W> {
+    pub(crate) fn new(inner: W, enabled: bool) -> Self {
+        Self {
+            inner,
+            enabled,
+            line: vec![],
+            blank_pending: false,
+            no_fill: false,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn end_line(&mut self) -> io::Result<()> {
+        let line = std::mem::take(&mut self.line);
+        if line.is_empty() && !self.no_fill {
+            self.blank_pending = true;
+            return Ok(());
+        }
+        if std::mem::take(&mut self.blank_pending) {
+            self.inner.write_all(b"\n")?;
+        }
+        if let Some(no_fill) = request_name(&line).and_then(no_fill_after) {
+            self.no_fill = no_fill;
+        }
+        self.inner.write_all(&line)?;
+        self.inner.write_all(b"\n")
+    }
+}
+
+impl<W: Write> Write for OutputNormalizer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled {
+            return self.inner.write(buf);
+        }
+        let mut rest = buf;
+        while let Some(pos) = rest.iter().position(|byte| *byte == b'\n') {
+            self.line.extend_from_slice(&rest[..pos]);
+            self.end_line()?;
+            rest = &rest[pos + 1..];
+        }
+        self.line.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.end_line()?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Writer that splits the filled text lines of the rendered document at sentence boundaries
+/// and wraps them at `width` columns. Lines are buffered until they are complete, so the last
+/// line is only written on flush.
+pub(crate) struct LineWrapper<W: Write> {
+    inner: W,
+    width: Option<usize>,
+    split_sentences: bool,
+    line: Vec<u8>,
+    no_fill: bool,
+    tag_next: bool,
+}
+
+impl<W: Write> LineWrapper<W> {
+    /// Creates a wrapper that passes the output through if there is no `width` and sentences are
+    /// not split.
+    pub(crate) fn new(inner: W, width: Option<usize>, split_sentences: bool) -> Self {
+        Self {
+            inner,
+            width,
+            split_sentences,
+            line: vec![],
+            no_fill: false,
+            tag_next: false,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+
+    /// Writes the buffered line, wrapped if it is filled text.
+    fn write_line(&mut self) -> io::Result<()> {
+        let line = std::mem::take(&mut self.line);
+        if let Some(name) = request_name(&line) {
+            if let Some(no_fill) = no_fill_after(name) {
+                self.no_fill = no_fill;
+            }
+            self.tag_next = name == b"TP";
+            return self.inner.write_all(&line);
+        }
+        if self.no_fill || std::mem::take(&mut self.tag_next) {
+            return self.inner.write_all(&line);
+        }
+        let text = match std::str::from_utf8(&line) {
+            Ok(text) => text,
+            Err(_) => return self.inner.write_all(&line),
+        };
+        let text = if self.split_sentences {
+            split_sentences(text)
+        } else {
+            Cow::Borrowed(text)
+        };
+        match self.width {
+            Some(width) => {
+                for (i, sentence) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        self.inner.write_all(b"\n")?;
+                    }
+                    self.inner
+                        .write_all(wrap_line(sentence, width).as_bytes())?;
+                }
+                Ok(())
+            }
+            None => self.inner.write_all(text.as_bytes()),
+        }
+    }
+}
+
+impl<W: Write> Write for LineWrapper<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.width.is_none() && !self.split_sentences {
+            return self.inner.write(buf);
+        }
+        let mut rest = buf;
+        while let Some(pos) = rest.iter().position(|byte| *byte == b'\n') {
+            self.line.extend_from_slice(&rest[..pos]);
+            self.write_line()?;
+            self.inner.write_all(b"\n")?;
+            rest = &rest[pos + 1..];
+        }
+        self.line.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.write_line()?;
+        }
+        self.inner.flush()
+    }
+}
+
+/// Wraps a line of filled text at the runs of spaces so that the lines are at most `width`
+/// characters long where possible. A line that would start with `.` or `'` is preceded with `\&`.
+fn wrap_line(line: &str, width: usize) -> Cow<'_, str> {
+    if line.chars().count() <= width {
+        return Cow::Borrowed(line);
+    }
+    let breaks = break_points(line);
+    if breaks.is_empty() {
+        return Cow::Borrowed(line);
+    }
+
+    let mut out = String::with_capacity(line.len() + line.len() / width.max(1) * 3);
+    let mut column = 0;
+    let mut start = 0;
+    let mut spaces = "";
+    for (space_start, space_end) in breaks.into_iter().chain(Some((line.len(), line.len()))) {
+        let word = &line[start..space_start];
+        let len = word.chars().count();
+        if column > 0 && column + spaces.len() + len > width {
+            out.push('\n');
+            column = 0;
+            if word.starts_with(['.', '\'']) {
+                out.push_str("\\&");
+                column += 2;
+            }
+        } else {
+            out.push_str(spaces);
+            column += spaces.len();
+        }
+        out.push_str(word);
+        column += len;
+        spaces = &line[space_start..space_end];
+        start = space_end;
+    }
+    Cow::Owned(out)
+}
+
+/// Words which are followed by a period without ending a sentence, compared in lowercase.
+const ABBREVIATIONS: &[&str] = &[
+    "approx", "cf", "dr", "e.g", "etc", "fig", "i.e", "jr", "mr", "mrs", "ms", "no", "prof",
+    "resp", "sr", "st", "vs",
+];
+
+/// Part of a line of filled text as seen by [`split_sentences`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token {
+    Space,
+    Char(char),
+    /// Escape that doesn't print anything, like a font change.
+    Invisible,
+    /// The `\&` escape that also marks a period as not ending a sentence.
+    ZeroWidth,
+    /// Any other escape, or a comment.
+    Escape,
+}
+
+/// Splits a line of filled text into `(byte range, token)` pairs.
+fn tokenize(line: &str) -> Vec<(usize, usize, Token)> {
+    let mut tokens = vec![];
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        let token = match ch {
+            ' ' => Token::Space,
+            '\\' => match chars.peek().map(|(_, ch)| *ch) {
+                Some('"') | Some('#') => {
+                    tokens.push((start, line.len(), Token::Escape));
+                    break;
+                }
+                Some('&') => {
+                    chars.next();
+                    Token::ZeroWidth
+                }
+                Some('f') | Some('s') | Some('F') => {
+                    skip_escape(&mut chars);
+                    Token::Invisible
+                }
+                _ => {
+                    skip_escape(&mut chars);
+                    Token::Escape
+                }
+            },
+            ch => Token::Char(ch),
+        };
+        let end = chars.peek().map_or(line.len(), |(i, _)| *i);
+        let token = match &line[start..end] {
+            "\\(dq" | "\\(lq" | "\\(rq" => Token::Char('"'),
+            "\\(aq" | "\\(oq" | "\\(cq" => Token::Char('\''),
+            _ => token,
+        };
+        tokens.push((start, end, token));
+    }
+    tokens
+}
+
+/// Returns whether the text of `tokens` ends a sentence.
+fn ends_sentence(tokens: &[(usize, usize, Token)]) -> bool {
+    let mut rest = tokens
+        .iter()
+        .rev()
+        .map(|(_, _, token)| *token)
+        .filter(|token| *token != Token::Invisible)
+        .skip_while(|token| {
+            matches!(
+                token,
+                Token::Char('"') | Token::Char('\'') | Token::Char(')')
+            )
+        });
+    match rest.next() {
+        Some(Token::Char('?')) | Some(Token::Char('!')) => true,
+        Some(Token::Char('.')) => {
+            let mut word = vec![];
+            for token in rest {
+                match token {
+                    Token::Char(ch) => word.push(ch),
+                    Token::Space => break,
+                    _ => return false,
+                }
+            }
+            let initial = word.len() == 1 && word[0].is_alphabetic();
+            let word: String = word.iter().rev().flat_map(|ch| ch.to_lowercase()).collect();
+            !initial && !ABBREVIATIONS.contains(&word.as_str())
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether the text of `tokens` starts a sentence.
+fn starts_sentence(tokens: &[(usize, usize, Token)]) -> bool {
+    tokens
+        .iter()
+        .map(|(_, _, token)| *token)
+        .filter(|token| *token != Token::Invisible)
+        .find(|token| {
+            !matches!(
+                token,
+                Token::Char('"') | Token::Char('\'') | Token::Char('(')
+            )
+        })
+        .is_some_and(|token| matches!(token, Token::Char(ch) if ch.is_uppercase()))
+}
+
+/// Splits a line of filled text so that each sentence starts on a new line. The runs of spaces
+/// between the sentences are replaced with a line break and a line that would start with `.` or
+/// `'` is preceded with `\&`.
+fn split_sentences(line: &str) -> Cow<'_, str> {
+    let tokens = tokenize(line);
+    let mut out = String::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].2 != Token::Space {
+            i += 1;
+            continue;
+        }
+        let run_end = tokens[i..]
+            .iter()
+            .position(|(_, _, token)| *token != Token::Space)
+            .map_or(tokens.len(), |len| i + len);
+        if i > 0 && ends_sentence(&tokens[..i]) && starts_sentence(&tokens[run_end..]) {
+            let next = tokens[run_end].0;
+            out.push_str(&line[start..tokens[i].0]);
+            out.push('\n');
+            if line[next..].starts_with(['.', '\'']) {
+                out.push_str("\\&");
+            }
+            start = next;
+        }
+        i = run_end;
+    }
+    if start == 0 {
+        return Cow::Borrowed(line);
+    }
+    out.push_str(&line[start..]);
+    Cow::Owned(out)
+}
+
+/// Returns the byte ranges of the runs of spaces in `line` at which it can be wrapped. Leading
+/// and trailing spaces are significant and spaces inside of escape sequences or after a comment
+/// can't be replaced with a line break.
+fn break_points(line: &str) -> Vec<(usize, usize)> {
+    let mut points = vec![];
+    let mut seen_text = false;
+    let mut space_start = None;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        if ch == ' ' {
+            if seen_text {
+                space_start.get_or_insert(i);
+            }
+            continue;
+        }
+        // A comment runs until the end of the line and can't start a line of its own.
+        if ch == '\\' && matches!(chars.peek(), Some((_, '"')) | Some((_, '#'))) {
+            break;
+        }
+        if let Some(start) = space_start.take() {
+            points.push((start, i));
+        }
+        seen_text = true;
+        if ch == '\\' {
+            skip_escape(&mut chars);
+        }
+    }
+    points
+}
+
+/// Skips the rest of an escape sequence following a backslash.
+fn skip_escape<I: Iterator<Item = (usize, char)>>(chars: &mut Peekable<I>) {
+    match chars.next() {
+        Some((_, 'f')) | Some((_, '*')) | Some((_, 'n')) | Some((_, 's')) | Some((_, 'F')) => {
+            chars.next_if(|(_, ch)| *ch == '+' || *ch == '-');
+            skip_name(chars);
+        }
+        Some((_, '(')) => {
+            chars.nth(1);
+        }
+        Some((_, '[')) => while chars.next().is_some_and(|(_, ch)| ch != ']') {},
+        // Escapes with an argument enclosed in a delimiter, like `\h'1m'`.
+        Some((_, 'h')) | Some((_, 'v')) | Some((_, 'w')) | Some((_, 'o')) | Some((_, 'b'))
+        | Some((_, 'l')) | Some((_, 'L')) | Some((_, 'D')) | Some((_, 'X')) | Some((_, 'Z')) => {
+            if let Some((_, delimiter)) = chars.next() {
+                while chars.next().is_some_and(|(_, ch)| ch != delimiter) {}
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Skips the name of an escape: a single character, two characters after `(` or anything up to
+/// `]` after `[`.
+fn skip_name<I: Iterator<Item = (usize, char)>>(chars: &mut Peekable<I>) {
+    match chars.next() {
+        Some((_, '(')) => {
+            chars.nth(1);
+        }
+        Some((_, '[')) => while chars.next().is_some_and(|(_, ch)| ch != ']') {},
+        _ => {}
+    }
+}
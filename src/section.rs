@@ -1,65 +1,381 @@
 use crate::_macro::{ENDL, SECTION_HEADER, SPACE, SUB_HEADER};
+use crate::node::{ungrouped, RoffNodeInner};
+use crate::render::RenderOptions;
 use crate::{
-    node::RoffNodeInner, write_quoted_if_whitespace, IntoRoffNode, RoffError, RoffText, Roffable,
+    write_quoted_if_whitespace, IntoRoffNode, NodePath, RoffError, RoffNode, RoffText, Roffable,
 };
 
 use std::io::Write;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A single section of the ROFF document.
 pub struct Section {
     title: RoffText,
     subtitle: Option<RoffText>,
-    nodes: Vec<RoffNodeInner>,
+    nodes: Vec<RoffNode>,
 }
 
 impl Section {
     /// Create a new section with `title` and `content`.
-    pub fn new<I, R>(title: impl Roffable, content: I) -> Self
+    pub fn new<I, R>(title: impl Into<RoffText>, content: I) -> Self
     where
         I: IntoIterator<Item = R>,
         R: IntoRoffNode,
     {
         Self {
-            title: title.roff(),
+            title: title.into(),
             subtitle: None,
-            nodes: content
-                .into_iter()
-                .map(|r| r.into_roff().into_inner())
-                .collect(),
+            nodes: content.into_iter().map(IntoRoffNode::into_roff).collect(),
         }
     }
 
     /// Set the sub heading of this section.
-    pub fn subtitle(mut self, subtitle: impl Roffable) -> Self {
-        self.subtitle = Some(subtitle.roff());
+    pub fn subtitle(mut self, subtitle: impl Into<RoffText>) -> Self {
+        self.subtitle = Some(subtitle.into());
         self
     }
 
+    /// Appends `node` to the content of this section.
+    pub fn node(mut self, node: impl IntoRoffNode) -> Self {
+        self.nodes.push(node.into_roff());
+        self
+    }
+
+    /// Appends the node returned by `node` to the content of this section only if `condition` is
+    /// `true`. The closure is not called otherwise.
+    pub fn node_if<N: IntoRoffNode>(self, condition: bool, node: impl FnOnce() -> N) -> Self {
+        if condition {
+            self.node(node())
+        } else {
+            self
+        }
+    }
+
+    /// Applies `f` to this section only if `condition` is `true`, so that optional content can be
+    /// added without breaking the chain of builder calls. `f` is not called otherwise.
+    pub fn apply_if(self, condition: bool, f: impl FnOnce(Self) -> Self) -> Self {
+        if condition {
+            f(self)
+        } else {
+            self
+        }
+    }
+
+    /// Appends a [`text`](RoffNode::text) node to this section.
+    pub fn text(self, content: impl Into<RoffText>) -> Self {
+        self.node(RoffNode::text(content))
+    }
+
+    /// Appends a [`subheading`](RoffNode::subheading) to this section.
+    pub fn subheading(self, title: impl Into<RoffText>) -> Self {
+        self.node(RoffNode::subheading(title))
+    }
+
+    /// Appends a [`paragraph`](RoffNode::paragraph) to this section.
+    pub fn paragraph<I, R>(self, content: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        self.node(RoffNode::paragraph(content))
+    }
+
+    /// Appends an [`indented paragraph`](RoffNode::indented_paragraph) to this section.
+    pub fn indented_paragraph<I, R>(
+        self,
+        content: I,
+        indentation: Option<u8>,
+        title: Option<impl Into<RoffText>>,
+    ) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        self.node(RoffNode::indented_paragraph(content, indentation, title))
+    }
+
+    /// Appends a [`tagged paragraph`](RoffNode::tagged_paragraph) with the `tag` as its title to
+    /// this section.
+    pub fn tagged<I, R>(self, tag: impl Into<RoffText>, content: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRoffNode,
+    {
+        self.node(RoffNode::tagged_paragraph(content, tag))
+    }
+
+    /// Appends an [`example`](RoffNode::example) block to this section.
+    pub fn example<I, R>(self, content: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<RoffText>,
+    {
+        self.node(RoffNode::example(content))
+    }
+
+    /// Appends a [`url`](RoffNode::url) to this section.
+    pub fn url(self, name: impl Into<RoffText>, address: impl Into<RoffText>) -> Self {
+        self.node(RoffNode::url(name, address))
+    }
+
+    /// Appends an [`email`](RoffNode::email) address to this section.
+    pub fn email(self, name: impl Into<RoffText>, address: impl Into<RoffText>) -> Self {
+        self.node(RoffNode::email(name, address))
+    }
+
+    /// Appends a [`linebreak`](RoffNode::linebreak) to this section.
+    pub fn linebreak(self) -> Self {
+        self.node(RoffNode::linebreak())
+    }
+
+    /// Returns the title of this section.
+    pub fn get_title(&self) -> &RoffText {
+        &self.title
+    }
+
+    /// Returns the sub heading of this section.
+    pub fn get_subtitle(&self) -> Option<&RoffText> {
+        self.subtitle.as_ref()
+    }
+
+    /// Returns the nodes of this section.
+    pub fn get_nodes(&self) -> &[RoffNode] {
+        &self.nodes
+    }
+
+    /// Returns the nodes of this section for modification.
+    pub fn get_nodes_mut(&mut self) -> &mut Vec<RoffNode> {
+        &mut self.nodes
+    }
+
+    /// Returns the title and the subtitle of this section for modification.
+    pub(crate) fn titles_mut(&mut self) -> (&mut RoffText, Option<&mut RoffText>) {
+        (&mut self.title, self.subtitle.as_mut())
+    }
+
+    /// Appends the nodes of `other` to this section. If `other` has a subtitle it is converted to a
+    /// sub heading node preceding the appended nodes.
+    pub(crate) fn merge(&mut self, other: Section) {
+        if let Some(subtitle) = other.subtitle {
+            self.nodes.push(RoffNode::subheading(subtitle));
+        }
+        self.nodes.extend(other.nodes);
+    }
+
+    /// Returns an estimate of the number of bytes this section takes when rendered.
+    pub(crate) fn estimated_size(&self) -> usize {
+        6 + self.title.estimated_size()
+            + self
+                .subtitle
+                .as_ref()
+                .map(|subtitle| 6 + subtitle.estimated_size())
+                .unwrap_or_default()
+            + self
+                .nodes
+                .iter()
+                .map(RoffNode::estimated_size)
+                .sum::<usize>()
+    }
+
+    /// Returns the standard section matching the title of this section if there is one.
+    pub(crate) fn standard(&self) -> Option<StandardSection> {
+        StandardSection::from_title(self.title.as_str())
+    }
+
+    /// Returns the index of the node of this section that the `index`th of the
+    /// [`ungrouped`](ungrouped) nodes comes from.
+    fn grouped_index(&self, index: usize) -> usize {
+        let mut end = 0;
+        for (i, node) in self.nodes.iter().enumerate() {
+            end += ungrouped(std::slice::from_ref(node)).len();
+            if index < end {
+                return i;
+            }
+        }
+        index
+    }
+
+    /// Renders this section as the section at `index` of its document. Failed writes are reported
+    /// with the location of the section or node being written.
     pub(crate) fn render<W: Write>(
         &self,
         writer: &mut W,
         was_text: bool,
+        index: usize,
+        options: &RenderOptions,
     ) -> Result<bool, RoffError> {
+        let path = || NodePath::section(index, self.title.original());
+        self.render_heading(writer, was_text, options)
+            .map_err(|err| err.at(path))?;
+
+        let mut was_text = false;
+        let mut after_heading = true;
+        let nodes = ungrouped(&self.nodes);
+        for (i, node) in nodes.iter().enumerate() {
+            let last = i + 1 == nodes.len();
+            was_text = node
+                .render_in_section(writer, was_text, after_heading, last, options)
+                .map_err(|err| err.at(|| path().node(self.grouped_index(i))))?;
+            after_heading = match node.inner_ref() {
+                RoffNodeInner::SubHeading(_) => true,
+                RoffNodeInner::Comment(_) | RoffNodeInner::Break => after_heading,
+                _ => false,
+            };
+        }
+
+        Ok(was_text)
+    }
+
+    fn render_heading<W: Write>(
+        &self,
+        writer: &mut W,
+        was_text: bool,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
         if was_text {
             writer.write_all(ENDL)?;
         }
         writer.write_all(SECTION_HEADER)?;
         writer.write_all(SPACE)?;
-        write_quoted_if_whitespace(&self.title, writer)?;
+        write_quoted_if_whitespace(&self.title, writer, options)?;
         writer.write_all(ENDL)?;
         if let Some(subtitle) = &self.subtitle {
             writer.write_all(SUB_HEADER)?;
             writer.write_all(SPACE)?;
-            write_quoted_if_whitespace(subtitle, writer)?;
+            write_quoted_if_whitespace(subtitle, writer, options)?;
             writer.write_all(ENDL)?;
         }
+        Ok(())
+    }
+}
 
-        let mut was_text = false;
-        for node in &self.nodes {
-            was_text = node.render(writer, was_text)?;
+/// Creates a section from a `(title, content)` pair, the same as [`Section::new`](Section::new).
+impl<T, I, R> From<(T, I)> for Section
+where
+    T: Into<RoffText>,
+    I: IntoIterator<Item = R>,
+    R: IntoRoffNode,
+{
+    fn from((title, content): (T, I)) -> Self {
+        Section::new(title, content)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// Sections commonly found in man pages with their conventional titles as described in
+/// [`man-pages(7)`](https://man7.org/linux/man-pages/man7/man-pages.7.html). Can be used anywhere
+/// a section title is expected.
+pub enum StandardSection {
+    Name,
+    Library,
+    Synopsis,
+    Configuration,
+    Description,
+    Options,
+    ExitStatus,
+    ReturnValue,
+    Errors,
+    Environment,
+    Files,
+    Attributes,
+    Versions,
+    Standards,
+    History,
+    Notes,
+    Caveats,
+    Bugs,
+    Examples,
+    Authors,
+    ReportingBugs,
+    Copyright,
+    SeeAlso,
+}
+
+impl StandardSection {
+    /// All standard sections in their conventional order.
+    pub const ALL: [StandardSection; 23] = [
+        StandardSection::Name,
+        StandardSection::Library,
+        StandardSection::Synopsis,
+        StandardSection::Configuration,
+        StandardSection::Description,
+        StandardSection::Options,
+        StandardSection::ExitStatus,
+        StandardSection::ReturnValue,
+        StandardSection::Errors,
+        StandardSection::Environment,
+        StandardSection::Files,
+        StandardSection::Attributes,
+        StandardSection::Versions,
+        StandardSection::Standards,
+        StandardSection::History,
+        StandardSection::Notes,
+        StandardSection::Caveats,
+        StandardSection::Bugs,
+        StandardSection::Examples,
+        StandardSection::Authors,
+        StandardSection::ReportingBugs,
+        StandardSection::Copyright,
+        StandardSection::SeeAlso,
+    ];
+
+    /// Returns the conventional title of this section.
+    pub fn title(&self) -> &'static str {
+        use StandardSection::*;
+        match self {
+            Name => "NAME",
+            Library => "LIBRARY",
+            Synopsis => "SYNOPSIS",
+            Configuration => "CONFIGURATION",
+            Description => "DESCRIPTION",
+            Options => "OPTIONS",
+            ExitStatus => "EXIT STATUS",
+            ReturnValue => "RETURN VALUE",
+            Errors => "ERRORS",
+            Environment => "ENVIRONMENT",
+            Files => "FILES",
+            Attributes => "ATTRIBUTES",
+            Versions => "VERSIONS",
+            Standards => "STANDARDS",
+            History => "HISTORY",
+            Notes => "NOTES",
+            Caveats => "CAVEATS",
+            Bugs => "BUGS",
+            Examples => "EXAMPLES",
+            Authors => "AUTHORS",
+            ReportingBugs => "REPORTING BUGS",
+            Copyright => "COPYRIGHT",
+            SeeAlso => "SEE ALSO",
         }
+    }
 
-        Ok(was_text)
+    /// Returns the standard section with the given `title`. The comparison is case insensitive.
+    pub fn from_title(title: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|section| section.title().eq_ignore_ascii_case(title.trim()))
+            .copied()
+    }
+
+    /// Returns the position of this section in the conventional order of sections, `NAME` being
+    /// the first one with index `0`.
+    pub fn order_index(&self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|section| section == self)
+            .unwrap_or_default()
+    }
+}
+
+impl Roffable for StandardSection {
+    fn roff(&self) -> RoffText {
+        self.title().roff()
+    }
+}
+
+impl From<StandardSection> for RoffText {
+    fn from(item: StandardSection) -> Self {
+        item.roff()
     }
 }
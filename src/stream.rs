@@ -0,0 +1,189 @@
+use crate::_macro::ENDL;
+use crate::render::{self, Output, RenderOptions};
+use crate::{Roff, RoffError, RoffText, Section, SectionNumber};
+
+use std::io::Write;
+
+/// Renders a document one section at a time, so that large documents don't have to be kept in
+/// memory. The output is the same as the one of [`Roff::render`](Roff::render) for a document
+/// with the same sections.
+///
+/// ```
+/// use roffman::{Roff, RoffStream, Section, SectionNumber};
+///
+/// let mut stream = RoffStream::new("test", SectionNumber::UserCommands, vec![]).unwrap();
+/// for name in ["NAME", "DESCRIPTION"] {
+///     stream.write_section(&Section::new(name, ["some text"])).unwrap();
+/// }
+/// let rendered = stream.finish().unwrap();
+///
+/// let roff = Roff::new("test", SectionNumber::UserCommands)
+///     .section("NAME", ["some text"])
+///     .section("DESCRIPTION", ["some text"]);
+/// assert_eq!(rendered, roff.render_to_vec().unwrap());
+/// ```
+pub struct RoffStream<W: Write> {
+    writer: Output<W>,
+    options: RenderOptions,
+    was_text: bool,
+    /// Number of sections written on the current page.
+    sections: usize,
+}
+
+impl<W: Write> RoffStream<W> {
+    /// Starts a document with a `title` and a `section` rendered with the default options. The
+    /// title header is written to `writer` right away.
+    pub fn new(
+        title: impl Into<RoffText>,
+        section: SectionNumber,
+        writer: W,
+    ) -> Result<Self, RoffError> {
+        Self::from_roff(&Roff::new(title, section), writer)
+    }
+
+    /// Starts a document with the header, the sections and the appended pages of `roff`, which
+    /// are written to `writer` right away. The sections written to the stream afterwards are
+    /// rendered with the options of `roff`.
+    pub fn from_roff(roff: &Roff, writer: W) -> Result<Self, RoffError> {
+        let mut stream = Self::start(writer, &roff.options);
+        stream.write_page(roff)?;
+        for page in &roff.pages {
+            stream.write_page(page)?;
+        }
+        Ok(stream)
+    }
+
+    /// Creates a stream that didn't write anything yet.
+    pub(crate) fn start(writer: W, options: &RenderOptions) -> Self {
+        Self {
+            writer: render::output(writer, options),
+            options: options.clone(),
+            was_text: false,
+            sections: 0,
+        }
+    }
+
+    /// Writes the header and the sections of `page` without the pages appended to it.
+    pub(crate) fn write_page(&mut self, page: &Roff) -> Result<(), RoffError> {
+        self.write_page_header(page)?;
+        for section in &page.sections {
+            self.write_page_section(section, page)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the comments and the title header of `page`, ending the previous page if any.
+    pub(crate) fn write_page_header(&mut self, page: &Roff) -> Result<(), RoffError> {
+        if self.was_text {
+            self.writer.write_all(ENDL)?;
+        }
+        page.write_header(&mut self.writer)?;
+        self.was_text = false;
+        self.sections = 0;
+        Ok(())
+    }
+
+    /// Renders `section` of `page` with the options of the page.
+    pub(crate) fn write_page_section(
+        &mut self,
+        section: &Section,
+        page: &Roff,
+    ) -> Result<(), RoffError> {
+        self.was_text = section.render(
+            &mut self.writer,
+            self.was_text,
+            self.sections,
+            &page.options,
+        )?;
+        self.sections += 1;
+        Ok(())
+    }
+
+    /// Renders `section` after the sections written so far.
+    pub fn write_section(&mut self, section: &Section) -> Result<(), RoffError> {
+        self.was_text = section.render(
+            &mut self.writer,
+            self.was_text,
+            self.sections,
+            &self.options,
+        )?;
+        self.sections += 1;
+        Ok(())
+    }
+
+    /// Flushes the writer. The output that can still change, like the line that is not terminated
+    /// yet when lines are wrapped, is held back until more sections are written or the stream is
+    /// finished.
+    pub fn flush(&mut self) -> Result<(), RoffError> {
+        self.writer.write_buffered()?;
+        self.writer.inner_mut().flush()?;
+        Ok(())
+    }
+
+    /// Finishes the document writing all of the output held back and returns the writer.
+    pub fn finish(self) -> Result<W, RoffError> {
+        self.writer.into_writer()
+    }
+}
+
+#[cfg(feature = "async")]
+impl RoffStream<Vec<u8>> {
+    /// Flushes the stream and takes the bytes written to the buffer so far.
+    pub(crate) fn take_written(&mut self) -> Result<Vec<u8>, RoffError> {
+        self.flush()?;
+        Ok(std::mem::take(self.writer.inner_mut()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoffStream;
+    use crate::tests::readme_example;
+    use crate::{RenderOptions, Roff, RoffNode, Section, SectionNumber};
+
+    #[test]
+    fn it_renders_the_same_as_the_whole_document() {
+        let options = RenderOptions::new()
+            .max_line_width(30)
+            .sentence_per_line(true)
+            .strip_trailing_whitespace(true)
+            .normalize_output(true);
+        let roff = readme_example()
+            .render_options(options.clone())
+            .section(
+                "DESCRIPTION",
+                [RoffNode::text(
+                    "A long line of text that ends with a sentence. And another one   ",
+                )],
+            )
+            .section("SEE ALSO", [RoffNode::text("roff(7)\n\n\n")]);
+        let rendered = roff.render_to_vec().unwrap();
+
+        let header = Roff::new("roffman", SectionNumber::Miscellaneous)
+            .date("August 2021")
+            .render_options(options);
+        let mut stream = RoffStream::from_roff(&header, vec![]).unwrap();
+        for section in &roff.sections {
+            stream.write_section(section).unwrap();
+            stream.flush().unwrap();
+            let written = stream.writer.inner_mut();
+            assert!(rendered.starts_with(written));
+        }
+        assert_eq!(stream.finish().unwrap(), rendered);
+    }
+
+    #[test]
+    fn it_continues_the_last_appended_page() {
+        let first = Roff::new("first", SectionNumber::UserCommands).section("NAME", ["first"]);
+        let second = Roff::new("second", SectionNumber::UserCommands).section("NAME", ["second"]);
+
+        let mut stream =
+            RoffStream::from_roff(&first.clone().append_page(second.clone()), vec![]).unwrap();
+        stream
+            .write_section(&Section::new("DESCRIPTION", ["streamed"]))
+            .unwrap();
+
+        let roff = first.append_page(second.section("DESCRIPTION", ["streamed"]));
+        assert_eq!(stream.finish().unwrap(), roff.render_to_vec().unwrap());
+    }
+}
@@ -0,0 +1,452 @@
+use crate::_macro::{BREAK, ENDL, SPACE, SUB_HEADER, SYNOPSIS_OPT, TAGGED_PARAGRAPH};
+use crate::render::{CompatLevel, RenderOptions};
+use crate::text::render_texts;
+use crate::{write_quoted_if_whitespace, RoffError, RoffText, Roffable};
+
+use std::io::Write;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// An option used by the [`RoffNode::synopsis`](crate::RoffNode::synopsis) block.
+pub struct SynopsisOpt {
+    pub(crate) name: RoffText,
+    pub(crate) argument: Option<RoffText>,
+    pub(crate) description: Option<Vec<RoffText>>,
+    pub(crate) default_value: Option<RoffText>,
+    pub(crate) possible_values: Vec<RoffText>,
+    pub(crate) inline_values: bool,
+    pub(crate) hidden: bool,
+}
+
+impl SynopsisOpt {
+    /// Creates a new option used in a synopsis block.
+    pub fn new<R: Into<RoffText>>(name: R) -> Self {
+        Self {
+            name: name.into().minus_hyphens(),
+            argument: None,
+            description: None,
+            default_value: None,
+            possible_values: vec![],
+            inline_values: false,
+            hidden: false,
+        }
+    }
+
+    /// Returns the name of this option.
+    pub fn get_name(&self) -> &RoffText {
+        &self.name
+    }
+
+    /// Set the name of the argument that this option takes.
+    pub fn argument<R: Into<RoffText>>(mut self, argument: R) -> Self {
+        self.argument = Some(argument.into().minus_hyphens());
+        self
+    }
+
+    /// Returns the name of the argument of this option if one was set.
+    pub fn get_argument(&self) -> Option<&RoffText> {
+        self.argument.as_ref()
+    }
+
+    /// Set the description for this command synopsis.
+    pub fn description<I, R>(mut self, description: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<RoffText>,
+    {
+        self.description = Some(description.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns the description of this option, empty if none was set.
+    pub fn get_description(&self) -> &[RoffText] {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    /// Set the default value of this option. It will be displayed as `(default: VAL)` right after
+    /// the description.
+    pub fn default_value<R: Into<RoffText>>(mut self, value: R) -> Self {
+        self.default_value = Some(value.into());
+        self
+    }
+
+    /// Returns the default value of this option if one was set.
+    pub fn get_default_value(&self) -> Option<&RoffText> {
+        self.default_value.as_ref()
+    }
+
+    /// Set the values accepted by this option. They will be listed as `Possible values: a, b, c`
+    /// right after the description.
+    pub fn possible_values<I, R>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<RoffText>,
+    {
+        self.possible_values = values.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// If set to `true` and this option has possible values defined, the argument in the synopsis
+    /// will be displayed as the list of values, like `auto|always|never`, instead of the argument
+    /// name.
+    pub fn inline_values(mut self, inline_values: bool) -> Self {
+        self.inline_values = inline_values;
+        self
+    }
+
+    /// Returns the possible values of this option.
+    pub fn get_possible_values(&self) -> &[RoffText] {
+        &self.possible_values
+    }
+
+    /// Returns `true` if the possible values of this option are displayed in place of the argument.
+    pub fn has_inline_values(&self) -> bool {
+        self.inline_values
+    }
+
+    /// Returns the name, the argument and the description of this option at once, the parts that
+    /// make up an entry of an options list.
+    pub fn parts(&self) -> (&RoffText, Option<&RoffText>, &[RoffText]) {
+        (self.get_name(), self.get_argument(), self.get_description())
+    }
+
+    /// Hides this option so that it is not displayed in the rendered document unless
+    /// [`Roff::include_hidden`](crate::Roff::include_hidden) is set.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Returns `true` if this option is hidden.
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Returns `true` if this option should be displayed with the given `options`.
+    pub(crate) fn is_visible(&self, options: &RenderOptions) -> bool {
+        !self.hidden || options.include_hidden
+    }
+
+    fn has_description(&self) -> bool {
+        self.description
+            .as_ref()
+            .map(|description| !description.is_empty())
+            .unwrap_or_default()
+    }
+
+    /// Returns an estimate of the number of bytes this option takes when rendered, the name,
+    /// argument and description with the values plus the surrounding macros.
+    pub(crate) fn estimated_size(&self) -> usize {
+        let texts = self
+            .description
+            .iter()
+            .flatten()
+            .chain(&self.argument)
+            .chain(&self.default_value)
+            .chain(&self.possible_values);
+        12 + self.name.estimated_size()
+            + texts.map(RoffText::estimated_size).sum::<usize>()
+            + 20 * (!self.possible_values.is_empty()) as usize
+            + 12 * self.default_value.is_some() as usize
+    }
+
+    /// Renders this option as a `.OP` entry of a synopsis block. Hidden options are skipped.
+    pub(crate) fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if !self.is_visible(options) {
+            return Ok(());
+        }
+        if options.compat == CompatLevel::Portable {
+            return self.render_portable(writer, options);
+        }
+        writer.write_all(SYNOPSIS_OPT)?;
+        writer.write_all(SPACE)?;
+        write_quoted_if_whitespace(&self.name, writer, options)?;
+        self.render_argument(writer, options)?;
+        writer.write_all(ENDL)?;
+        self.render_description_line(writer, options)
+    }
+
+    /// Renders this option as a line with the bold name and italic argument in brackets followed
+    /// by the description, without the `.OP` macro.
+    fn render_portable<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        let mut usage = "[".roff();
+        usage += self.name.clone().bold();
+        if self.inline_values && !self.possible_values.is_empty() {
+            usage += " ".roff();
+            usage += RoffText::join(
+                self.possible_values
+                    .iter()
+                    .map(|value| value.clone().italic()),
+                "|",
+            );
+        } else if let Some(argument) = &self.argument {
+            usage += " ".roff();
+            usage += argument.clone().italic();
+        }
+        usage += "]".roff();
+        usage.render_line_start(writer, options)?;
+        writer.write_all(ENDL)?;
+        self.render_description_line(writer, options)
+    }
+
+    /// Renders this option as a `.TP` entry of an options list with the bold name and italic
+    /// argument as the tag. Hidden options are skipped.
+    pub(crate) fn render_tagged<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if !self.is_visible(options) {
+            return Ok(());
+        }
+        writer.write_all(TAGGED_PARAGRAPH)?;
+        writer.write_all(ENDL)?;
+        self.name.clone().bold().render(writer, options)?;
+        if self.inline_values && !self.possible_values.is_empty() {
+            writer.write_all(SPACE)?;
+            RoffText::join(
+                self.possible_values
+                    .iter()
+                    .map(|value| value.clone().italic()),
+                "|",
+            )
+            .render(writer, options)?;
+        } else if let Some(argument) = &self.argument {
+            writer.write_all(SPACE)?;
+            argument.clone().italic().render(writer, options)?;
+        }
+        writer.write_all(ENDL)?;
+        self.render_description_line(writer, options)
+    }
+
+    /// Renders the argument of this option preceded by a space. If the values are inlined they are
+    /// rendered instead of the argument name.
+    pub(crate) fn render_argument<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if self.inline_values && !self.possible_values.is_empty() {
+            writer.write_all(SPACE)?;
+            let values = RoffText::join(self.possible_values.iter().cloned(), "|");
+            write_quoted_if_whitespace(&values, writer, options)?;
+        } else if let Some(argument) = &self.argument {
+            writer.write_all(SPACE)?;
+            write_quoted_if_whitespace(argument, writer, options)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the description of this option as a terminated line, if there is anything to
+    /// describe, so that no blank lines are left between the entries.
+    fn render_description_line<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if self.has_description()
+            || !self.possible_values.is_empty()
+            || self.default_value.is_some()
+        {
+            self.render_description(writer, options)?;
+            writer.write_all(ENDL)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the description of this option followed by the possible values and the default
+    /// value if any are set, each on its own line.
+    pub(crate) fn render_description<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        let mut was_text = self.has_description();
+        let mut line_start = true;
+        if let Some(description) = &self.description {
+            line_start = render_texts(description, true, writer, options)?;
+        }
+        if !self.possible_values.is_empty() {
+            if was_text {
+                write_break(line_start, writer)?;
+            }
+            "Possible values: ".roff().render(writer, options)?;
+            RoffText::join(
+                self.possible_values
+                    .iter()
+                    .map(|value| value.clone().bold()),
+                ", ",
+            )
+            .render(writer, options)?;
+            was_text = true;
+            line_start = false;
+        }
+        if let Some(default_value) = &self.default_value {
+            if was_text {
+                write_break(line_start, writer)?;
+            }
+            "(default: ".roff().render(writer, options)?;
+            default_value.render(writer, options)?;
+            ")".roff().render(writer, options)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ends the current output line unless it is already at `line_start` and writes a `.br` request
+/// so that the following text starts on a new line.
+fn write_break<W: Write>(line_start: bool, writer: &mut W) -> Result<(), RoffError> {
+    if !line_start {
+        writer.write_all(ENDL)?;
+    }
+    writer.write_all(BREAK)?;
+    writer.write_all(ENDL)?;
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A group of [`SynopsisOpt`s](SynopsisOpt) displayed under a common title, like
+/// "Output options".
+pub struct SynopsisOptGroup {
+    pub(crate) title: RoffText,
+    pub(crate) opts: Vec<SynopsisOpt>,
+}
+
+impl SynopsisOptGroup {
+    /// Creates a new group of `opts` with a `title`.
+    pub fn new<O>(title: impl Into<RoffText>, opts: O) -> Self
+    where
+        O: IntoIterator<Item = SynopsisOpt>,
+    {
+        Self {
+            title: title.into(),
+            opts: opts.into_iter().collect(),
+        }
+    }
+
+    /// Returns the title of this group.
+    pub fn get_title(&self) -> &RoffText {
+        &self.title
+    }
+
+    /// Returns the options of this group.
+    pub fn get_opts(&self) -> &[SynopsisOpt] {
+        &self.opts
+    }
+
+    /// Returns an estimate of the number of bytes this group takes when rendered.
+    pub(crate) fn estimated_size(&self) -> usize {
+        8 + self.title.estimated_size()
+            + self
+                .opts
+                .iter()
+                .map(SynopsisOpt::estimated_size)
+                .sum::<usize>()
+    }
+
+    /// Renders the bold title of this group followed by all of the options. If none of the options
+    /// are visible the title is skipped as well.
+    pub(crate) fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if !self.opts.iter().any(|opt| opt.is_visible(options)) {
+            return Ok(());
+        }
+        if options.compat != CompatLevel::Portable {
+            writer.write_all(BREAK)?;
+            writer.write_all(ENDL)?;
+        }
+        self.title
+            .clone()
+            .bold()
+            .render_line_start(writer, options)?;
+        writer.write_all(ENDL)?;
+        for opt in &self.opts {
+            opt.render(writer, options)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the title of this group as a sub heading followed by all of the options as `.TP`
+    /// entries. If none of the options are visible the title is skipped as well.
+    pub(crate) fn render_tagged<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if !self.opts.iter().any(|opt| opt.is_visible(options)) {
+            return Ok(());
+        }
+        writer.write_all(SUB_HEADER)?;
+        writer.write_all(SPACE)?;
+        write_quoted_if_whitespace(&self.title, writer, options)?;
+        writer.write_all(ENDL)?;
+        for opt in &self.opts {
+            opt.render_tagged(writer, options)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// An item of the [`RoffNode::synopsis`](crate::RoffNode::synopsis) block. Either a single option
+/// or a group of options.
+pub enum SynopsisItem {
+    Opt(SynopsisOpt),
+    Group(SynopsisOptGroup),
+}
+
+impl SynopsisItem {
+    pub(crate) fn estimated_size(&self) -> usize {
+        match self {
+            SynopsisItem::Opt(opt) => opt.estimated_size(),
+            SynopsisItem::Group(group) => group.estimated_size(),
+        }
+    }
+
+    pub(crate) fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        match self {
+            SynopsisItem::Opt(opt) => opt.render(writer, options),
+            SynopsisItem::Group(group) => group.render(writer, options),
+        }
+    }
+
+    pub(crate) fn render_tagged<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        match self {
+            SynopsisItem::Opt(opt) => opt.render_tagged(writer, options),
+            SynopsisItem::Group(group) => group.render_tagged(writer, options),
+        }
+    }
+}
+
+impl From<SynopsisOpt> for SynopsisItem {
+    fn from(opt: SynopsisOpt) -> Self {
+        SynopsisItem::Opt(opt)
+    }
+}
+
+impl From<SynopsisOptGroup> for SynopsisItem {
+    fn from(group: SynopsisOptGroup) -> Self {
+        SynopsisItem::Group(group)
+    }
+}
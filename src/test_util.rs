@@ -0,0 +1,392 @@
+//! Assertions comparing rendered documents in tests, enabled with the `test-util` feature.
+//!
+//! The rendered and the expected output are normalized before they are compared: spaces and tabs
+//! at the end of every line and line breaks at the end of the document are ignored. On mismatch
+//! the assertions panic with a line by line diff of the output.
+//!
+//! [`check_with_system_formatters`] validates the output with `groff` and `mandoc`, when they are
+//! installed, failing on any warning.
+//!
+//! ```
+//! use roffman::{assert_roff_eq, Roff, SectionNumber};
+//!
+//! let roff = Roff::new("test", SectionNumber::UserCommands).section("NAME", ["test"]);
+//! assert_roff_eq!(roff, ".TH test 1\n.SH NAME\ntest\n");
+//! ```
+
+use crate::Roff;
+
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// Environment variable that makes [`assert_roff_matches_file!`](crate::assert_roff_matches_file)
+/// write the rendered output to the golden file instead of comparing it, when set to anything but
+/// an empty string or `0`.
+pub const UPDATE_GOLDEN_ENV: &str = "ROFFMAN_UPDATE_GOLDEN";
+
+/// Number of unchanged lines displayed around the changed ones in a diff.
+const CONTEXT: usize = 2;
+
+/// A formatter run by [`check_with_system_formatters`] with the arguments making it report
+/// every warning about the input.
+struct Formatter {
+    program: &'static str,
+    args: &'static [&'static str],
+    /// Whether the warnings are written to the standard output instead of the formatted page.
+    reports_to_stdout: bool,
+}
+
+const FORMATTERS: &[Formatter] = &[
+    Formatter {
+        program: "groff",
+        args: &["-man", "-Tutf8", "-ww"],
+        reports_to_stdout: false,
+    },
+    Formatter {
+        program: "mandoc",
+        args: &["-Tlint", "-W", "warning"],
+        reports_to_stdout: true,
+    },
+];
+
+/// Asserts that a [`Roff`](crate::Roff) renders to the expected string. Spaces and tabs at the
+/// end of the lines and line breaks at the end of the document are ignored. On mismatch panics
+/// with a diff of the output.
+#[macro_export]
+macro_rules! assert_roff_eq {
+    ($roff:expr, $expected:expr $(,)?) => {
+        $crate::test_util::assert_roff_eq(&$roff, $expected)
+    };
+}
+
+/// Asserts that a [`Roff`](crate::Roff) renders to the contents of a golden file, compared the
+/// same way as [`assert_roff_eq!`](crate::assert_roff_eq). Relative paths are resolved from the
+/// working directory, which is the root of the package when running `cargo test`. Run the tests
+/// with the `ROFFMAN_UPDATE_GOLDEN=1` environment variable to write the rendered output to the
+/// file instead.
+#[macro_export]
+macro_rules! assert_roff_matches_file {
+    ($roff:expr, $path:expr $(,)?) => {
+        $crate::test_util::assert_roff_matches_file(&$roff, $path)
+    };
+}
+
+/// Implementation of [`assert_roff_eq!`](crate::assert_roff_eq).
+#[track_caller]
+pub fn assert_roff_eq(roff: &Roff, expected: &str) {
+    let actual = render(roff);
+    if let Some(diff) = diff(expected, &actual) {
+        panic!(
+            "rendered roff doesn't match the expected output (- expected, + rendered):\n{}",
+            diff
+        );
+    }
+}
+
+/// Implementation of [`assert_roff_matches_file!`](crate::assert_roff_matches_file).
+#[track_caller]
+pub fn assert_roff_matches_file(roff: &Roff, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = render(roff);
+    if update_golden() {
+        if let Err(e) = std::fs::write(path, &actual) {
+            panic!("failed to write golden file `{}` - {}", path.display(), e);
+        }
+        return;
+    }
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(e) => panic!(
+            "failed to read golden file `{}` - {}, set {}=1 to create it",
+            path.display(),
+            e,
+            UPDATE_GOLDEN_ENV
+        ),
+    };
+    if let Some(diff) = diff(&expected, &actual) {
+        panic!(
+            "rendered roff doesn't match `{}` (- expected, + rendered), set {}=1 to update it:\n{}",
+            path.display(),
+            UPDATE_GOLDEN_ENV,
+            diff
+        );
+    }
+}
+
+/// Pipes the rendered `roff` through `groff -man -Tutf8 -ww` and `mandoc -Tlint -W warning`,
+/// skipping the formatters that are not found on `PATH`. Returns the names of the formatters that
+/// accepted the document, or the warnings reported by the first one that didn't. The names are
+/// empty if neither formatter is installed, so callers that require a check should assert them.
+///
+/// ```no_run
+/// use roffman::{Roff, RoffNode, SectionNumber};
+/// use roffman::test_util::check_with_system_formatters;
+///
+/// let roff = Roff::new("test", SectionNumber::UserCommands)
+///     .date("2021-08-01")
+///     .section("NAME", [RoffNode::whatis(["test"], "a test page")]);
+/// match check_with_system_formatters(&roff) {
+///     Ok(formatters) => assert!(!formatters.is_empty(), "neither groff nor mandoc is installed"),
+///     Err(warnings) => panic!("{}", warnings),
+/// }
+/// ```
+pub fn check_with_system_formatters(roff: &Roff) -> Result<Vec<&'static str>, String> {
+    let rendered = roff
+        .render_to_vec()
+        .map_err(|e| format!("failed to render roff - {}", e))?;
+    let mut accepted = vec![];
+    for formatter in FORMATTERS {
+        let command = format!("{} {}", formatter.program, formatter.args.join(" "));
+        let output = match run(formatter, &rendered) {
+            Ok(output) => output,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(format!("failed to run `{}` - {}", command, e)),
+        };
+        let mut warnings = String::from_utf8_lossy(&output.stderr).into_owned();
+        if formatter.reports_to_stdout {
+            warnings.push_str(&String::from_utf8_lossy(&output.stdout));
+        }
+        if !output.status.success() || !warnings.trim().is_empty() {
+            return Err(format!(
+                "`{}` rejected the document ({}):\n{}",
+                command, output.status, warnings
+            ));
+        }
+        accepted.push(formatter.program);
+    }
+    Ok(accepted)
+}
+
+/// Runs `formatter` with `input` on the standard input. The input is written from another thread
+/// so that a formatter writing a lot of output before reading all of the input can't block.
+fn run(formatter: &Formatter, input: &[u8]) -> io::Result<Output> {
+    let mut child = Command::new(formatter.program)
+        .args(formatter.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("the standard input is piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    // A formatter that exits early closes the pipe, which its exit status and output explain.
+    let _ = writer.join();
+    Ok(output)
+}
+
+#[track_caller]
+fn render(roff: &Roff) -> String {
+    match roff.render_to_string() {
+        Ok(rendered) => rendered,
+        Err(e) => panic!("failed to render roff - {}", e),
+    }
+}
+
+fn update_golden() -> bool {
+    std::env::var(UPDATE_GOLDEN_ENV)
+        .map(|value| !value.is_empty() && value != "0")
+        .unwrap_or_default()
+}
+
+/// Removes spaces and tabs at the end of every line and line breaks at the end of `text`.
+pub fn normalize(text: &str) -> String {
+    let lines: Vec<_> = text
+        .split('\n')
+        .map(|line| line.trim_end_matches([' ', '\t']))
+        .collect();
+    lines.join("\n").trim_end_matches('\n').to_string()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// A line of a diff with the index of the line in the expected and the rendered output.
+enum Change {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// Returns a diff of the normalized `expected` and `actual` text, or `None` if they are the same.
+/// Changed lines are displayed with their line numbers, the expected line marked with `-` and the
+/// rendered one with `+`, followed by a caret under the first column that differs.
+pub fn diff(expected: &str, actual: &str) -> Option<String> {
+    let (expected, actual) = (normalize(expected), normalize(actual));
+    if expected == actual {
+        return None;
+    }
+    let expected: Vec<_> = expected.split('\n').collect();
+    let actual: Vec<_> = actual.split('\n').collect();
+    let changes = changes(&expected, &actual);
+
+    let visible: Vec<_> = (0..changes.len())
+        .map(|i| {
+            let start = i.saturating_sub(CONTEXT);
+            let end = (i + CONTEXT + 1).min(changes.len());
+            changes[start..end]
+                .iter()
+                .any(|change| !matches!(change, Change::Same(..)))
+        })
+        .collect();
+    let width = expected.len().max(actual.len()).to_string().len();
+
+    let mut out = String::new();
+    let mut skipped = false;
+    let mut i = 0;
+    while i < changes.len() {
+        if !visible[i] {
+            skipped = true;
+            i += 1;
+            continue;
+        }
+        if std::mem::take(&mut skipped) {
+            out.push_str("...\n");
+        }
+        match changes[i] {
+            Change::Same(_, line) => {
+                let _ = writeln!(out, "  {:>w$} | {}", line + 1, actual[line], w = width);
+                i += 1;
+            }
+            _ => {
+                let end = changes[i..]
+                    .iter()
+                    .position(|change| matches!(change, Change::Same(..)))
+                    .map(|len| i + len)
+                    .unwrap_or(changes.len());
+                let removed: Vec<_> = changes[i..end]
+                    .iter()
+                    .filter_map(|change| match change {
+                        Change::Removed(line) => Some(*line),
+                        _ => None,
+                    })
+                    .collect();
+                let added: Vec<_> = changes[i..end]
+                    .iter()
+                    .filter_map(|change| match change {
+                        Change::Added(line) => Some(*line),
+                        _ => None,
+                    })
+                    .collect();
+                for j in 0..removed.len().max(added.len()) {
+                    let old = removed.get(j).map(|line| (*line, expected[*line]));
+                    let new = added.get(j).map(|line| (*line, actual[*line]));
+                    if let Some((line, text)) = old {
+                        let _ = writeln!(out, "- {:>w$} | {}", line + 1, text, w = width);
+                    }
+                    if let Some((line, text)) = new {
+                        let _ = writeln!(out, "+ {:>w$} | {}", line + 1, text, w = width);
+                    }
+                    if let (Some((_, old)), Some((_, new))) = (old, new) {
+                        let column = old
+                            .chars()
+                            .zip(new.chars())
+                            .take_while(|(a, b)| a == b)
+                            .count();
+                        let _ = writeln!(out, "  {:w$}   {:column$}^", "", "", w = width);
+                    }
+                }
+                i = end;
+            }
+        }
+    }
+    if skipped {
+        out.push_str("...\n");
+    }
+    Some(out)
+}
+
+/// Returns the changes turning the `expected` lines into the `actual` lines, computed from their
+/// longest common subsequence. Removed lines come before the added ones.
+fn changes(expected: &[&str], actual: &[&str]) -> Vec<Change> {
+    let (n, m) = (expected.len(), actual.len());
+    let at = |i: usize, j: usize| i * (m + 1) + j;
+    let mut lcs = vec![0usize; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[at(i, j)] = if expected[i] == actual[j] {
+                lcs[at(i + 1, j + 1)] + 1
+            } else {
+                lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+            };
+        }
+    }
+
+    let mut changes = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            changes.push(Change::Same(i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+            changes.push(Change::Removed(i));
+            i += 1;
+        } else {
+            changes.push(Change::Added(j));
+            j += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, normalize};
+    use crate::tests::readme_example;
+    use crate::{Roff, SectionNumber};
+
+    #[test]
+    fn it_normalizes_trailing_whitespace() {
+        assert_eq!(normalize("a  \nb\t\n\n"), "a\nb");
+        assert_eq!(normalize("\n a"), "\n a");
+        assert_eq!(diff("a \n", "a"), None);
+    }
+
+    #[test]
+    fn it_displays_a_diff() {
+        let expected = "1\n2\n3\n4\n.SH NAME\ntest\n5\n6\n7\n8";
+        let actual = "1\n2\n3\n4\n.SH NAMES\ntest\nadded\n5\n6\n7\n8";
+        assert_eq!(
+            diff(expected, actual).unwrap(),
+            r#"...
+   3 | 3
+   4 | 4
+-  5 | .SH NAME
++  5 | .SH NAMES
+               ^
+   6 | test
++  7 | added
+   8 | 5
+   9 | 6
+...
+"#
+        );
+    }
+
+    #[test]
+    fn it_compares_rendered_roff() {
+        let roff = Roff::new("test", SectionNumber::UserCommands).section("NAME", ["test"]);
+        assert_roff_eq!(roff, ".TH test 1\n.SH NAME\ntest  \n\n");
+
+        let result = std::panic::catch_unwind(|| {
+            assert_roff_eq!(roff, ".TH test 1\n.SH NAME\ntset");
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.ends_with("- 3 | tset\n+ 3 | test\n       ^\n"));
+    }
+
+    #[test]
+    fn it_compares_golden_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("readme.1");
+        std::fs::write(&path, readme_example().render_to_string().unwrap()).unwrap();
+        assert_roff_matches_file!(readme_example(), &path);
+
+        let other = Roff::new("other", SectionNumber::UserCommands);
+        let result = std::panic::catch_unwind(|| assert_roff_matches_file!(other, &path));
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.contains("ROFFMAN_UPDATE_GOLDEN=1"));
+        assert!(message.contains("-  1 | .TH roffman 7 \"August 2021\"\n+  1 | .TH other 1\n"));
+    }
+}
@@ -1,59 +1,748 @@
-use crate::_macro::{BOLD, FONT_END, ITALIC};
-use crate::{escape, RoffError, Roffable};
+use crate::_macro::{
+    BOLD, CONSTANT_WIDTH, FONT_END, ITALIC, NO_HYPHENATION, SIZE_RESET, ZERO_WIDTH,
+};
+use crate::escape::is_removed_control;
+use crate::render::{LiteralFont, RenderOptions};
+use crate::{
+    escape_with_policy, unescape, EscapePolicy, HyphenMode, RoffError, Roffable, TabPolicy,
+};
 
+use std::borrow::Cow;
+use std::fmt;
 use std::io::Write;
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, Deref};
+use std::sync::{Arc, OnceLock};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Style that can be applied to [`RoffText`](RoffText).
 pub enum FontStyle {
     Bold,
     Italic,
+    #[default]
     Roman,
+    /// Constant-width (monospaced) font used for inline code. Terminal devices don't have a
+    /// constant-width font, `groff` maps it to the regular roman font there so the text is still
+    /// displayed, just not monospaced.
+    ConstantWidth,
+    /// Literal text like inline code, file names or commands. Rendered in the font selected with
+    /// [`Roff::literal_font`](crate::Roff::literal_font), never hyphenated and never treated as
+    /// the end of a sentence.
+    Literal,
 }
 
-impl Default for FontStyle {
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Wrapper type for styled text in ROFF. The most basic unit of text used in the document. It can
+/// be styled with various [`FontStyle`s](FontStyle) and keeps the content as it was provided. The
+/// content is escaped when it is first rendered so that it is safe to render and will be correctly
+/// displayed on various viewers.
+///
+/// A single `RoffText` can consist of multiple differently styled segments joined with
+/// [`RoffText::append`](RoffText::append).
+///
+/// The content is shared between clones of a text, so cloning is cheap. Documents that repeat the
+/// same text many times, like the names of parameters, can create it once and clone it.
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RoffText {
+    segments: Vec<Segment>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    escaped: OnceLock<Arc<Escaped>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A continuous fragment of [`RoffText`](RoffText) with a single style.
+#[cfg_attr(feature = "serde", serde(default))]
+struct Segment {
+    #[cfg_attr(feature = "serde", serde(rename = "text"))]
+    original: SharedStr,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "FontStyle::is_roman"))]
+    style: FontStyle,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_zero"))]
+    size: i8,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    raw: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    policy: Option<EscapePolicy>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    hyphens: Option<HyphenMode>,
+}
+
+#[derive(Clone)]
+/// Content of a [`Segment`](Segment) shared between all clones of a text. Static strings are
+/// borrowed without an allocation and owned strings are moved behind the `Arc` without copying
+/// their content.
+enum SharedStr {
+    Static(&'static str),
+    Owned(Arc<String>),
+    Shared(Arc<str>),
+}
+
+impl SharedStr {
+    /// Appends `s` to this content. Owned content that isn't shared with other clones is extended
+    /// in place, otherwise both are copied to a new allocation so that the other clones of the
+    /// text are not affected.
+    fn push_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if let Some(content) = match self {
+            SharedStr::Owned(content) => Arc::get_mut(content),
+            _ => None,
+        } {
+            content.push_str(s);
+            return;
+        }
+        let mut content = String::with_capacity(self.len() + s.len());
+        content.push_str(self);
+        content.push_str(s);
+        *self = content.into();
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            SharedStr::Static(content) => content,
+            SharedStr::Owned(content) => content,
+            SharedStr::Shared(content) => content,
+        }
+    }
+}
+
+impl Default for SharedStr {
     fn default() -> Self {
-        FontStyle::Roman
+        SharedStr::Static("")
     }
 }
 
-#[derive(Clone, Debug, Default, PartialEq)]
-/// Wrapper type for styled text in ROFF. The most basic unit of text used in the document. It can
-/// be styled with various [`FontStyle`s](FontStyle) and will escape it's contents on creation so
-/// that they are safe to render and will be correctly displayed on various viewers.
-pub struct RoffText {
-    content: String,
-    style: FontStyle,
+impl From<String> for SharedStr {
+    fn from(content: String) -> Self {
+        SharedStr::Owned(Arc::new(content))
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(content: &str) -> Self {
+        SharedStr::Shared(content.into())
+    }
+}
+
+impl From<Cow<'static, str>> for SharedStr {
+    fn from(content: Cow<'static, str>) -> Self {
+        match content {
+            Cow::Borrowed(content) => SharedStr::Static(content),
+            Cow::Owned(content) => content.into(),
+        }
+    }
+}
+
+/// Compares the content regardless of how it is stored.
+impl PartialEq for SharedStr {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for SharedStr {}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SharedStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SharedStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SharedStr::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FontStyle {
+    fn is_roman(&self) -> bool {
+        *self == FontStyle::Roman
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_zero(size: &i8) -> bool {
+    *size == 0
+}
+
+#[cfg(feature = "serde")]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Clone, Debug, Default)]
+/// Memoized escaped content of all segments of a [`RoffText`](RoffText) with the byte offsets at
+/// which each segment ends. The content is `None` for a single segment that didn't need any
+/// escaping, in which case the original content is used directly.
+struct Escaped {
+    content: Option<String>,
+    ends: Vec<usize>,
 }
 
 impl RoffText {
-    /// Create a new `RoffText` with `content` and optional font `style`. The text will automatically
-    /// be escaped on initialization.
+    /// Create a new `RoffText` with `content` and optional font `style`. The text will be escaped
+    /// when rendered.
     pub fn new<C: AsRef<str>>(content: C, style: Option<FontStyle>) -> Self {
-        Self {
-            content: escape(content),
+        Self::from_content(content.as_ref().into(), style)
+    }
+
+    fn from_content(original: SharedStr, style: Option<FontStyle>) -> Self {
+        Self::from_segment(Segment {
+            original,
             style: style.unwrap_or_default(),
+            size: 0,
+            raw: false,
+            policy: None,
+            hyphens: None,
+        })
+    }
+
+    /// Create a new `RoffText` from `content` that is rendered verbatim without escaping. Useful
+    /// for embedding hand-written escape sequences like the `\[u1F980]` glyph. The font style is
+    /// still applied around the content.
+    ///
+    /// The caller is responsible for the content being valid ROFF.
+    pub fn raw<C: Into<Cow<'static, str>>>(content: C) -> Self {
+        Self::from_segment(Segment {
+            original: content.into().into(),
+            style: FontStyle::default(),
+            size: 0,
+            raw: true,
+            policy: None,
+            hyphens: None,
+        })
+    }
+
+    /// Create a new `RoffText` from a static string without copying it. If the content doesn't
+    /// need escaping it is never copied, even when rendered.
+    pub fn from_static(content: &'static str) -> Self {
+        Self::from_segment(Segment {
+            original: SharedStr::Static(content),
+            style: FontStyle::default(),
+            size: 0,
+            raw: false,
+            policy: None,
+            hyphens: None,
+        })
+    }
+
+    fn from_segment(segment: Segment) -> Self {
+        Self {
+            segments: vec![segment],
+            escaped: OnceLock::new(),
         }
     }
 
     /// Set the style of this text to bold.
-    pub fn bold(mut self) -> Self {
-        self.style = FontStyle::Bold;
-        self
+    pub fn bold(self) -> Self {
+        self.with_style(FontStyle::Bold)
     }
 
     /// Set the style of this text to italic.
-    pub fn italic(mut self) -> Self {
-        self.style = FontStyle::Italic;
+    pub fn italic(self) -> Self {
+        self.with_style(FontStyle::Italic)
+    }
+
+    /// Set the style of this text to constant-width. Useful for inline code.
+    pub fn code(self) -> Self {
+        self.with_style(FontStyle::ConstantWidth)
+    }
+
+    /// Marks this text as a literal, like inline code or a file name. Unlike
+    /// [`code`](RoffText::code) the font used is selected at render time with
+    /// [`Roff::literal_font`](crate::Roff::literal_font) so that it can fall back to bold on
+    /// terminals where the constant-width font is indistinguishable from regular text. The text is
+    /// also protected from hyphenation and end of sentence spacing, and `-` is always rendered as a
+    /// minus sign so that the text can be copied from the page.
+    pub fn literal(self) -> Self {
+        self.with_style(FontStyle::Literal)
+    }
+
+    /// Sets the policy used to escape this text, overriding the one set for the whole document with
+    /// [`Roff::escape_policy`](crate::Roff::escape_policy).
+    pub fn with_policy(mut self, policy: EscapePolicy) -> Self {
+        self.segments
+            .iter_mut()
+            .for_each(|segment| segment.policy = Some(policy));
+        self.escaped = OnceLock::new();
+        self
+    }
+
+    /// Sets how `-` is rendered in this text regardless of the escape policy, see
+    /// [`HyphenMode`](HyphenMode). Text marked as [`literal`](RoffText::literal) uses
+    /// [`HyphenMode::Minus`](HyphenMode::Minus) unless set otherwise.
+    pub fn hyphens(mut self, hyphens: HyphenMode) -> Self {
+        self.segments
+            .iter_mut()
+            .for_each(|segment| segment.hyphens = Some(hyphens));
+        self.escaped = OnceLock::new();
         self
     }
 
-    /// Return the underlying escaped text.
-    pub(crate) fn content(&self) -> &str {
-        &self.content
+    /// Marks this text as prose where `-` is rendered as a hyphen, like in "well-known". Same as
+    /// setting [`hyphens`](RoffText::hyphens) to [`HyphenMode::Hyphen`](HyphenMode::Hyphen).
+    pub fn prose(self) -> Self {
+        self.hyphens(HyphenMode::Hyphen)
+    }
+
+    /// Renders `-` as a minus sign in all segments that don't have a hyphen mode set explicitly.
+    /// Used for option names and code that the reader may copy from the page.
+    pub(crate) fn minus_hyphens(mut self) -> Self {
+        self.segments
+            .iter_mut()
+            .filter(|segment| segment.hyphens.is_none())
+            .for_each(|segment| segment.hyphens = Some(HyphenMode::Minus));
+        self.escaped = OnceLock::new();
+        self
+    }
+
+    /// Change the size of this text relative to the surrounding text by `delta` points. Negative
+    /// values make the text smaller. The size is restored right after the text.
+    pub fn size(mut self, delta: i8) -> Self {
+        self.segments
+            .iter_mut()
+            .for_each(|segment| segment.size = delta);
+        self
+    }
+
+    /// Appends `other` to the end of this text keeping the styles of both, so that text like
+    /// `\fB\-v\fR, \fB\-\-verbose\fR=\fIWHEN\fR` can be used wherever a single text is
+    /// accepted.
+    ///
+    /// Styles applied to the text after appending apply to all of its segments.
+    pub fn append(mut self, other: impl Into<RoffText>) -> Self {
+        self += other.into();
+        self
+    }
+
+    /// Joins `items` into a single text with `separator` placed between each of them. The styles of
+    /// the items and the separator are kept.
+    pub fn join<I>(items: I, separator: impl Into<RoffText>) -> Self
+    where
+        I: IntoIterator<Item = RoffText>,
+    {
+        let separator = separator.into();
+        let mut out = RoffText::default();
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                out += separator.clone();
+            }
+            out += item;
+        }
+        out
+    }
+
+    /// Returns the escaped content of this text without any font or size escapes, the way it
+    /// will appear in the document between the font changes. Use the [`Display`](fmt::Display)
+    /// implementation to get the styled form and [`original`](RoffText::original) to get the text
+    /// as it was before escaping.
+    pub fn as_str(&self) -> &str {
+        match (&self.escaped().content, self.segments.as_slice()) {
+            (Some(content), _) => content,
+            (None, [segment]) => &segment.original,
+            (None, _) => "",
+        }
+    }
+
+    /// Returns the text as it was passed in, before escaping and without any styling.
+    pub fn plain(&self) -> String {
+        self.original().into_owned()
+    }
+
+    /// Returns the text as it was passed in, before escaping and without any styling. Borrows the
+    /// content if the text consists of a single segment.
+    pub fn original(&self) -> Cow<'_, str> {
+        match self.segments.as_slice() {
+            [segment] => Cow::Borrowed(&segment.original),
+            segments => Cow::Owned(segments.iter().map(|s| &*s.original).collect()),
+        }
+    }
+
+    /// Returns the text as it is displayed, with the escape sequences of raw segments replaced by
+    /// the characters they stand for.
+    pub(crate) fn unescaped(&self) -> Cow<'_, str> {
+        match self.segments.as_slice() {
+            [segment] if !segment.raw => Cow::Borrowed(&segment.original),
+            segments => Cow::Owned(
+                segments
+                    .iter()
+                    .map(|s| match s.raw {
+                        true => unescape(&s.original),
+                        false => Cow::Borrowed(s.original.as_ref()),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the displayed content of every segment like [`unescaped`](RoffText::unescaped)
+    /// together with its font style.
+    pub(crate) fn unescaped_segments(&self) -> impl Iterator<Item = (Cow<'_, str>, FontStyle)> {
+        self.segments.iter().map(|s| {
+            let content = match s.raw {
+                true => unescape(&s.original),
+                false => Cow::Borrowed(s.original.as_ref()),
+            };
+            (content, s.style)
+        })
+    }
+
+    /// Returns `true` if the text contains control characters that are removed when escaping.
+    pub(crate) fn has_control_chars(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| !segment.raw && segment.original.chars().any(is_removed_control))
+    }
+
+    /// Returns `true` if this text has no content.
+    pub fn is_empty(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| segment.original.is_empty())
+    }
+
+    /// Returns an estimate of the number of bytes this text takes when rendered. Escaping grows
+    /// the content by a sixteenth and styled segments are surrounded with font escapes.
+    pub(crate) fn estimated_size(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| {
+                let escapes = if segment.style == FontStyle::Roman && segment.size == 0 {
+                    0
+                } else {
+                    8
+                };
+                segment.original.len() + segment.original.len() / 16 + escapes
+            })
+            .sum()
+    }
+
+    /// Returns the number of characters of this text before escaping.
+    pub fn len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|segment| segment.original.chars().count())
+            .sum()
+    }
+
+    /// Appends `s` to the end of this text with the same style, size, escape policy and hyphen
+    /// mode as the last segment. The string is escaped.
+    pub fn push_str(&mut self, s: &str) {
+        match self.segments.last_mut() {
+            Some(segment) if !segment.raw => segment.original.push_str(s),
+            Some(segment) => {
+                let appended = Segment {
+                    original: s.to_string().into(),
+                    raw: false,
+                    ..segment.clone()
+                };
+                self.segments.push(appended);
+            }
+            None => *self += s.roff(),
+        }
+        self.escaped = OnceLock::new();
+    }
+
+    /// Returns the font style of this text. If the text consists of differently styled segments
+    /// the style of the first one is returned.
+    pub fn style(&self) -> FontStyle {
+        self.segments
+            .first()
+            .map(|segment| segment.style)
+            .unwrap_or_default()
+    }
+
+    /// Sets the font style of this text, including all of its segments.
+    pub fn set_style(&mut self, style: FontStyle) {
+        self.segments
+            .iter_mut()
+            .for_each(|segment| segment.style = style);
+        self.escaped = OnceLock::new();
     }
 
-    pub(crate) fn render<W: Write>(&self, writer: &mut W) -> Result<(), RoffError> {
+    fn with_style(mut self, style: FontStyle) -> Self {
+        self.set_style(style);
+        self
+    }
+
+    fn escaped(&self) -> &Escaped {
+        self.escaped
+            .get_or_init(|| Arc::new(self.escape_segments()))
+    }
+
+    fn escape_segments(&self) -> Escaped {
+        match self.segments.as_slice() {
+            [segment] => Escaped {
+                content: match segment.escape(&EscapePolicy::default()) {
+                    Cow::Borrowed(_) => None,
+                    Cow::Owned(content) => Some(content),
+                },
+                ends: vec![],
+            },
+            segments => {
+                let mut content = String::new();
+                let mut ends = Vec::with_capacity(segments.len());
+                for segment in segments {
+                    content.push_str(&segment.escape(&EscapePolicy::default()));
+                    ends.push(content.len());
+                }
+                Escaped {
+                    content: Some(content),
+                    ends,
+                }
+            }
+        }
+    }
+
+    /// Returns a new text with the same styles as this one where the original content of each
+    /// segment is replaced with the output of `f`.
+    pub(crate) fn map_original<F>(&self, f: F) -> Self
+    where
+        F: Fn(&str) -> String,
+    {
+        Self {
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| Segment {
+                    original: f(&segment.original).into(),
+                    ..*segment
+                })
+                .collect(),
+            escaped: OnceLock::new(),
+        }
+    }
+
+    /// Replaces the content of every segment of this text with the output of `f`, keeping the
+    /// styles of the segments. `f` receives the content as it was provided, before escaping, and
+    /// its output is escaped when the text is rendered.
+    pub fn map_content<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str) -> String,
+    {
+        for segment in &mut self.segments {
+            segment.original = f(&segment.original).into();
+        }
+        self.escaped = OnceLock::new();
+    }
+
+    /// Removes spaces and tabs at the end of each line of this text. Whitespace at the very end of
+    /// the text is kept as it is usually followed by more text on the same line.
+    pub fn trim_trailing_whitespace(self) -> Self {
+        if !self.original().contains(" \n") && !self.original().contains("\t\n") {
+            return self;
+        }
+        self.map_original(|original| {
+            let mut lines = original.split('\n').peekable();
+            let mut out = String::with_capacity(original.len());
+            while let Some(line) = lines.next() {
+                if lines.peek().is_some() {
+                    out.push_str(line.trim_end_matches([' ', '\t']));
+                    out.push('\n');
+                } else {
+                    out.push_str(line);
+                }
+            }
+            out
+        })
+    }
+
+    /// Replaces all tab characters and newlines in this text with spaces, so that it stays in a
+    /// single cell of a [`columns`](crate::RoffNode::columns) row.
+    pub(crate) fn single_line(self) -> Self {
+        if self.original().contains(['\t', '\n']) {
+            self.map_original(|original| original.replace(['\t', '\n'], " "))
+        } else {
+            self
+        }
+    }
+
+    /// Renders this text at the start of a line. A line starting with a `.` or `'` is interpreted as
+    /// a control line, so a leading dot or apostrophe is preceded with the zero-width `\&` escape.
+    /// Raw text is left as it is.
+    pub(crate) fn render_line_start<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        let first = self
+            .segments
+            .iter()
+            .map(|segment| {
+                (
+                    segment,
+                    segment.original.trim_start_matches(is_removed_control),
+                )
+            })
+            .find(|(_, content)| !content.is_empty());
+        if let Some((segment, content)) = first {
+            if !segment.raw && content.starts_with(['.', '\'', '…']) {
+                writer.write_all(ZERO_WIDTH)?;
+            }
+        }
+        self.render(writer, options)
+    }
+
+    /// Returns `true` if the rendered text ends with a newline, so that the output following it
+    /// starts a new line. Styled or resized text ends with the escape resetting the font or size
+    /// instead.
+    pub(crate) fn ends_line(&self) -> bool {
+        self.segments
+            .iter()
+            .rev()
+            .map(|segment| {
+                (
+                    segment,
+                    segment.original.trim_end_matches(is_removed_control),
+                )
+            })
+            .find(|(_, content)| !content.is_empty())
+            .is_some_and(|(segment, content)| {
+                segment.style == FontStyle::Roman && segment.size == 0 && content.ends_with('\n')
+            })
+    }
+
+    pub(crate) fn render<W: Write>(
+        &self,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if let [segment] = self.segments.as_slice() {
+            let content = segment.escape_for(self.as_str(), options);
+            return segment.render(&content, writer, options);
+        }
+
+        let escaped = self.escaped();
+        let content = escaped.content.as_deref().unwrap_or_default();
+        let mut start = 0;
+        for (segment, &end) in self.segments.iter().zip(&escaped.ends) {
+            let content = segment.escape_for(&content[start..end], options);
+            segment.render(&content, writer, options)?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+/// Renders `texts` one after another, starting at the beginning of a line if `line_start` is set.
+/// Every text that begins a line is protected from being read as a control line, including the
+/// ones following a text that ends with a newline. Returns `true` if the output ends at the
+/// beginning of a line.
+pub(crate) fn render_texts<'a, W: Write>(
+    texts: impl IntoIterator<Item = &'a RoffText>,
+    mut line_start: bool,
+    writer: &mut W,
+    options: &RenderOptions,
+) -> Result<bool, RoffError> {
+    for text in texts {
+        if line_start {
+            text.render_line_start(writer, options)?;
+        } else {
+            text.render(writer, options)?;
+        }
+        // Escaped text made only of removed control characters leaves the line start as it was.
+        let empty = text.segments.iter().all(|segment| {
+            segment.original.is_empty()
+                || (!segment.raw && segment.original.chars().all(is_removed_control))
+        });
+        line_start = text.ends_line() || (line_start && empty);
+    }
+    Ok(line_start)
+}
+
+/// Two texts are equal if they consist of the same segments, comparing the original content
+/// before escaping together with the style, size and escaping options of every segment. Whether
+/// the content was already escaped for rendering doesn't matter.
+impl PartialEq for RoffText {
+    fn eq(&self, other: &Self) -> bool {
+        self.segments == other.segments
+    }
+}
+
+impl Eq for RoffText {}
+
+/// Number of spaces a tab is expanded to in filled text.
+const DEFAULT_TAB_WIDTH: u8 = 4;
+
+impl Segment {
+    /// Returns the policy used to escape this segment, its own one or `default` with the hyphen
+    /// mode of the segment applied. Smart punctuation is disabled for literal and constant-width
+    /// text.
+    fn policy(&self, default: &EscapePolicy) -> EscapePolicy {
+        let mut policy = self.policy.unwrap_or(*default);
+        if matches!(self.style, FontStyle::Literal | FontStyle::ConstantWidth) {
+            policy = policy.smart_punctuation(false);
+        }
+        match (self.hyphens, self.style) {
+            (Some(hyphens), _) => policy.hyphens(hyphens),
+            (None, FontStyle::Literal) => policy.hyphens(HyphenMode::Minus),
+            (None, _) => policy,
+        }
+    }
+
+    /// Escapes this segment with its own policy or the `default` one if it doesn't have one.
+    fn escape(&self, default: &EscapePolicy) -> Cow<'_, str> {
+        if self.raw {
+            Cow::Borrowed(&self.original)
+        } else {
+            escape_with_policy(&self.original, &self.policy(default))
+        }
+    }
+
+    /// Returns the content of this segment escaped for rendering with `options`. The `memoized`
+    /// content escaped with the default policy is used unless the options define a different one
+    /// or the tabs of the content have to be handled according to the rendering context.
+    fn escape_for<'a>(&'a self, memoized: &'a str, options: &RenderOptions) -> Cow<'a, str> {
+        if self.raw {
+            return Cow::Borrowed(memoized);
+        }
+        let mut policy = self.policy(&options.escape_policy);
+        if options.no_fill {
+            policy = policy.smart_punctuation(false);
+        }
+        if policy.get_tabs().is_none() && self.original.contains('\t') {
+            let tabs = if options.no_fill {
+                TabPolicy::Keep
+            } else {
+                TabPolicy::Expand(DEFAULT_TAB_WIDTH)
+            };
+            return escape_with_policy(&self.original, &policy.tabs(tabs));
+        }
+
+        if policy == self.policy(&EscapePolicy::default()) {
+            Cow::Borrowed(memoized)
+        } else {
+            escape_with_policy(&self.original, &policy)
+        }
+    }
+
+    fn render<W: Write>(
+        &self,
+        content: &str,
+        writer: &mut W,
+        options: &RenderOptions,
+    ) -> Result<(), RoffError> {
+        if self.size != 0 {
+            write_size(self.size, writer)?;
+        }
+
         let styled = match self.style {
             FontStyle::Bold => {
                 writer.write_all(BOLD)?;
@@ -63,20 +752,237 @@ impl RoffText {
                 writer.write_all(ITALIC)?;
                 true
             }
+            FontStyle::ConstantWidth => {
+                writer.write_all(CONSTANT_WIDTH)?;
+                true
+            }
+            FontStyle::Literal => {
+                match options.literal_font {
+                    LiteralFont::ConstantWidth => writer.write_all(CONSTANT_WIDTH)?,
+                    LiteralFont::Bold => writer.write_all(BOLD)?,
+                }
+                true
+            }
             FontStyle::Roman => false,
         };
 
-        writer.write_all(self.content.as_bytes())?;
+        if self.style == FontStyle::Literal {
+            writer.write_all(NO_HYPHENATION)?;
+        }
+        writer.write_all(content.as_bytes())?;
+        if self.style == FontStyle::Literal && content.ends_with(['.', '?', '!']) {
+            writer.write_all(ZERO_WIDTH)?;
+        }
         if styled {
             writer.write_all(FONT_END)?;
         }
+        if self.size != 0 {
+            writer.write_all(SIZE_RESET)?;
+        }
 
         Ok(())
     }
 }
 
+/// Creates a [`RoffText`](crate::RoffText) from a format string like [`format!`](format!). Literal
+/// parts of the format string are escaped while the arguments are converted with
+/// [`Roffable::roff`](crate::Roffable::roff) so that the styles of interpolated
+/// [`RoffText`s](crate::RoffText) are kept. Only the `{}` placeholder is supported, literal braces
+/// are written as `{{` and `}}`.
+///
+/// ```
+/// use roffman::{roff_text, Roff, Roffable, SectionNumber};
+///
+/// let text = roff_text!("listening on {} port {}", "localhost".roff().bold(), 80u8);
+/// let roff = Roff::new("test", SectionNumber::Miscellaneous).section("DESCRIPTION", [text]);
+/// assert!(roff
+///     .render_to_string()
+///     .unwrap()
+///     .ends_with(r"listening on \fBlocalhost\fR port 80"));
+/// ```
+///
+/// # Panics
+///
+/// Panics if the number of placeholders doesn't match the number of arguments or if the format
+/// string contains an unmatched brace.
+#[macro_export]
+macro_rules! roff_text {
+    ($fmt:expr $(, $arg:expr)* $(,)?) => {
+        $crate::RoffText::__format($fmt, ::std::vec![$($crate::Roffable::roff(&$arg)),*])
+    };
+}
+
+impl RoffText {
+    #[doc(hidden)]
+    /// Implementation of the [`roff_text!`](roff_text!) macro.
+    pub fn __format(fmt: &str, args: Vec<RoffText>) -> RoffText {
+        let mut args = args.into_iter();
+        let mut out = RoffText::default();
+        let mut literal = String::new();
+        let mut chars = fmt.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                ('{', Some('}')) => {
+                    chars.next();
+                    if !literal.is_empty() {
+                        out += literal.as_str();
+                        literal.clear();
+                    }
+                    out += args
+                        .next()
+                        .expect("roff_text!: missing argument for a `{}` placeholder");
+                }
+                ('{', _) | ('}', _) => {
+                    panic!("roff_text!: invalid format string, unmatched `{}`", c)
+                }
+                _ => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            out += literal.as_str();
+        }
+        assert!(
+            args.next().is_none(),
+            "roff_text!: more arguments than placeholders"
+        );
+        out
+    }
+}
+
+/// Writes the escape sequence changing the size of text by `delta`. Single digit sizes use the
+/// `\s-2` form supported by all implementations of `troff`, larger ones use the two digit `\s-(12`
+/// form or the `\s[-120]` form for three digits.
+fn write_size<W: Write>(delta: i8, writer: &mut W) -> Result<(), RoffError> {
+    let sign = if delta < 0 { '-' } else { '+' };
+    let value = delta.unsigned_abs();
+    let escape = if value < 10 {
+        format!("\\s{}{}", sign, value)
+    } else if value < 100 {
+        format!("\\s{}({}", sign, value)
+    } else {
+        format!("\\s[{}{}]", sign, value)
+    };
+    writer.write_all(escape.as_bytes())?;
+    Ok(())
+}
+
+/// Joins two texts keeping the styles of both.
+///
+/// ```
+/// use roffman::{Roff, Roffable, SectionNumber};
+///
+/// let opt = "--color".roff().bold() + "=".roff() + "WHEN".roff().italic();
+/// let roff = Roff::new("test", SectionNumber::Miscellaneous).section("OPTIONS", [opt]);
+/// assert!(roff.render_to_string().unwrap().ends_with(r"\fB\-\-color\fR=\fIWHEN\fR"));
+/// ```
+impl Add for RoffText {
+    type Output = RoffText;
+
+    fn add(self, rhs: RoffText) -> Self::Output {
+        self.append(rhs)
+    }
+}
+
+/// Appends a plain, unstyled string to the text. The string is escaped.
+///
+/// ```
+/// use roffman::{Roff, Roffable, SectionNumber};
+///
+/// let opt = "-o".roff().bold() + " FILE";
+/// let roff = Roff::new("test", SectionNumber::Miscellaneous).section("OPTIONS", [opt]);
+/// assert!(roff.render_to_string().unwrap().ends_with(r"\fB\-o\fR FILE"));
+/// ```
+impl Add<&str> for RoffText {
+    type Output = RoffText;
+
+    fn add(self, rhs: &str) -> Self::Output {
+        self.append(rhs)
+    }
+}
+
+/// Appends a text to this one in place keeping the styles of both.
+///
+/// ```
+/// use roffman::{Roff, Roffable, SectionNumber};
+///
+/// let mut opt = "--jobs".roff().bold();
+/// opt += "=";
+/// opt += "N".roff().italic();
+/// let roff = Roff::new("test", SectionNumber::Miscellaneous).section("OPTIONS", [opt]);
+/// assert!(roff.render_to_string().unwrap().ends_with(r"\fB\-\-jobs\fR=\fIN\fR"));
+/// ```
+impl AddAssign for RoffText {
+    fn add_assign(&mut self, rhs: RoffText) {
+        self.segments.extend(rhs.segments);
+        self.escaped = OnceLock::new();
+    }
+}
+
+impl AddAssign<&str> for RoffText {
+    fn add_assign(&mut self, rhs: &str) {
+        *self += rhs.roff();
+    }
+}
+
+/// Displays the text in its styled ROFF form, that is escaped and wrapped in font and size
+/// escapes like `\fBbold\fR`. Use [`as_str`](RoffText::as_str) for the escaped text without styling.
+impl fmt::Display for RoffText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = vec![];
+        self.render(&mut out, &RenderOptions::default())
+            .map_err(|_| fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&out))
+    }
+}
+
+/// Returns the escaped text without styling, same as [`as_str`](RoffText::as_str).
+impl AsRef<str> for RoffText {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// Concatenates the texts keeping all of their styles.
+impl FromIterator<RoffText> for RoffText {
+    fn from_iter<I: IntoIterator<Item = RoffText>>(iter: I) -> Self {
+        let mut out = RoffText::default();
+        for item in iter {
+            out += item;
+        }
+        out
+    }
+}
+
 impl Roffable for RoffText {
     fn roff(&self) -> RoffText {
         self.clone()
     }
 }
+
+/// Creates unstyled text from the owned `content`, moving it into the text without copying it.
+impl From<String> for RoffText {
+    fn from(content: String) -> Self {
+        Self::from_content(content.into(), None)
+    }
+}
+
+/// Creates unstyled text, same as [`RoffText::new`](RoffText::new) without a style.
+impl From<&str> for RoffText {
+    fn from(content: &str) -> Self {
+        Self::from_content(content.into(), None)
+    }
+}
+
+/// Borrowed content is copied and owned content is moved into the text.
+impl From<Cow<'_, str>> for RoffText {
+    fn from(content: Cow<'_, str>) -> Self {
+        match content {
+            Cow::Borrowed(content) => content.into(),
+            Cow::Owned(content) => content.into(),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::node::RoffNodeInner;
+use crate::{Roff, RoffNode, RoffText, SynopsisItem, SynopsisOpt};
+
+use std::iter;
+
+impl Roff {
+    /// Applies `f` to every piece of text of this document and of all appended pages: the title,
+    /// aliases and header fields, section titles and subtitles and all text of the nodes,
+    /// including paragraph titles, option names and descriptions, and link addresses. Only the
+    /// tab stops of [`columns`](RoffNode::columns) are skipped as they are not text.
+    ///
+    /// The text is passed as it was provided and is escaped when the document is rendered, so `f`
+    /// can modify it freely with methods like [`RoffText::map_content`](RoffText::map_content) or
+    /// replace it with a new text, and the rendered output stays safe.
+    pub fn map_text(&mut self, mut f: impl FnMut(&mut RoffText)) {
+        self.map_page_text(&mut f);
+        for page in &mut self.pages {
+            page.map_page_text(&mut f);
+        }
+    }
+
+    fn map_page_text(&mut self, f: &mut impl FnMut(&mut RoffText)) {
+        iter::once(&mut self.title)
+            .chain(&mut self.aliases)
+            .chain(&mut self.date)
+            .chain(&mut self.source)
+            .chain(&mut self.manual)
+            .for_each(&mut *f);
+        for section in &mut self.sections {
+            let (title, subtitle) = section.titles_mut();
+            f(title);
+            subtitle.into_iter().for_each(&mut *f);
+            for node in section.get_nodes_mut() {
+                map_node_text(node, f);
+            }
+        }
+    }
+
+    /// Calls `f` for every node of this document and of all appended pages depth-first, replacing
+    /// the node with the returned one if `f` returns `Some`. The children of a node are visited
+    /// after the node itself only if it is kept, a replacement is not visited again.
+    pub fn map_nodes(&mut self, mut f: impl FnMut(&RoffNode) -> Option<RoffNode>) {
+        let pages = iter::once(&mut self.sections)
+            .chain(self.pages.iter_mut().map(|page| &mut page.sections));
+        for sections in pages {
+            for section in sections {
+                map_nodes(section.get_nodes_mut(), &mut f);
+            }
+        }
+    }
+}
+
+fn map_nodes(nodes: &mut [RoffNode], f: &mut impl FnMut(&RoffNode) -> Option<RoffNode>) {
+    for node in nodes {
+        match f(node) {
+            Some(replacement) => *node = replacement,
+            None => match node.inner_mut() {
+                RoffNodeInner::Paragraph(content)
+                | RoffNodeInner::IndentedParagraph { content, .. }
+                | RoffNodeInner::TaggedParagraph { content, .. }
+                | RoffNodeInner::Nested { nodes: content, .. }
+                | RoffNodeInner::Group(content) => map_nodes(content, f),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn map_node_text(node: &mut RoffNode, f: &mut impl FnMut(&mut RoffText)) {
+    match node.inner_mut() {
+        RoffNodeInner::Text(text) | RoffNodeInner::SubHeading(text) => f(text),
+        RoffNodeInner::Whatis { names, description } => {
+            names.iter_mut().chain(iter::once(description)).for_each(f)
+        }
+        RoffNodeInner::Paragraph(content)
+        | RoffNodeInner::Nested { nodes: content, .. }
+        | RoffNodeInner::Group(content) => {
+            for node in content {
+                map_node_text(node, f);
+            }
+        }
+        RoffNodeInner::IndentedParagraph { content, title, .. } => {
+            title.iter_mut().for_each(&mut *f);
+            for node in content {
+                map_node_text(node, f);
+            }
+        }
+        RoffNodeInner::TaggedParagraph { content, title } => {
+            f(title);
+            for node in content {
+                map_node_text(node, f);
+            }
+        }
+        RoffNodeInner::Example(lines) => lines.iter_mut().for_each(f),
+        RoffNodeInner::Columns { rows, .. } => {
+            for (key, value) in rows {
+                f(key);
+                f(value);
+            }
+        }
+        RoffNodeInner::Synopsis {
+            command,
+            text,
+            opts,
+        } => {
+            iter::once(command).chain(text).for_each(&mut *f);
+            map_options_text(opts, f);
+        }
+        RoffNodeInner::OptionList(opts) => map_options_text(opts, f),
+        RoffNodeInner::Url { name, address } | RoffNodeInner::Email { name, address } => {
+            f(name);
+            f(address);
+        }
+        RoffNodeInner::RegisteredSign
+        | RoffNodeInner::LeftQuote
+        | RoffNodeInner::RightQuote
+        | RoffNodeInner::TrademarkSign
+        | RoffNodeInner::Break
+        | RoffNodeInner::EmDash
+        | RoffNodeInner::EnDash
+        | RoffNodeInner::NonBreakingSpace
+        | RoffNodeInner::Comment(_) => {}
+    }
+}
+
+fn map_options_text(opts: &mut [SynopsisItem], f: &mut impl FnMut(&mut RoffText)) {
+    for item in opts {
+        match item {
+            SynopsisItem::Opt(opt) => map_option_text(opt, f),
+            SynopsisItem::Group(group) => {
+                f(&mut group.title);
+                for opt in &mut group.opts {
+                    map_option_text(opt, f);
+                }
+            }
+        }
+    }
+}
+
+fn map_option_text(opt: &mut SynopsisOpt, f: &mut impl FnMut(&mut RoffText)) {
+    iter::once(&mut opt.name)
+        .chain(&mut opt.argument)
+        .chain(opt.description.iter_mut().flatten())
+        .chain(&mut opt.default_value)
+        .chain(&mut opt.possible_values)
+        .for_each(f);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Roff, RoffNode, Roffable, Section, SectionNumber, SynopsisOpt};
+
+    fn roff() -> Roff {
+        Roff::new("foo", SectionNumber::UserCommands)
+            .section(
+                "description",
+                [
+                    RoffNode::paragraph([
+                        RoffNode::text("See "),
+                        RoffNode::url("the docs", "https://example.com/foo"),
+                        RoffNode::nested([RoffNode::tagged_paragraph(
+                            [RoffNode::url("mirror", "https://example.com/bar")],
+                            "links",
+                        )]),
+                    ]),
+                    RoffNode::option_list([SynopsisOpt::new("--url")
+                        .argument("URL")
+                        .default_value("https://example.com")]),
+                ],
+            )
+            .append_page(Roff::new("bar", SectionNumber::UserCommands).section(
+                "see also",
+                [RoffNode::url("home", "https://example.com/home")],
+            ))
+    }
+
+    #[test]
+    fn it_rewrites_urls_in_nested_nodes() {
+        let mut roff = roff();
+        roff.map_text(|text| {
+            text.map_content(|content| content.replace("https://example.com", "https://mirror.org"))
+        });
+
+        let rendered = roff.render_to_string().unwrap();
+        assert!(!rendered.contains("example.com"));
+        assert_eq!(rendered.matches("mirror.org").count(), 4);
+        assert!(rendered.contains(".UR https://mirror.org/bar\n"));
+        assert!(rendered.contains(".UR https://mirror.org/home\n"));
+    }
+
+    #[test]
+    fn it_uppercases_titles() {
+        let mut roff = Roff::new("foo", SectionNumber::UserCommands)
+            .section("options", [RoffNode::tagged_paragraph(["body"], "tag")])
+            .add_section(Section::new("see also", None::<RoffNode>).subtitle("subtitle"));
+        roff.map_text(|text| text.map_content(str::to_uppercase));
+
+        assert_eq!(
+            roff.render_to_string().unwrap(),
+            r#".TH FOO 1
+.SH OPTIONS
+.TP
+TAG
+BODY
+.SH "SEE ALSO"
+.SS SUBTITLE
+"#
+        );
+    }
+
+    #[test]
+    fn it_replaces_nodes() {
+        let mut roff = roff();
+        let mut visited = vec![];
+        roff.map_nodes(|node| {
+            visited.push(node.kind_name());
+            match node.kind_name() {
+                "url" => Some(RoffNode::text(node.text_content().roff().bold())),
+                _ => None,
+            }
+        });
+        assert_eq!(
+            visited,
+            [
+                "paragraph",
+                "text",
+                "url",
+                "nested block",
+                "tagged paragraph",
+                "url",
+                "option list",
+                "url"
+            ]
+        );
+
+        let rendered = roff.render_to_string().unwrap();
+        assert!(!rendered.contains(".UR"));
+        assert!(rendered.contains("\\fBthe docs\\fR"));
+        assert!(rendered.contains("\\fBmirror\\fR"));
+        assert!(rendered.contains("\\fBhome\\fR"));
+    }
+}
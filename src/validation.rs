@@ -0,0 +1,311 @@
+use crate::node::{is_valid_tab_stop, RoffNodeInner};
+use crate::{NodePath, Roff, RoffError, RoffNode, RoffText, SynopsisItem, SynopsisOpt};
+
+use std::slice;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// The kind of a structural problem rejected by [`Roff::validated`](Roff::validated).
+pub enum ValidationKind {
+    /// The title of the document is empty.
+    EmptyTitle,
+    /// The title of a section is empty.
+    EmptySectionTitle,
+    /// A title or a field of the title header contains control characters like newlines.
+    ControlCharacters,
+    /// An option of a synopsis or an options list has an empty name.
+    EmptyOptionName,
+    /// The tab stop of a [`columns`](RoffNode::columns) node is not a distance like `2i`.
+    InvalidTabStop,
+}
+
+impl Roff {
+    /// Returns this document if it is structurally valid, or
+    /// [`RoffError::Validation`](RoffError::Validation) with the location of the first problem
+    /// otherwise. A document is rejected if the title or a section title is empty, if the titles,
+    /// the date, the source or the manual contain control characters, if a synopsis option has an
+    /// empty name, or if the tab stop of a [`columns`](RoffNode::columns) node is not a valid
+    /// distance. Appended pages are checked as well, with paths into the page.
+    ///
+    /// Unlike the conventions checked by [`validate`](Roff::validate), these problems produce
+    /// malformed output like `.TH  7`. Rendering doesn't check them, so documents that are not
+    /// validated are rendered as they are.
+    pub fn validated(self) -> Result<Self, RoffError> {
+        self.check_structure()?;
+        for (i, page) in self.pages.iter().enumerate() {
+            page.check_structure().map_err(|err| match err {
+                RoffError::Validation {
+                    kind,
+                    path,
+                    message,
+                } => RoffError::Validation {
+                    kind,
+                    path: path.in_page(i),
+                    message,
+                },
+                err => err,
+            })?;
+        }
+        Ok(self)
+    }
+
+    fn check_structure(&self) -> Result<(), RoffError> {
+        if is_blank(&self.title) {
+            return Err(RoffError::Validation {
+                kind: ValidationKind::EmptyTitle,
+                path: NodePath::document(),
+                message: "the document title is empty".to_string(),
+            });
+        }
+        let fields = [
+            ("title", Some(&self.title)),
+            ("date", self.date.as_ref()),
+            ("source", self.source.as_ref()),
+            ("manual", self.manual.as_ref()),
+        ];
+        for (what, text) in fields {
+            if let Some(text) = text {
+                check_control_chars(what, text, NodePath::document)?;
+            }
+        }
+
+        for (i, section) in self.sections.iter().enumerate() {
+            let path = NodePath::section(i, section.get_title().original());
+            if is_blank(section.get_title()) {
+                return Err(RoffError::Validation {
+                    kind: ValidationKind::EmptySectionTitle,
+                    path,
+                    message: "the section title is empty".to_string(),
+                });
+            }
+            check_control_chars("section title", section.get_title(), || path.clone())?;
+            if let Some(subtitle) = section.get_subtitle() {
+                check_control_chars("section subtitle", subtitle, || path.clone())?;
+            }
+            check_nodes(section.get_nodes(), &path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `text` is empty or consists only of whitespace.
+fn is_blank(text: &RoffText) -> bool {
+    text.original().trim().is_empty()
+}
+
+fn check_control_chars(
+    what: &str,
+    text: &RoffText,
+    path: impl FnOnce() -> NodePath,
+) -> Result<(), RoffError> {
+    match text.original().chars().find(|ch| ch.is_control()) {
+        Some(ch) => Err(RoffError::Validation {
+            kind: ValidationKind::ControlCharacters,
+            path: path(),
+            message: format!(
+                "the {} contains the control character U+{:04X}",
+                what,
+                u32::from(ch)
+            ),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks the options of all synopsis blocks and options lists and the tab stops of all columns in
+/// `nodes` and their children.
+fn check_nodes(nodes: &[RoffNode], path: &NodePath) -> Result<(), RoffError> {
+    for (i, node) in nodes.iter().enumerate() {
+        let path = path.clone().node(i);
+        let items = match node.inner_ref() {
+            RoffNodeInner::Synopsis { opts, .. } | RoffNodeInner::OptionList(opts) => {
+                opts.as_slice()
+            }
+            _ => &[],
+        };
+        if items
+            .iter()
+            .flat_map(item_opts)
+            .any(|opt| is_blank(opt.get_name()))
+        {
+            return Err(RoffError::Validation {
+                kind: ValidationKind::EmptyOptionName,
+                path,
+                message: "an option has an empty name".to_string(),
+            });
+        }
+        if let RoffNodeInner::Columns { tab_stop, .. } = node.inner_ref() {
+            if !is_valid_tab_stop(&tab_stop.original()) {
+                return Err(RoffError::Validation {
+                    kind: ValidationKind::InvalidTabStop,
+                    path,
+                    message: format!(
+                        "the tab stop `{}` is not a valid distance",
+                        tab_stop.original()
+                    ),
+                });
+            }
+        }
+        check_nodes(node.children(), &path)?;
+    }
+    Ok(())
+}
+
+fn item_opts(item: &SynopsisItem) -> &[SynopsisOpt] {
+    match item {
+        SynopsisItem::Opt(opt) => slice::from_ref(opt),
+        SynopsisItem::Group(group) => group.get_opts(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationKind;
+    use crate::{
+        NodePath, Roff, RoffError, RoffNode, Section, SectionNumber, SynopsisItem, SynopsisOpt,
+        SynopsisOptGroup,
+    };
+
+    fn valid() -> Roff {
+        Roff::new("foo", SectionNumber::UserCommands)
+            .date("August 2021")
+            .source("foo 1.0")
+            .section("NAME", ["foo - does things"])
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis(
+                    "foo",
+                    ["does things"],
+                    [SynopsisOpt::new("-v").description(["verbose"])],
+                )],
+            )
+    }
+
+    fn rejection(roff: Roff) -> (ValidationKind, NodePath, String) {
+        match roff.validated() {
+            Err(RoffError::Validation {
+                kind,
+                path,
+                message,
+            }) => (kind, path, message),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_accepts_valid_documents() {
+        let roff = valid().append_page(valid());
+        assert_eq!(roff.clone().validated().unwrap(), roff);
+    }
+
+    #[test]
+    fn it_rejects_empty_titles() {
+        let (kind, path, _) = rejection(Roff::new("", SectionNumber::Miscellaneous));
+        assert_eq!(kind, ValidationKind::EmptyTitle);
+        assert_eq!(path, NodePath::document());
+        // Rendering stays permissive.
+        assert_eq!(
+            Roff::new("", SectionNumber::Miscellaneous)
+                .render_to_string()
+                .unwrap(),
+            ".TH  7\n"
+        );
+
+        let (kind, path, message) = rejection(valid().section(" ", ["text"]));
+        assert_eq!(kind, ValidationKind::EmptySectionTitle);
+        assert_eq!(path, NodePath::section(2, " "));
+        assert_eq!(message, "the section title is empty");
+
+        let page = valid().append_page(Roff::new(" ", SectionNumber::UserCommands));
+        let (kind, path, _) = rejection(page);
+        assert_eq!(kind, ValidationKind::EmptyTitle);
+        assert_eq!(path, NodePath::page(0));
+    }
+
+    #[test]
+    fn it_rejects_control_characters() {
+        let (kind, path, message) = rejection(valid().date("August\n2021"));
+        assert_eq!(kind, ValidationKind::ControlCharacters);
+        assert_eq!(path, NodePath::document());
+        assert_eq!(message, "the date contains the control character U+000A");
+
+        let (_, _, message) = rejection(Roff::new("foo\u{1b}[1m", SectionNumber::UserCommands));
+        assert_eq!(message, "the title contains the control character U+001B");
+        assert_eq!(
+            rejection(valid().manual("Foo\tManual")).2,
+            "the manual contains the control character U+0009"
+        );
+        assert_eq!(
+            rejection(valid().source("foo\r")).2,
+            "the source contains the control character U+000D"
+        );
+
+        let (kind, path, message) = rejection(valid().section("SEE\nALSO", ["bar(1)"]));
+        assert_eq!(kind, ValidationKind::ControlCharacters);
+        assert_eq!(path, NodePath::section(2, "SEE\nALSO"));
+        assert_eq!(
+            message,
+            "the section title contains the control character U+000A"
+        );
+
+        let section = Section::new("NOTES", ["text"]).subtitle("sub\u{7}title");
+        let (_, path, message) = rejection(valid().add_section(section));
+        assert_eq!(path, NodePath::section(2, "NOTES"));
+        assert_eq!(
+            message,
+            "the section subtitle contains the control character U+0007"
+        );
+    }
+
+    #[test]
+    fn it_rejects_options_with_empty_names() {
+        let roff = valid().section(
+            "OPTIONS",
+            [
+                RoffNode::text("Options:"),
+                RoffNode::paragraph([RoffNode::option_list([
+                    SynopsisItem::Opt(SynopsisOpt::new("-a")),
+                    SynopsisItem::Group(SynopsisOptGroup::new("Output", [SynopsisOpt::new("")])),
+                ])]),
+            ],
+        );
+        let (kind, path, message) = rejection(roff);
+        assert_eq!(kind, ValidationKind::EmptyOptionName);
+        assert_eq!(path, NodePath::section(2, "OPTIONS").node(1).node(0));
+        assert_eq!(message, "an option has an empty name");
+
+        let roff = Roff::new("foo", SectionNumber::UserCommands).section(
+            "SYNOPSIS",
+            [RoffNode::synopsis(
+                "foo",
+                ["does things"],
+                [SynopsisOpt::new(" ")],
+            )],
+        );
+        assert_eq!(
+            rejection(roff),
+            (
+                ValidationKind::EmptyOptionName,
+                NodePath::section(0, "SYNOPSIS").node(0),
+                "an option has an empty name".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_tab_stops() {
+        for tab_stop in ["2i", "8", "1.5c", ".5i", "20n", "1."] {
+            let roff = valid().section("LIST", [RoffNode::columns([("a", "b")], tab_stop)]);
+            assert!(roff.validated().is_ok(), "{}", tab_stop);
+        }
+        for tab_stop in ["", "x", "2ii", "-1i", "1 i", "i", ".", "2i\n.sp"] {
+            let roff = valid().section("LIST", [RoffNode::columns([("a", "b")], tab_stop)]);
+            let (kind, path, message) = rejection(roff);
+            assert_eq!(kind, ValidationKind::InvalidTabStop, "{}", tab_stop);
+            assert_eq!(path, NodePath::section(2, "LIST").node(0));
+            assert_eq!(
+                message,
+                format!("the tab stop `{}` is not a valid distance", tab_stop)
+            );
+        }
+    }
+}
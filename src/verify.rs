@@ -0,0 +1,449 @@
+use crate::CLASSIC_PREAMBLE;
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Requests and macros generated by this crate. A control line starting with anything else comes
+/// from text that wasn't escaped properly.
+const KNOWN_REQUESTS: &[&str] = &[
+    "TH", "SH", "SS", "P", "IP", "TP", "RS", "RE", "EX", "EE", "SY", "YS", "OP", "UR", "UE", "MT",
+    "ME", "br", "ta", "nf", "fi", "ft", "so",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+/// The kind of a problem found by [`verify_output`](verify_output).
+pub enum OutputIssueKind {
+    /// A line starts with `.` or `'` but is not a request generated by this crate, so text ended
+    /// up being interpreted as a control line.
+    UnexpectedControlLine,
+    /// A font escape is cut off or the font is not restored to roman before the next control line
+    /// or the end of the document.
+    UnterminatedFont,
+    /// A quoted macro argument is not closed before the end of the line or is closed in the middle
+    /// of a word, splitting the argument.
+    UnbalancedQuote,
+    /// An escape sequence is cut off by the end of the line. A backslash at the very end of a line
+    /// joins it with the next one.
+    TruncatedEscape,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A single problem found in the rendered output by [`verify_output`](verify_output).
+pub struct OutputIssue {
+    kind: OutputIssueKind,
+    line: usize,
+    message: String,
+}
+
+impl OutputIssue {
+    fn new(kind: OutputIssueKind, line: usize, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the kind of this issue.
+    pub fn kind(&self) -> OutputIssueKind {
+        self.kind
+    }
+
+    /// Returns the number of the line with this issue, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the human-readable description of this issue.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for OutputIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Checks rendered ROFF `output` for problems that are caused by text that wasn't escaped
+/// properly: lines unintentionally starting with `.` or `'`, unterminated font escapes and
+/// unbalanced quotes in macro arguments. Returns all of the issues found, an empty list means that
+/// the output is well formed.
+///
+/// ```
+/// use roffman::{verify_output, Roff, RoffNode, SectionNumber};
+///
+/// let roff = Roff::new("test", SectionNumber::UserCommands)
+///     .section("NAME", [RoffNode::text(".SH \"not a section\"")]);
+/// assert!(verify_output(&roff.render_to_string().unwrap()).is_empty());
+///
+/// let issues = verify_output(".TH test 1\n.SH \"NAME\n\\fBtest");
+/// assert_eq!(issues.len(), 2);
+/// ```
+pub fn verify_output(output: &str) -> Vec<OutputIssue> {
+    let mut issues = vec![];
+    let mut font_changed = false;
+    let mut offset = 0;
+    let mut skipped = 0;
+    for (i, line) in output.split('\n').enumerate() {
+        let number = i + 1;
+        // The classic preamble is written verbatim and uses requests not generated otherwise.
+        if output[offset..].starts_with(CLASSIC_PREAMBLE) {
+            skipped = CLASSIC_PREAMBLE.lines().count();
+        }
+        offset += line.len() + 1;
+        if skipped > 0 {
+            skipped -= 1;
+            continue;
+        }
+        let mut chars = line.chars().peekable();
+        match chars.peek() {
+            Some('.') | Some('\'') => {
+                let control = chars.next().unwrap_or_default();
+                while chars.next_if(|ch| *ch == ' ' || *ch == '\t').is_some() {}
+                let name: String = chars
+                    .clone()
+                    .take_while(|ch| *ch != ' ' && *ch != '\t')
+                    .collect();
+                if name.starts_with("\\\"") {
+                    continue;
+                }
+                if font_changed {
+                    issues.push(OutputIssue::new(
+                        OutputIssueKind::UnterminatedFont,
+                        number,
+                        "the font is not restored before the control line",
+                    ));
+                    font_changed = false;
+                }
+                if control == '\'' || (!name.is_empty() && !KNOWN_REQUESTS.contains(&&*name)) {
+                    issues.push(OutputIssue::new(
+                        OutputIssueKind::UnexpectedControlLine,
+                        number,
+                        format!("unexpected control line `{}`", line),
+                    ));
+                    continue;
+                }
+                chars.nth(name.chars().count().saturating_sub(1));
+                check_arguments(chars, number, &mut font_changed, &mut issues);
+            }
+            _ => {
+                check_text(&mut chars, None, number, &mut font_changed, &mut issues);
+            }
+        }
+    }
+    if font_changed {
+        issues.push(OutputIssue::new(
+            OutputIssueKind::UnterminatedFont,
+            output.split('\n').count(),
+            "the font is not restored at the end of the document",
+        ));
+    }
+    issues
+}
+
+/// Checks the arguments of a macro on line `number`.
+fn check_arguments(
+    mut chars: Peekable<Chars<'_>>,
+    number: usize,
+    font_changed: &mut bool,
+    issues: &mut Vec<OutputIssue>,
+) {
+    loop {
+        while chars.next_if(|ch| *ch == ' ').is_some() {}
+        match chars.peek() {
+            None => return,
+            Some('"') => {
+                chars.next();
+                if !check_text(&mut chars, Some('"'), number, font_changed, issues) {
+                    issues.push(OutputIssue::new(
+                        OutputIssueKind::UnbalancedQuote,
+                        number,
+                        "quoted argument is not closed",
+                    ));
+                    return;
+                }
+                if chars.peek().is_some_and(|ch| *ch != ' ') {
+                    issues.push(OutputIssue::new(
+                        OutputIssueKind::UnbalancedQuote,
+                        number,
+                        "quoted argument is closed in the middle of a word",
+                    ));
+                    return;
+                }
+            }
+            Some(_) => {
+                if !check_text(&mut chars, Some(' '), number, font_changed, issues) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Checks the escapes of text up to the `end` character or the end of the line. Returns `true` if
+/// the `end` character was found. A doubled quote inside of a quoted argument is a literal quote.
+fn check_text(
+    chars: &mut Peekable<Chars<'_>>,
+    end: Option<char>,
+    number: usize,
+    font_changed: &mut bool,
+    issues: &mut Vec<OutputIssue>,
+) -> bool {
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if end == Some('"') && chars.next_if_eq(&'"').is_none() => return true,
+            '"' if end == Some('"') => {}
+            ch if Some(ch) == end => return true,
+            '\\' => match chars.next() {
+                // Comments run until the end of the line.
+                Some('"') | Some('#') => {
+                    chars.for_each(drop);
+                    return false;
+                }
+                Some('f') => match escape_name(chars) {
+                    Some(font) => *font_changed = !matches!(&*font, "R" | "P" | "1"),
+                    None => issues.push(OutputIssue::new(
+                        OutputIssueKind::UnterminatedFont,
+                        number,
+                        "font escape is cut off",
+                    )),
+                },
+                Some('s') => {
+                    chars.next_if(|ch| *ch == '+' || *ch == '-');
+                    if escape_name(chars).is_none() {
+                        push_truncated(number, issues);
+                    }
+                }
+                Some(open @ '(') | Some(open @ '[') => {
+                    if name_after(open, chars).is_none() {
+                        push_truncated(number, issues);
+                    }
+                }
+                Some('*') => {
+                    if escape_name(chars).is_none() {
+                        push_truncated(number, issues);
+                    }
+                }
+                Some(_) => {}
+                None => push_truncated(number, issues),
+            },
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Reads the name of an escape like the font of `\f`: a single character, two characters after
+/// `(` or anything up to `]` after `[`. Returns `None` if the name is cut off.
+fn escape_name(chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    let first = chars.next()?;
+    name_after(first, chars)
+}
+
+/// Reads the rest of an escape name starting with `first`, see [`escape_name`](escape_name).
+fn name_after(first: char, chars: &mut Peekable<Chars<'_>>) -> Option<String> {
+    match first {
+        '(' => {
+            let name: String = chars.take(2).collect();
+            (name.chars().count() == 2).then_some(name)
+        }
+        '[' => {
+            let mut name = String::new();
+            for ch in chars {
+                if ch == ']' {
+                    return Some(name);
+                }
+                name.push(ch);
+            }
+            None
+        }
+        ch => Some(ch.to_string()),
+    }
+}
+
+fn push_truncated(number: usize, issues: &mut Vec<OutputIssue>) {
+    issues.push(OutputIssue::new(
+        OutputIssueKind::TruncatedEscape,
+        number,
+        "escape sequence is cut off by the end of the line",
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_output, OutputIssueKind};
+    use crate::{
+        EscapePolicy, IntoRoffNode, RenderOptions, Roff, RoffNode, Roffable, SectionNumber,
+        SynopsisOpt,
+    };
+
+    use proptest::prelude::*;
+
+    fn kinds(output: &str) -> Vec<OutputIssueKind> {
+        verify_output(output)
+            .iter()
+            .map(|issue| issue.kind())
+            .collect()
+    }
+
+    #[test]
+    fn it_accepts_valid_output() {
+        assert!(kinds(
+            r#".\" comment with "quotes
+.TH test 1
+.SH "NAME WITH ""QUOTES"""
+\fBbold\fR \f(CWcode\fR \s+2big\s0 \(em \[u1F980] \*(lq
+\&.not a request \e
+.TP
+\fB\-\-opt\fR
+text"#
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn it_finds_issues() {
+        use OutputIssueKind::*;
+        assert_eq!(kinds(".XX text\n'br"), [UnexpectedControlLine; 2]);
+        assert_eq!(kinds(".SH \"NAME"), [UnbalancedQuote]);
+        assert_eq!(kinds(".SH \"NA\"ME\""), [UnbalancedQuote]);
+        assert_eq!(kinds("\\fBbold\n.SH NAME"), [UnterminatedFont]);
+        assert_eq!(kinds("\\fBbold"), [UnterminatedFont]);
+        assert_eq!(kinds("\\f"), [UnterminatedFont]);
+        assert_eq!(kinds("text\\\n\\(e"), [TruncatedEscape; 2]);
+    }
+
+    #[test]
+    fn it_protects_text_following_a_newline() {
+        let nodes = || [RoffNode::text("a\n"), RoffNode::text(".bad macro")];
+        let sections = [
+            Roff::new("test", SectionNumber::UserCommands).section("S", nodes()),
+            Roff::new("test", SectionNumber::UserCommands).section("S", [nodes().into_roff()]),
+            Roff::new("test", SectionNumber::UserCommands)
+                .section("S", [RoffNode::example(["x\n", ".ex"])]),
+            Roff::new("test", SectionNumber::UserCommands).section(
+                "S",
+                [RoffNode::option_list([
+                    SynopsisOpt::new("-a").description(["x\n", "'br"])
+                ])],
+            ),
+        ];
+        for roff in sections {
+            let output = roff.render_to_string().unwrap();
+            assert!(kinds(&output).is_empty(), "{}", output);
+        }
+
+        let output = Roff::new("test", SectionNumber::UserCommands)
+            .section("S", nodes())
+            .render_to_string()
+            .unwrap();
+        assert_eq!(output, ".TH test 1\n.SH S\na\n\\&.bad macro");
+    }
+
+    /// Strings made of the characters that are meaningful in ROFF, mixed with arbitrary ones, and
+    /// lines that end with a newline or start like a control line so that adjacent texts meet at
+    /// the start of a line.
+    fn roff_string() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<String>(),
+            "([.'\"\\\\ \n\r\t-]|\\\\f|[a-z]|\\.SH ){0,16}",
+            "[a-z .']{0,4}\n",
+            "[.'][a-z .']{0,6}",
+        ]
+    }
+
+    /// Renders `text` in every kind of node, and `next` right after it wherever a node takes more
+    /// than one text or where adjacent nodes are joined, so that the start of `next` may follow a
+    /// newline at the end of `text`.
+    fn render(text: &str, next: &str, policy: EscapePolicy) -> String {
+        Roff::new(text, SectionNumber::UserCommands)
+            .escape_policy(policy)
+            .section(text, [RoffNode::text(text), RoffNode::text(next)])
+            .section(
+                "ADJACENT",
+                [
+                    vec![RoffNode::text(text), RoffNode::text(next)].into_roff(),
+                    RoffNode::paragraph([text.roff().bold(), next.roff(), text.roff()]),
+                    RoffNode::example([text, next, text]),
+                ],
+            )
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::paragraph([text.roff(), text.roff().bold()]),
+                    RoffNode::subheading(text),
+                    RoffNode::tagged_paragraph([text], text.roff().italic()),
+                    RoffNode::indented_paragraph([text], Some(4), Some(text)),
+                    RoffNode::example([text]),
+                    RoffNode::url(text, text),
+                    RoffNode::email(text, text),
+                    RoffNode::comment(text),
+                ],
+            )
+            .section(
+                "SYNOPSIS",
+                [RoffNode::synopsis(
+                    text,
+                    [text, next],
+                    [SynopsisOpt::new(text)
+                        .argument(text)
+                        .description([text, next])],
+                )],
+            )
+            .section(
+                "OPTIONS",
+                [RoffNode::option_list([SynopsisOpt::new(text)
+                    .argument(text)
+                    .default_value(text)
+                    .possible_values([text])
+                    .description([text, next])])],
+            )
+            .render_to_string()
+            .unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn any_text_renders_valid_output(text in roff_string(), next in roff_string()) {
+            let output = render(&text, &next, EscapePolicy::default());
+            prop_assert!(verify_output(&output).is_empty(), "{:?}\n{}", verify_output(&output), output);
+        }
+
+        #[test]
+        fn any_text_renders_valid_output_without_escaping_quotes(text in roff_string(), next in roff_string()) {
+            let output = render(&text, &next, EscapePolicy::new().quotes(false).special_chars(false).smart_punctuation(true));
+            prop_assert!(verify_output(&output).is_empty(), "{:?}\n{}", verify_output(&output), output);
+        }
+
+        #[test]
+        fn wrapped_text_renders_valid_output(text in roff_string(), width in 1usize..16, split in any::<bool>()) {
+            let roff = |options| {
+                Roff::new("wrap", SectionNumber::UserCommands)
+                    .escape_policy(EscapePolicy::new().quotes(false))
+                    .render_options(options)
+                    .section("DESCRIPTION", [RoffNode::paragraph([
+                        RoffNode::text(&text),
+                        RoffNode::text(text.roff().bold()),
+                        RoffNode::comment(&text),
+                        RoffNode::text(&text),
+                    ])])
+                    .render_to_string()
+                    .unwrap()
+            };
+            let output = roff(RenderOptions::new().max_line_width(width).sentence_per_line(split));
+            prop_assert!(verify_output(&output).is_empty(), "{:?}\n{}", verify_output(&output), output);
+
+            let unwrapped = roff(RenderOptions::new());
+            let words = |output: &str| {
+                output
+                    .split_whitespace()
+                    .map(|word| word.trim_start_matches("\\&").to_string())
+                    .collect::<Vec<_>>()
+            };
+            prop_assert_eq!(words(&output), words(&unwrapped));
+        }
+    }
+}
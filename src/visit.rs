@@ -0,0 +1,459 @@
+use crate::node::RoffNodeInner;
+use crate::{Roff, RoffNode, RoffText, Section, SynopsisItem, SynopsisOpt};
+
+use std::iter;
+
+/// Callbacks invoked by [`Roff::walk`](Roff::walk) and [`RoffNode::walk`](RoffNode::walk) for
+/// every part of a document, depth-first in the order the parts are rendered. All methods do
+/// nothing by default, so a visitor only implements the ones it needs.
+///
+/// [`visit_node`](Visitor::visit_node) is invoked for every node before the callback specific to
+/// the kind of the node. Nodes containing other nodes invoke a `_start` callback before their
+/// children and an `_end` callback after them. The children of a [`group`](RoffNode::group) are
+/// visited in place of the group, which only invokes `visit_node`.
+pub trait Visitor {
+    /// Invoked for this document and every appended page before their sections.
+    fn visit_page(&mut self, _page: &Roff) {}
+
+    /// Invoked before the nodes of a section.
+    fn visit_section_start(&mut self, _section: &Section) {}
+
+    /// Invoked after the nodes of a section.
+    fn visit_section_end(&mut self, _section: &Section) {}
+
+    /// Invoked for every node before the callback specific to its kind.
+    fn visit_node(&mut self, _node: &RoffNode) {}
+
+    /// Invoked for a [`text`](RoffNode::text) node.
+    fn visit_text(&mut self, _text: &RoffText) {}
+
+    /// Invoked for a [`subheading`](RoffNode::subheading).
+    fn visit_sub_heading(&mut self, _title: &RoffText) {}
+
+    /// Invoked for a [`whatis`](RoffNode::whatis) line.
+    fn visit_whatis(&mut self, _names: &[RoffText], _description: &RoffText) {}
+
+    /// Invoked before the content of a paragraph, an indented paragraph or a tagged paragraph with
+    /// the title of the paragraph if it has one.
+    fn visit_paragraph_start(&mut self, _title: Option<&RoffText>) {}
+
+    /// Invoked after the content of a paragraph.
+    fn visit_paragraph_end(&mut self) {}
+
+    /// Invoked before the content of a [`nested`](RoffNode::nested) block.
+    fn visit_nested_start(&mut self, _indentation: Option<u8>) {}
+
+    /// Invoked after the content of a nested block.
+    fn visit_nested_end(&mut self) {}
+
+    /// Invoked for an [`example`](RoffNode::example) block.
+    fn visit_example(&mut self, _lines: &[RoffText]) {}
+
+    /// Invoked for [`columns`](RoffNode::columns) of text.
+    fn visit_columns(&mut self, _rows: &[(RoffText, RoffText)]) {}
+
+    /// Invoked for a [`synopsis`](RoffNode::synopsis) before its options.
+    fn visit_synopsis(&mut self, _command: &RoffText, _text: &[RoffText]) {}
+
+    /// Invoked for a group of options of a synopsis or an option list before its options.
+    fn visit_option_group(&mut self, _title: &RoffText) {}
+
+    /// Invoked for every option of a synopsis or an option list, including hidden ones.
+    fn visit_option(&mut self, _opt: &SynopsisOpt) {}
+
+    /// Invoked for a [`url`](RoffNode::url).
+    fn visit_url(&mut self, _name: &RoffText, _address: &RoffText) {}
+
+    /// Invoked for an [`email`](RoffNode::email) address.
+    fn visit_email(&mut self, _name: &RoffText, _address: &RoffText) {}
+
+    /// Invoked for special characters like signs, quotes and dashes, line breaks and
+    /// non-breaking spaces. The kind can be told apart with
+    /// [`RoffNode::kind_name`](RoffNode::kind_name).
+    fn visit_special(&mut self, _node: &RoffNode) {}
+
+    /// Invoked for a [`comment`](RoffNode::comment).
+    fn visit_comment(&mut self, _comment: &str) {}
+}
+
+impl Roff {
+    /// Walks the sections of this document and of all appended pages depth-first, invoking the
+    /// callbacks of `visitor` for every part of them.
+    pub fn walk(&self, visitor: &mut impl Visitor) {
+        for page in iter::once(self).chain(&self.pages) {
+            visitor.visit_page(page);
+            for section in &page.sections {
+                visitor.visit_section_start(section);
+                for node in section.get_nodes() {
+                    node.walk(visitor);
+                }
+                visitor.visit_section_end(section);
+            }
+        }
+    }
+}
+
+impl RoffNode {
+    /// Walks this node and the nodes nested in it depth-first, invoking the callbacks of
+    /// `visitor` for every one of them.
+    pub fn walk(&self, visitor: &mut impl Visitor) {
+        visitor.visit_node(self);
+        match self.inner_ref() {
+            RoffNodeInner::Text(text) => visitor.visit_text(text),
+            RoffNodeInner::SubHeading(title) => visitor.visit_sub_heading(title),
+            RoffNodeInner::Whatis { names, description } => {
+                visitor.visit_whatis(names, description)
+            }
+            RoffNodeInner::Paragraph(content) => walk_paragraph(None, content, visitor),
+            RoffNodeInner::IndentedParagraph { content, title, .. } => {
+                walk_paragraph(title.as_ref(), content, visitor)
+            }
+            RoffNodeInner::TaggedParagraph { content, title } => {
+                walk_paragraph(Some(title), content, visitor)
+            }
+            RoffNodeInner::Nested { nodes, indentation } => {
+                visitor.visit_nested_start(*indentation);
+                for node in nodes {
+                    node.walk(visitor);
+                }
+                visitor.visit_nested_end();
+            }
+            RoffNodeInner::Group(nodes) => {
+                for node in nodes {
+                    node.walk(visitor);
+                }
+            }
+            RoffNodeInner::Example(lines) => visitor.visit_example(lines),
+            RoffNodeInner::Columns { rows, .. } => visitor.visit_columns(rows),
+            RoffNodeInner::Synopsis {
+                command,
+                text,
+                opts,
+            } => {
+                visitor.visit_synopsis(command, text);
+                walk_options(opts, visitor);
+            }
+            RoffNodeInner::OptionList(opts) => walk_options(opts, visitor),
+            RoffNodeInner::Url { name, address } => visitor.visit_url(name, address),
+            RoffNodeInner::Email { name, address } => visitor.visit_email(name, address),
+            RoffNodeInner::Comment(comment) => visitor.visit_comment(comment),
+            RoffNodeInner::RegisteredSign
+            | RoffNodeInner::LeftQuote
+            | RoffNodeInner::RightQuote
+            | RoffNodeInner::TrademarkSign
+            | RoffNodeInner::Break
+            | RoffNodeInner::EmDash
+            | RoffNodeInner::EnDash
+            | RoffNodeInner::NonBreakingSpace => visitor.visit_special(self),
+        }
+    }
+}
+
+fn walk_paragraph(title: Option<&RoffText>, content: &[RoffNode], visitor: &mut impl Visitor) {
+    visitor.visit_paragraph_start(title);
+    for node in content {
+        node.walk(visitor);
+    }
+    visitor.visit_paragraph_end();
+}
+
+fn walk_options(opts: &[SynopsisItem], visitor: &mut impl Visitor) {
+    for item in opts {
+        match item {
+            SynopsisItem::Opt(opt) => visitor.visit_option(opt),
+            SynopsisItem::Group(group) => {
+                visitor.visit_option_group(&group.title);
+                for opt in &group.opts {
+                    visitor.visit_option(opt);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+/// A [`Visitor`] that collects the text displayed by a document without styling and escapes, for
+/// example to spellcheck it or to index it for search. Section titles, headings, paragraphs and
+/// other blocks start on a new line while inline text is joined. Addresses of links, comments and
+/// special characters are skipped.
+///
+/// ```
+/// use roffman::{CollectText, Roff, RoffNode, SectionNumber};
+///
+/// let roff = Roff::new("foo", SectionNumber::UserCommands).section(
+///     "DESCRIPTION",
+///     [RoffNode::paragraph(["Does ", "things."]), RoffNode::example(["$ foo"])],
+/// );
+/// let mut text = CollectText::new();
+/// roff.walk(&mut text);
+/// assert_eq!(text.text(), "DESCRIPTION\nDoes things.\n$ foo\n");
+/// ```
+pub struct CollectText {
+    text: String,
+}
+
+impl CollectText {
+    /// Creates a visitor with no text collected.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the text collected so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Consumes the visitor returning the collected text.
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    fn inline(&mut self, text: &RoffText) {
+        self.text.push_str(&text.unescaped());
+    }
+
+    /// Ends the current line unless it is empty.
+    fn end_line(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn line(&mut self, text: &RoffText) {
+        self.end_line();
+        self.inline(text);
+        self.end_line();
+    }
+
+    fn lines(&mut self, texts: &[RoffText]) {
+        self.end_line();
+        for text in texts {
+            self.inline(text);
+        }
+        self.end_line();
+    }
+}
+
+impl Visitor for CollectText {
+    fn visit_section_start(&mut self, section: &Section) {
+        self.line(section.get_title());
+        if let Some(subtitle) = section.get_subtitle() {
+            self.line(subtitle);
+        }
+    }
+
+    fn visit_section_end(&mut self, _section: &Section) {
+        self.end_line();
+    }
+
+    fn visit_text(&mut self, text: &RoffText) {
+        self.inline(text);
+    }
+
+    fn visit_sub_heading(&mut self, title: &RoffText) {
+        self.line(title);
+    }
+
+    fn visit_whatis(&mut self, names: &[RoffText], description: &RoffText) {
+        self.end_line();
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                self.text.push_str(", ");
+            }
+            self.inline(name);
+        }
+        self.text.push_str(" - ");
+        self.inline(description);
+        self.end_line();
+    }
+
+    fn visit_paragraph_start(&mut self, title: Option<&RoffText>) {
+        self.end_line();
+        if let Some(title) = title {
+            self.line(title);
+        }
+    }
+
+    fn visit_paragraph_end(&mut self) {
+        self.end_line();
+    }
+
+    fn visit_nested_start(&mut self, _indentation: Option<u8>) {
+        self.end_line();
+    }
+
+    fn visit_nested_end(&mut self) {
+        self.end_line();
+    }
+
+    fn visit_example(&mut self, lines: &[RoffText]) {
+        self.lines(lines);
+    }
+
+    fn visit_columns(&mut self, rows: &[(RoffText, RoffText)]) {
+        self.end_line();
+        for (key, value) in rows {
+            self.inline(key);
+            self.text.push(' ');
+            self.inline(value);
+            self.end_line();
+        }
+    }
+
+    fn visit_synopsis(&mut self, command: &RoffText, text: &[RoffText]) {
+        self.end_line();
+        self.inline(command);
+        if !text.is_empty() {
+            self.text.push(' ');
+        }
+        for text in text {
+            self.inline(text);
+        }
+        self.end_line();
+    }
+
+    fn visit_option_group(&mut self, title: &RoffText) {
+        self.line(title);
+    }
+
+    fn visit_option(&mut self, opt: &SynopsisOpt) {
+        self.end_line();
+        self.inline(opt.get_name());
+        if let Some(argument) = opt.get_argument() {
+            self.text.push(' ');
+            self.inline(argument);
+        }
+        self.end_line();
+        self.lines(opt.get_description());
+    }
+
+    fn visit_url(&mut self, name: &RoffText, _address: &RoffText) {
+        self.inline(name);
+    }
+
+    fn visit_email(&mut self, name: &RoffText, _address: &RoffText) {
+        self.inline(name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CollectText, Visitor};
+    use crate::tests::readme_example;
+    use crate::{Roff, RoffNode, RoffText, Section, SectionNumber};
+
+    /// Records the callbacks invoked while walking a document.
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Visitor for Trace {
+        fn visit_page(&mut self, page: &Roff) {
+            self.0.push(format!("page {}", page.get_title().original()));
+        }
+
+        fn visit_section_start(&mut self, section: &Section) {
+            self.0
+                .push(format!("section {}", section.get_title().original()));
+        }
+
+        fn visit_section_end(&mut self, _section: &Section) {
+            self.0.push("end section".to_string());
+        }
+
+        fn visit_text(&mut self, text: &RoffText) {
+            self.0.push(format!("text {}", text.original()));
+        }
+
+        fn visit_paragraph_start(&mut self, title: Option<&RoffText>) {
+            let title = title.map(|title| title.original().into_owned());
+            self.0.push(format!("paragraph {:?}", title));
+        }
+
+        fn visit_paragraph_end(&mut self) {
+            self.0.push("end paragraph".to_string());
+        }
+
+        fn visit_nested_start(&mut self, indentation: Option<u8>) {
+            self.0.push(format!("nested {:?}", indentation));
+        }
+
+        fn visit_nested_end(&mut self) {
+            self.0.push("end nested".to_string());
+        }
+
+        fn visit_special(&mut self, node: &RoffNode) {
+            self.0.push(node.kind_name().to_string());
+        }
+    }
+
+    #[test]
+    fn it_walks_depth_first() {
+        let roff = Roff::new("foo", SectionNumber::UserCommands)
+            .section(
+                "DESCRIPTION",
+                [
+                    RoffNode::paragraph([
+                        RoffNode::text("a"),
+                        RoffNode::nested_indented([RoffNode::tagged_paragraph(["b"], "tag")], 2),
+                        RoffNode::em_dash(),
+                    ]),
+                    RoffNode::text("c"),
+                ],
+            )
+            .append_page(Roff::new("bar", SectionNumber::UserCommands).section("NAME", ["d"]));
+
+        let mut trace = Trace::default();
+        roff.walk(&mut trace);
+        assert_eq!(
+            trace.0,
+            [
+                "page foo",
+                "section DESCRIPTION",
+                "paragraph None",
+                "text a",
+                "nested Some(2)",
+                "paragraph Some(\"tag\")",
+                "text b",
+                "end paragraph",
+                "end nested",
+                "em dash",
+                "end paragraph",
+                "text c",
+                "end section",
+                "page bar",
+                "section NAME",
+                "text d",
+                "end section",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_collects_the_text_of_the_readme_example() {
+        let mut text = CollectText::new();
+        readme_example().walk(&mut text);
+        assert_eq!(
+            text.into_text(),
+            r#"BASIC USAGE
+This is how you create a basic paragraph using roffman.
+optional-title
+This line should be slightly indented to the right.
+roffman-command This is the description of this command. It will be displayed right next to it
+--opt
+some simple opt
+--opt-with-arg ARG
+opt with an argument
+--bold
+Example:
+
+impl Roffable for u8 {
+    fn roff(&self) -> RoffText {
+        self.to_string().roff()
+    }
+}
+GitHub
+vv9k
+"#
+        );
+    }
+}
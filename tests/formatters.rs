@@ -0,0 +1,186 @@
+//! Validates the output with `groff` and `mandoc` found on `PATH`, failing on any warning they
+//! report or if neither of them is installed. The documents exercise every kind of node in both
+//! compatibility levels.
+//!
+//! Run with `cargo test --features test-util --test formatters -- --ignored`.
+
+use roffman::test_util::check_with_system_formatters;
+use roffman::{
+    BugContact, CompatLevel, Roff, RoffNode, Roffable, SectionNumber, SynopsisItem, SynopsisOpt,
+    SynopsisOptGroup, TitleCase,
+};
+
+fn opts() -> Vec<SynopsisOpt> {
+    vec![
+        SynopsisOpt::new("-v").description(["print more output"]),
+        SynopsisOpt::new("--color")
+            .argument("WHEN")
+            .description(["colorize the output"])
+            .possible_values(["auto", "always", "never"])
+            .default_value("auto"),
+        SynopsisOpt::new("--format")
+            .possible_values(["long", "single column"])
+            .inline_values(true),
+        SynopsisOpt::new("--quiet"),
+    ]
+}
+
+fn nodes_page() -> Roff {
+    Roff::new("roffman-nodes", SectionNumber::Miscellaneous)
+        .title_case(TitleCase::Upper)
+        .date("2021-08-01")
+        .section(
+            "NAME",
+            [RoffNode::whatis(
+                ["roffman-nodes", "nodes"],
+                "every node rendered by roffman",
+            )],
+        )
+        .section(
+            "SYNOPSIS",
+            [RoffNode::synopsis(
+                "roffman-nodes",
+                ["[".roff(), "OPTIONS".roff().italic(), "]".roff()],
+                [
+                    SynopsisItem::from(SynopsisOpt::new("--version")),
+                    SynopsisOptGroup::new("Output options", opts()).into(),
+                ],
+            )],
+        )
+        .section(
+            "DESCRIPTION",
+            [
+                RoffNode::paragraph([
+                    "A paragraph with ".roff(),
+                    "bold".roff().bold(),
+                    " and ".roff(),
+                    "italic".roff().italic(),
+                    " text, a ".roff(),
+                    "literal".roff().literal(),
+                    " and a .dot or 'quote at the start of a line:".roff(),
+                ]),
+                RoffNode::text("\n.dot\n'quote"),
+                RoffNode::comment("a comment\nspanning two lines"),
+                RoffNode::linebreak(),
+                RoffNode::text("Text after a line break."),
+                RoffNode::subheading("Special characters"),
+                RoffNode::paragraph([
+                    RoffNode::left_quote(),
+                    RoffNode::text("quoted"),
+                    RoffNode::right_quote(),
+                    RoffNode::text(" roffman"),
+                    RoffNode::registered_sign(),
+                    RoffNode::text(" roffman"),
+                    RoffNode::trademark_sign(),
+                    RoffNode::text(" a"),
+                    RoffNode::em_dash(),
+                    RoffNode::text("b 1"),
+                    RoffNode::en_dash(),
+                    RoffNode::text("2 non"),
+                    RoffNode::non_breaking_space(),
+                    RoffNode::text("breaking"),
+                ]),
+                RoffNode::subheading("Paragraphs"),
+                RoffNode::indented_paragraph(["Indented without a title."], None, None::<&str>),
+                RoffNode::indented_paragraph(["Indented by 4."], Some(4), None::<&str>),
+                RoffNode::indented_paragraph(["Indented with a title."], Some(8), Some("title")),
+                RoffNode::indented_paragraph(
+                    [RoffNode::text("Ends with a break."), RoffNode::linebreak()],
+                    Some(2),
+                    Some("with spaces"),
+                ),
+                RoffNode::tagged_paragraph(["The body of a tagged paragraph."], "tag"),
+                RoffNode::tagged_paragraph(Vec::<RoffNode>::new(), "empty"),
+                RoffNode::nested([
+                    RoffNode::paragraph(["Nested once."]),
+                    RoffNode::nested_indented([RoffNode::text("Nested twice by 2.")], 2),
+                    RoffNode::text("Back to the first level."),
+                ]),
+                RoffNode::group([
+                    RoffNode::tagged_paragraph(["A tagged paragraph in a group."], "grouped"),
+                    RoffNode::text("Continued in place."),
+                ]),
+                RoffNode::paragraph(["Columns:"]),
+                RoffNode::columns([("key", "value"), ("another key", "another\tvalue")], "2i"),
+            ],
+        )
+        .section("OPTIONS", [RoffNode::option_list(opts())])
+        .section(
+            "EXAMPLES",
+            [
+                RoffNode::text("Run it:"),
+                RoffNode::example(["$ roffman-nodes --color always\n\n$ roffman-nodes -v"]),
+            ],
+        )
+        .section(
+            "SEE ALSO",
+            [
+                RoffNode::text("The project at "),
+                RoffNode::url("GitHub", "https://github.com/vv9k/roffman"),
+                RoffNode::text(", or mail "),
+                RoffNode::email("the author", "wojciech@wkepka.dev"),
+                RoffNode::text("."),
+            ],
+        )
+}
+
+fn helpers_page() -> Roff {
+    Roff::new("roffman-helpers", SectionNumber::UserCommands)
+        .title_case(TitleCase::Upper)
+        .date("2021-08-01")
+        .name_section("pages built with the section helpers")
+        .options_section(opts())
+        .examples([("List everything:", "ls -a"), ("Long format:", "ls -l")])
+        .reporting_bugs(
+            BugContact::both(
+                "https://github.com/vv9k/roffman/issues",
+                "bugs@invalid.domain",
+            ),
+            ["Include the version in the report."],
+        )
+        .authors([
+            ("John Test", Some("john@invalid.domain")),
+            ("Jane Test", None),
+        ])
+}
+
+fn check(roff: Roff) {
+    for compat in [CompatLevel::Groff, CompatLevel::Portable] {
+        let roff = roff.clone().compat(compat);
+        match check_with_system_formatters(&roff) {
+            Ok(formatters) => assert!(
+                !formatters.is_empty(),
+                "neither groff nor mandoc was found on PATH, nothing was checked"
+            ),
+            Err(warnings) => panic!(
+                "{:?} output was rejected: {}\n{}",
+                compat,
+                warnings,
+                roff.render_to_string().unwrap()
+            ),
+        }
+    }
+}
+
+#[test]
+#[ignore = "runs groff and mandoc found on PATH"]
+fn formatters_accept_every_node() {
+    check(nodes_page());
+}
+
+#[test]
+#[ignore = "runs groff and mandoc found on PATH"]
+fn formatters_accept_section_helpers() {
+    check(helpers_page());
+}
+
+#[test]
+#[ignore = "runs groff and mandoc found on PATH"]
+fn formatters_accept_the_classic_preamble() {
+    check(
+        nodes_page()
+            .classic_preamble(true)
+            .generated_by_roffman()
+            .preamble_comments(["a preamble comment"]),
+    );
+}